@@ -0,0 +1,61 @@
+//!
+//! Compares the set and borrow paths of RcOCell with a plain Rc<RefCell<Option<T>>>, which is all the first version of the crate did.
+//!
+//! Cells that never had any shared state, which includes every cell without observers, labels or weak handles,
+//! should be as fast as the plain Rc. A cell with a weak handle has to look its state up and is measured separately.
+//!
+use std::cell::RefCell;
+use std::hint::black_box;
+use std::rc::Rc;
+use criterion::{criterion_group, criterion_main, Criterion};
+use rco_cell::RcOCell;
+
+fn set(c: &mut Criterion) {
+    let mut group = c.benchmark_group("set");
+
+    let plain = Rc::new(RefCell::new(Some(0u64)));
+    group.bench_function("plain", |b| b.iter(|| black_box(plain.borrow_mut().replace(black_box(1)))));
+
+    let cell = RcOCell::from_value(0u64);
+    group.bench_function("cell", |b| b.iter(|| black_box(cell.set(black_box(1)))));
+
+    let cell = RcOCell::from_value(0u64);
+    let weak = cell.downgrade();
+    group.bench_function("cell_with_weak", |b| b.iter(|| black_box(cell.set(black_box(1)))));
+    drop(weak);
+
+    group.finish();
+}
+
+fn borrow(c: &mut Criterion) {
+    let mut group = c.benchmark_group("borrow");
+
+    let plain = Rc::new(RefCell::new(Some(0u64)));
+    group.bench_function("plain", |b| b.iter(|| black_box(*plain.borrow().as_ref().unwrap())));
+
+    let cell = RcOCell::from_value(0u64);
+    group.bench_function("cell", |b| b.iter(|| black_box(*cell.borrow())));
+
+    let plain_mut = Rc::new(RefCell::new(Some(0u64)));
+    group.bench_function("plain_mut", |b| b.iter(|| *plain_mut.borrow_mut().as_mut().unwrap() += 1));
+
+    let cell_mut = RcOCell::from_value(0u64);
+    group.bench_function("cell_mut", |b| b.iter(|| *cell_mut.borrow_mut() += 1));
+
+    let cell_weak = RcOCell::from_value(0u64);
+    let weak = cell_weak.downgrade();
+    group.bench_function("cell_mut_with_weak", |b| b.iter(|| *cell_weak.borrow_mut() += 1));
+    drop(weak);
+
+    group.finish();
+}
+
+fn create_and_drop(c: &mut Criterion) {
+    let mut group = c.benchmark_group("create_and_drop");
+    group.bench_function("plain", |b| b.iter(|| drop(black_box(Rc::new(RefCell::new(Some(black_box(1u64))))))));
+    group.bench_function("cell", |b| b.iter(|| drop(black_box(RcOCell::from_value(black_box(1u64))))));
+    group.finish();
+}
+
+criterion_group!(benches, set, borrow, create_and_drop);
+criterion_main!(benches);
@@ -0,0 +1,43 @@
+//!
+//! Compares the counter operations of NumericCellExt with the compute based code they replace.
+//!
+use std::hint::black_box;
+use criterion::{criterion_group, criterion_main, Criterion};
+use rco_cell::{IntegerCellExt, NumericCellExt, RcOCell, RcOCellComputeResult};
+
+fn fetch_add(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fetch_add");
+
+    let cell = RcOCell::from_value(0u64);
+    group.bench_function("compute", |b| b.iter(|| {
+        let mut previous = 0;
+        cell.compute(|value| {
+            previous = value.map(|v| *v).unwrap_or_default();
+            RcOCellComputeResult::Replace(previous.wrapping_add(black_box(1)))
+        });
+        black_box(previous)
+    }));
+
+    let cell = RcOCell::from_value(0u64);
+    group.bench_function("fetch_add", |b| b.iter(|| black_box(cell.wrapping_fetch_add(black_box(1)))));
+
+    group.finish();
+}
+
+fn increment(c: &mut Criterion) {
+    let mut group = c.benchmark_group("increment");
+
+    let cell = RcOCell::from_value(0u64);
+    group.bench_function("compute", |b| b.iter(|| {
+        cell.compute(|value| RcOCellComputeResult::Replace(value.map(|v| *v).unwrap_or_default().wrapping_add(1)));
+        black_box(cell.get_and_clone())
+    }));
+
+    let cell = RcOCell::from_value(0u64);
+    group.bench_function("increment", |b| b.iter(|| black_box(cell.increment())));
+
+    group.finish();
+}
+
+criterion_group!(benches, fetch_add, increment);
+criterion_main!(benches);
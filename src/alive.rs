@@ -0,0 +1,166 @@
+use crate::{RcOCell, RcOCellComputeResult, RcOCellError, WeakRcOCell};
+
+impl <T> WeakRcOCell<T> {
+    ///
+    /// Calls the Fn with the upgraded cell if it was not dropped yet and returns the result, does nothing otherwise.
+    /// The cell is only upgraded for the duration of the call.
+    ///
+    pub fn if_alive<R, F>(&self, f: F) -> Option<R>
+        where F: FnOnce(&RcOCell<T>) -> R
+    {
+        let cell = self.try_upgrade().ok()?;
+        return Some(f(&cell));
+    }
+
+    ///
+    /// Upgrades the cell once and calls the Fn with it, i.e. to perform several operations on a cell that cannot be dropped in between.
+    /// The upgraded handle is dropped when the Fn returns.
+    /// It keeps the cell alive during the call, even if the Fn drops every other strong handle of the cell.
+    /// Fails with Dropped without calling the Fn if the cell was already dropped.
+    ///
+    pub fn with_upgraded<R, F>(&self, f: F) -> Result<R, RcOCellError>
+        where F: FnOnce(&RcOCell<T>) -> R
+    {
+        let cell = self.try_upgrade()?;
+        return Ok(f(&cell));
+    }
+
+    ///
+    /// Upgrades the cell once and calls the Fn with it, see with_upgraded.
+    /// Returns the default without calling the Fn if the cell was already dropped.
+    ///
+    pub fn with_upgraded_or<R, F>(&self, default: R, f: F) -> R
+        where F: FnOnce(&RcOCell<T>) -> R
+    {
+        return self.if_alive(f).unwrap_or(default);
+    }
+
+    ///
+    /// Sets the value returning the old value (if an old value existed).
+    /// Gives the value back as Err if the cell was dropped, so a value that cannot be cloned is not lost.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    pub fn set_if_alive(&self, value: T) -> Result<Option<T>, T> {
+        return match self.try_upgrade() {
+            Ok(cell) => Ok(cell.set(value)),
+            Err(_) => Err(value),
+        };
+    }
+
+    ///
+    /// Clears the value returning the old value, None if there was no value or the cell was dropped.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    pub fn clear_if_alive(&self) -> Option<T> {
+        return self.if_alive(|cell| cell.clear()).flatten();
+    }
+
+    ///
+    /// Calls the Fn with the value and returns the result, the default if there is no value or the cell was dropped.
+    /// Panics if the value is borrowed mutably somewhere, unlike map_or which uses the default in that case as well.
+    ///
+    pub fn map_or_dropped<R, F>(&self, default: R, f: F) -> R
+        where F: FnOnce(&T) -> R
+    {
+        return self.if_alive(|cell| cell.map(f)).flatten().unwrap_or(default);
+    }
+
+    ///
+    /// Runs the Fn with the ref to the value (if present) like compute, but does nothing if the cell was dropped.
+    /// Returns false if the cell was dropped, the Fn is not called in that case.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    pub fn compute_if_alive<F>(&self, f: F) -> bool
+        where F: FnOnce(Option<&mut T>) -> RcOCellComputeResult<T>
+    {
+        return self.if_alive(|cell| cell.compute(f)).is_some();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Handle(u32);
+
+    #[test]
+    fn test_set_if_alive() {
+        let cell = RcOCell::from_value(Handle(1));
+        let weak = cell.downgrade();
+        assert_eq!(weak.set_if_alive(Handle(2)), Ok(Some(Handle(1))));
+        assert_eq!(weak.clear_if_alive(), Some(Handle(2)));
+        assert_eq!(weak.clear_if_alive(), None);
+        assert_eq!(weak.set_if_alive(Handle(3)), Ok(None));
+
+        drop(cell);
+        assert_eq!(weak.set_if_alive(Handle(4)), Err(Handle(4)));
+        assert_eq!(weak.clear_if_alive(), None);
+    }
+
+    #[test]
+    fn test_map_or_dropped() {
+        let cell = RcOCell::from_value(String::from("abc"));
+        let weak = cell.downgrade();
+        assert_eq!(weak.map_or_dropped(0, |s| s.len()), 3);
+
+        let brw = cell.borrow_mut();
+        assert_eq!(weak.catch(|w| { w.map_or_dropped(0, |s| s.len()); }).is_err(), true);
+        drop(brw);
+
+        cell.clear();
+        assert_eq!(weak.map_or_dropped(0, |_| panic!("unexpected")), 0);
+        drop(cell);
+        assert_eq!(weak.map_or_dropped(7, |_| panic!("unexpected")), 7);
+    }
+
+    #[test]
+    fn test_with_upgraded() {
+        let cell = RcOCell::from_value(vec![1u32]);
+        let weak = cell.downgrade();
+        let len = weak.with_upgraded(|c| {
+            c.borrow_mut().push(2);
+            c.borrow_mut().push(3);
+            return c.borrow().len();
+        });
+        assert_eq!(len.unwrap(), 3);
+        assert_eq!(weak.with_upgraded_or(0, |c| c.borrow().len()), 3);
+
+        let mut external = Some(cell);
+        let sum = weak.with_upgraded(|c| {
+            drop(external.take());
+            c.borrow_mut().push(4);
+            assert_eq!(weak.try_upgrade().is_ok(), true);
+            return c.borrow().iter().sum::<u32>();
+        });
+        assert_eq!(sum.unwrap(), 10);
+        assert_eq!(weak.try_upgrade().is_err(), true);
+
+        match weak.with_upgraded(|_| panic!("unexpected")) {
+            Err::<(), _>(RcOCellError::Dropped) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(weak.with_upgraded_or(5, |_| panic!("unexpected")), 5);
+    }
+
+    #[test]
+    fn test_if_alive() {
+        let cell: RcOCell<u32> = RcOCell::new();
+        let weak = cell.downgrade();
+        assert_eq!(weak.if_alive(|c| c.is_none()), Some(true));
+
+        assert_eq!(weak.compute_if_alive(|v| {
+            assert_eq!(v, None);
+            return RcOCellComputeResult::Replace(1);
+        }), true);
+        assert_eq!(weak.compute_if_alive(|v| {
+            *v.unwrap() += 1;
+            return RcOCellComputeResult::DoNothing;
+        }), true);
+        assert_eq!(weak.if_alive(|c| c.get_and_clone()), Some(2));
+
+        drop(cell);
+        assert_eq!(weak.if_alive(|_| panic!("unexpected")), None::<()>);
+        assert_eq!(weak.compute_if_alive(|_| panic!("unexpected")), false);
+    }
+}
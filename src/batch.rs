@@ -0,0 +1,186 @@
+use crate::{shared, RcOCell};
+
+///
+/// Open batch of a cell, returned by begin_batch, the batch ends when the guard is dropped.
+///
+/// While a batch is open modifications are applied immediately but observers are not notified.
+/// When the outermost batch of the cell ends the observers are notified once with the final state of the cell,
+/// if anything notified them while the batch was open. Nested batches end silently.
+/// A batch that ends because of a panic unwinding notifies nobody, the modifications stay applied.
+///
+/// Only observers are batched, the version, the dirty flags, the spy and the tracing events see every modification.
+///
+pub struct BatchGuard<T> {
+    cell: RcOCell<T>,
+    notify: bool,
+}
+
+impl <T> RcOCell<T> {
+    ///
+    /// Opens a batch of the cell, see BatchGuard.
+    ///
+    pub fn begin_batch(&self) -> BatchGuard<T> {
+        shared::with_state(&self.rc, |state| state.batch_depth += 1);
+        return BatchGuard {cell: self.clone(), notify: true};
+    }
+
+    ///
+    /// Runs the Fn in a batch of the cell and returns its result, see BatchGuard.
+    ///
+    pub fn batch<R, F>(&self, f: F) -> R
+        where F: FnOnce(&RcOCell<T>) -> R
+    {
+        let guard = self.begin_batch();
+        let result = f(self);
+        drop(guard);
+        return result;
+    }
+
+    ///
+    /// Runs the Fn in a batch of the cell and returns its result, see BatchGuard.
+    /// If this is the outermost batch and the value is equal to the value before the batch the observers are not notified.
+    /// The value before the batch is cloned when the batch starts.
+    /// Panics if the value is borrowed mutably when the batch starts.
+    ///
+    #[track_caller]
+    pub fn batch_eq<R, F>(&self, f: F) -> R
+        where F: FnOnce(&RcOCell<T>) -> R, T: Clone + PartialEq
+    {
+        let before = self.raw_borrow().clone();
+        let guard = self.begin_batch();
+        let result = f(self);
+        let unchanged = self.rc.try_borrow().is_ok_and(|value| *value == before);
+        guard.end(!unchanged);
+        return result;
+    }
+}
+
+impl <T> BatchGuard<T> {
+    ///
+    /// Returns the cell the batch belongs to.
+    ///
+    pub fn cell(&self) -> &RcOCell<T> {
+        return &self.cell;
+    }
+
+    ///
+    /// Ends the batch, the observers are only notified if notify is true and this is the outermost batch.
+    ///
+    fn end(mut self, notify: bool) {
+        self.notify = notify;
+        drop(self);
+    }
+}
+
+impl <T> Drop for BatchGuard<T> {
+    fn drop(&mut self) {
+        let panicking = std::thread::panicking();
+        let pending = shared::with_existing_state(&self.cell.rc, |state| {
+            state.batch_depth -= 1;
+            if state.batch_depth > 0 {
+                return false;
+            }
+            return std::mem::take(&mut state.batch_pending);
+        }).unwrap_or(false);
+
+        if pending && self.notify && !panicking {
+            shared::notify_batched(&self.cell.rc);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use std::cell::Cell;
+    use crate::*;
+
+    fn counted(cell: &RcOCell<u32>) -> Rc<Cell<Vec<Option<u32>>>> {
+        let seen = Rc::new(Cell::new(Vec::new()));
+        let on_set = seen.clone();
+        cell.on_set(move |v| {
+            let mut all = on_set.take();
+            all.push(Some(*v));
+            on_set.set(all);
+        });
+        let on_clear = seen.clone();
+        cell.on_clear(move || {
+            let mut all = on_clear.take();
+            all.push(None);
+            on_clear.set(all);
+        });
+        return seen;
+    }
+
+    #[test]
+    fn test_batch_coalesces() {
+        let cell = RcOCell::from_value(0u32);
+        let seen = counted(&cell);
+        let sum = cell.batch(|c| {
+            for i in 1..=1000 {
+                c.set(i);
+            }
+            c.get_and_clone()
+        });
+        assert_eq!(sum, 1000);
+        assert_eq!(seen.take(), vec![Some(1000)]);
+
+        cell.batch(|c| {
+            c.set(1);
+            c.clear();
+        });
+        assert_eq!(seen.take(), vec![None]);
+
+        cell.batch(|c| c.is_some());
+        assert_eq!(seen.take(), vec![]);
+
+        cell.set(2);
+        assert_eq!(seen.take(), vec![Some(2)]);
+    }
+
+    #[test]
+    fn test_batch_nested() {
+        let cell = RcOCell::from_value(0u32);
+        let seen = counted(&cell);
+        let outer = cell.begin_batch();
+        cell.set(1);
+        cell.batch(|c| {
+            c.set(2);
+            c.batch(|c| c.set(3));
+        });
+        assert_eq!(seen.take(), vec![]);
+        outer.cell().replace(4);
+        drop(outer);
+        assert_eq!(seen.take(), vec![Some(4)]);
+
+        cell.batch_eq(|c| {
+            c.set(5);
+            c.batch_eq(|c| c.set(6));
+            c.set(4);
+        });
+        assert_eq!(seen.take(), vec![]);
+        cell.batch(|c| c.batch_eq(|c| {
+            c.set(7);
+            c.set(4);
+        }));
+        assert_eq!(seen.take(), vec![Some(4)]);
+        cell.batch_eq(|c| c.set(8));
+        assert_eq!(seen.take(), vec![Some(8)]);
+    }
+
+    #[test]
+    fn test_batch_panic() {
+        let cell = RcOCell::from_value(0u32);
+        let seen = counted(&cell);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| cell.batch(|c| {
+            c.set(1);
+            panic!("inside batch");
+        })));
+        assert_eq!(result.is_err(), true);
+        assert_eq!(cell.get_and_clone(), 1);
+        assert_eq!(seen.take(), vec![]);
+
+        cell.set(2);
+        assert_eq!(seen.take(), vec![Some(2)]);
+    }
+}
@@ -0,0 +1,232 @@
+use crate::{RcOCell, RcOCellError, RcORefMut, WeakRcOCell};
+
+///
+/// Flag operations for cells holding a bool.
+///
+/// An empty cell counts as false.
+/// set_true and set_false return the previous value so transitions (i.e. the rising edge) can be detected.
+/// Observers are notified about every write, also when the value did not change.
+///
+pub trait BoolCellExt {
+    ///
+    /// Inverts the value and returns the new value.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn toggle(&self) -> bool;
+
+    ///
+    /// Inverts the value and returns the new value.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    fn try_toggle(&self) -> Result<bool, RcOCellError>;
+
+    ///
+    /// Sets the value to true and returns the previous value.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn set_true(&self) -> bool;
+
+    ///
+    /// Sets the value to true and returns the previous value.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    fn try_set_true(&self) -> Result<bool, RcOCellError>;
+
+    ///
+    /// Sets the value to false and returns the previous value.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn set_false(&self) -> bool;
+
+    ///
+    /// Sets the value to false and returns the previous value.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    fn try_set_false(&self) -> Result<bool, RcOCellError>;
+
+    ///
+    /// Returns true if the value is true.
+    /// Panics if the value is borrowed mutably somewhere.
+    ///
+    fn is_true(&self) -> bool;
+
+    ///
+    /// Returns true if the value is true.
+    /// Fails if the value is borrowed mutably somewhere.
+    ///
+    fn try_is_true(&self) -> Result<bool, RcOCellError>;
+
+    ///
+    /// Returns true if the value is false or the cell is empty.
+    /// Panics if the value is borrowed mutably somewhere.
+    ///
+    fn is_false(&self) -> bool;
+
+    ///
+    /// Returns true if the value is false or the cell is empty.
+    /// Fails if the value is borrowed mutably somewhere.
+    ///
+    fn try_is_false(&self) -> Result<bool, RcOCellError>;
+}
+
+///
+/// Stores the result of the Fn and returns the previous value, an empty cell is passed to the Fn as false.
+///
+fn update(cell: &RcOCell<bool>, mut value: RcORefMut<'_, Option<bool>, bool>, f: impl FnOnce(bool) -> bool) -> bool {
+    let previous = value.unwrap_or(false);
+    *value = Some(f(previous));
+    drop(value);
+    cell.changed();
+    return previous;
+}
+
+impl BoolCellExt for RcOCell<bool> {
+    fn toggle(&self) -> bool {
+        return !update(self, self.raw_borrow_mut(), |v| !v);
+    }
+
+    fn try_toggle(&self) -> Result<bool, RcOCellError> {
+        return Ok(!update(self, self.try_raw_borrow_mut()?, |v| !v));
+    }
+
+    fn set_true(&self) -> bool {
+        return update(self, self.raw_borrow_mut(), |_| true);
+    }
+
+    fn try_set_true(&self) -> Result<bool, RcOCellError> {
+        return Ok(update(self, self.try_raw_borrow_mut()?, |_| true));
+    }
+
+    fn set_false(&self) -> bool {
+        return update(self, self.raw_borrow_mut(), |_| false);
+    }
+
+    fn try_set_false(&self) -> Result<bool, RcOCellError> {
+        return Ok(update(self, self.try_raw_borrow_mut()?, |_| false));
+    }
+
+    fn is_true(&self) -> bool {
+        return self.raw_borrow().unwrap_or(false);
+    }
+
+    fn try_is_true(&self) -> Result<bool, RcOCellError> {
+        return Ok(self.try_raw_borrow()?.unwrap_or(false));
+    }
+
+    fn is_false(&self) -> bool {
+        return !self.is_true();
+    }
+
+    fn try_is_false(&self) -> Result<bool, RcOCellError> {
+        return Ok(!self.try_is_true()?);
+    }
+}
+
+impl BoolCellExt for WeakRcOCell<bool> {
+    fn toggle(&self) -> bool {
+        self.try_upgrade()
+            .expect("WeakRcOCell::toggle called on a dropped cell")
+            .toggle()
+    }
+
+    fn try_toggle(&self) -> Result<bool, RcOCellError> {
+        self.try_upgrade()?.try_toggle()
+    }
+
+    fn set_true(&self) -> bool {
+        self.try_upgrade()
+            .expect("WeakRcOCell::set_true called on a dropped cell")
+            .set_true()
+    }
+
+    fn try_set_true(&self) -> Result<bool, RcOCellError> {
+        self.try_upgrade()?.try_set_true()
+    }
+
+    fn set_false(&self) -> bool {
+        self.try_upgrade()
+            .expect("WeakRcOCell::set_false called on a dropped cell")
+            .set_false()
+    }
+
+    fn try_set_false(&self) -> Result<bool, RcOCellError> {
+        self.try_upgrade()?.try_set_false()
+    }
+
+    fn is_true(&self) -> bool {
+        self.try_upgrade()
+            .expect("WeakRcOCell::is_true called on a dropped cell")
+            .is_true()
+    }
+
+    fn try_is_true(&self) -> Result<bool, RcOCellError> {
+        self.try_upgrade()?.try_is_true()
+    }
+
+    fn is_false(&self) -> bool {
+        self.try_upgrade()
+            .expect("WeakRcOCell::is_false called on a dropped cell")
+            .is_false()
+    }
+
+    fn try_is_false(&self) -> Result<bool, RcOCellError> {
+        self.try_upgrade()?.try_is_false()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_empty_is_false() {
+        let x: RcOCell<bool> = RcOCell::new();
+        assert_eq!(x.is_true(), false);
+        assert_eq!(x.is_false(), true);
+        assert_eq!(x.set_false(), false);
+        assert_eq!(x.get_and_clone(), false);
+    }
+
+    #[test]
+    fn test_toggle() {
+        let x: RcOCell<bool> = RcOCell::new();
+        assert_eq!(x.toggle(), true);
+        assert_eq!(x.is_true(), true);
+        assert_eq!(x.toggle(), false);
+        assert_eq!(x.is_false(), true);
+    }
+
+    #[test]
+    fn test_rising_edge() {
+        let dirty: RcOCell<bool> = RcOCell::new();
+        let mut events = 0;
+        for _ in 0..3 {
+            if !dirty.set_true() {
+                events += 1;
+            }
+        }
+        assert_eq!(events, 1);
+        assert_eq!(dirty.set_false(), true);
+        assert_eq!(dirty.set_true(), false);
+    }
+
+    #[test]
+    fn test_try_bool() {
+        let x = RcOCell::from_value(false);
+        let brw = x.borrow_mut();
+        assert_eq!(x.try_toggle().is_err(), true);
+        assert_eq!(x.try_set_true().is_err(), true);
+        assert_eq!(x.try_is_true().is_err(), true);
+        drop(brw);
+        let brw = x.borrow();
+        assert_eq!(x.try_set_false().is_err(), true);
+        assert_eq!(x.try_is_false().unwrap(), true);
+        drop(brw);
+        assert_eq!(x.try_toggle().unwrap(), true);
+
+        let w = x.downgrade();
+        assert_eq!(w.set_false(), true);
+        drop(x);
+        assert_eq!(w.try_is_true().is_err(), true);
+    }
+}
@@ -0,0 +1,218 @@
+//!
+//! Records where the outstanding borrows of a cell were taken (feature `debug-borrow-tracking`).
+//!
+//! Every guard registers the location of the call that created it in the shared state of the cell
+//! and removes it again when it is dropped, so a failing borrow can report who is still holding the value.
+//! Locations are passed down with track_caller, the attribute is only added to the borrowing methods with the feature enabled.
+//!
+use std::cell::Cell;
+use std::panic::Location;
+use crate::{shared, RcOCell, WeakRcOCell};
+
+thread_local! {
+    ///
+    /// Location of the borrow that conflicted with the latest failed borrow on this thread, see conflicting_location.
+    ///
+    static LAST_CONFLICT: Cell<Option<&'static Location<'static>>> = const { Cell::new(None) };
+}
+
+///
+/// An outstanding borrow of a cell as seen by outstanding_borrows.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BorrowInfo {
+    ///
+    /// Increases with every borrow on the thread, so borrows can be ordered by the time they were taken.
+    ///
+    pub sequence: u64,
+    ///
+    /// Location of the call that created the guard.
+    /// Methods that borrow the value internally report their own location inside this crate.
+    ///
+    pub location: &'static Location<'static>,
+    ///
+    /// True for a mutable borrow.
+    ///
+    pub mutable: bool,
+}
+
+///
+/// Registers a new borrow of the cell at the location of the caller, returns its sequence number.
+///
+#[track_caller]
+pub(crate) fn borrowed<T>(cell: &RcOCell<T>, mutable: bool) -> u64 {
+    let info = BorrowInfo {sequence: shared::next_id(), location: Location::caller(), mutable};
+    shared::with_state(&cell.rc, |state| state.borrows.push(info));
+    return info.sequence;
+}
+
+///
+/// Removes the borrow when its guard is dropped.
+///
+pub(crate) fn released<T>(cell: &RcOCell<T>, sequence: u64) {
+    shared::with_existing_state(&cell.rc, |state| state.borrows.retain(|b| b.sequence != sequence));
+}
+
+///
+/// Marks the borrow as shared when its guard is downgraded, it keeps the location it was taken at.
+///
+pub(crate) fn downgraded<T>(cell: &RcOCell<T>, sequence: u64) {
+    shared::with_existing_state(&cell.rc, |state| {
+        for b in state.borrows.iter_mut().filter(|b| b.sequence == sequence) {
+            b.mutable = false;
+        }
+    });
+}
+
+///
+/// Remembers the location of the oldest outstanding borrow of the cell after borrowing it failed.
+///
+pub(crate) fn conflicted<T>(cell: &RcOCell<T>) {
+    let location = cell.outstanding_borrows().first().map(|b| b.location);
+    LAST_CONFLICT.with(|c| c.set(location));
+}
+
+///
+/// Forgets the remembered location, for borrow errors that did not come from a cell.
+///
+pub(crate) fn clear_conflict() {
+    let _ = LAST_CONFLICT.try_with(|c| c.set(None));
+}
+
+///
+/// Returns the remembered location, see conflicting_location.
+///
+pub(crate) fn last_conflict() -> Option<&'static Location<'static>> {
+    return LAST_CONFLICT.try_with(|c| c.get()).ok().flatten();
+}
+
+///
+/// Formats the outstanding borrows for a panic message, None if there are none.
+///
+pub(crate) fn describe<T>(cell: &RcOCell<T>) -> Option<String> {
+    let borrows = cell.outstanding_borrows();
+    if borrows.is_empty() {
+        return None;
+    }
+
+    let list: Vec<String> = borrows.iter()
+        .map(|b| format!("{} borrow #{} at {}", if b.mutable { "mutable" } else { "shared" }, b.sequence, b.location))
+        .collect();
+    return Some(list.join(", "));
+}
+
+impl <T> RcOCell<T> {
+    ///
+    /// Returns the borrows of the value whose guards were not dropped yet, oldest first.
+    /// Guards converted with into_std are not tracked anymore.
+    ///
+    pub fn outstanding_borrows(&self) -> Vec<BorrowInfo> {
+        return shared::with_existing_state(&self.rc, |state| state.borrows.clone()).unwrap_or_default();
+    }
+}
+
+impl <T> WeakRcOCell<T> {
+    ///
+    /// Returns the borrows of the value whose guards were not dropped yet, empty if the cell was dropped.
+    ///
+    pub fn outstanding_borrows(&self) -> Vec<BorrowInfo> {
+        return self.try_upgrade().map(|cell| cell.outstanding_borrows()).unwrap_or_default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_outstanding_borrows() {
+        let cell = RcOCell::from_value(1u32);
+        let first_line = line!() + 1;
+        let a = cell.borrow();
+        let second_line = line!() + 1;
+        let b = cell.try_borrow().unwrap();
+
+        let borrows = cell.outstanding_borrows();
+        assert_eq!(borrows.len(), 2);
+        assert_eq!(borrows[0].location.line(), first_line);
+        assert_eq!(borrows[1].location.line(), second_line);
+        assert_eq!(borrows[0].location.file(), file!());
+        assert_eq!(borrows[0].sequence < borrows[1].sequence, true);
+        assert_eq!(borrows.iter().any(|b| b.mutable), false);
+
+        let msg = match cell.catch(|c| { c.borrow_mut(); }) {
+            Err(e) => e.downcast::<String>().map(|s| *s).unwrap_or_default(),
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(msg.contains(&format!("{}:{}", file!(), first_line)), true);
+        assert_eq!(msg.contains(&format!("{}:{}", file!(), second_line)), true);
+
+        drop(a);
+        assert_eq!(cell.outstanding_borrows().len(), 1);
+        drop(b);
+        assert_eq!(cell.outstanding_borrows().is_empty(), true);
+        assert_eq!(cell.downgrade().outstanding_borrows().is_empty(), true);
+    }
+
+    #[test]
+    fn test_conflicting_location() {
+        let cell = RcOCell::from_value(1u32);
+        let line = line!() + 1;
+        let a = cell.borrow();
+        let b = cell.borrow();
+        let e = match cell.try_borrow_mut() {
+            Err(RcOCellError::BorrowError(e)) => e,
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(e.conflicting_location().map(|l| (l.file(), l.line())), Some((file!(), line)));
+        assert_eq!(format!("{}", e).starts_with(&format!("RefCell already borrowed (conflicting borrow at {}:{}:", file!(), line)), true);
+        drop((a, b));
+
+        let line = line!() + 1;
+        let guard = cell.borrow_mut();
+        match cell.try_borrow() {
+            Err(RcOCellError::BorrowError(e)) => assert_eq!(e.conflicting_location().unwrap().line(), line),
+            _ => panic!("unexpected"),
+        };
+        match cell.try_set(2) {
+            Err(RcOCellError::BorrowError(e)) => assert_eq!(e.conflicting_location().unwrap().line(), line),
+            _ => panic!("unexpected"),
+        };
+        drop(guard);
+
+        let plain = std::cell::RefCell::new(1u32);
+        let brw = plain.borrow();
+        match RcOCellError::from(plain.try_borrow_mut().unwrap_err()) {
+            RcOCellError::BorrowError(e) => assert_eq!(e.conflicting_location(), None),
+            _ => panic!("unexpected"),
+        };
+        drop(brw);
+    }
+
+    #[test]
+    fn test_outstanding_borrows_mut() {
+        let cell = RcOCell::from_value((1u32, 2u32));
+        let line = line!() + 1;
+        let guard = cell.borrow_mut();
+        let borrows = cell.outstanding_borrows();
+        assert_eq!((borrows.len(), borrows[0].mutable, borrows[0].location.line()), (1, true, line));
+
+        let guard = RcORefMut::downgrade(guard).unwrap_or_else(|_| panic!("unexpected"));
+        let copy = RcORef::duplicate(&guard);
+        let borrows = cell.outstanding_borrows();
+        assert_eq!(borrows.len(), 2);
+        assert_eq!((borrows[0].mutable, borrows[0].location.line()), (false, line));
+        drop((guard, copy));
+
+        let (a, b) = cell.borrow_mut_split(|v| (&mut v.0, &mut v.1));
+        assert_eq!(cell.outstanding_borrows().len(), 2);
+        drop(a);
+        assert_eq!(cell.outstanding_borrows().len(), 1);
+        drop(b);
+        assert_eq!(cell.outstanding_borrows().is_empty(), true);
+
+        let std_ref: std::cell::Ref<(u32, u32)> = cell.borrow().into();
+        assert_eq!(cell.outstanding_borrows().is_empty(), true);
+        drop(std_ref);
+    }
+}
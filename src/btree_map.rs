@@ -0,0 +1,338 @@
+use std::borrow::Borrow;
+use std::collections::BTreeMap;
+use std::ops::RangeBounds;
+use crate::{RcOCell, RcOCellError, RcORefMut};
+
+///
+/// Operations for `RcOCell<BTreeMap<K, V>>`, i.e. ordered indexes.
+///
+/// The modifying operations treat an empty cell like an empty map, insert creates the map on first use.
+/// Observers are notified if the map changed, a map that was created counts as a change.
+/// The reading operations never panic because of the cell, they treat a cell whose value is borrowed mutably like an empty cell.
+/// The range operations panic like BTreeMap::range if the range starts after it ends.
+///
+pub trait BTreeMapCellExt<K, V> {
+    ///
+    /// Inserts the entry and returns the previous value of the key, an empty cell gets a new map.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn insert(&self, k: K, v: V) -> Option<V>;
+
+    ///
+    /// Inserts the entry and returns the previous value of the key, an empty cell gets a new map.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    fn try_insert(&self, k: K, v: V) -> Result<Option<V>, RcOCellError>;
+
+    ///
+    /// Removes the entry of the key and returns its value, None for an empty cell.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn remove<Q>(&self, k: &Q) -> Option<V>
+        where K: Borrow<Q>, Q: Ord + ?Sized;
+
+    ///
+    /// Removes the entry of the key and returns its value, None for an empty cell.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    fn try_remove<Q>(&self, k: &Q) -> Result<Option<V>, RcOCellError>
+        where K: Borrow<Q>, Q: Ord + ?Sized;
+
+    ///
+    /// Returns a clone of the value of the key.
+    /// Never panics, returns None if the cell is empty or the value is borrowed mutably somewhere.
+    ///
+    fn get_cloned<Q>(&self, k: &Q) -> Option<V>
+        where K: Borrow<Q>, Q: Ord + ?Sized, V: Clone;
+
+    ///
+    /// Returns true if the map contains the key.
+    /// Never panics, returns false if the cell is empty or the value is borrowed mutably somewhere.
+    ///
+    fn contains_key<Q>(&self, k: &Q) -> bool
+        where K: Borrow<Q>, Q: Ord + ?Sized;
+
+    ///
+    /// Returns a clone of the smallest key.
+    /// Never panics, returns None if the cell is empty or the value is borrowed mutably somewhere.
+    ///
+    fn first_key_cloned(&self) -> Option<K>
+        where K: Clone;
+
+    ///
+    /// Returns a clone of the largest key.
+    /// Never panics, returns None if the cell is empty or the value is borrowed mutably somewhere.
+    ///
+    fn last_key_cloned(&self) -> Option<K>
+        where K: Clone;
+
+    ///
+    /// Removes the entry with the smallest key and returns it, None for an empty cell.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn pop_first(&self) -> Option<(K, V)>;
+
+    ///
+    /// Removes the entry with the smallest key and returns it, None for an empty cell.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    fn try_pop_first(&self) -> Result<Option<(K, V)>, RcOCellError>;
+
+    ///
+    /// Removes the entry with the largest key and returns it, None for an empty cell.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn pop_last(&self) -> Option<(K, V)>;
+
+    ///
+    /// Removes the entry with the largest key and returns it, None for an empty cell.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    fn try_pop_last(&self) -> Result<Option<(K, V)>, RcOCellError>;
+
+    ///
+    /// Returns clones of the entries in the range in ascending order of their keys.
+    /// Returns an empty Vec if the cell is empty or the value is borrowed mutably somewhere.
+    ///
+    fn range_cloned<R>(&self, r: R) -> Vec<(K, V)>
+        where R: RangeBounds<K>, K: Clone, V: Clone;
+
+    ///
+    /// Calls the Fn with the entries in the range in ascending order of their keys without cloning them.
+    /// The value is borrowed while the Fn runs, so the Fn cannot modify the cell.
+    /// Does nothing if the cell is empty or the value is borrowed mutably somewhere.
+    ///
+    fn range_for_each<R, F>(&self, r: R, f: F)
+        where R: RangeBounds<K>, F: FnMut(&K, &V);
+
+    ///
+    /// Calls the Fn with the entries in the range, see range_for_each.
+    /// Fails without calling the Fn if the value is borrowed mutably somewhere.
+    ///
+    fn try_range_for_each<R, F>(&self, r: R, f: F) -> Result<(), RcOCellError>
+        where R: RangeBounds<K>, F: FnMut(&K, &V);
+}
+
+///
+/// Runs the Fn with the map, an empty cell gets a new map first.
+///
+fn update<K, V, R>(cell: &RcOCell<BTreeMap<K, V>>, mut value: RcORefMut<'_, Option<BTreeMap<K, V>>, BTreeMap<K, V>>, f: impl FnOnce(&mut BTreeMap<K, V>) -> R) -> R {
+    let result = f(value.get_or_insert_with(BTreeMap::new));
+    drop(value);
+    cell.changed();
+    return result;
+}
+
+///
+/// Runs the Fn with the map if the cell holds one, notifies the observers if the Fn returns Some.
+///
+fn remove_with<K, V, R>(cell: &RcOCell<BTreeMap<K, V>>, mut value: RcORefMut<'_, Option<BTreeMap<K, V>>, BTreeMap<K, V>>, f: impl FnOnce(&mut BTreeMap<K, V>) -> Option<R>) -> Option<R> {
+    let removed = value.as_mut().and_then(f);
+    drop(value);
+    if removed.is_some() {
+        cell.changed();
+    }
+
+    return removed;
+}
+
+impl <K: Ord, V> BTreeMapCellExt<K, V> for RcOCell<BTreeMap<K, V>> {
+    fn insert(&self, k: K, v: V) -> Option<V> {
+        return update(self, self.raw_borrow_mut(), |map| map.insert(k, v));
+    }
+
+    fn try_insert(&self, k: K, v: V) -> Result<Option<V>, RcOCellError> {
+        return Ok(update(self, self.try_raw_borrow_mut()?, |map| map.insert(k, v)));
+    }
+
+    fn remove<Q>(&self, k: &Q) -> Option<V>
+        where K: Borrow<Q>, Q: Ord + ?Sized
+    {
+        return remove_with(self, self.raw_borrow_mut(), |map| map.remove(k));
+    }
+
+    fn try_remove<Q>(&self, k: &Q) -> Result<Option<V>, RcOCellError>
+        where K: Borrow<Q>, Q: Ord + ?Sized
+    {
+        return Ok(remove_with(self, self.try_raw_borrow_mut()?, |map| map.remove(k)));
+    }
+
+    fn get_cloned<Q>(&self, k: &Q) -> Option<V>
+        where K: Borrow<Q>, Q: Ord + ?Sized, V: Clone
+    {
+        return self.map_or(None, |map| map.get(k).cloned());
+    }
+
+    fn contains_key<Q>(&self, k: &Q) -> bool
+        where K: Borrow<Q>, Q: Ord + ?Sized
+    {
+        return self.is_some_and(|map| map.contains_key(k));
+    }
+
+    fn first_key_cloned(&self) -> Option<K>
+        where K: Clone
+    {
+        return self.map_or(None, |map| map.keys().next().cloned());
+    }
+
+    fn last_key_cloned(&self) -> Option<K>
+        where K: Clone
+    {
+        return self.map_or(None, |map| map.keys().next_back().cloned());
+    }
+
+    fn pop_first(&self) -> Option<(K, V)> {
+        return remove_with(self, self.raw_borrow_mut(), BTreeMap::pop_first);
+    }
+
+    fn try_pop_first(&self) -> Result<Option<(K, V)>, RcOCellError> {
+        return Ok(remove_with(self, self.try_raw_borrow_mut()?, BTreeMap::pop_first));
+    }
+
+    fn pop_last(&self) -> Option<(K, V)> {
+        return remove_with(self, self.raw_borrow_mut(), BTreeMap::pop_last);
+    }
+
+    fn try_pop_last(&self) -> Result<Option<(K, V)>, RcOCellError> {
+        return Ok(remove_with(self, self.try_raw_borrow_mut()?, BTreeMap::pop_last));
+    }
+
+    fn range_cloned<R>(&self, r: R) -> Vec<(K, V)>
+        where R: RangeBounds<K>, K: Clone, V: Clone
+    {
+        return self.map_or(Vec::new(), |map| map.range(r).map(|(k, v)| (k.clone(), v.clone())).collect());
+    }
+
+    fn range_for_each<R, F>(&self, r: R, f: F)
+        where R: RangeBounds<K>, F: FnMut(&K, &V)
+    {
+        let _ = self.try_range_for_each(r, f);
+    }
+
+    fn try_range_for_each<R, F>(&self, r: R, mut f: F) -> Result<(), RcOCellError>
+        where R: RangeBounds<K>, F: FnMut(&K, &V)
+    {
+        if let Some(map) = self.try_raw_borrow()?.as_ref() {
+            map.range(r).for_each(|(k, v)| f(k, v));
+        }
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::ops::Bound;
+    use crate::*;
+
+    fn index() -> RcOCell<BTreeMap<u32, String>> {
+        let cell = RcOCell::new();
+        for k in [5, 1, 3, 9, 7] {
+            cell.insert(k, format!("v{}", k));
+        }
+
+        return cell;
+    }
+
+    #[test]
+    fn test_btree_map_basics() {
+        let cell = index();
+        assert_eq!(cell.insert(3, "x".to_string()), Some("v3".to_string()));
+        assert_eq!(cell.try_insert(4, "v4".to_string()).unwrap(), None);
+        assert_eq!(cell.get_cloned(&3), Some("x".to_string()));
+        assert_eq!(cell.contains_key(&4), true);
+        assert_eq!(cell.remove(&4), Some("v4".to_string()));
+        assert_eq!(cell.try_remove(&4).unwrap(), None);
+        assert_eq!(cell.first_key_cloned(), Some(1));
+        assert_eq!(cell.last_key_cloned(), Some(9));
+        assert_eq!(cell.pop_first(), Some((1, "v1".to_string())));
+        assert_eq!(cell.try_pop_last().unwrap(), Some((9, "v9".to_string())));
+        assert_eq!(cell.first_key_cloned(), Some(3));
+        assert_eq!(cell.try_pop_first().unwrap(), Some((3, "x".to_string())));
+        assert_eq!(cell.pop_last(), Some((7, "v7".to_string())));
+
+        let names: RcOCell<BTreeMap<String, u32>> = RcOCell::new();
+        names.insert("b".to_string(), 2);
+        assert_eq!(names.get_cloned("b"), Some(2));
+        assert_eq!(names.contains_key("a"), false);
+        assert_eq!(names.remove("b"), Some(2));
+    }
+
+    #[test]
+    fn test_btree_map_ranges() {
+        let cell = index();
+        let keys = |entries: Vec<(u32, String)>| entries.into_iter().map(|(k, _)| k).collect::<Vec<_>>();
+        assert_eq!(keys(cell.range_cloned(3..7)), vec![3, 5]);
+        assert_eq!(keys(cell.range_cloned(3..=7)), vec![3, 5, 7]);
+        assert_eq!(keys(cell.range_cloned(..5)), vec![1, 3]);
+        assert_eq!(keys(cell.range_cloned(6..)), vec![7, 9]);
+        assert_eq!(keys(cell.range_cloned(..)), vec![1, 3, 5, 7, 9]);
+        assert_eq!(keys(cell.range_cloned((Bound::Excluded(3), Bound::Excluded(9)))), vec![5, 7]);
+        assert_eq!(cell.range_cloned(10..20).is_empty(), true);
+
+        let mut seen = Vec::new();
+        cell.range_for_each(2..=5, |k, v| seen.push(format!("{}={}", k, v)));
+        assert_eq!(seen, vec!["3=v3".to_string(), "5=v5".to_string()]);
+        let mut seen = Vec::new();
+        cell.try_range_for_each((Bound::Excluded(5), Bound::Unbounded), |k, _| seen.push(*k)).unwrap();
+        assert_eq!(seen, vec![7, 9]);
+    }
+
+    #[test]
+    fn test_btree_map_empty() {
+        let cell: RcOCell<BTreeMap<u32, u32>> = RcOCell::new();
+        assert_eq!(cell.get_cloned(&1), None);
+        assert_eq!(cell.contains_key(&1), false);
+        assert_eq!(cell.first_key_cloned(), None);
+        assert_eq!(cell.last_key_cloned(), None);
+        assert_eq!(cell.range_cloned(..).is_empty(), true);
+        assert_eq!(cell.remove(&1), None);
+        assert_eq!(cell.pop_first(), None);
+        assert_eq!(cell.try_pop_last().unwrap(), None);
+        cell.try_range_for_each(.., |_, _| panic!("unexpected")).unwrap();
+        assert_eq!(cell.is_none(), true);
+
+        cell.insert(1, 1);
+        assert_eq!(cell.pop_last(), Some((1, 1)));
+        assert_eq!(cell.is_some(), true);
+        assert_eq!(cell.range_cloned(..).is_empty(), true);
+    }
+
+    #[test]
+    fn test_btree_map_borrowed() {
+        let cell = index();
+        let changes = RcOCell::from_value(0u32);
+        let changes2 = changes.clone();
+        cell.on_set(move |_| { changes2.set(changes2.get_and_clone() + 1); });
+
+        let brw = cell.borrow();
+        assert_eq!(cell.get_cloned(&1), Some("v1".to_string()));
+        match cell.try_insert(2, "v2".to_string()) {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(cell.try_remove(&1).is_err(), true);
+        assert_eq!(cell.try_pop_first().is_err(), true);
+        assert_eq!(cell.catch(|c| c.pop_last()).is_err(), true);
+        drop(brw);
+
+        let brw = cell.borrow_mut();
+        assert_eq!(cell.get_cloned(&1), None);
+        assert_eq!(cell.first_key_cloned(), None);
+        assert_eq!(cell.range_cloned(..).is_empty(), true);
+        cell.range_for_each(.., |_, _| panic!("unexpected"));
+        match cell.try_range_for_each(.., |_, _| {}) {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        drop(brw);
+
+        assert_eq!(changes.get_and_clone(), 0);
+        cell.remove(&2);
+        assert_eq!(changes.get_and_clone(), 0);
+        cell.remove(&1);
+        cell.insert(2, "v2".to_string());
+        assert_eq!(changes.get_and_clone(), 2);
+    }
+}
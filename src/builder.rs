@@ -0,0 +1,165 @@
+use std::borrow::Cow;
+use std::fmt::{Debug, Formatter};
+use crate::RcOCell;
+
+type SetFn<T> = Box<dyn Fn(&T)>;
+type ClearFn = Box<dyn Fn()>;
+
+///
+/// Configures a cell before it is created, obtained from RcOCell::builder.
+///
+/// The cell is only allocated by build. The builder holds boxed closures and therefore
+/// cannot be used in const or static initializers, use `rco_thread_local!` for cells that should exist once per thread.
+///
+/// ```
+/// use std::cell::Cell;
+/// use std::rc::Rc;
+/// use rco_cell::RcOCell;
+///
+/// let sets = Rc::new(Cell::new(0));
+/// let sets_clone = sets.clone();
+/// let cell = RcOCell::builder()
+///     .value(1u32)
+///     .on_set(move |_| sets_clone.set(sets_clone.get() + 1))
+///     .build();
+/// cell.set(2);
+/// assert_eq!(sets.get(), 1);
+/// ```
+///
+pub struct RcOCellBuilder<T> {
+    value: Option<T>,
+    label: Option<Cow<'static, str>>,
+    on_set: Vec<SetFn<T>>,
+    on_clear: Vec<ClearFn>,
+}
+
+impl <T: 'static> RcOCell<T> {
+    ///
+    /// Returns a builder to configure a new cell in one expression.
+    ///
+    pub fn builder() -> RcOCellBuilder<T> {
+        return RcOCellBuilder {value: None, label: None, on_set: Vec::new(), on_clear: Vec::new()};
+    }
+}
+
+impl <T: 'static> RcOCellBuilder<T> {
+    ///
+    /// Sets the initial value of the cell.
+    ///
+    pub fn value(mut self, value: T) -> RcOCellBuilder<T> {
+        self.value = Some(value);
+        return self;
+    }
+
+    ///
+    /// Sets the initial value of the cell, None leaves the cell empty.
+    ///
+    pub fn option(mut self, value: Option<T>) -> RcOCellBuilder<T> {
+        self.value = value;
+        return self;
+    }
+
+    ///
+    /// Sets the label of the cell, see RcOCell::with_label.
+    ///
+    pub fn label(mut self, label: impl Into<Cow<'static, str>>) -> RcOCellBuilder<T> {
+        self.label = Some(label.into());
+        return self;
+    }
+
+    ///
+    /// Registers a Fn as with RcOCell::on_set.
+    /// Storing the initial value does not call it.
+    ///
+    pub fn on_set<F>(mut self, f: F) -> RcOCellBuilder<T>
+        where F: Fn(&T) + 'static
+    {
+        self.on_set.push(Box::new(f));
+        return self;
+    }
+
+    ///
+    /// Registers a Fn as with RcOCell::on_clear.
+    ///
+    pub fn on_clear<F>(mut self, f: F) -> RcOCellBuilder<T>
+        where F: Fn() + 'static
+    {
+        self.on_clear.push(Box::new(f));
+        return self;
+    }
+
+    ///
+    /// Creates the cell.
+    ///
+    #[track_caller]
+    pub fn build(self) -> RcOCell<T> {
+        let cell = RcOCell::from_option(self.value);
+        if let Some(label) = self.label {
+            cell.set_label(label);
+        }
+
+        for f in self.on_set {
+            cell.on_set(f);
+        }
+
+        for f in self.on_clear {
+            cell.on_clear(f);
+        }
+
+        return cell;
+    }
+}
+
+impl <T: Debug> Debug for RcOCellBuilder<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RcOCellBuilder")
+            .field("value", &self.value)
+            .field("label", &self.label)
+            .field("on_set", &self.on_set.len())
+            .field("on_clear", &self.on_clear.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use crate::*;
+
+    #[test]
+    fn test_builder_observers() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let set_log = log.clone();
+        let clear_log = log.clone();
+        let cell = RcOCell::builder()
+            .value(1u32)
+            .on_set(move |v| set_log.borrow_mut().push(format!("set {}", v)))
+            .on_clear(move || clear_log.borrow_mut().push("clear".to_string()))
+            .build();
+        assert_eq!(cell.get_and_clone(), 1);
+        assert_eq!(log.borrow().len(), 0);
+        assert_eq!(cell.observer_count(), 2);
+
+        cell.set(2);
+        cell.clear();
+        assert_eq!(*log.borrow(), vec!["set 2".to_string(), "clear".to_string()]);
+    }
+
+    #[test]
+    fn test_builder_label() {
+        let cell: RcOCell<u32> = RcOCell::builder().label("player_score").build();
+        assert_eq!(format!("{:?}", cell), "RcOCell { label: \"player_score\", value: None }");
+        let r = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| { cell.borrow(); }));
+        assert_eq!(r.unwrap_err().downcast_ref::<String>().unwrap(), "RcOCell[player_score]: No value present");
+    }
+
+    #[test]
+    fn test_builder_option() {
+        let cell: RcOCell<u32> = RcOCell::builder().option(None).build();
+        assert_eq!(cell.is_none(), true);
+        let cell = RcOCell::builder().value(1u32).option(Some(3)).build();
+        assert_eq!(cell.get_and_clone(), 3);
+        assert_eq!(cell.observer_count(), 0);
+    }
+}
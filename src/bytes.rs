@@ -0,0 +1,266 @@
+use std::fmt::Write;
+use crate::{RcOCell, RcOCellError, RcORefMut};
+
+///
+/// Operations for `RcOCell<Vec<u8>>` used as a shared byte buffer, i.e. between a decoder and its consumer.
+///
+/// The modifying operations treat an empty cell like an empty buffer and leave a buffer in the cell, like StringCellExt.
+/// Observers are notified about every modification.
+/// The reading operations never panic, they treat a cell whose value is borrowed mutably like an empty cell.
+///
+pub trait BytesCellExt {
+    ///
+    /// Appends the bytes, an empty cell gets a new buffer.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn append_bytes(&self, bytes: &[u8]);
+
+    ///
+    /// Appends the bytes, an empty cell gets a new buffer.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    fn try_append_bytes(&self, bytes: &[u8]) -> Result<(), RcOCellError>;
+
+    ///
+    /// Takes the buffer out of the cell and leaves a new empty buffer without capacity in its place.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn take_bytes(&self) -> Vec<u8>;
+
+    ///
+    /// Takes the buffer out of the cell and leaves a new empty buffer without capacity in its place.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    fn try_take_bytes(&self) -> Result<Vec<u8>, RcOCellError>;
+
+    ///
+    /// Takes the bytes out of the cell and leaves an empty buffer with the capacity of the previous one in its place,
+    /// so a producer that refills the buffer does not have to grow it again.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn take_bytes_keep_capacity(&self) -> Vec<u8>;
+
+    ///
+    /// Takes the bytes out of the cell, see take_bytes_keep_capacity.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    fn try_take_bytes_keep_capacity(&self) -> Result<Vec<u8>, RcOCellError>;
+
+    ///
+    /// Removes the first n bytes from the buffer and returns them, i.e. to consume a framed prefix.
+    /// Returns None and leaves the buffer untouched if it holds fewer than n bytes,
+    /// so a consumer can wait for the rest of a frame without putting a partial frame back.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn split_off_front(&self, n: usize) -> Option<Vec<u8>>;
+
+    ///
+    /// Removes the first n bytes from the buffer and returns them, see split_off_front.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    fn try_split_off_front(&self, n: usize) -> Result<Option<Vec<u8>>, RcOCellError>;
+
+    ///
+    /// Returns the number of bytes in the buffer.
+    /// Never panics, returns 0 if the cell is empty or the value is borrowed mutably somewhere.
+    ///
+    fn byte_len(&self) -> usize;
+
+    ///
+    /// Empties the buffer but keeps its allocation, an empty cell gets a new buffer.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn clear_bytes(&self);
+
+    ///
+    /// Empties the buffer but keeps its allocation, an empty cell gets a new buffer.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    fn try_clear_bytes(&self) -> Result<(), RcOCellError>;
+
+    ///
+    /// Returns the first max_len bytes as lower case hex for logging, followed by "..." if the buffer holds more.
+    /// Never panics, returns an empty String if the cell is empty or the value is borrowed mutably somewhere.
+    ///
+    fn as_hex(&self, max_len: usize) -> String;
+}
+
+///
+/// Runs the Fn with the buffer, an empty cell gets a new buffer first.
+///
+fn update<R>(cell: &RcOCell<Vec<u8>>, mut value: RcORefMut<'_, Option<Vec<u8>>, Vec<u8>>, f: impl FnOnce(&mut Vec<u8>) -> R) -> R {
+    let result = f(value.get_or_insert_with(Vec::new));
+    drop(value);
+    cell.changed();
+    return result;
+}
+
+///
+/// Removes the first n bytes if there are at least n.
+///
+fn split_front(cell: &RcOCell<Vec<u8>>, value: RcORefMut<'_, Option<Vec<u8>>, Vec<u8>>, n: usize) -> Option<Vec<u8>> {
+    if value.as_ref().map_or(0, |bytes| bytes.len()) < n {
+        return None;
+    }
+
+    return Some(update(cell, value, |bytes| bytes.drain(..n).collect()));
+}
+
+impl BytesCellExt for RcOCell<Vec<u8>> {
+    fn append_bytes(&self, bytes: &[u8]) {
+        update(self, self.raw_borrow_mut(), |v| v.extend_from_slice(bytes));
+    }
+
+    fn try_append_bytes(&self, bytes: &[u8]) -> Result<(), RcOCellError> {
+        update(self, self.try_raw_borrow_mut()?, |v| v.extend_from_slice(bytes));
+        return Ok(());
+    }
+
+    fn take_bytes(&self) -> Vec<u8> {
+        return update(self, self.raw_borrow_mut(), std::mem::take);
+    }
+
+    fn try_take_bytes(&self) -> Result<Vec<u8>, RcOCellError> {
+        return Ok(update(self, self.try_raw_borrow_mut()?, std::mem::take));
+    }
+
+    fn take_bytes_keep_capacity(&self) -> Vec<u8> {
+        return update(self, self.raw_borrow_mut(), |v| v.split_off(0));
+    }
+
+    fn try_take_bytes_keep_capacity(&self) -> Result<Vec<u8>, RcOCellError> {
+        return Ok(update(self, self.try_raw_borrow_mut()?, |v| v.split_off(0)));
+    }
+
+    fn split_off_front(&self, n: usize) -> Option<Vec<u8>> {
+        return split_front(self, self.raw_borrow_mut(), n);
+    }
+
+    fn try_split_off_front(&self, n: usize) -> Result<Option<Vec<u8>>, RcOCellError> {
+        return Ok(split_front(self, self.try_raw_borrow_mut()?, n));
+    }
+
+    fn byte_len(&self) -> usize {
+        return self.map_or(0, |v| v.len());
+    }
+
+    fn clear_bytes(&self) {
+        update(self, self.raw_borrow_mut(), |v| v.clear());
+    }
+
+    fn try_clear_bytes(&self) -> Result<(), RcOCellError> {
+        update(self, self.try_raw_borrow_mut()?, |v| v.clear());
+        return Ok(());
+    }
+
+    fn as_hex(&self, max_len: usize) -> String {
+        return self.map_or(String::new(), |v| {
+            let mut hex = String::with_capacity(v.len().min(max_len) * 2 + 3);
+            for b in v.iter().take(max_len) {
+                let _ = write!(hex, "{:02x}", b);
+            }
+
+            if v.len() > max_len {
+                hex.push_str("...");
+            }
+
+            return hex;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_bytes_cell() {
+        let buffer: RcOCell<Vec<u8>> = RcOCell::new();
+        assert_eq!(buffer.byte_len(), 0);
+        assert_eq!(buffer.as_hex(4), "");
+        assert_eq!(buffer.split_off_front(1), None);
+        assert_eq!(buffer.split_off_front(0), Some(Vec::new()));
+
+        buffer.clear();
+        buffer.append_bytes(&[0xde, 0xad]);
+        buffer.try_append_bytes(&[0xbe, 0xef, 0x01]).unwrap();
+        assert_eq!(buffer.byte_len(), 5);
+        assert_eq!(buffer.as_hex(4), "deadbeef...");
+        assert_eq!(buffer.as_hex(5), "deadbeef01");
+
+        assert_eq!(buffer.split_off_front(2), Some(vec![0xde, 0xad]));
+        assert_eq!(buffer.split_off_front(4), None);
+        assert_eq!(buffer.byte_len(), 3);
+        assert_eq!(buffer.try_split_off_front(3).unwrap(), Some(vec![0xbe, 0xef, 0x01]));
+        assert_eq!(buffer.byte_len(), 0);
+
+        buffer.append_bytes(&[1, 2, 3]);
+        assert_eq!(buffer.take_bytes(), vec![1, 2, 3]);
+        assert_eq!(buffer.borrow().capacity(), 0);
+
+        buffer.append_bytes(&[1; 64]);
+        let capacity = buffer.borrow().capacity();
+        assert_eq!(buffer.take_bytes_keep_capacity(), vec![1; 64]);
+        assert_eq!(buffer.byte_len(), 0);
+        assert_eq!(buffer.borrow().capacity(), capacity);
+
+        buffer.append_bytes(&[1; 8]);
+        buffer.clear_bytes();
+        assert_eq!(buffer.borrow().capacity(), capacity);
+        buffer.clear();
+        buffer.try_clear_bytes().unwrap();
+        assert_eq!(buffer.get_and_clone(), Vec::<u8>::new());
+        buffer.clear();
+        assert_eq!(buffer.try_take_bytes().unwrap(), Vec::<u8>::new());
+        assert_eq!(buffer.is_some(), true);
+    }
+
+    #[test]
+    fn test_bytes_cell_borrowed() {
+        let buffer = RcOCell::from_value(vec![1u8, 2, 3]);
+        let brw = buffer.borrow();
+        assert_eq!(buffer.byte_len(), 3);
+        assert_eq!(buffer.as_hex(8), "010203");
+        match buffer.try_append_bytes(&[4]) {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(buffer.try_split_off_front(1).is_err(), true);
+        assert_eq!(buffer.try_take_bytes().is_err(), true);
+        assert_eq!(buffer.try_take_bytes_keep_capacity().is_err(), true);
+        assert_eq!(buffer.try_clear_bytes().is_err(), true);
+        assert_eq!(buffer.catch(|b| b.split_off_front(1)).is_err(), true);
+        drop(brw);
+
+        let brw = buffer.borrow_mut();
+        assert_eq!(buffer.byte_len(), 0);
+        assert_eq!(buffer.as_hex(8), "");
+        drop(brw);
+        assert_eq!(buffer.get_and_clone(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_bytes_produce_consume() {
+        let buffer: RcOCell<Vec<u8>> = RcOCell::new();
+        let mut stream = Vec::new();
+        for frame in [&b"abc"[..], b"", b"defgh", b"i"] {
+            stream.push(frame.len() as u8);
+            stream.extend_from_slice(frame);
+        }
+
+        let mut frames = Vec::new();
+        for chunk in stream.chunks(3) {
+            buffer.append_bytes(chunk);
+            while let Some(len) = buffer.map_or(None, |b| b.first().copied()) {
+                let Some(frame) = buffer.split_off_front(1 + len as usize) else {
+                    break;
+                };
+
+                frames.push(frame[1..].to_vec());
+            }
+        }
+
+        assert_eq!(frames, vec![b"abc".to_vec(), Vec::new(), b"defgh".to_vec(), b"i".to_vec()]);
+        assert_eq!(buffer.byte_len(), 0);
+    }
+}
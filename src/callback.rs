@@ -0,0 +1,211 @@
+use crate::{shared, RcOCell, RcOCellError};
+
+///
+/// Takes the handler out of the cell together with the version of the cell, so a replacement by the handler can be detected.
+///
+fn take<H>(cell: &RcOCell<H>) -> Result<Option<(H, u64)>, RcOCellError> {
+    cell.check_mut()?;
+    let version = shared::with_state(&cell.rc, |state| state.version);
+    return Ok(cell.raw_replace(None).map(|handler| (handler, version)));
+}
+
+///
+/// Puts the handler back unless the cell was modified while the handler ran, the handler is dropped then.
+///
+fn restore<H>(cell: &RcOCell<H>, handler: H, version: u64) {
+    let modified = shared::with_existing_state(&cell.rc, |state| state.version != version).unwrap_or(true);
+    if modified {
+        drop(handler);
+        return;
+    }
+
+    drop(cell.raw_replace(Some(handler)));
+}
+
+macro_rules! handler_impl {
+    ($($f:ident),*) => {$(
+        impl <A, R> RcOCell<Box<dyn $f(A) -> R>> {
+            ///
+            /// Calls the handler in the cell and returns its result, None if the cell is empty.
+            /// The handler is taken out of the cell while it runs, so it can replace or clear the cell.
+            /// It is put back afterwards unless the cell was modified while it ran, a replacement or a clear wins then.
+            /// The cell is empty while the handler runs, calling the cell from within the handler returns None.
+            /// If the handler panics it is dropped and the cell stays empty.
+            ///
+            /// Panics if the value is borrowed somewhere.
+            ///
+            #[track_caller]
+            pub fn call(&self, arg: A) -> Option<R> {
+                return match self.try_call(arg) {
+                    Ok(result) => result,
+                    Err(e) => self.fail(e),
+                };
+            }
+
+            ///
+            /// Calls the handler in the cell and returns its result, None if the cell is empty, see call.
+            /// Fails if the value is borrowed somewhere.
+            ///
+            pub fn try_call(&self, arg: A) -> Result<Option<R>, RcOCellError> {
+                let Some((mut handler, version)) = take(self)? else {
+                    return Ok(None);
+                };
+
+                let result = (&mut handler)(arg);
+                restore(self, handler, version);
+                return Ok(Some(result));
+            }
+
+            ///
+            /// Boxes the closure and stores it as the handler, returns the previous handler.
+            /// Panics if the value is borrowed somewhere.
+            ///
+            pub fn set_handler<F>(&self, f: F) -> Option<Box<dyn $f(A) -> R>>
+                where F: $f(A) -> R + 'static
+            {
+                return self.set(Box::new(f));
+            }
+
+            ///
+            /// Removes the handler and returns it.
+            /// Panics if the value is borrowed somewhere.
+            ///
+            pub fn clear_handler(&self) -> Option<Box<dyn $f(A) -> R>> {
+                return self.clear();
+            }
+        }
+    )*};
+}
+
+handler_impl!(FnMut, Fn);
+
+impl <A, R> RcOCell<Box<dyn FnOnce(A) -> R>> {
+    ///
+    /// Takes the handler out of the cell, calls it and returns its result, None if the cell is empty.
+    /// The cell stays empty afterwards unless the handler stored a new handler while it ran.
+    /// Observers are notified about the removal before the handler is called.
+    ///
+    /// Panics if the value is borrowed somewhere.
+    ///
+    #[track_caller]
+    pub fn call(&self, arg: A) -> Option<R> {
+        return match self.try_call(arg) {
+            Ok(result) => result,
+            Err(e) => self.fail(e),
+        };
+    }
+
+    ///
+    /// Takes the handler out of the cell, calls it and returns its result, None if the cell is empty, see call.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    pub fn try_call(&self, arg: A) -> Result<Option<R>, RcOCellError> {
+        return Ok(self.try_clear()?.map(|handler| handler(arg)));
+    }
+
+    ///
+    /// Boxes the closure and stores it as the handler, returns the previous handler.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    pub fn set_handler<F>(&self, f: F) -> Option<Box<dyn FnOnce(A) -> R>>
+        where F: FnOnce(A) -> R + 'static
+    {
+        return self.set(Box::new(f));
+    }
+
+    ///
+    /// Removes the handler and returns it.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    pub fn clear_handler(&self) -> Option<Box<dyn FnOnce(A) -> R>> {
+        return self.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    type Slot = RcOCell<Box<dyn FnMut(u32) -> u32>>;
+
+    #[test]
+    fn test_call_handler() {
+        let slot: Slot = RcOCell::new();
+        assert_eq!(slot.call(1), None);
+
+        let mut calls = 0;
+        slot.set_handler(move |x| {
+            calls += 1;
+            x + calls
+        });
+        assert_eq!(slot.call(1), Some(2));
+        assert_eq!(slot.try_call(1).unwrap(), Some(3));
+        assert_eq!(slot.is_some(), true);
+
+        let notified = RcOCell::from_value(0u32);
+        let notified2 = notified.clone();
+        slot.on_set(move |_| { notified2.set(notified2.get_and_clone() + 1); });
+        assert_eq!(slot.call(1), Some(4));
+        assert_eq!(notified.get_and_clone(), 0);
+
+        assert_eq!(slot.clear_handler().is_some(), true);
+        assert_eq!(slot.call(1), None);
+    }
+
+    #[test]
+    fn test_handler_replaces_itself() {
+        let slot: Slot = RcOCell::new();
+        let weak = slot.downgrade();
+        slot.set_handler(move |x| {
+            weak.upgrade().set_handler(|x| x * 10);
+            x
+        });
+
+        assert_eq!(slot.call(2), Some(2));
+        assert_eq!(slot.call(2), Some(20));
+        assert_eq!(slot.call(3), Some(30));
+    }
+
+    #[test]
+    fn test_handler_clears_slot() {
+        let slot: RcOCell<Box<dyn Fn(u32) -> Option<u32>>> = RcOCell::new();
+        let weak = slot.downgrade();
+        slot.set_handler(move |x| {
+            let inner = weak.upgrade().call(x);
+            weak.upgrade().clear_handler();
+            inner.flatten()
+        });
+
+        assert_eq!(slot.call(1), Some(None));
+        assert_eq!(slot.is_none(), true);
+        assert_eq!(slot.call(1), None);
+    }
+
+    #[test]
+    fn test_handler_borrowed() {
+        let slot: Slot = RcOCell::new();
+        slot.set_handler(|x| x);
+        let brw = slot.borrow();
+        match slot.try_call(1) {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(slot.catch(|s| s.call(1)).is_err(), true);
+        drop(brw);
+        assert_eq!(slot.call(1), Some(1));
+    }
+
+    #[test]
+    fn test_call_once() {
+        let slot: RcOCell<Box<dyn FnOnce(String) -> usize>> = RcOCell::new();
+        let weak = slot.downgrade();
+        slot.set_handler(move |s| {
+            weak.upgrade().set_handler(|s: String| s.len() * 2);
+            s.len()
+        });
+        assert_eq!(slot.call("ab".to_string()), Some(2));
+        assert_eq!(slot.call("ab".to_string()), Some(4));
+        assert_eq!(slot.is_none(), true);
+        assert_eq!(slot.call("ab".to_string()), None);
+    }
+}
@@ -0,0 +1,78 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+///
+/// Source of the current time for cells that work with timestamps.
+/// Tests can use ManualClock to control the time.
+///
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
+
+///
+/// Clock that returns `Instant::now()`.
+///
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        return Instant::now();
+    }
+}
+
+///
+/// Clock that only advances when told so.
+///
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    now: Cell<Instant>,
+}
+
+impl ManualClock {
+    ///
+    /// Constructs a new clock starting at the current time.
+    ///
+    pub fn new() -> ManualClock {
+        return ManualClock {now: Cell::new(Instant::now())};
+    }
+
+    ///
+    /// Moves the clock forward.
+    ///
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+
+    ///
+    /// Sets the clock to the given time.
+    ///
+    pub fn set(&self, now: Instant) {
+        self.now.set(now);
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        return ManualClock::new();
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        return self.now.get();
+    }
+}
+
+impl <C: Clock + ?Sized> Clock for Rc<C> {
+    fn now(&self) -> Instant {
+        return C::now(self);
+    }
+}
+
+impl <C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> Instant {
+        return C::now(self);
+    }
+}
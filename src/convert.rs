@@ -0,0 +1,124 @@
+//!
+//! Conversions from common ownership shapes into cells.
+//!
+//! RcOCell implements `From<T>` for every T, so a conversion that unwraps its input into the value of the cell
+//! must not be expressible as `From<T>` as well. The impls in here do not overlap with it because the type of the
+//! cell differs from the input, i.e. `RcOCell<Vec<T>>` from `[T; N]`, or because the input would have to contain itself,
+//! i.e. `RcOCell<T>` from `Box<T>`. `From<Box<T>>` therefore unboxes, the boxed value is only stored as is
+//! with from_value or for an `RcOCell<Box<T>>`.
+//!
+//! `Rc<T>` converts with TryFrom only: the conversion can only move the value out if the Rc is not shared,
+//! and a `From<Rc<T>>` for T: Clone would conflict with the blanket TryFrom of std.
+//! from_shared clones the value in that case instead.
+//! The type of the cell has to be named for try_from, `RcOCell<Rc<T>>` converts from `Rc<T>` as well.
+//!
+use std::rc::Rc;
+use crate::RcOCell;
+
+impl <T> RcOCell<T> {
+    ///
+    /// Constructs a new RcOCell from the value of the Rc, the value is moved out if the Rc is not shared and cloned otherwise.
+    /// Use try_from to get the Rc back instead of cloning.
+    ///
+    #[track_caller]
+    pub fn from_shared(value: Rc<T>) -> RcOCell<T>
+        where T: Clone
+    {
+        return RcOCell::from_value(Rc::unwrap_or_clone(value));
+    }
+}
+
+impl <T> From<Box<T>> for RcOCell<T> {
+    #[track_caller]
+    fn from(value: Box<T>) -> Self {
+        return RcOCell::from_value(*value);
+    }
+}
+
+///
+/// Moves the value out of the Rc, fails with the Rc if it is shared.
+///
+impl <T> TryFrom<Rc<T>> for RcOCell<T> {
+    type Error = Rc<T>;
+
+    #[track_caller]
+    fn try_from(value: Rc<T>) -> Result<Self, Self::Error> {
+        return Ok(RcOCell::from_value(Rc::try_unwrap(value)?));
+    }
+}
+
+impl <T, const N: usize> From<[T; N]> for RcOCell<Vec<T>> {
+    #[track_caller]
+    fn from(value: [T; N]) -> Self {
+        return RcOCell::from_value(Vec::from(value));
+    }
+}
+
+impl <T: Clone> From<&[T]> for RcOCell<Vec<T>> {
+    #[track_caller]
+    fn from(value: &[T]) -> Self {
+        return RcOCell::from_value(value.to_vec());
+    }
+}
+
+impl From<&str> for RcOCell<String> {
+    #[track_caller]
+    fn from(value: &str) -> Self {
+        return RcOCell::from_value(value.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use crate::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Token(u32);
+
+    #[test]
+    fn test_from_box() {
+        let cell: RcOCell<Token> = Box::new(Token(1)).into();
+        assert_eq!(*cell.borrow(), Token(1));
+
+        let boxed: RcOCell<Box<Token>> = RcOCell::from_value(Box::new(Token(2)));
+        assert_eq!(**boxed.borrow(), Token(2));
+    }
+
+    #[test]
+    fn test_from_rc() {
+        let cell: RcOCell<Token> = RcOCell::try_from(Rc::new(Token(1))).unwrap();
+        assert_eq!(*cell.borrow(), Token(1));
+
+        let shared = Rc::new(Token(2));
+        let other = shared.clone();
+        let shared = RcOCell::<Token>::try_from(shared).unwrap_err();
+        assert_eq!(Rc::strong_count(&other), 2);
+        drop(shared);
+        let cell: RcOCell<Token> = other.try_into().unwrap();
+        assert_eq!(*cell.borrow(), Token(2));
+
+        let shared = Rc::new(String::from("a"));
+        let cell = RcOCell::from_shared(shared.clone());
+        cell.borrow_mut().push('b');
+        assert_eq!((shared.as_str(), cell.get_and_clone().as_str()), ("a", "ab"));
+        assert_eq!(RcOCell::from_shared(shared).get_and_clone(), "a");
+    }
+
+    #[test]
+    fn test_from_collections() {
+        let cell: RcOCell<Vec<u32>> = [1, 2, 3].into();
+        assert_eq!(cell.get_and_clone(), vec![1, 2, 3]);
+        let cell: RcOCell<Vec<u32>> = RcOCell::from([0u32; 0]);
+        assert_eq!(cell.get_and_clone(), Vec::<u32>::new());
+
+        let items = [String::from("a"), String::from("b")];
+        let cell: RcOCell<Vec<String>> = items[1..].into();
+        assert_eq!(cell.get_and_clone(), vec![String::from("b")]);
+
+        let cell: RcOCell<String> = "text".into();
+        assert_eq!(cell.get_and_clone(), "text");
+        let cell: RcOCell<&str> = RcOCell::from("text");
+        assert_eq!(cell.get_and_clone(), "text");
+    }
+}
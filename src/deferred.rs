@@ -0,0 +1,231 @@
+//!
+//! Values that are stored once the borrows of a cell end.
+//!
+//! set_deferred keeps the value in the shared state of the cell while the cell is borrowed.
+//! Every guard checks for a pending value after its borrow was released and stores it if no other borrow is left,
+//! so observers are notified when the value is stored and not when it was queued.
+//! A guard that is dropped by a panic unwinding does not store the value since that would run observers during the unwind,
+//! the value stays pending until the next guard of the cell is released.
+//!
+use std::any::Any;
+use std::cell::Cell;
+use crate::{shared, RcOCell, WeakRcOCell};
+
+thread_local! {
+    ///
+    /// Number of pending values on this thread, lets guards skip the lookup in the common case.
+    ///
+    static PENDING: Cell<usize> = const { Cell::new(0) };
+}
+
+struct PendingSet<T> {
+    cell: WeakRcOCell<T>,
+    value: T,
+}
+
+///
+/// A value waiting for the borrows of its cell to end.
+///
+pub(crate) struct Pending {
+    value: Option<Box<dyn Any>>,
+    apply: fn(Box<dyn Any>),
+}
+
+impl Pending {
+    fn new<T: 'static>(cell: &RcOCell<T>, value: T) -> Pending {
+        PENDING.with(|p| p.set(p.get() + 1));
+        let value: Box<dyn Any> = Box::new(PendingSet {cell: cell.downgrade(), value});
+        return Pending {value: Some(value), apply: apply::<T>};
+    }
+
+    fn into_value<T: 'static>(mut self) -> T {
+        let pending = self.value.take().unwrap().downcast::<PendingSet<T>>().unwrap();
+        return pending.value;
+    }
+
+    fn apply(mut self) {
+        let value = self.value.take().unwrap();
+        let apply = self.apply;
+        drop(self);
+        apply(value);
+    }
+}
+
+impl Drop for Pending {
+    fn drop(&mut self) {
+        let _ = PENDING.try_with(|p| p.set(p.get() - 1));
+    }
+}
+
+fn apply<T: 'static>(pending: Box<dyn Any>) {
+    let pending = pending.downcast::<PendingSet<T>>().unwrap();
+    if let Ok(cell) = pending.cell.try_upgrade() {
+        cell.discard(cell.set(pending.value));
+    }
+}
+
+///
+/// Stores the pending value of the cell if there is one and the cell is neither borrowed nor poisoned.
+///
+#[inline]
+pub(crate) fn flush<T>(cell: &RcOCell<T>) {
+    if !shared::may_have_state(&cell.rc) || PENDING.with(|p| p.get()) == 0 {
+        return;
+    }
+
+    if cell.rc.try_borrow_mut().is_err() || cell.is_poisoned() {
+        return;
+    }
+
+    let pending = shared::with_existing_state(&cell.rc, |state| state.deferred.take()).flatten();
+    if let Some(pending) = pending {
+        pending.apply();
+    }
+}
+
+///
+/// Discards the pending value of the cell, called when a value is stored directly.
+///
+#[inline]
+pub(crate) fn cancel<T>(cell: &RcOCell<T>) {
+    if !shared::may_have_state(&cell.rc) || PENDING.with(|p| p.get()) == 0 {
+        return;
+    }
+
+    drop(shared::with_existing_state(&cell.rc, |state| state.deferred.take()));
+}
+
+impl <T: 'static> RcOCell<T> {
+    ///
+    /// Sets the value once the cell is no longer borrowed.
+    /// The value is set immediately if the cell is not borrowed right now,
+    /// otherwise it is set when the last borrow of the cell ends.
+    /// This allows a Fn that borrows the cell, for example in map or an observer, to decide the next value of the cell.
+    ///
+    /// Only the latest deferred value is kept, an earlier one that was not set yet is dropped.
+    /// Storing a value directly with set, clear or similar methods drops the deferred value.
+    ///
+    /// Panics if the value is set immediately and the cell is poisoned.
+    ///
+    pub fn set_deferred(&self, value: T) {
+        if self.rc.try_borrow_mut().is_ok() {
+            self.set(value);
+            return;
+        }
+
+        let pending = Pending::new(self, value);
+        let previous = shared::with_state(&self.rc, |state| state.deferred.replace(pending));
+        drop(previous);
+    }
+
+    ///
+    /// Removes the deferred value of the cell, it is not set anymore.
+    /// Returns the removed value or None if no value was deferred.
+    ///
+    pub fn clear_deferred(&self) -> Option<T> {
+        let pending = shared::with_existing_state(&self.rc, |state| state.deferred.take()).flatten()?;
+        return Some(pending.into_value());
+    }
+
+    ///
+    /// Returns true if a value was deferred with set_deferred and was not set yet.
+    ///
+    pub fn has_deferred(&self) -> bool {
+        return shared::with_existing_state(&self.rc, |state| state.deferred.is_some()).unwrap_or(false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use crate::*;
+
+    #[test]
+    fn test_set_deferred() {
+        let x = RcOCell::from_value(1u32);
+        x.set_deferred(2);
+        assert_eq!(x.has_deferred(), false);
+        assert_eq!(x.get_and_clone(), 2);
+
+        let brw = x.borrow();
+        x.set_deferred(3);
+        assert_eq!(x.has_deferred(), true);
+        assert_eq!(*brw, 2);
+        drop(brw);
+        assert_eq!(x.has_deferred(), false);
+        assert_eq!(x.get_and_clone(), 3);
+
+        x.map(|v| x.set_deferred(v + 1));
+        assert_eq!(x.get_and_clone(), 4);
+    }
+
+    #[test]
+    fn test_set_deferred_coalesce() {
+        let x = RcOCell::from_value(1u32);
+        let calls = Rc::new(Cell::new(0));
+        let calls2 = calls.clone();
+        x.on_set(move |_| calls2.set(calls2.get() + 1));
+
+        let brw = x.borrow();
+        let brw2 = x.borrow();
+        x.set_deferred(2);
+        x.set_deferred(3);
+        drop(brw);
+        assert_eq!(calls.get(), 0);
+        assert_eq!(x.has_deferred(), true);
+        drop(brw2);
+        assert_eq!(calls.get(), 1);
+        assert_eq!(x.get_and_clone(), 3);
+    }
+
+    #[test]
+    fn test_set_deferred_observer() {
+        let x = RcOCell::from_value(1u32);
+        let y = x.downgrade();
+        x.on_set(move |v| {
+            if *v < 3 {
+                y.upgrade().set_deferred(v + 1);
+            }
+        });
+        x.set(1);
+        assert_eq!(x.get_and_clone(), 3);
+    }
+
+    #[test]
+    fn test_clear_deferred() {
+        let x = RcOCell::from_value(1u32);
+        assert_eq!(x.clear_deferred(), None);
+        let brw = x.borrow_mut();
+        x.set_deferred(2);
+        x.set_deferred(3);
+        assert_eq!(x.clear_deferred(), Some(3));
+        assert_eq!(x.has_deferred(), false);
+        drop(brw);
+        assert_eq!(x.get_and_clone(), 1);
+    }
+
+    #[test]
+    fn test_set_deferred_panic() {
+        let x = RcOCell::from_value(1u32);
+        let r = x.catch(|x| {
+            let _brw = x.borrow();
+            x.set_deferred(2);
+            panic!("Oh no");
+        });
+        assert_eq!(r.is_err(), true);
+        assert_eq!(x.has_deferred(), true);
+        assert_eq!(x.get_and_clone(), 1);
+        assert_eq!(x.get_and_clone(), 2);
+
+        let r = x.catch(|x| {
+            let _brw = x.borrow();
+            x.set_deferred(3);
+            panic!("Oh no");
+        });
+        assert_eq!(r.is_err(), true);
+        x.set(4);
+        assert_eq!(x.has_deferred(), false);
+        assert_eq!(x.get_and_clone(), 4);
+    }
+}
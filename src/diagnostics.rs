@@ -0,0 +1,223 @@
+//!
+//! Registry of live cells to find cells that are never dropped (feature `diagnostics`).
+//!
+//! Every cell constructed on a thread is registered together with the location that constructed it
+//! and removed again when its last strong handle is dropped.
+//! The registry only holds weak references, so it does not keep any value alive.
+//!
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io;
+use std::panic::Location;
+use std::rc::{Rc, Weak};
+use crate::{shared, CellId};
+
+///
+/// A live cell as seen by live_cells.
+///
+#[derive(Debug, Clone)]
+pub struct CellReport {
+    ///
+    /// Id of the cell, equal to RcOCell::id of its handles.
+    ///
+    pub id: CellId,
+    ///
+    /// Name of the type of the value.
+    ///
+    pub type_name: &'static str,
+    ///
+    /// Label of the cell if it has one.
+    ///
+    pub label: Option<Cow<'static, str>>,
+    ///
+    /// Location of the constructor call that created the cell.
+    ///
+    pub location: &'static Location<'static>,
+    ///
+    /// Number of strong handles.
+    ///
+    pub strong_count: usize,
+    ///
+    /// Number of weak handles, not counting the references held by the crate itself.
+    ///
+    pub weak_count: usize,
+}
+
+struct Entry {
+    anchor: *const (),
+    anchor_strong_count: unsafe fn(*const ()) -> usize,
+    anchor_weak_count: unsafe fn(*const ()) -> usize,
+    anchor_drop: unsafe fn(*const ()),
+    type_name: &'static str,
+    location: &'static Location<'static>,
+}
+
+impl Drop for Entry {
+    fn drop(&mut self) {
+        unsafe { (self.anchor_drop)(self.anchor) };
+    }
+}
+
+unsafe fn anchor_strong_count<T>(ptr: *const ()) -> usize {
+    let weak = std::mem::ManuallyDrop::new(Weak::from_raw(ptr as *const RefCell<Option<T>>));
+    return weak.strong_count();
+}
+
+unsafe fn anchor_weak_count<T>(ptr: *const ()) -> usize {
+    let weak = std::mem::ManuallyDrop::new(Weak::from_raw(ptr as *const RefCell<Option<T>>));
+    return weak.weak_count();
+}
+
+unsafe fn anchor_drop<T>(ptr: *const ()) {
+    drop(Weak::from_raw(ptr as *const RefCell<Option<T>>));
+}
+
+thread_local! {
+    static REGISTRY: RefCell<HashMap<usize, Entry>> = RefCell::new(HashMap::new());
+}
+
+///
+/// Registers the cell unless it is already registered.
+///
+pub(crate) fn register<T>(rc: &Rc<RefCell<Option<T>>>, location: &'static Location<'static>) {
+    let key = shared::key(rc);
+    let _ = REGISTRY.try_with(|registry| {
+        registry.borrow_mut().entry(key).or_insert_with(|| Entry {
+            anchor: Weak::into_raw(Rc::downgrade(rc)) as *const (),
+            anchor_strong_count: anchor_strong_count::<T>,
+            anchor_weak_count: anchor_weak_count::<T>,
+            anchor_drop: anchor_drop::<T>,
+            type_name: std::any::type_name::<T>(),
+            location,
+        });
+    });
+}
+
+///
+/// Removes the cell, called by the last strong handle right before it drops its Rc.
+///
+pub(crate) fn unregister<T>(rc: &Rc<RefCell<Option<T>>>) {
+    let key = shared::key(rc);
+    let _ = REGISTRY.try_with(|registry| registry.borrow_mut().remove(&key));
+}
+
+///
+/// Returns all live cells of the current thread ordered by id.
+/// Cells whose last strong reference was a plain Rc are removed from the registry instead of being reported.
+///
+pub fn live_cells() -> Vec<CellReport> {
+    let (mut reports, dead) = REGISTRY.with(|registry| {
+        let mut registry = registry.borrow_mut();
+        let dead: Vec<usize> = registry.iter().filter(|(_, e)| unsafe { (e.anchor_strong_count)(e.anchor) } == 0).map(|(k, _)| *k).collect();
+        let dead: Vec<Entry> = dead.iter().map(|k| registry.remove(k).unwrap()).collect();
+        let reports: Vec<(usize, CellReport)> = registry.iter().map(|(key, e)| {
+            let weak_count = unsafe { (e.anchor_weak_count)(e.anchor) } - 1;
+            (*key, CellReport {
+                id: CellId::from_key(*key),
+                type_name: e.type_name,
+                label: None,
+                location: e.location,
+                strong_count: unsafe { (e.anchor_strong_count)(e.anchor) },
+                weak_count,
+            })
+        }).collect();
+        (reports, dead)
+    });
+    drop(dead);
+
+    for (key, report) in reports.iter_mut() {
+        if let Some(label) = shared::with_existing_key(*key, |state| state.label.clone()) {
+            //The side table holds one weak reference of its own.
+            report.label = label;
+            report.weak_count -= 1;
+        }
+    }
+
+    let mut reports: Vec<CellReport> = reports.into_iter().map(|(_, r)| r).collect();
+    reports.sort_by_key(|r| r.id);
+    return reports;
+}
+
+///
+/// Writes a table of all live cells of the current thread.
+///
+pub fn dump<W: io::Write>(mut writer: W) -> io::Result<()> {
+    writeln!(writer, "{:<20} {:<30} {:<20} {:>6} {:>6} location", "id", "type", "label", "strong", "weak")?;
+    for report in live_cells() {
+        writeln!(writer, "{:<20} {:<30} {:<20} {:>6} {:>6} {}",
+                 format!("{:?}", report.id),
+                 report.type_name,
+                 report.label.as_deref().unwrap_or("-"),
+                 report.strong_count,
+                 report.weak_count,
+                 report.location)?;
+    }
+
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use crate::*;
+    use crate::diagnostics::{dump, live_cells, CellReport};
+
+    fn find(cell: &RcOCell<u32>) -> Option<CellReport> {
+        return live_cells().into_iter().find(|r| r.id == cell.id());
+    }
+
+    #[test]
+    fn test_registry() {
+        let line = line!() + 1;
+        let x = RcOCell::from_value(1u32).with_label("x");
+        let y = x.clone();
+        let w = x.downgrade();
+        let report = find(&x).unwrap();
+        assert_eq!(report.type_name, "u32");
+        assert_eq!(report.label.as_deref(), Some("x"));
+        assert_eq!(report.location.file(), file!());
+        assert_eq!(report.location.line(), line);
+        assert_eq!(report.strong_count, 2);
+        assert_eq!(report.weak_count, 1);
+
+        let id = x.id();
+        drop(x);
+        drop(y);
+        assert_eq!(live_cells().iter().any(|r| r.id == id), false);
+        drop(w);
+    }
+
+    #[test]
+    fn test_registry_leak() {
+        struct Holder(Option<RcOCell<u32>>);
+        let line = line!() + 1;
+        let cycle: RcOCell<Holder> = RcOCell::from_value(Holder(None));
+        let inner = RcOCell::new();
+        cycle.borrow_mut().0 = Some(inner.clone());
+        let leaked_id = cycle.id();
+        let inner_id = inner.id();
+        std::mem::forget(cycle);
+        drop(inner);
+
+        let report = live_cells().into_iter().find(|r| r.id == leaked_id).unwrap();
+        assert_eq!(report.location.line(), line);
+        assert_eq!(report.type_name.ends_with("Holder"), true);
+        assert_eq!(live_cells().iter().any(|r| r.id == inner_id), true);
+
+        let mut out = Vec::new();
+        dump(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out.contains(&format!("{}:{}", file!(), line)), true);
+    }
+
+    #[test]
+    fn test_registry_plain_rc() {
+        let x = RcOCell::from_value(1u32);
+        let id = x.id();
+        let rc: Rc<std::cell::RefCell<Option<u32>>> = x.into();
+        assert_eq!(live_cells().iter().any(|r| r.id == id), true);
+        drop(rc);
+        assert_eq!(live_cells().iter().any(|r| r.id == id), false);
+    }
+}
@@ -0,0 +1,318 @@
+//!
+//! Dirty flags, so consumers can tell whether a cell changed since they last looked at it.
+//!
+//! Tracking starts with the first call to one of the methods of this module on a cell and lasts as long as the cell.
+//! Every modification that notifies observers increments the generation of a tracked cell,
+//! the dirty flag of the cell and every DirtyToken compare the generation with the one they saw last.
+//! Mutable access handed to the caller, a guard like borrow_mut or entry or a Fn called with &mut T like map_mut, compute or if_present_mut,
+//! increments it when it ends even if it did not change the value, so a compute Fn that returns DoNothing marks the cell dirty.
+//! Methods that only borrow the cell internally count only if they change it, so compute_if_absent on a cell with a value
+//! or advancing an iterator in the cell do not mark the cell dirty.
+//!
+use std::cell::Cell;
+use crate::{shared, RcOCell, WeakRcOCell};
+
+thread_local! {
+    ///
+    /// Number of tracked cells on this thread, lets modifications skip the lookup in the common case.
+    ///
+    static TRACKED: Cell<usize> = const { Cell::new(0) };
+}
+
+///
+/// Generations of a tracked cell, stored in the shared state of the cell.
+///
+pub(crate) struct Tracking {
+    generation: u64,
+    clean: u64,
+}
+
+impl Tracking {
+    fn new() -> Tracking {
+        TRACKED.with(|t| t.set(t.get() + 1));
+        return Tracking {generation: 1, clean: 0};
+    }
+}
+
+impl Drop for Tracking {
+    fn drop(&mut self) {
+        let _ = TRACKED.try_with(|t| t.set(t.get() - 1));
+    }
+}
+
+///
+/// Runs the Fn with the generations of the cell, starting to track the cell if it is not tracked yet.
+///
+fn with_tracking<T, R>(cell: &RcOCell<T>, f: impl FnOnce(&mut Tracking) -> R) -> R {
+    return shared::with_state(&cell.rc, |state| f(state.dirty.get_or_insert_with(Tracking::new)));
+}
+
+///
+/// Increments the generation of the cell if it is tracked.
+///
+#[inline]
+pub(crate) fn touched<T>(cell: &RcOCell<T>) {
+    if !shared::may_have_state(&cell.rc) || TRACKED.with(|t| t.get()) == 0 {
+        return;
+    }
+
+    shared::with_existing_state(&cell.rc, |state| {
+        if let Some(tracking) = state.dirty.as_mut() {
+            tracking.generation += 1;
+        }
+    });
+}
+
+///
+/// Clones the value if the generation differs from the one seen, returns the current generation with it.
+/// None for the generation if the value is borrowed mutably, the caller must not update what it has seen then.
+///
+fn clone_if_newer<T: Clone>(cell: &RcOCell<T>, seen: u64) -> (Option<u64>, Option<T>) {
+    let generation = with_tracking(cell, |tracking| tracking.generation);
+    if generation == seen {
+        return (None, None);
+    }
+
+    return match cell.try_raw_borrow() {
+        Ok(value) => (Some(generation), value.clone()),
+        Err(_) => (None, None),
+    };
+}
+
+impl <T> RcOCell<T> {
+    ///
+    /// Returns true if the cell changed since mark_clean was last called.
+    /// A cell counts as dirty until mark_clean is called the first time.
+    /// Mutable access handed to the caller, like borrow_mut or a compute Fn that got the value, marks the cell dirty when it ends even if it did not change the value.
+    /// Methods that do not hand out the value mutably only mark it dirty if they change it, like compute_if_absent on a cell with a value.
+    ///
+    pub fn is_dirty(&self) -> bool {
+        return with_tracking(self, |tracking| tracking.generation != tracking.clean);
+    }
+
+    ///
+    /// Resets the dirty flag of the cell, see is_dirty.
+    ///
+    pub fn mark_clean(&self) {
+        with_tracking(self, |tracking| tracking.clean = tracking.generation);
+    }
+
+    ///
+    /// Returns a clone of the value and resets the dirty flag if the cell is dirty, None otherwise.
+    /// Returns None and resets the dirty flag if the cell is dirty but empty.
+    /// Returns None and leaves the flag alone if the value is borrowed mutably.
+    ///
+    pub fn clone_if_dirty(&self) -> Option<T>
+        where T: Clone
+    {
+        let clean = with_tracking(self, |tracking| tracking.clean);
+        let (generation, value) = clone_if_newer(self, clean);
+        if let Some(generation) = generation {
+            with_tracking(self, |tracking| tracking.clean = generation);
+        }
+
+        return value;
+    }
+
+    ///
+    /// Returns a token with its own dirty flag, for several consumers that each need their own view.
+    /// The token starts out clean.
+    ///
+    pub fn dirty_token(&self) -> DirtyToken<T> {
+        let generation = with_tracking(self, |tracking| tracking.generation);
+        return DirtyToken {cell: self.downgrade(), seen: generation};
+    }
+}
+
+///
+/// Dirty flag of one consumer of a cell, returned by dirty_token.
+///
+/// Works like the dirty flag of the cell itself, see RcOCell::is_dirty, but is independent of it and of all other tokens.
+/// The token does not keep the cell alive, a token of a dropped cell is never dirty.
+///
+pub struct DirtyToken<T> {
+    cell: WeakRcOCell<T>,
+    seen: u64,
+}
+
+impl <T> DirtyToken<T> {
+    ///
+    /// Returns the cell the token belongs to.
+    ///
+    pub fn cell(&self) -> &WeakRcOCell<T> {
+        return &self.cell;
+    }
+
+    ///
+    /// Returns true if the cell changed since the token was created or mark_clean was last called.
+    ///
+    pub fn is_dirty(&self) -> bool {
+        return match self.cell.try_upgrade() {
+            Ok(cell) => with_tracking(&cell, |tracking| tracking.generation != self.seen),
+            Err(_) => false,
+        };
+    }
+
+    ///
+    /// Resets the dirty flag of the token.
+    ///
+    pub fn mark_clean(&mut self) {
+        if let Ok(cell) = self.cell.try_upgrade() {
+            self.seen = with_tracking(&cell, |tracking| tracking.generation);
+        }
+    }
+
+    ///
+    /// Returns a clone of the value and resets the dirty flag of the token if it is dirty, None otherwise.
+    /// Behaves like RcOCell::clone_if_dirty otherwise.
+    ///
+    pub fn clone_if_dirty(&mut self) -> Option<T>
+        where T: Clone
+    {
+        let Ok(cell) = self.cell.try_upgrade() else {
+            return None;
+        };
+
+        let (generation, value) = clone_if_newer(&cell, self.seen);
+        if let Some(generation) = generation {
+            self.seen = generation;
+        }
+
+        return value;
+    }
+}
+
+impl <T> Clone for DirtyToken<T> {
+    fn clone(&self) -> Self {
+        return DirtyToken {cell: self.cell.clone(), seen: self.seen};
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_dirty_flag() {
+        let cell = RcOCell::from_value(1u32);
+        assert_eq!(cell.is_dirty(), true);
+        cell.mark_clean();
+        assert_eq!(cell.is_dirty(), false);
+        assert_eq!(cell.clone_if_dirty(), None);
+
+        cell.set(2);
+        assert_eq!(cell.is_dirty(), true);
+        assert_eq!(cell.clone_if_dirty(), Some(2));
+        assert_eq!(cell.is_dirty(), false);
+        assert_eq!(cell.clone_if_dirty(), None);
+
+        cell.replace(3);
+        assert_eq!(cell.clone_if_dirty(), Some(3));
+        cell.compute(|v| RcOCellComputeResult::Replace(v.copied().unwrap_or(0) + 1));
+        assert_eq!(cell.clone_if_dirty(), Some(4));
+
+        drop(cell.borrow());
+        assert_eq!(cell.is_dirty(), false);
+
+        cell.clear();
+        assert_eq!(cell.is_dirty(), true);
+        assert_eq!(cell.clone_if_dirty(), None);
+        assert_eq!(cell.is_dirty(), false);
+    }
+
+    #[test]
+    fn test_dirty_borrow_mut() {
+        let cell = RcOCell::from_value(vec![1u32]);
+        cell.mark_clean();
+        let mut guard = cell.borrow_mut();
+        guard.push(2);
+        assert_eq!(cell.is_dirty(), false);
+        assert_eq!(cell.clone_if_dirty(), None);
+        drop(guard);
+        assert_eq!(cell.is_dirty(), true);
+        assert_eq!(cell.clone_if_dirty(), Some(vec![1, 2]));
+
+        let mut guard = cell.borrow_mut();
+        guard.push(3);
+        let guard = RcORefMut::downgrade(guard).unwrap_or_else(|_| panic!("unexpected"));
+        assert_eq!(cell.is_dirty(), true);
+        assert_eq!(cell.clone_if_dirty(), Some(vec![1, 2, 3]));
+        drop(guard);
+        assert_eq!(cell.is_dirty(), false);
+
+        cell.downgrade().borrow_mut().push(4);
+        assert_eq!(cell.clone_if_dirty(), Some(vec![1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn test_dirty_do_nothing() {
+        let cell = RcOCell::from_value(1u32);
+        cell.mark_clean();
+        assert_eq!(cell.compute_if_absent(|| Some(2)), false);
+        cell.compute_if_present(|_| RcOCellComputeResult::Replace(2));
+        assert_eq!(cell.is_dirty(), true);
+
+        cell.mark_clean();
+        cell.compute_if_present(|_| RcOCellComputeResult::DoNothing);
+        assert_eq!(cell.is_dirty(), true);
+        cell.mark_clean();
+        cell.compute(|_| RcOCellComputeResult::DoNothing);
+        assert_eq!(cell.is_dirty(), true);
+        cell.mark_clean();
+        cell.map_mut(|_| ());
+        assert_eq!(cell.is_dirty(), true);
+        cell.mark_clean();
+        cell.if_present_mut(|v| {
+            *v += 1;
+            RcOCellComputeResult::DoNothing
+        });
+        assert_eq!(cell.is_dirty(), true);
+        cell.mark_clean();
+        cell.downgrade().try_if_present_mut(|_| RcOCellComputeResult::DoNothing).unwrap();
+        assert_eq!(cell.is_dirty(), true);
+
+        let empty: RcOCell<u32> = RcOCell::new();
+        empty.mark_clean();
+        empty.compute(|_| RcOCellComputeResult::DoNothing);
+        assert_eq!(empty.if_present_mut(|_| RcOCellComputeResult::DoNothing), false);
+        assert_eq!(empty.compute_if_absent(|| None), true);
+        assert_eq!(empty.is_dirty(), false);
+        assert_eq!(cell.get_and_clone(), 3);
+    }
+
+    #[test]
+    fn test_dirty_tokens() {
+        let cell = RcOCell::from_value(1u32);
+        let mut renderer = cell.dirty_token();
+        let mut logger = cell.dirty_token();
+        assert_eq!(renderer.is_dirty(), false);
+
+        cell.set(2);
+        assert_eq!(renderer.clone_if_dirty(), Some(2));
+        assert_eq!(renderer.is_dirty(), false);
+        assert_eq!(logger.is_dirty(), true);
+        assert_eq!(cell.is_dirty(), true);
+
+        cell.set(3);
+        assert_eq!(renderer.is_dirty(), true);
+        assert_eq!(logger.clone_if_dirty(), Some(3));
+        assert_eq!(renderer.clone_if_dirty(), Some(3));
+        assert_eq!(logger.clone_if_dirty(), None);
+
+        *cell.borrow_mut() += 1;
+        let brw = cell.borrow_mut();
+        assert_eq!(logger.clone_if_dirty(), None);
+        assert_eq!(logger.is_dirty(), true);
+        drop(brw);
+        logger.mark_clean();
+        assert_eq!(logger.is_dirty(), false);
+        assert_eq!(renderer.clone_if_dirty(), Some(4));
+
+        cell.set(5);
+        let weak = renderer.cell().clone();
+        drop(cell);
+        assert_eq!(weak.try_upgrade().is_err(), true);
+        assert_eq!(renderer.is_dirty(), false);
+        assert_eq!(renderer.clone_if_dirty(), None);
+    }
+}
@@ -0,0 +1,72 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::Deref;
+use crate::RcOCell;
+
+///
+/// Displays a cell using the Debug representation of its value, returned by display_debug.
+///
+/// Formatter flags are passed on to the Debug impl of the value, so `{:#}` pretty prints like `{:#?}`.
+/// Empty, borrowed and poisoned cells are displayed with the same text as the Display impl of RcOCell.
+///
+pub struct DisplayDebug<'a, T> {
+    cell: &'a RcOCell<T>,
+}
+
+impl <T: Debug> RcOCell<T> {
+    ///
+    /// Returns an adapter that displays the cell using the Debug representation of its value, see DisplayDebug.
+    /// For values that implement Debug but not Display.
+    ///
+    pub fn display_debug(&self) -> DisplayDebug<'_, T> {
+        return DisplayDebug {cell: self};
+    }
+}
+
+impl <T: Debug> Display for DisplayDebug<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self.cell.try_borrow() {
+            Ok(value) => Debug::fmt(value.deref(), f),
+            Err(e) => self.cell.fmt_inaccessible(e, f),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[derive(Debug)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    #[test]
+    fn test_display_debug() {
+        let cell = RcOCell::from_value(Point {x: 1, y: 2});
+        assert_eq!(cell.display_debug().to_string(), format!("{:?}", Point {x: 1, y: 2}));
+        assert_eq!(format!("{:#}", cell.display_debug()), format!("{:#?}", Point {x: 1, y: 2}));
+        assert_eq!(format!("{:>8}", RcOCell::from_value(7u32).display_debug()), format!("{:>8?}", 7u32));
+        assert_eq!((cell.borrow().x, cell.borrow().y), (1, 2));
+
+        let guard = cell.borrow_mut();
+        assert_eq!(cell.display_debug().to_string(), "Value currently inaccessible because it is borrowed mutably somewhere");
+        drop(guard);
+
+        cell.clear();
+        assert_eq!(cell.display_debug().to_string(), "No value present");
+        cell.set_label("point");
+        assert_eq!(cell.display_debug().to_string(), "RcOCell[point]: No value present");
+    }
+
+    #[test]
+    fn test_display_debug_matches_display() {
+        let cell: RcOCell<u32> = RcOCell::new();
+        assert_eq!(cell.display_debug().to_string(), cell.to_string());
+        let cell = RcOCell::from_value(1u32).with_label("one");
+        let brw = cell.borrow_mut();
+        assert_eq!(cell.display_debug().to_string(), cell.to_string());
+        drop(brw);
+        assert_eq!(cell.display_debug().to_string(), cell.to_string());
+    }
+}
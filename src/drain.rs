@@ -0,0 +1,219 @@
+use std::fmt::{Debug, Display, Formatter};
+use crate::{deferred, RcOCell, RcOCellError, WeakRcOCell};
+#[cfg(feature = "tracing")]
+use crate::trace;
+
+///
+/// What drain_to and its variants did.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrainOutcome {
+    ///
+    /// The value was moved, the source cell is now empty.
+    ///
+    Moved,
+    ///
+    /// The destination cell already held a value, nothing was changed.
+    ///
+    DestOccupied,
+    ///
+    /// The source cell was empty, nothing was changed.
+    ///
+    SourceEmpty,
+}
+
+///
+/// Error of try_drain_to and try_drain_to_weak, tells which of the two cells could not be accessed.
+/// Nothing was changed if this error is returned.
+///
+pub enum DrainError {
+    ///
+    /// The source cell could not be borrowed mutably.
+    ///
+    Source(RcOCellError),
+    ///
+    /// The destination cell could not be borrowed mutably, was dropped or is the source cell.
+    ///
+    Dest(RcOCellError),
+}
+
+impl DrainError {
+    ///
+    /// Returns the error of the cell that could not be accessed.
+    ///
+    pub fn error(&self) -> &RcOCellError {
+        return match self {
+            DrainError::Source(e) => e,
+            DrainError::Dest(e) => e,
+        };
+    }
+}
+
+impl Debug for DrainError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            DrainError::Source(e) => write!(f, "Source({:?})", e),
+            DrainError::Dest(e) => write!(f, "Dest({:?})", e),
+        };
+    }
+}
+
+impl Display for DrainError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            DrainError::Source(e) => write!(f, "Source cell: {}", e),
+            DrainError::Dest(e) => write!(f, "Destination cell: {}", e),
+        };
+    }
+}
+
+impl <T> RcOCell<T> {
+    ///
+    /// Moves the value of this cell into the destination cell if the destination cell is empty.
+    /// Both cells are left untouched if this cell is empty or the destination cell holds a value, see DrainOutcome.
+    /// Both cells stay borrowed mutably from the check until the value was moved,
+    /// observers of both cells are notified afterwards.
+    ///
+    /// Panics if either value is borrowed somewhere or both are the same cell.
+    ///
+    #[track_caller]
+    pub fn drain_to(&self, dest: &RcOCell<T>) -> DrainOutcome {
+        return match self.try_drain_to(dest) {
+            Ok(outcome) => outcome,
+            Err(e) => self.fail(e),
+        };
+    }
+
+    ///
+    /// Moves the value of this cell into the destination cell if the destination cell is empty, see drain_to.
+    /// Fails without changing either cell if either value is borrowed somewhere,
+    /// fails with DrainError::Dest(RcOCellError::DuplicateCell) if both are the same cell.
+    ///
+    pub fn try_drain_to(&self, dest: &RcOCell<T>) -> Result<DrainOutcome, DrainError> {
+        if self.id() == dest.id() {
+            return Err(DrainError::Dest(RcOCellError::DuplicateCell));
+        }
+
+        let mut source = self.try_raw_borrow_mut().map_err(DrainError::Source)?;
+        let mut target = dest.try_raw_borrow_mut().map_err(DrainError::Dest)?;
+        if source.is_none() {
+            return Ok(DrainOutcome::SourceEmpty);
+        }
+
+        if target.is_some() {
+            return Ok(DrainOutcome::DestOccupied);
+        }
+
+        *target = source.take();
+        drop(target);
+        drop(source);
+        deferred::cancel(self);
+        deferred::cancel(dest);
+        #[cfg(feature = "tracing")]
+        {
+            trace::changed(self, "drain_to");
+            trace::changed(dest, "drain_to");
+        }
+        self.changed();
+        dest.changed();
+        return Ok(DrainOutcome::Moved);
+    }
+
+    ///
+    /// Moves the value of this cell into the destination cell if the destination cell is empty, see drain_to.
+    ///
+    /// Panics if the destination cell was dropped, either value is borrowed somewhere or both are the same cell.
+    ///
+    #[track_caller]
+    pub fn drain_to_weak(&self, dest: &WeakRcOCell<T>) -> DrainOutcome {
+        return match self.try_drain_to_weak(dest) {
+            Ok(outcome) => outcome,
+            Err(e) => self.fail(e),
+        };
+    }
+
+    ///
+    /// Moves the value of this cell into the destination cell if the destination cell is empty, see drain_to.
+    /// Fails with DrainError::Dest(RcOCellError::Dropped) if the destination cell was dropped, see try_drain_to.
+    ///
+    pub fn try_drain_to_weak(&self, dest: &WeakRcOCell<T>) -> Result<DrainOutcome, DrainError> {
+        let dest = dest.try_upgrade().map_err(DrainError::Dest)?;
+        return self.try_drain_to(&dest);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_drain_outcomes() {
+        let staged = RcOCell::from_value(1u32);
+        let active = RcOCell::new();
+        assert_eq!(staged.drain_to(&active), DrainOutcome::Moved);
+        assert_eq!(staged.is_none(), true);
+        assert_eq!(active.get_and_clone(), 1);
+
+        assert_eq!(staged.drain_to(&active), DrainOutcome::SourceEmpty);
+        assert_eq!(active.get_and_clone(), 1);
+
+        staged.set(2);
+        assert_eq!(staged.try_drain_to(&active).unwrap(), DrainOutcome::DestOccupied);
+        assert_eq!(staged.get_and_clone(), 2);
+        assert_eq!(active.get_and_clone(), 1);
+
+        active.clear();
+        assert_eq!(staged.drain_to_weak(&active.downgrade()), DrainOutcome::Moved);
+        assert_eq!(active.get_and_clone(), 2);
+    }
+
+    #[test]
+    fn test_drain_notifies() {
+        let staged = RcOCell::from_value(3u32);
+        let active = RcOCell::new();
+        let seen = RcOCell::from_value(Vec::new());
+        let seen2 = seen.clone();
+        active.on_set(move |v| { seen2.borrow_mut().push(format!("active {}", v)); });
+        let seen3 = seen.clone();
+        staged.on_clear(move || { seen3.borrow_mut().push("staged cleared".to_string()); });
+        staged.drain_to(&active);
+        assert_eq!(seen.get_and_clone(), vec!["staged cleared".to_string(), "active 3".to_string()]);
+    }
+
+    #[test]
+    fn test_drain_borrow_conflict() {
+        let staged = RcOCell::from_value(1u32);
+        let active: RcOCell<u32> = RcOCell::new();
+
+        let brw = active.raw_borrow_mut();
+        match staged.try_drain_to(&active) {
+            Err(DrainError::Dest(RcOCellError::BorrowError(_))) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(staged.catch(|s| s.drain_to(&active)).is_err(), true);
+        drop(brw);
+        assert_eq!(staged.get_and_clone(), 1);
+        assert_eq!(active.is_none(), true);
+
+        let brw = staged.borrow();
+        match staged.try_drain_to(&active) {
+            Err(DrainError::Source(RcOCellError::BorrowError(_))) => {}
+            _ => panic!("unexpected"),
+        };
+        drop(brw);
+        assert_eq!(staged.get_and_clone(), 1);
+        assert_eq!(active.is_none(), true);
+
+        match staged.try_drain_to(&staged.clone()) {
+            Err(DrainError::Dest(RcOCellError::DuplicateCell)) => {}
+            _ => panic!("unexpected"),
+        };
+
+        let dropped = RcOCell::<u32>::new().downgrade();
+        match staged.try_drain_to_weak(&dropped) {
+            Err(DrainError::Dest(RcOCellError::Dropped)) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(staged.get_and_clone(), 1);
+    }
+}
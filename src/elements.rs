@@ -0,0 +1,321 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::{Bound, RangeBounds};
+use crate::{RcOCell, RcOCellError, RcORef, RcORefMut};
+
+///
+/// Error of borrow_slice and borrow_slice_mut.
+///
+pub enum SliceCellError {
+    ///
+    /// The cell could not be accessed because it is empty or borrowed.
+    ///
+    Cell(RcOCellError),
+    ///
+    /// The range starts after it ends or ends after the last element.
+    /// start and end are the resolved bounds of the range with end being exclusive.
+    ///
+    OutOfBounds {
+        start: usize,
+        end: usize,
+        len: usize,
+    },
+}
+
+impl Debug for SliceCellError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            SliceCellError::Cell(e) => Debug::fmt(e, f),
+            SliceCellError::OutOfBounds {..} => Display::fmt(self, f),
+        };
+    }
+}
+
+impl Display for SliceCellError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            SliceCellError::Cell(e) => Display::fmt(e, f),
+            SliceCellError::OutOfBounds {start, end, len} => write!(f, "Range {}..{} is out of bounds for {} elements", start, end, len),
+        };
+    }
+}
+
+impl From<RcOCellError> for SliceCellError {
+    fn from(value: RcOCellError) -> Self {
+        return SliceCellError::Cell(value);
+    }
+}
+
+///
+/// Access to single elements of cells holding a fixed size array, a Vec or anything else that can be viewed as a slice.
+///
+/// Indices are checked, an index that is out of bounds fails with RcOCellError::IndexOutOfBounds instead of panicking.
+/// set_at and fill notify observers, modifications through map_at and borrow_slice_mut are in place and are not observed.
+///
+#[allow(clippy::len_without_is_empty)]
+pub trait ElementCellExt<T> {
+    ///
+    /// The type of the value of the cell.
+    ///
+    type Value;
+
+    ///
+    /// Borrows the element at the index.
+    /// Returns None if the index is out of bounds, there is no value or the value is borrowed mutably somewhere.
+    ///
+    fn get_at(&self, index: usize) -> Option<RcORef<'_, T, Self::Value>>;
+
+    ///
+    /// Replaces the element at the index and returns the previous element.
+    /// Fails if the index is out of bounds, there is no value or the value is borrowed somewhere.
+    ///
+    fn set_at(&self, index: usize, value: T) -> Result<T, RcOCellError>;
+
+    ///
+    /// Calls the Fn with the element at the index and returns the result of the Fn.
+    /// Fails if the index is out of bounds, there is no value or the value is borrowed somewhere.
+    ///
+    fn map_at<R, F>(&self, index: usize, f: F) -> Result<R, RcOCellError>
+        where F: FnOnce(&mut T) -> R;
+
+    ///
+    /// Calls the Fn with the index and a reference of every element in order.
+    /// Fails if there is no value or the value is borrowed mutably somewhere.
+    ///
+    fn for_each<F>(&self, f: F) -> Result<(), RcOCellError>
+        where F: FnMut(usize, &T);
+
+    ///
+    /// Replaces every element with a clone of the value.
+    /// Fails if there is no value or the value is borrowed somewhere.
+    ///
+    fn fill(&self, value: T) -> Result<(), RcOCellError>
+        where T: Clone;
+
+    ///
+    /// Returns the number of elements.
+    /// Fails if there is no value or the value is borrowed mutably somewhere.
+    ///
+    fn len(&self) -> Result<usize, RcOCellError>;
+
+    ///
+    /// Returns true if there are no elements, not to be confused with an empty cell which is an error.
+    /// Fails if there is no value or the value is borrowed mutably somewhere.
+    ///
+    fn is_empty_slice(&self) -> Result<bool, RcOCellError>;
+
+    ///
+    /// Borrows the elements in the range.
+    /// Fails if the range is out of bounds, there is no value or the value is borrowed mutably somewhere.
+    ///
+    fn borrow_slice<R>(&self, range: R) -> Result<RcORef<'_, [T], Self::Value>, SliceCellError>
+        where R: RangeBounds<usize>;
+
+    ///
+    /// Borrows the elements in the range mutably.
+    /// Fails if the range is out of bounds, there is no value or the value is borrowed somewhere.
+    ///
+    fn borrow_slice_mut<R>(&self, range: R) -> Result<RcORefMut<'_, [T], Self::Value>, SliceCellError>
+        where R: RangeBounds<usize>;
+}
+
+///
+/// Resolves the range to start and exclusive end, fails if it does not fit into len elements.
+///
+fn resolve<R: RangeBounds<usize>>(range: &R, len: usize) -> Result<(usize, usize), SliceCellError> {
+    let start = match range.start_bound() {
+        Bound::Included(start) => *start,
+        Bound::Excluded(start) => start.saturating_add(1),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(end) => end.saturating_add(1),
+        Bound::Excluded(end) => *end,
+        Bound::Unbounded => len,
+    };
+
+    if start > end || end > len {
+        return Err(SliceCellError::OutOfBounds {start, end, len});
+    }
+
+    return Ok((start, end));
+}
+
+impl <T, C> ElementCellExt<T> for RcOCell<C> where C: AsRef<[T]> + AsMut<[T]> {
+    type Value = C;
+
+    fn get_at(&self, index: usize) -> Option<RcORef<'_, T, C>> {
+        return RcORef::try_map(self.try_borrow().ok()?, |v| v.as_ref().get(index)).ok();
+    }
+
+    fn set_at(&self, index: usize, value: T) -> Result<T, RcOCellError> {
+        let previous = self.map_at(index, |element| std::mem::replace(element, value))?;
+        self.changed();
+        return Ok(previous);
+    }
+
+    fn map_at<R, F>(&self, index: usize, f: F) -> Result<R, RcOCellError>
+        where F: FnOnce(&mut T) -> R
+    {
+        let mut value = self.try_borrow_mut()?;
+        let elements = value.as_mut();
+        let len = elements.len();
+        let element = elements.get_mut(index).ok_or(RcOCellError::IndexOutOfBounds {index, len})?;
+        return Ok(f(element));
+    }
+
+    fn for_each<F>(&self, mut f: F) -> Result<(), RcOCellError>
+        where F: FnMut(usize, &T)
+    {
+        for (index, element) in self.try_borrow()?.as_ref().iter().enumerate() {
+            f(index, element);
+        }
+
+        return Ok(());
+    }
+
+    fn fill(&self, value: T) -> Result<(), RcOCellError>
+        where T: Clone
+    {
+        self.try_borrow_mut()?.as_mut().fill(value);
+        self.changed();
+        return Ok(());
+    }
+
+    fn len(&self) -> Result<usize, RcOCellError> {
+        return Ok(self.try_borrow()?.as_ref().len());
+    }
+
+    fn is_empty_slice(&self) -> Result<bool, RcOCellError> {
+        return Ok(self.try_borrow()?.as_ref().is_empty());
+    }
+
+    fn borrow_slice<R>(&self, range: R) -> Result<RcORef<'_, [T], C>, SliceCellError>
+        where R: RangeBounds<usize>
+    {
+        let value = self.try_borrow()?;
+        let (start, end) = resolve(&range, value.as_ref().len())?;
+        return Ok(RcORef::map(value, |v| &v.as_ref()[start..end]));
+    }
+
+    fn borrow_slice_mut<R>(&self, range: R) -> Result<RcORefMut<'_, [T], C>, SliceCellError>
+        where R: RangeBounds<usize>
+    {
+        let value = self.try_borrow_mut()?;
+        let (start, end) = resolve(&range, value.as_ref().len())?;
+        return Ok(RcORefMut::map(value, |v| &mut v.as_mut()[start..end]));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_array_elements() {
+        let table = RcOCell::from_value([1u32, 2, 3]);
+        assert_eq!(*table.get_at(1).unwrap(), 2);
+        assert_eq!(table.get_at(3).is_none(), true);
+        assert_eq!(table.set_at(0, 5).unwrap(), 1);
+        match table.set_at(3, 5) {
+            Err(RcOCellError::IndexOutOfBounds {index: 3, len: 3}) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(table.map_at(2, |v| { *v += 1; *v }).unwrap(), 4);
+
+        let mut seen = Vec::new();
+        table.for_each(|i, v| seen.push((i, *v))).unwrap();
+        assert_eq!(seen, vec![(0, 5), (1, 2), (2, 4)]);
+
+        table.fill(7).unwrap();
+        assert_eq!(table.get_and_clone(), [7, 7, 7]);
+    }
+
+    #[test]
+    fn test_empty_array_elements() {
+        let table: RcOCell<[u32; 0]> = RcOCell::from_value([]);
+        assert_eq!(table.get_at(0).is_none(), true);
+        match table.set_at(0, 1) {
+            Err(RcOCellError::IndexOutOfBounds {index: 0, len: 0}) => {}
+            _ => panic!("unexpected"),
+        };
+        table.for_each(|_, _| panic!("unexpected")).unwrap();
+        table.fill(1).unwrap();
+
+        let empty: RcOCell<[u32; 2]> = RcOCell::new();
+        assert_eq!(empty.get_at(0).is_none(), true);
+        match empty.set_at(0, 1) {
+            Err(RcOCellError::NoValue) => {}
+            _ => panic!("unexpected"),
+        };
+    }
+
+    #[test]
+    fn test_borrowed_elements() {
+        let table = RcOCell::from_value(vec![1u32, 2]);
+        let element = table.get_at(0).unwrap();
+        assert_eq!(*table.get_at(1).unwrap(), 2);
+        match table.set_at(0, 3) {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(table.fill(3).is_err(), true);
+        assert_eq!(table.map_at(0, |_| ()).is_err(), true);
+        drop(element);
+
+        let brw = table.borrow_mut();
+        assert_eq!(table.get_at(0).is_none(), true);
+        assert_eq!(table.for_each(|_, _| ()).is_err(), true);
+        drop(brw);
+
+        let changes = RcOCell::from_value(0u32);
+        let changes2 = changes.clone();
+        table.on_set(move |_| { changes2.set(changes2.get_and_clone() + 1); });
+        table.set_at(1, 4).unwrap();
+        table.map_at(0, |v| *v = 5).unwrap();
+        assert_eq!(changes.get_and_clone(), 1);
+        assert_eq!(table.get_and_clone(), vec![5, 4]);
+    }
+
+    #[test]
+    fn test_borrow_slice() {
+        let buffer = RcOCell::from_value(vec![1u8, 2, 3, 4]);
+        assert_eq!(buffer.len().unwrap(), 4);
+        assert_eq!(buffer.is_empty_slice().unwrap(), false);
+        assert_eq!(&*buffer.borrow_slice(1..3).unwrap(), &[2, 3]);
+        assert_eq!(&*buffer.borrow_slice(..).unwrap(), &[1, 2, 3, 4]);
+        assert_eq!(&*buffer.borrow_slice(2..2).unwrap(), &[] as &[u8]);
+        assert_eq!(&*buffer.borrow_slice(4..).unwrap(), &[] as &[u8]);
+        assert_eq!(&*buffer.borrow_slice(..=3).unwrap(), &[1, 2, 3, 4]);
+        match buffer.borrow_slice(2..5) {
+            Err(SliceCellError::OutOfBounds {start: 2, end: 5, len: 4}) => {}
+            _ => panic!("unexpected"),
+        };
+        match buffer.borrow_slice(5..) {
+            Err(SliceCellError::OutOfBounds {start: 5, end: 4, len: 4}) => {}
+            _ => panic!("unexpected"),
+        };
+        match buffer.borrow_slice_mut(..=4) {
+            Err(SliceCellError::OutOfBounds {start: 0, end: 5, len: 4}) => {}
+            _ => panic!("unexpected"),
+        };
+
+        let mut slice = buffer.borrow_slice_mut(1..3).unwrap();
+        slice.reverse();
+        match buffer.borrow_slice(..) {
+            Err(SliceCellError::Cell(RcOCellError::BorrowError(_))) => {}
+            _ => panic!("unexpected"),
+        };
+        drop(slice);
+        assert_eq!(buffer.get_and_clone(), vec![1, 3, 2, 4]);
+
+        let array = RcOCell::from_value([0u8; 0]);
+        assert_eq!(array.is_empty_slice().unwrap(), true);
+        assert_eq!(array.borrow_slice(..).unwrap().len(), 0);
+        let empty: RcOCell<[u8; 2]> = RcOCell::new();
+        match empty.borrow_slice(..) {
+            Err(SliceCellError::Cell(RcOCellError::NoValue)) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(empty.len().is_err(), true);
+    }
+}
@@ -0,0 +1,245 @@
+use std::fmt::{Debug, Formatter};
+use crate::{RcOCell, RcOCellError, RcORefMut};
+
+///
+/// View into a cell that is either occupied or vacant, see RcOCell::entry.
+///
+/// The entry holds the mutable borrow of the cell for its whole lifetime,
+/// so the occupied/vacant decision cannot be invalidated while it exists.
+/// Dropping an entry without acting on it leaves the cell unchanged.
+///
+/// Like changes made through borrow_mut, changes made through an entry are not reported to observers.
+/// OccupiedEntry::remove is the exception since it releases the borrow.
+///
+pub enum CellEntry<'a, T> {
+    Occupied(OccupiedEntry<'a, T>),
+    Vacant(VacantEntry<'a, T>),
+}
+
+///
+/// Entry of a cell that holds a value.
+///
+pub struct OccupiedEntry<'a, T> {
+    cell: &'a RcOCell<T>,
+    value: RcORefMut<'a, Option<T>, T>,
+}
+
+///
+/// Entry of a cell that holds no value.
+///
+pub struct VacantEntry<'a, T> {
+    value: RcORefMut<'a, Option<T>, T>,
+}
+
+impl <T> RcOCell<T> {
+    ///
+    /// Returns the entry of the cell for in-place manipulation.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    pub fn entry(&self) -> Result<CellEntry<'_, T>, RcOCellError> {
+        let mut value = self.try_raw_borrow_mut()?;
+        RcORefMut::marks_dirty(&mut value);
+        if value.is_some() {
+            return Ok(CellEntry::Occupied(OccupiedEntry {cell: self, value}));
+        }
+
+        return Ok(CellEntry::Vacant(VacantEntry {value}));
+    }
+}
+
+impl <'a, T> CellEntry<'a, T> {
+    ///
+    /// Inserts the default if the entry is vacant and returns a mutable reference to the value.
+    ///
+    pub fn or_insert(self, default: T) -> RcORefMut<'a, T> {
+        return match self {
+            CellEntry::Occupied(entry) => entry.into_mut(),
+            CellEntry::Vacant(entry) => entry.insert(default),
+        };
+    }
+
+    ///
+    /// Inserts the result of the Fn if the entry is vacant and returns a mutable reference to the value.
+    ///
+    pub fn or_insert_with<F>(self, f: F) -> RcORefMut<'a, T>
+        where F: FnOnce() -> T
+    {
+        return match self {
+            CellEntry::Occupied(entry) => entry.into_mut(),
+            CellEntry::Vacant(entry) => entry.insert_with(f),
+        };
+    }
+
+    ///
+    /// Inserts the default value of T if the entry is vacant and returns a mutable reference to the value.
+    ///
+    pub fn or_default(self) -> RcORefMut<'a, T>
+        where T: Default
+    {
+        return self.or_insert_with(T::default);
+    }
+
+    ///
+    /// Runs the Fn with the value if the entry is occupied.
+    ///
+    pub fn and_modify<F>(self, f: F) -> CellEntry<'a, T>
+        where F: FnOnce(&mut T)
+    {
+        return match self {
+            CellEntry::Occupied(mut entry) => {
+                f(entry.get_mut());
+                CellEntry::Occupied(entry)
+            }
+            CellEntry::Vacant(entry) => CellEntry::Vacant(entry),
+        };
+    }
+}
+
+impl <'a, T> OccupiedEntry<'a, T> {
+    ///
+    /// Returns a reference to the value.
+    ///
+    pub fn get(&self) -> &T {
+        return self.value.as_ref().unwrap();
+    }
+
+    ///
+    /// Returns a mutable reference to the value.
+    ///
+    pub fn get_mut(&mut self) -> &mut T {
+        return self.value.as_mut().unwrap();
+    }
+
+    ///
+    /// Converts the entry into a mutable reference to the value that keeps the cell borrowed.
+    ///
+    pub fn into_mut(self) -> RcORefMut<'a, T> {
+        return RcORefMut::map(self.value, |v| v.as_mut().unwrap());
+    }
+
+    ///
+    /// Replaces the value and returns the old value.
+    ///
+    pub fn replace(&mut self, value: T) -> T {
+        return std::mem::replace(self.get_mut(), value);
+    }
+
+    ///
+    /// Removes the value from the cell and returns it.
+    /// Observers are notified after the borrow was released.
+    ///
+    pub fn remove(mut self) -> T {
+        let value = self.value.take().unwrap();
+        drop(self.value);
+        self.cell.changed();
+        return value;
+    }
+}
+
+impl <'a, T> VacantEntry<'a, T> {
+    ///
+    /// Stores the value in the cell and returns a mutable reference to it.
+    ///
+    pub fn insert(mut self, value: T) -> RcORefMut<'a, T> {
+        *self.value = Some(value);
+        return RcORefMut::map(self.value, |v| v.as_mut().unwrap());
+    }
+
+    ///
+    /// Stores the result of the Fn in the cell and returns a mutable reference to it.
+    /// The cell stays borrowed while the Fn runs.
+    ///
+    pub fn insert_with<F>(self, f: F) -> RcORefMut<'a, T>
+        where F: FnOnce() -> T
+    {
+        return self.insert(f());
+    }
+}
+
+impl <T: Debug> Debug for CellEntry<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            CellEntry::Occupied(entry) => f.debug_tuple("Occupied").field(entry.get()).finish(),
+            CellEntry::Vacant(_) => f.write_str("Vacant"),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use crate::*;
+
+    #[test]
+    fn test_entry_or_insert() {
+        let x: RcOCell<u32> = RcOCell::new();
+        *x.entry().unwrap().or_insert(1) += 10;
+        assert_eq!(x.get_and_clone(), 11);
+        *x.entry().unwrap().or_insert(1) += 10;
+        assert_eq!(x.get_and_clone(), 21);
+
+        let x: RcOCell<Vec<u32>> = RcOCell::new();
+        x.entry().unwrap().or_default().push(1);
+        x.entry().unwrap().or_insert_with(|| panic!("not called")).push(2);
+        assert_eq!(x.get_and_clone(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_entry_and_modify() {
+        let x: RcOCell<u32> = RcOCell::new();
+        x.entry().unwrap().and_modify(|v| *v += 1).or_insert(42);
+        assert_eq!(x.get_and_clone(), 42);
+        x.entry().unwrap().and_modify(|v| *v += 1).or_insert(42);
+        assert_eq!(x.get_and_clone(), 43);
+    }
+
+    #[test]
+    fn test_entry_occupied() {
+        let x = RcOCell::from_value("a".to_string());
+        match x.entry().unwrap() {
+            CellEntry::Occupied(mut entry) => {
+                assert_eq!(entry.get(), "a");
+                entry.get_mut().push('b');
+                assert_eq!(entry.replace("c".to_string()), "ab");
+                assert_eq!(x.try_borrow().is_err(), true);
+            }
+            CellEntry::Vacant(_) => panic!("unexpected"),
+        };
+        assert_eq!(x.get_and_clone(), "c");
+
+        let cleared = Rc::new(Cell::new(false));
+        let cleared_clone = cleared.clone();
+        x.on_clear(move || cleared_clone.set(true));
+        match x.entry().unwrap() {
+            CellEntry::Occupied(entry) => assert_eq!(entry.remove(), "c"),
+            CellEntry::Vacant(_) => panic!("unexpected"),
+        };
+        assert_eq!(x.is_none(), true);
+        assert_eq!(cleared.get(), true);
+    }
+
+    #[test]
+    fn test_entry_vacant() {
+        let x: RcOCell<u32> = RcOCell::new();
+        match x.entry().unwrap() {
+            CellEntry::Vacant(entry) => {
+                let mut value = entry.insert(1);
+                *value += 1;
+            }
+            CellEntry::Occupied(_) => panic!("unexpected"),
+        };
+        assert_eq!(x.get_and_clone(), 2);
+
+        x.clear();
+        let entry = x.entry().unwrap();
+        assert_eq!(format!("{:?}", entry), "Vacant");
+        drop(entry);
+        assert_eq!(x.is_none(), true);
+
+        x.set(1);
+        let brw = x.borrow();
+        assert_eq!(x.entry().is_err(), true);
+        drop(brw);
+    }
+}
@@ -0,0 +1,378 @@
+use std::fmt::{Debug, Formatter};
+use std::time::{Duration, Instant};
+use crate::{Clock, RcOCell, RcOCellError, RcORef, SystemClock};
+
+///
+/// The value of an ExpiringRcOCell together with its deadline, the cell type of the guards the cell hands out.
+/// A deadline that does not fit into an Instant is stored as no deadline, the value then never expires.
+///
+pub struct ExpiringEntry<T> {
+    value: T,
+    deadline: Option<Instant>,
+}
+
+///
+/// A cell whose value expires after a time to live.
+///
+/// An expired value is treated exactly like an absent value by all methods.
+/// There is no background timer, the expired value is dropped by the next method that notices it has expired
+/// and is able to remove it (i.e. the value is not borrowed).
+/// A value expires at its deadline, so a value whose deadline is exactly now is already expired.
+/// A time to live too large to be added to the current time, like Duration::MAX, means the value never expires.
+///
+/// Clones share the value, each clone keeps its own copy of the default time to live and the clock.
+///
+pub struct ExpiringRcOCell<T, C: Clock = SystemClock> {
+    cell: RcOCell<ExpiringEntry<T>>,
+    ttl: Duration,
+    clock: C,
+}
+
+impl <T> ExpiringRcOCell<T, SystemClock> {
+    ///
+    /// Constructs a new empty cell whose values expire after the time to live.
+    ///
+    pub fn new(ttl: Duration) -> ExpiringRcOCell<T, SystemClock> {
+        return ExpiringRcOCell::with_clock(ttl, SystemClock);
+    }
+
+    ///
+    /// Constructs a new cell containing the value that expires after the time to live.
+    ///
+    pub fn from_value(value: T, ttl: Duration) -> ExpiringRcOCell<T, SystemClock> {
+        let cell = ExpiringRcOCell::new(ttl);
+        cell.set(value);
+        return cell;
+    }
+}
+
+impl <T, C: Clock> ExpiringRcOCell<T, C> {
+    ///
+    /// Constructs a new empty cell that uses the clock to determine whether values have expired.
+    ///
+    pub fn with_clock(ttl: Duration, clock: C) -> ExpiringRcOCell<T, C> {
+        return ExpiringRcOCell {cell: RcOCell::new(), ttl, clock};
+    }
+
+    ///
+    /// Returns the default time to live of values stored with set.
+    ///
+    pub fn ttl(&self) -> Duration {
+        return self.ttl;
+    }
+
+    fn is_expired(&self, entry: &ExpiringEntry<T>) -> bool {
+        return entry.deadline.map(|deadline| self.clock.now() >= deadline).unwrap_or(false);
+    }
+
+    ///
+    /// Returns the deadline for a value stored now with the time to live, None if it does not fit into an Instant.
+    ///
+    fn deadline(&self, ttl: Duration) -> Option<Instant> {
+        return self.clock.now().checked_add(ttl);
+    }
+
+    ///
+    /// Drops the value if it has expired and is not borrowed.
+    ///
+    fn expire(&self) {
+        let expired = match self.cell.rc.try_borrow() {
+            Ok(entry) => entry.as_ref().map(|e| self.is_expired(e)).unwrap_or(false),
+            Err(_) => false,
+        };
+
+        if expired {
+            let _ = self.cell.try_clear();
+        }
+    }
+
+    ///
+    /// Returns the unexpired value of an entry that was removed from the cell.
+    ///
+    fn unexpired(&self, entry: Option<ExpiringEntry<T>>) -> Option<T> {
+        return entry.filter(|e| !self.is_expired(e)).map(|e| e.value);
+    }
+
+    ///
+    /// Sets the value with the default time to live, returning the old value if an unexpired old value existed.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    pub fn set(&self, value: T) -> Option<T> {
+        return self.set_with_ttl(value, self.ttl);
+    }
+
+    ///
+    /// Sets the value with the given time to live, returning the old value if an unexpired old value existed.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    pub fn set_with_ttl(&self, value: T, ttl: Duration) -> Option<T> {
+        let deadline = self.deadline(ttl);
+        return self.unexpired(self.cell.set(ExpiringEntry {value, deadline}));
+    }
+
+    ///
+    /// Sets the value with the default time to live, returning the old value if an unexpired old value existed.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    pub fn try_set(&self, value: T) -> Result<Option<T>, RcOCellError> {
+        self.cell.check_mut()?;
+        return Ok(self.set(value));
+    }
+
+    ///
+    /// Clears the value returning the old value if an unexpired old value existed.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    pub fn clear(&self) -> Option<T> {
+        return self.unexpired(self.cell.clear());
+    }
+
+    ///
+    /// Clears the value returning the old value if an unexpired old value existed.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    pub fn try_clear(&self) -> Result<Option<T>, RcOCellError> {
+        return Ok(self.unexpired(self.cell.try_clear()?));
+    }
+
+    ///
+    /// Returns true if an unexpired value is set.
+    /// A mutably borrowed value counts as set.
+    /// Never panics.
+    ///
+    pub fn is_some(&self) -> bool {
+        self.expire();
+        return match self.cell.rc.try_borrow() {
+            Ok(entry) => entry.as_ref().map(|e| !self.is_expired(e)).unwrap_or(false),
+            Err(_) => true,
+        };
+    }
+
+    ///
+    /// Returns true if no value or only an expired value is set.
+    /// Never panics.
+    ///
+    pub fn is_none(&self) -> bool {
+        return !self.is_some();
+    }
+
+    ///
+    /// Borrows the value.
+    /// Fails if the value is already borrowed mutably somewhere or there is no unexpired value.
+    ///
+    pub fn try_borrow(&self) -> Result<RcORef<'_, T, ExpiringEntry<T>>, RcOCellError> {
+        self.expire();
+        let entry = self.cell.try_borrow()?;
+        if self.is_expired(&entry) {
+            return Err(RcOCellError::NoValue);
+        }
+
+        return Ok(RcORef::map(entry, |e| &e.value));
+    }
+
+    ///
+    /// Borrows the value.
+    /// Panics if the value is already borrowed mutably somewhere or there is no unexpired value.
+    ///
+    #[track_caller]
+    pub fn borrow(&self) -> RcORef<'_, T, ExpiringEntry<T>> {
+        return match self.try_borrow() {
+            Ok(r) => r,
+            Err(e) => panic!("ExpiringRcOCell::borrow failed: {}", e),
+        };
+    }
+
+    ///
+    /// Calls the Fn with the value (if an unexpired value is present) and returns the result as an option.
+    /// Panics if the value is already borrowed mutably somewhere.
+    ///
+    pub fn map<F, X>(&self, f: F) -> Option<X> where
+        F: FnOnce(&T) -> X,
+    {
+        self.expire();
+        return self.cell.map(|e| if self.is_expired(e) { None } else { Some(f(&e.value)) }).flatten();
+    }
+
+    ///
+    /// Runs the Fn if there is no unexpired value to calculate a new value that is stored with the default time to live.
+    /// Returns true if the Fn was executed.
+    /// False if an unexpired value was present or the value is borrowed elsewhere.
+    /// This function does not panic.
+    ///
+    pub fn compute_if_absent<F>(&self, f: F) -> bool
+        where F: FnOnce() -> Option<T>
+    {
+        self.expire();
+        let deadline = self.deadline(self.ttl);
+        return self.cell.compute_if_absent(|| f().map(|value| ExpiringEntry {value, deadline}));
+    }
+
+    ///
+    /// Returns the point in time at which the current value expires.
+    /// None if there is no unexpired value, the value never expires or the value is borrowed mutably.
+    ///
+    pub fn expires_at(&self) -> Option<Instant> {
+        return self.unexpired_deadline()?;
+    }
+
+    ///
+    /// Returns how long the current value has left until it expires, Duration::MAX if it never expires.
+    /// None if there is no unexpired value or the value is borrowed mutably.
+    ///
+    pub fn remaining_ttl(&self) -> Option<Duration> {
+        return match self.unexpired_deadline()? {
+            Some(deadline) => Some(deadline - self.clock.now()),
+            None => Some(Duration::MAX),
+        };
+    }
+
+    ///
+    /// Returns the deadline of the current value, None if there is no unexpired value or the value is borrowed mutably.
+    ///
+    fn unexpired_deadline(&self) -> Option<Option<Instant>> {
+        self.expire();
+        let entry = self.cell.try_borrow().ok()?;
+        if self.is_expired(&entry) {
+            return None;
+        }
+
+        return Some(entry.deadline);
+    }
+
+    ///
+    /// Resets the deadline of the current value to the default time to live from now.
+    /// Returns false if there is no unexpired value or the value is borrowed.
+    ///
+    pub fn touch(&self) -> bool {
+        return self.touch_for(self.ttl);
+    }
+
+    ///
+    /// Resets the deadline of the current value to the given time to live from now.
+    /// Returns false if there is no unexpired value or the value is borrowed.
+    ///
+    pub fn touch_for(&self, ttl: Duration) -> bool {
+        self.expire();
+        let Ok(mut entry) = self.cell.try_borrow_mut() else {
+            return false;
+        };
+        if self.is_expired(&entry) {
+            return false;
+        }
+
+        entry.deadline = self.deadline(ttl);
+        return true;
+    }
+}
+
+impl <T, C: Clock + Clone> Clone for ExpiringRcOCell<T, C> {
+    fn clone(&self) -> Self {
+        return ExpiringRcOCell {cell: self.cell.clone(), ttl: self.ttl, clock: self.clock.clone()};
+    }
+}
+
+impl <T: Debug, C: Clock> Debug for ExpiringRcOCell<T, C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self.try_borrow() {
+            Ok(value) => f.debug_struct("ExpiringRcOCell").field("value", &*value).field("remaining_ttl", &self.remaining_ttl()).finish(),
+            Err(e) => f.debug_struct("ExpiringRcOCell").field("value", &e).finish(),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use std::time::Duration;
+    use crate::*;
+
+    fn cell() -> (ExpiringRcOCell<String, Rc<ManualClock>>, Rc<ManualClock>) {
+        let clock = Rc::new(ManualClock::new());
+        return (ExpiringRcOCell::with_clock(Duration::from_secs(10), clock.clone()), clock);
+    }
+
+    #[test]
+    fn test_expiry_boundary() {
+        let (x, clock) = cell();
+        assert_eq!(x.is_none(), true);
+        x.set("Baum".to_string());
+        assert_eq!(x.remaining_ttl(), Some(Duration::from_secs(10)));
+        clock.advance(Duration::from_secs(9));
+        assert_eq!(x.is_some(), true);
+        assert_eq!(x.borrow().as_str(), "Baum");
+        assert_eq!(x.map(|v| v.len()), Some(4));
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(x.is_some(), false);
+        assert_eq!(x.try_borrow().is_err(), true);
+        assert_eq!(x.map(|v| v.len()), None);
+        assert_eq!(x.expires_at(), None);
+        assert_eq!(x.clear(), None);
+    }
+
+    #[test]
+    fn test_expired_compute_if_absent() {
+        let (x, clock) = cell();
+        x.set_with_ttl("Baum".to_string(), Duration::from_secs(1));
+        assert_eq!(x.compute_if_absent(|| Some("Nase".to_string())), false);
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(x.compute_if_absent(|| Some("Nase".to_string())), true);
+        assert_eq!(x.borrow().as_str(), "Nase");
+        assert_eq!(x.remaining_ttl(), Some(Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn test_touch() {
+        let (x, clock) = cell();
+        assert_eq!(x.touch(), false);
+        x.set("Baum".to_string());
+        clock.advance(Duration::from_secs(8));
+        assert_eq!(x.touch(), true);
+        assert_eq!(x.remaining_ttl(), Some(Duration::from_secs(10)));
+        clock.advance(Duration::from_secs(8));
+        assert_eq!(x.is_some(), true);
+        clock.advance(Duration::from_secs(2));
+        assert_eq!(x.touch(), false);
+        assert_eq!(x.set("Nase".to_string()), None);
+    }
+
+    #[test]
+    fn test_expired_value_dropped_lazily() {
+        let clock = Rc::new(ManualClock::new());
+        let x = ExpiringRcOCell::with_clock(Duration::from_secs(1), clock.clone());
+        let payload = Rc::new(());
+        x.set(payload.clone());
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(Rc::strong_count(&payload), 2);
+        assert_eq!(x.is_none(), true);
+        assert_eq!(Rc::strong_count(&payload), 1);
+    }
+
+    #[test]
+    fn test_never_expires() {
+        let (x, clock) = cell();
+        x.set_with_ttl("Baum".to_string(), Duration::MAX);
+        assert_eq!(x.expires_at(), None);
+        assert_eq!(x.remaining_ttl(), Some(Duration::MAX));
+        clock.advance(Duration::from_secs(1_000_000));
+        assert_eq!(x.is_some(), true);
+        assert_eq!(x.touch_for(Duration::MAX), true);
+        assert_eq!(x.touch(), true);
+        assert_eq!(x.remaining_ttl(), Some(Duration::from_secs(10)));
+
+        let never: ExpiringRcOCell<u32, Rc<ManualClock>> = ExpiringRcOCell::with_clock(Duration::MAX, clock.clone());
+        assert_eq!(never.compute_if_absent(|| Some(1)), true);
+        clock.advance(Duration::from_secs(1_000_000));
+        assert_eq!(*never.borrow(), 1);
+    }
+
+    #[test]
+    fn test_borrow_guard() {
+        let (x, _clock) = cell();
+        x.set("Baum".to_string());
+        let brw: RcORef<'_, String, ExpiringEntry<String>> = x.borrow();
+        assert_eq!(brw.as_str(), "Baum");
+        assert_eq!(x.try_set("Nase".to_string()).is_err(), true);
+        drop(brw);
+        assert_eq!(x.try_borrow().unwrap().as_str(), "Baum");
+    }
+}
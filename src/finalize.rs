@@ -0,0 +1,93 @@
+use crate::{shared, RcOCell};
+
+impl <T: 'static> RcOCell<T> {
+    ///
+    /// Registers a Fn that runs exactly once when the last strong RcOCell handle of this cell is dropped,
+    /// regardless of which clone that is. Weak handles neither delay nor repeat it.
+    ///
+    /// The first finalizer in registration order receives the remaining value by move (None if the cell was empty),
+    /// later finalizers receive None since the value can only be moved once.
+    /// A panicking finalizer does not prevent the other finalizers from running,
+    /// the first panic is resumed after all of them ran.
+    ///
+    /// Finalizers do not run if the last strong reference is a plain `Rc<RefCell<Option<T>>>`
+    /// obtained by converting a cell, since dropping it bypasses the cell.
+    ///
+    pub fn on_final_drop<F>(&self, f: F)
+        where F: FnOnce(Option<T>) + 'static
+    {
+        let f: shared::Finalizer<T> = Box::new(f);
+        shared::with_typed(&self.rc, |typed| typed.on_final_drop.push(f));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Cell, RefCell};
+    use std::panic;
+    use std::panic::AssertUnwindSafe;
+    use std::rc::Rc;
+    use crate::*;
+
+    struct Payload(Rc<Cell<u32>>);
+
+    impl Drop for Payload {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn test_final_drop_once() {
+        let drops = Rc::new(Cell::new(0));
+        let runs = Rc::new(Cell::new(0));
+        let a = RcOCell::from_value(Payload(drops.clone()));
+        let b = a.clone();
+        let w = a.downgrade();
+        let runs_clone = runs.clone();
+        a.on_final_drop(move |value| {
+            assert_eq!(value.is_some(), true);
+            runs_clone.set(runs_clone.get() + 1);
+        });
+        drop(a);
+        assert_eq!(runs.get(), 0);
+        assert_eq!(w.is_some(), true);
+        drop(b);
+        assert_eq!(runs.get(), 1);
+        assert_eq!(drops.get(), 1);
+        assert_eq!(w.try_upgrade().is_err(), true);
+    }
+
+    #[test]
+    fn test_final_drop_order_and_empty() {
+        let order = Rc::new(RefCell::new(Vec::new()));
+        let x: RcOCell<u32> = RcOCell::new();
+        for i in 0..3 {
+            let order = order.clone();
+            x.on_final_drop(move |value| order.borrow_mut().push((i, value)));
+        }
+        drop(x);
+        assert_eq!(*order.borrow(), vec![(0, None), (1, None), (2, None)]);
+
+        order.borrow_mut().clear();
+        let x = RcOCell::from_value(7u32);
+        for i in 0..2 {
+            let order = order.clone();
+            x.on_final_drop(move |value| order.borrow_mut().push((i, value)));
+        }
+        drop(x);
+        assert_eq!(*order.borrow(), vec![(0, Some(7)), (1, None)]);
+    }
+
+    #[test]
+    fn test_final_drop_panic() {
+        let runs = Rc::new(Cell::new(0));
+        let x = RcOCell::from_value(1u32);
+        x.on_final_drop(|_| panic!("Oh no"));
+        let runs_clone = runs.clone();
+        x.on_final_drop(move |_| runs_clone.set(runs_clone.get() + 1));
+        let r = panic::catch_unwind(AssertUnwindSafe(|| drop(x)));
+        assert_eq!(r.is_err(), true);
+        assert_eq!(runs.get(), 1);
+    }
+}
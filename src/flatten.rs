@@ -0,0 +1,144 @@
+use crate::{RcOCell, RcOCellError, RcORef};
+
+///
+/// Helpers for cells that hold an Option themselves.
+/// They treat an empty cell and a cell holding None the same, both count as absent.
+///
+impl <T> RcOCell<Option<T>> {
+    ///
+    /// Returns true if the cell holds `Some(Some(_))`.
+    /// A mutably borrowed value counts as set.
+    /// Never panics.
+    ///
+    pub fn flatten_is_some(&self) -> bool {
+        return match self.rc.try_borrow() {
+            Ok(value) => matches!(value.as_ref(), Some(Some(_))),
+            Err(_) => true,
+        };
+    }
+
+    ///
+    /// Takes the inner value out of the cell and leaves the cell empty.
+    /// A cell holding None is cleared as well.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    pub fn flatten_take(&self) -> Option<T> {
+        return self.clear().flatten();
+    }
+
+    ///
+    /// Takes the inner value out of the cell and leaves the cell empty, see flatten_take.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    pub fn try_flatten_take(&self) -> Result<Option<T>, RcOCellError> {
+        return Ok(self.try_clear()?.flatten());
+    }
+
+    ///
+    /// Borrows the inner value.
+    /// Fails if the value is already borrowed mutably somewhere or the cell is empty or holds None.
+    ///
+    pub fn flatten_borrow(&self) -> Result<RcORef<'_, T, Option<T>>, RcOCellError> {
+        let borrowed = self.try_borrow()?;
+        if borrowed.is_none() {
+            return Err(RcOCellError::NoValue);
+        }
+
+        return Ok(RcORef::map(borrowed, |v| v.as_ref().unwrap()));
+    }
+
+    ///
+    /// Clears the cell if it holds None, so afterwards the cell is either empty or holds `Some(_)`.
+    /// Returns true if the cell was cleared.
+    /// Panics if the value is borrowed mutably somewhere or the cell holds None and is borrowed.
+    ///
+    pub fn normalize(&self) -> bool {
+        let holds_none = matches!(self.raw_borrow().as_ref(), Some(None));
+        if holds_none {
+            self.clear();
+        }
+
+        return holds_none;
+    }
+
+    ///
+    /// Moves the inner value into a new cell, leaving this cell empty.
+    /// The new cell is empty if this cell was empty or held None.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    pub fn flatten(&self) -> RcOCell<T> {
+        return RcOCell::from_option(self.flatten_take());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn states() -> [RcOCell<Option<u32>>; 3] {
+        return [RcOCell::new(), RcOCell::from_value(None), RcOCell::from_value(Some(1))];
+    }
+
+    #[test]
+    fn test_flatten_is_some() {
+        let [empty, none, some] = states();
+        assert_eq!(empty.flatten_is_some(), false);
+        assert_eq!(none.flatten_is_some(), false);
+        assert_eq!(some.flatten_is_some(), true);
+    }
+
+    #[test]
+    fn test_flatten_take() {
+        let [empty, none, some] = states();
+        assert_eq!(empty.flatten_take(), None);
+        assert_eq!(none.flatten_take(), None);
+        assert_eq!(none.is_none(), true);
+        assert_eq!(some.flatten_take(), Some(1));
+        assert_eq!(some.is_none(), true);
+
+        let x = RcOCell::from_value(Some(1u32));
+        let brw = x.borrow();
+        assert_eq!(x.try_flatten_take().is_err(), true);
+        drop(brw);
+        assert_eq!(x.try_flatten_take().unwrap(), Some(1));
+    }
+
+    #[test]
+    fn test_flatten_borrow() {
+        let [empty, none, some] = states();
+        match empty.flatten_borrow() {
+            Err(RcOCellError::NoValue) => {}
+            _ => panic!("unexpected"),
+        };
+        match none.flatten_borrow() {
+            Err(RcOCellError::NoValue) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(*some.flatten_borrow().unwrap(), 1);
+        let brw = some.borrow_mut();
+        assert_eq!(some.flatten_borrow().is_err(), true);
+        drop(brw);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let [empty, none, some] = states();
+        assert_eq!(empty.normalize(), false);
+        assert_eq!(empty.is_none(), true);
+        assert_eq!(none.normalize(), true);
+        assert_eq!(none.is_none(), true);
+        assert_eq!(some.normalize(), false);
+        assert_eq!(some.get_and_clone(), Some(1));
+    }
+
+    #[test]
+    fn test_flatten() {
+        let [empty, none, some] = states();
+        assert_eq!(empty.flatten().is_none(), true);
+        assert_eq!(none.flatten().is_none(), true);
+        assert_eq!(none.is_none(), true);
+        let flat = some.flatten();
+        assert_eq!(flat.get_and_clone(), 1);
+        assert_eq!(some.is_none(), true);
+    }
+}
@@ -0,0 +1,502 @@
+use std::future::{Future, IntoFuture};
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+use crate::{shared, RcOCell, RcOCellError, RcORef, RcORefMut};
+#[cfg(feature = "tracing")]
+use crate::trace;
+
+impl <F: Future + Unpin> RcOCell<F> {
+    ///
+    /// Polls the future in the cell (feature `futures`).
+    /// Once the future is ready it is removed from the cell and its output is returned, observers are notified.
+    /// Returns Ready(None) if the cell is empty.
+    ///
+    /// Futures that are not Unpin can be stored as `Pin<Box<F>>`, i.e. `RcOCell<Pin<Box<dyn Future<Output = T>>>>`.
+    ///
+    /// Panics if the value is borrowed somewhere, this includes polling the cell from within the poll of its future.
+    ///
+    #[track_caller]
+    pub fn poll_inner(&self, cx: &mut Context<'_>) -> Poll<Option<F::Output>> {
+        return match self.try_poll_inner(cx) {
+            Ok(poll) => poll,
+            Err(e) => self.fail(e),
+        };
+    }
+
+    ///
+    /// Polls the future in the cell (feature `futures`), see poll_inner.
+    /// Fails if the value is borrowed somewhere, this includes polling the cell from within the poll of its future.
+    ///
+    pub fn try_poll_inner(&self, cx: &mut Context<'_>) -> Result<Poll<Option<F::Output>>, RcOCellError> {
+        let mut value = self.try_raw_borrow_mut()?;
+        let Some(future) = value.as_mut() else {
+            return Ok(Poll::Ready(None));
+        };
+
+        let Poll::Ready(output) = Pin::new(future).poll(cx) else {
+            return Ok(Poll::Pending);
+        };
+
+        let finished = value.take();
+        drop(value);
+        drop(finished);
+        #[cfg(feature = "tracing")]
+        trace::changed(self, "poll");
+        self.changed();
+        return Ok(Poll::Ready(Some(output)));
+    }
+
+    ///
+    /// Returns a future that completes with the output of the future in the cell, see CellFuture.
+    ///
+    pub fn future(&self) -> CellFuture<F> {
+        return CellFuture {cell: self.clone()};
+    }
+}
+
+///
+/// Future that polls the future in a cell (feature `futures`), returned by future and by awaiting the cell directly.
+///
+/// Completes with the output of the future in the cell, which is removed from the cell then.
+/// Completes with RcOCellError::NoValue if the cell is empty
+/// and with a borrow error if the value is borrowed somewhere while it is polled, i.e. when it is polled from within itself.
+///
+pub struct CellFuture<F> {
+    cell: RcOCell<F>,
+}
+
+impl <F: Future + Unpin> Future for CellFuture<F> {
+    type Output = Result<F::Output, RcOCellError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        return match self.cell.try_poll_inner(cx) {
+            Ok(Poll::Ready(Some(output))) => Poll::Ready(Ok(output)),
+            Ok(Poll::Ready(None)) => Poll::Ready(Err(RcOCellError::NoValue)),
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(e) => Poll::Ready(Err(e)),
+        };
+    }
+}
+
+impl <F: Future + Unpin> IntoFuture for RcOCell<F> {
+    type Output = Result<F::Output, RcOCellError>;
+    type IntoFuture = CellFuture<F>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        return CellFuture {cell: self};
+    }
+}
+
+///
+/// A task waiting in borrow_async or borrow_mut_async, kept in the shared state of the cell.
+///
+pub(crate) struct Waiter {
+    id: u64,
+    waker: Waker,
+}
+
+impl <T> RcOCell<T> {
+    ///
+    /// Returns a future that borrows the value once it is not borrowed mutably anymore (feature `futures`).
+    /// This allows tasks on a single threaded executor to share a cell while one of them holds a guard across an await.
+    ///
+    /// Waiting tasks are served in the order they started waiting, a borrow is not granted before a borrow_mut_async
+    /// that started waiting earlier, so a stream of readers cannot starve a writer.
+    /// Waiting tasks are woken when a guard of the cell is dropped, borrows that are not guards of this crate,
+    /// i.e. guards converted with into_std, do not wake them.
+    ///
+    /// The future completes with RcOCellError::NoValue if the cell is empty when it is its turn.
+    ///
+    pub fn borrow_async(&self) -> BorrowFuture<'_, T> {
+        return BorrowFuture {cell: self, waiter: None};
+    }
+
+    ///
+    /// Returns a future that borrows the value mutably once it is not borrowed anymore (feature `futures`), see borrow_async.
+    /// The future completes with RcOCellError::NoValue if the cell is empty when it is its turn.
+    ///
+    pub fn borrow_mut_async(&self) -> BorrowMutFuture<'_, T> {
+        return BorrowMutFuture {cell: self, waiter: None};
+    }
+
+    ///
+    /// Polls a borrow_async or borrow_mut_async future.
+    /// Only the first waiting task may borrow, a task that did not wait yet may only borrow if nobody waits.
+    ///
+    pub(crate) fn poll_borrow<G>(&self, waiter: &mut Option<u64>, exclusive: bool, cx: &mut Context<'_>, borrow: impl FnOnce() -> Result<G, RcOCellError>) -> Poll<Result<G, RcOCellError>> {
+        let first = shared::with_existing_state(&self.rc, |state| state.waiters.front().map(|w| w.id)).flatten();
+        if first.is_none() || first == *waiter {
+            match borrow() {
+                Err(RcOCellError::BorrowError(_)) => {}
+                result => {
+                    let wake_next = !exclusive || result.is_err();
+                    self.stop_waiting(waiter, wake_next);
+                    return Poll::Ready(result);
+                }
+            }
+        }
+
+        let id = *waiter.get_or_insert_with(shared::next_id);
+        let old = shared::with_state(&self.rc, |state| {
+            match state.waiters.iter_mut().find(|w| w.id == id) {
+                Some(w) => Some(std::mem::replace(&mut w.waker, cx.waker().clone())),
+                None => {
+                    state.waiters.push_back(Waiter {id, waker: cx.waker().clone()});
+                    None
+                }
+            }
+        });
+        drop(old);
+        return Poll::Pending;
+    }
+
+    ///
+    /// Removes the waiting task, optionally waking the next one since it may be able to borrow now.
+    ///
+    pub(crate) fn stop_waiting(&self, waiter: &mut Option<u64>, wake_next: bool) {
+        let Some(id) = waiter.take() else {
+            return;
+        };
+
+        let (removed, next) = shared::with_existing_state(&self.rc, |state| {
+            let removed = state.waiters.iter().position(|w| w.id == id).and_then(|i| state.waiters.remove(i));
+            let next = state.waiters.front().filter(|_| wake_next).map(|w| w.waker.clone());
+            (removed, next)
+        }).unwrap_or((None, None));
+
+        drop(removed);
+        if let Some(next) = next {
+            next.wake();
+        }
+    }
+}
+
+///
+/// Wakes the first waiting task when a guard of the cell was dropped and the value is not borrowed anymore.
+///
+pub(crate) fn released<T>(cell: &RcOCell<T>) {
+    if cell.rc.try_borrow_mut().is_err() {
+        return;
+    }
+
+    let next = shared::with_existing_state(&cell.rc, |state| state.waiters.front().map(|w| w.waker.clone())).flatten();
+    if let Some(next) = next {
+        next.wake();
+    }
+}
+
+///
+/// Future returned by borrow_async (feature `futures`), completes with a guard once it is the turn of the task.
+/// Dropping the future before it completed gives up the place of the task in the queue of the cell.
+///
+pub struct BorrowFuture<'a, T> {
+    cell: &'a RcOCell<T>,
+    waiter: Option<u64>,
+}
+
+impl <'a, T> Future for BorrowFuture<'a, T> {
+    type Output = Result<RcORef<'a, T>, RcOCellError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let cell = this.cell;
+        return cell.poll_borrow(&mut this.waiter, false, cx, || cell.try_borrow());
+    }
+}
+
+impl <T> Drop for BorrowFuture<'_, T> {
+    fn drop(&mut self) {
+        self.cell.stop_waiting(&mut self.waiter, true);
+    }
+}
+
+///
+/// Future returned by borrow_mut_async (feature `futures`), completes with a mutable guard once it is the turn of the task.
+/// Dropping the future before it completed gives up the place of the task in the queue of the cell.
+///
+pub struct BorrowMutFuture<'a, T> {
+    cell: &'a RcOCell<T>,
+    waiter: Option<u64>,
+}
+
+impl <'a, T> Future for BorrowMutFuture<'a, T> {
+    type Output = Result<RcORefMut<'a, T>, RcOCellError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let cell = this.cell;
+        return cell.poll_borrow(&mut this.waiter, true, cx, || cell.try_borrow_mut());
+    }
+}
+
+impl <T> Drop for BorrowMutFuture<'_, T> {
+    fn drop(&mut self) {
+        self.cell.stop_waiting(&mut self.waiter, true);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::task::{Context, Poll, Wake, Waker};
+    use crate::*;
+
+    type BoxFuture<T> = Pin<Box<dyn Future<Output = T>>>;
+    type Task<'a> = Pin<Box<dyn Future<Output = ()> + 'a>>;
+
+    struct TaskWaker {
+        id: usize,
+        ready: Arc<Mutex<VecDeque<usize>>>,
+    }
+
+    impl Wake for TaskWaker {
+        fn wake(self: Arc<Self>) {
+            self.ready.lock().unwrap().push_back(self.id);
+        }
+    }
+
+    #[derive(Default)]
+    struct Flag(AtomicBool);
+
+    impl Flag {
+        fn take(&self) -> bool {
+            return self.0.swap(false, Ordering::SeqCst);
+        }
+    }
+
+    impl Wake for Flag {
+        fn wake(self: Arc<Self>) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    struct YieldNow(bool);
+
+    impl Future for YieldNow {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            if self.0 {
+                return Poll::Ready(());
+            }
+
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+    }
+
+    ///
+    /// Polls the tasks in the order they were woken until all of them completed, every task is polled once at the start.
+    ///
+    fn run_tasks(tasks: Vec<Task<'_>>) {
+        let ready = Arc::new(Mutex::new((0..tasks.len()).collect::<VecDeque<usize>>()));
+        let mut tasks: Vec<Option<Task>> = tasks.into_iter().map(Some).collect();
+        loop {
+            let Some(id) = ready.lock().unwrap().pop_front() else {
+                break;
+            };
+            let Some(task) = tasks[id].as_mut() else {
+                continue;
+            };
+
+            let waker = Waker::from(Arc::new(TaskWaker {id, ready: ready.clone()}));
+            if task.as_mut().poll(&mut Context::from_waker(&waker)).is_ready() {
+                tasks[id] = None;
+            }
+        }
+
+        assert_eq!(tasks.iter().all(Option::is_none), true);
+    }
+
+    struct Countdown(u32);
+
+    impl Future for Countdown {
+        type Output = &'static str;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            if self.0 == 0 {
+                return Poll::Ready("done");
+            }
+
+            self.0 -= 1;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+    }
+
+    struct Reentrant(WeakRcOCell<BoxFuture<bool>>);
+
+    impl Future for Reentrant {
+        type Output = bool;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            return Poll::Ready(self.0.upgrade().try_poll_inner(cx).is_err());
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = std::pin::pin!(future);
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    #[test]
+    fn test_poll_inner() {
+        let mut cx = Context::from_waker(Waker::noop());
+        let cell = RcOCell::from_value(Countdown(2));
+        let cleared = RcOCell::from_value(false);
+        let cleared2 = cleared.clone();
+        cell.on_clear(move || { cleared2.set(true); });
+
+        assert_eq!(cell.poll_inner(&mut cx), Poll::Pending);
+        assert_eq!(cell.poll_inner(&mut cx), Poll::Pending);
+        assert_eq!(cell.is_some(), true);
+        assert_eq!(cleared.get_and_clone(), false);
+        assert_eq!(cell.poll_inner(&mut cx), Poll::Ready(Some("done")));
+        assert_eq!(cell.is_none(), true);
+        assert_eq!(cleared.get_and_clone(), true);
+        assert_eq!(cell.poll_inner(&mut cx), Poll::Ready(None));
+
+        cell.set(Countdown(0));
+        let brw = cell.borrow();
+        match cell.try_poll_inner(&mut cx) {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(cell.catch(|c| c.poll_inner(&mut Context::from_waker(Waker::noop()))).is_err(), true);
+        drop(brw);
+        assert_eq!(cell.try_poll_inner(&mut cx).unwrap(), Poll::Ready(Some("done")));
+    }
+
+    #[test]
+    fn test_await_cell() {
+        let cell: RcOCell<BoxFuture<u32>> = RcOCell::from_value(Box::pin(async { 1 }));
+        assert_eq!(block_on(cell.future()).unwrap(), 1);
+        assert_eq!(cell.is_none(), true);
+        match block_on(cell.future()) {
+            Err(RcOCellError::NoValue) => {}
+            _ => panic!("unexpected"),
+        };
+
+        let countdown = RcOCell::from_value(Countdown(3));
+        let handle = countdown.clone();
+        assert_eq!(block_on(async move { handle.await }).unwrap(), "done");
+        assert_eq!(countdown.is_none(), true);
+    }
+
+    #[test]
+    fn test_reentrant_poll() {
+        let cell: RcOCell<BoxFuture<bool>> = RcOCell::new();
+        cell.set(Box::pin(Reentrant(cell.downgrade())));
+        assert_eq!(block_on(cell.future()).unwrap(), true);
+        assert_eq!(cell.is_none(), true);
+    }
+
+    #[test]
+    fn test_borrow_mut_async_contention() {
+        let cell = RcOCell::from_value(Vec::<&str>::new());
+        run_tasks(vec![
+            Box::pin(async {
+                let mut guard = cell.borrow_mut_async().await.unwrap();
+                guard.push("a1");
+                YieldNow(false).await;
+                guard.push("a2");
+            }),
+            Box::pin(async {
+                assert_eq!(cell.try_borrow_mut().is_err(), true);
+                cell.borrow_mut_async().await.unwrap().push("b");
+            }),
+        ]);
+        assert_eq!(cell.get_and_clone(), vec!["a1", "a2", "b"]);
+    }
+
+    #[test]
+    fn test_borrow_async_fairness() {
+        let cell = RcOCell::from_value(0u32);
+        let log = RcOCell::from_value(Vec::<String>::new());
+        let record = |s: &str| log.borrow_mut().push(s.to_string());
+        run_tasks(vec![
+            Box::pin(async {
+                let mut guard = cell.borrow_mut_async().await.unwrap();
+                record("w1 start");
+                YieldNow(false).await;
+                YieldNow(false).await;
+                *guard += 1;
+                record("w1 end");
+            }),
+            Box::pin(async {
+                let guard = cell.borrow_async().await.unwrap();
+                record(&format!("r1 start {}", *guard));
+                YieldNow(false).await;
+                YieldNow(false).await;
+                record("r1 end");
+            }),
+            Box::pin(async {
+                *cell.borrow_mut_async().await.unwrap() += 1;
+                record("w2");
+            }),
+            Box::pin(async {
+                let guard = cell.borrow_async().await.unwrap();
+                record(&format!("r2 {}", *guard));
+            }),
+        ]);
+        assert_eq!(log.get_and_clone(), vec!["w1 start", "w1 end", "r1 start 1", "r1 end", "w2", "r2 2"]);
+    }
+
+    #[test]
+    fn test_borrow_async_cancel() {
+        let cell = RcOCell::from_value(1u32);
+        let (first_flag, second_flag) = (Arc::new(Flag::default()), Arc::new(Flag::default()));
+        let (first_waker, second_waker) = (Waker::from(first_flag.clone()), Waker::from(second_flag.clone()));
+
+        let guard = cell.borrow_mut();
+        let mut first = cell.borrow_async();
+        let mut second = cell.borrow_mut_async();
+        assert_eq!(Pin::new(&mut first).poll(&mut Context::from_waker(&first_waker)).is_pending(), true);
+        assert_eq!(Pin::new(&mut second).poll(&mut Context::from_waker(&second_waker)).is_pending(), true);
+        drop(guard);
+        assert_eq!((first_flag.take(), second_flag.take()), (true, false));
+
+        drop(first);
+        assert_eq!(second_flag.take(), true);
+        match Pin::new(&mut second).poll(&mut Context::from_waker(&second_waker)) {
+            Poll::Ready(Ok(mut guard)) => *guard = 2,
+            _ => panic!("unexpected"),
+        };
+
+        let guard = cell.borrow();
+        let mut third = cell.borrow_mut_async();
+        assert_eq!(Pin::new(&mut third).poll(&mut Context::from_waker(&first_waker)).is_pending(), true);
+        drop(third);
+        drop(guard);
+        assert_eq!(first_flag.take(), false);
+        assert_eq!(*block_on(cell.borrow_async()).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_borrow_async_no_value() {
+        let cell: RcOCell<u32> = RcOCell::new();
+        match block_on(cell.borrow_mut_async()) {
+            Err(RcOCellError::NoValue) => {}
+            _ => panic!("unexpected"),
+        };
+
+        let guard = cell.entry().unwrap().or_insert(1);
+        let flag = Arc::new(Flag::default());
+        let waker = Waker::from(flag.clone());
+        let mut waiting = cell.borrow_async();
+        assert_eq!(Pin::new(&mut waiting).poll(&mut Context::from_waker(&waker)).is_pending(), true);
+        drop(guard);
+        assert_eq!(flag.take(), true);
+        assert_eq!(*block_on(waiting).unwrap(), 1);
+    }
+}
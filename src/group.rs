@@ -0,0 +1,343 @@
+use std::cell::RefCell;
+use std::fmt::{Debug, Formatter};
+use crate::{RcOCell, RcOCellError, WeakRcOCell};
+
+///
+/// Set of related cells that bulk operations are applied to, i.e. one cell per panel of a UI.
+///
+/// Members are either strong, the group keeps them alive, or weak, see push_weak.
+/// Weak members whose cell was dropped are removed from the group whenever the group is accessed.
+/// Indices passed to for_each and reported by the bulk operations refer to the live members
+/// in the order they were pushed, after removing the dropped ones.
+///
+/// The bulk operations that modify the cells come in two forms:
+/// the normal form skips cells that are borrowed somewhere,
+/// the try_ form fails with the index of the first borrowed cell before modifying any cell.
+///
+/// No user code runs while the group itself is borrowed, so observers and the Fn of for_each may push to the group.
+///
+pub struct RcOCellGroup<T> {
+    members: RefCell<Vec<Member<T>>>,
+}
+
+enum Member<T> {
+    Strong(RcOCell<T>),
+    Weak(WeakRcOCell<T>),
+}
+
+impl <T> Clone for Member<T> {
+    fn clone(&self) -> Self {
+        return match self {
+            Member::Strong(cell) => Member::Strong(cell.clone()),
+            Member::Weak(cell) => Member::Weak(cell.clone()),
+        };
+    }
+}
+
+impl <T> RcOCellGroup<T> {
+    ///
+    /// Creates an empty group.
+    ///
+    pub fn new() -> RcOCellGroup<T> {
+        return RcOCellGroup {members: RefCell::new(Vec::new())};
+    }
+
+    ///
+    /// Adds the cell to the group, the group keeps it alive.
+    ///
+    pub fn push(&self, cell: RcOCell<T>) {
+        self.members.borrow_mut().push(Member::Strong(cell));
+    }
+
+    ///
+    /// Adds the cell to the group without keeping it alive.
+    /// It is removed from the group once it was dropped.
+    ///
+    pub fn push_weak(&self, cell: &RcOCell<T>) {
+        self.members.borrow_mut().push(Member::Weak(cell.downgrade()));
+    }
+
+    ///
+    /// Returns the number of live members.
+    ///
+    pub fn len(&self) -> usize {
+        return self.cells().len();
+    }
+
+    ///
+    /// Returns true if the group has no live members.
+    ///
+    pub fn is_empty(&self) -> bool {
+        return self.cells().is_empty();
+    }
+
+    ///
+    /// Returns the live members in order, removing the dropped weak members from the group.
+    ///
+    pub fn cells(&self) -> Vec<RcOCell<T>> {
+        let mut members = self.members.borrow_mut();
+        let mut cells = Vec::with_capacity(members.len());
+        members.retain(|member| {
+            let cell = match member {
+                Member::Strong(cell) => Some(cell.clone()),
+                Member::Weak(cell) => cell.try_upgrade().ok(),
+            };
+            let live = cell.is_some();
+            cells.extend(cell);
+            return live;
+        });
+
+        return cells;
+    }
+
+    ///
+    /// Calls the Fn with the index and the cell of every live member.
+    ///
+    pub fn for_each<F>(&self, mut f: F)
+        where F: FnMut(usize, &RcOCell<T>)
+    {
+        for (index, cell) in self.cells().iter().enumerate() {
+            f(index, cell);
+        }
+    }
+
+    ///
+    /// Returns true if every member holds a value, true for an empty group.
+    /// Never panics, a borrowed value counts as present.
+    ///
+    pub fn all_some(&self) -> bool {
+        return self.cells().iter().all(|cell| cell.is_some());
+    }
+
+    ///
+    /// Returns true if any member holds a value, false for an empty group.
+    /// Never panics, a borrowed value counts as present.
+    ///
+    pub fn any_some(&self) -> bool {
+        return self.cells().iter().any(|cell| cell.is_some());
+    }
+
+    ///
+    /// Clears every member and returns how many values were removed.
+    /// Members that are borrowed somewhere are skipped, use try_clear_all to detect them.
+    ///
+    pub fn clear_all(&self) -> usize {
+        let mut removed = 0;
+        for cell in self.cells() {
+            if let Ok(Some(value)) = cell.try_clear() {
+                drop(value);
+                removed += 1;
+            }
+        }
+
+        return removed;
+    }
+
+    ///
+    /// Clears every member and returns how many values were removed.
+    /// Fails with the index of the first member that is borrowed somewhere, no member is cleared then.
+    ///
+    pub fn try_clear_all(&self) -> Result<usize, (usize, RcOCellError)> {
+        let cells = self.cells();
+        check_all(&cells)?;
+        return Ok(cells.iter().filter_map(|cell| cell.clear()).count());
+    }
+}
+
+impl <T: Clone> RcOCellGroup<T> {
+    ///
+    /// Sets every member to a clone of the value.
+    /// Members that are borrowed somewhere are skipped, their indices are returned.
+    ///
+    pub fn set_all_cloned(&self, value: &T) -> Vec<usize> {
+        let mut skipped = Vec::new();
+        for (index, cell) in self.cells().iter().enumerate() {
+            if cell.try_set(value.clone()).is_err() {
+                skipped.push(index);
+            }
+        }
+
+        return skipped;
+    }
+
+    ///
+    /// Sets every member to a clone of the value.
+    /// Fails with the index of the first member that is borrowed somewhere, no member is set then.
+    ///
+    pub fn try_set_all_cloned(&self, value: &T) -> Result<(), (usize, RcOCellError)> {
+        let cells = self.cells();
+        check_all(&cells)?;
+        for cell in cells.iter() {
+            cell.set(value.clone());
+        }
+
+        return Ok(());
+    }
+
+    ///
+    /// Returns clones of the values of the members that hold a value, in order.
+    /// Panics if a value is borrowed mutably somewhere.
+    ///
+    #[track_caller]
+    pub fn collect_values(&self) -> Vec<T> {
+        return self.cells().iter().filter_map(|cell| cell.raw_borrow().clone()).collect();
+    }
+
+    ///
+    /// Returns clones of the values of the members that hold a value, in order.
+    /// Fails with the index of the first member whose value is borrowed mutably somewhere.
+    ///
+    pub fn try_collect_values(&self) -> Result<Vec<T>, (usize, RcOCellError)> {
+        let mut values = Vec::new();
+        for (index, cell) in self.cells().iter().enumerate() {
+            match cell.try_get_and_clone() {
+                Ok(value) => values.push(value),
+                Err(RcOCellError::NoValue) => {}
+                Err(e) => return Err((index, e)),
+            }
+        }
+
+        return Ok(values);
+    }
+}
+
+///
+/// Checks that every cell can be modified, so a bulk operation either modifies all cells or none.
+///
+fn check_all<T>(cells: &[RcOCell<T>]) -> Result<(), (usize, RcOCellError)> {
+    for (index, cell) in cells.iter().enumerate() {
+        cell.check_mut().map_err(|e| (index, e))?;
+    }
+
+    return Ok(());
+}
+
+impl <T> Default for RcOCellGroup<T> {
+    fn default() -> Self {
+        return RcOCellGroup::new();
+    }
+}
+
+impl <T> Clone for RcOCellGroup<T> {
+    fn clone(&self) -> Self {
+        return RcOCellGroup {members: RefCell::new(self.members.borrow().clone())};
+    }
+}
+
+impl <T> FromIterator<RcOCell<T>> for RcOCellGroup<T> {
+    fn from_iter<I: IntoIterator<Item=RcOCell<T>>>(iter: I) -> Self {
+        return RcOCellGroup {members: RefCell::new(iter.into_iter().map(Member::Strong).collect())};
+    }
+}
+
+impl <T: Debug> Debug for RcOCellGroup<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return f.debug_list().entries(self.cells().iter()).finish();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn panels() -> (RcOCellGroup<String>, Vec<RcOCell<String>>) {
+        let cells = vec![
+            RcOCell::from_value("a".to_string()),
+            RcOCell::new(),
+            RcOCell::from_value("c".to_string()),
+        ];
+        return (cells.iter().cloned().collect(), cells);
+    }
+
+    #[test]
+    fn test_group_queries() {
+        let (group, cells) = panels();
+        assert_eq!(group.len(), 3);
+        assert_eq!(group.all_some(), false);
+        assert_eq!(group.any_some(), true);
+        assert_eq!(group.collect_values(), vec!["a".to_string(), "c".to_string()]);
+
+        let mut seen = Vec::new();
+        group.for_each(|index, cell| seen.push((index, cell.is_some())));
+        assert_eq!(seen, vec![(0, true), (1, false), (2, true)]);
+
+        let brw = cells[2].borrow_mut();
+        assert_eq!(group.all_some(), false);
+        match group.try_collect_values() {
+            Err((2, RcOCellError::BorrowError(_))) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(cells[0].catch(|_| group.collect_values()).is_err(), true);
+        drop(brw);
+
+        cells[1].set("b".to_string());
+        assert_eq!(group.all_some(), true);
+        assert_eq!(RcOCellGroup::<u32>::new().all_some(), true);
+        assert_eq!(RcOCellGroup::<u32>::new().any_some(), false);
+    }
+
+    #[test]
+    fn test_group_bulk_skips_borrowed() {
+        let (group, cells) = panels();
+        let brw = cells[0].borrow();
+        assert_eq!(group.set_all_cloned(&"x".to_string()), vec![0]);
+        assert_eq!(cells[1].get_and_clone(), "x");
+        assert_eq!(cells[2].get_and_clone(), "x");
+        assert_eq!(group.clear_all(), 2);
+        assert_eq!(cells[0].is_some(), true);
+        drop(brw);
+
+        assert_eq!(group.clear_all(), 1);
+        assert_eq!(group.any_some(), false);
+        assert_eq!(group.clear_all(), 0);
+    }
+
+    #[test]
+    fn test_group_try_aborts() {
+        let (group, cells) = panels();
+        let brw = cells[2].borrow();
+        match group.try_set_all_cloned(&"x".to_string()) {
+            Err((2, RcOCellError::BorrowError(_))) => {}
+            _ => panic!("unexpected"),
+        };
+        match group.try_clear_all() {
+            Err((2, RcOCellError::BorrowError(_))) => {}
+            _ => panic!("unexpected"),
+        };
+        drop(brw);
+        assert_eq!(group.collect_values(), vec!["a".to_string(), "c".to_string()]);
+
+        group.try_set_all_cloned(&"x".to_string()).unwrap();
+        assert_eq!(group.collect_values().len(), 3);
+        assert_eq!(group.try_clear_all().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_group_weak_members() {
+        let group = RcOCellGroup::new();
+        let kept = RcOCell::from_value(1u32);
+        group.push(kept.clone());
+        let weak = RcOCell::from_value(2u32);
+        group.push_weak(&weak);
+        let temp = RcOCell::from_value(3u32);
+        group.push_weak(&temp);
+        group.push(RcOCell::from_value(4u32));
+
+        assert_eq!(group.collect_values(), vec![1, 2, 3, 4]);
+        drop(temp);
+        assert_eq!(group.len(), 3);
+        assert_eq!(group.set_all_cloned(&0).is_empty(), true);
+        assert_eq!(weak.get_and_clone(), 0);
+
+        let mut indices = Vec::new();
+        group.for_each(|index, _| indices.push(index));
+        assert_eq!(indices, vec![0, 1, 2]);
+
+        let probe = weak.downgrade();
+        drop(weak);
+        assert_eq!(probe.try_upgrade().is_err(), true);
+        assert_eq!(group.clear_all(), 2);
+        assert_eq!(group.is_empty(), false);
+        assert_eq!(format!("{:?}", group), format!("{:?}", vec![kept.clone(), group.cells()[1].clone()]));
+    }
+}
@@ -0,0 +1,545 @@
+use std::cell::{Ref, RefMut};
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::{Deref, DerefMut};
+use crate::{deferred, dirty, RcOCell};
+#[cfg(feature = "debug-borrow-tracking")]
+use crate::borrow_tracking;
+
+///
+/// Borrow of the value of a cell, returned by borrow and similar methods.
+///
+/// Behaves like Ref, the borrow ends when the guard is dropped.
+/// Dropping the last guard of a cell applies a value stored with set_deferred.
+/// C is the type of the value of the cell, it differs from T after the guard was mapped to a part of the value.
+///
+/// The methods of the guard are associated functions like the ones of Ref so they do not hide methods of T.
+///
+pub struct RcORef<'a, T: ?Sized, C = T> {
+    value: Ref<'a, T>,
+    release: Release<'a, C>,
+}
+
+///
+/// Mutable borrow of the value of a cell, returned by borrow_mut and similar methods.
+///
+/// Behaves like RefMut, the borrow ends when the guard is dropped.
+/// Dropping the last guard of a cell applies a value stored with set_deferred.
+/// With the `poison` feature the cell is poisoned if the guard is dropped by a panic unwinding.
+/// C is the type of the value of the cell, it differs from T after the guard was mapped to a part of the value.
+///
+/// The methods of the guard are associated functions like the ones of RefMut so they do not hide methods of T.
+///
+pub struct RcORefMut<'a, T: ?Sized, C = T> {
+    value: RefMut<'a, T>,
+    release: Release<'a, C>,
+}
+
+///
+/// Runs when a guard is dropped, after the borrow of the guard was released since it is declared after the borrow.
+///
+pub(crate) struct Release<'a, C> {
+    cell: &'a RcOCell<C>,
+    exclusive: bool,
+    ///
+    /// Set on both halves of a split guard, the cell stays borrowed mutably by the other half.
+    ///
+    split: bool,
+    ///
+    /// Set for guards that give the caller mutable access to the value, marks the cell dirty when dropped.
+    ///
+    touch: bool,
+    #[cfg(feature = "poison")]
+    poison_on_unwind: bool,
+    #[cfg(feature = "debug-borrow-tracking")]
+    sequence: u64,
+}
+
+impl <'a, C> Release<'a, C> {
+    #[cfg_attr(feature = "debug-borrow-tracking", track_caller)]
+    #[inline]
+    pub(crate) fn shared(cell: &'a RcOCell<C>) -> Release<'a, C> {
+        return Release {
+            cell,
+            exclusive: false,
+            split: false,
+            touch: false,
+            #[cfg(feature = "poison")]
+            poison_on_unwind: false,
+            #[cfg(feature = "debug-borrow-tracking")]
+            sequence: borrow_tracking::borrowed(cell, false),
+        };
+    }
+
+    #[cfg_attr(feature = "debug-borrow-tracking", track_caller)]
+    #[inline]
+    pub(crate) fn exclusive(cell: &'a RcOCell<C>) -> Release<'a, C> {
+        return Release {
+            cell,
+            exclusive: true,
+            split: false,
+            touch: false,
+            #[cfg(feature = "poison")]
+            poison_on_unwind: !std::thread::panicking(),
+            #[cfg(feature = "debug-borrow-tracking")]
+            sequence: borrow_tracking::borrowed(cell, true),
+        };
+    }
+}
+
+impl <'a, C> Release<'a, C> {
+    ///
+    /// Second release for the other half of a split guard, the cell is flushed once both halves are dropped
+    /// since flushing needs the cell to be borrowed by nothing. Marks both halves as split.
+    ///
+    #[cfg_attr(feature = "debug-borrow-tracking", track_caller)]
+    fn split(&mut self) -> Release<'a, C> {
+        self.split = true;
+        return Release {
+            cell: self.cell,
+            exclusive: self.exclusive,
+            split: true,
+            touch: self.touch,
+            #[cfg(feature = "poison")]
+            poison_on_unwind: self.poison_on_unwind,
+            #[cfg(feature = "debug-borrow-tracking")]
+            sequence: borrow_tracking::borrowed(self.cell, self.exclusive),
+        };
+    }
+}
+
+impl <C> Drop for Release<'_, C> {
+    #[inline]
+    fn drop(&mut self) {
+        #[cfg(feature = "debug-borrow-tracking")]
+        borrow_tracking::released(self.cell, self.sequence);
+
+        if self.touch {
+            dirty::touched(self.cell);
+        }
+
+        #[cfg(feature = "futures")]
+        crate::future::released(self.cell);
+
+        if std::thread::panicking() {
+            #[cfg(feature = "poison")]
+            if self.poison_on_unwind {
+                crate::poison::poison(self.cell);
+            }
+            return;
+        }
+
+        deferred::flush(self.cell);
+    }
+}
+
+impl <'a, T: ?Sized, C> RcORef<'a, T, C> {
+    pub(crate) fn new(value: Ref<'a, T>, release: Release<'a, C>) -> RcORef<'a, T, C> {
+        return RcORef {value, release};
+    }
+
+    ///
+    /// Returns the cell the value was borrowed from.
+    ///
+    pub fn cell(orig: &RcORef<'a, T, C>) -> &'a RcOCell<C> {
+        return orig.release.cell;
+    }
+
+    ///
+    /// Makes a guard for a component of the borrowed value, like Ref::map.
+    ///
+    pub fn map<U: ?Sized, F>(orig: RcORef<'a, T, C>, f: F) -> RcORef<'a, U, C>
+        where F: FnOnce(&T) -> &U
+    {
+        return RcORef {value: Ref::map(orig.value, f), release: orig.release};
+    }
+
+    ///
+    /// Makes a second guard for the same borrowed value, like Ref::clone, without borrowing the cell again.
+    /// Both guards are independent, the cell stays borrowed until both are dropped in any order.
+    ///
+    /// There is no such function for RcORefMut since two mutable guards for the same value would alias,
+    /// use RcORefMut::map_split to borrow disjoint components mutably instead.
+    ///
+    #[cfg_attr(feature = "debug-borrow-tracking", track_caller)]
+    pub fn duplicate(orig: &RcORef<'a, T, C>) -> RcORef<'a, T, C> {
+        return RcORef {value: Ref::clone(&orig.value), release: Release::shared(orig.release.cell)};
+    }
+
+    ///
+    /// Makes a guard for a component of the borrowed value if the Fn returns one, like Ref::filter_map.
+    /// Returns the original guard if the Fn returns None.
+    ///
+    pub fn try_map<U: ?Sized, F>(orig: RcORef<'a, T, C>, f: F) -> Result<RcORef<'a, U, C>, RcORef<'a, T, C>>
+        where F: FnOnce(&T) -> Option<&U>
+    {
+        let release = orig.release;
+        return match Ref::filter_map(orig.value, f) {
+            Ok(value) => Ok(RcORef {value, release}),
+            Err(value) => Err(RcORef {value, release}),
+        };
+    }
+
+    ///
+    /// Ends the borrow, same as dropping the guard.
+    ///
+    pub fn release(orig: RcORef<'a, T, C>) {
+        drop(orig);
+    }
+
+    ///
+    /// Converts the guard into the Ref of the std library.
+    /// A value stored with set_deferred is not applied when the returned Ref is dropped,
+    /// only when the next guard of the cell is released.
+    ///
+    pub fn into_std(orig: RcORef<'a, T, C>) -> Ref<'a, T> {
+        return orig.value;
+    }
+}
+
+impl <'a, T: ?Sized, C> RcORefMut<'a, T, C> {
+    pub(crate) fn new(value: RefMut<'a, T>, release: Release<'a, C>) -> RcORefMut<'a, T, C> {
+        return RcORefMut {value, release};
+    }
+
+    ///
+    /// Returns the cell the value was borrowed from.
+    ///
+    pub fn cell(orig: &RcORefMut<'a, T, C>) -> &'a RcOCell<C> {
+        return orig.release.cell;
+    }
+
+    ///
+    /// Marks the cell dirty when the guard is dropped, for guards that hand mutable access to the caller.
+    /// Guards the crate drops itself do not, the methods using them call changed if they modify the value.
+    ///
+    pub(crate) fn marks_dirty(orig: &mut RcORefMut<'a, T, C>) {
+        orig.release.touch = true;
+    }
+
+    ///
+    /// Makes a guard for a component of the borrowed value, like RefMut::map.
+    ///
+    pub fn map<U: ?Sized, F>(orig: RcORefMut<'a, T, C>, f: F) -> RcORefMut<'a, U, C>
+        where F: FnOnce(&mut T) -> &mut U
+    {
+        return RcORefMut {value: RefMut::map(orig.value, f), release: orig.release};
+    }
+
+    ///
+    /// Makes a guard for a component of the borrowed value if the Fn returns one, like RefMut::filter_map.
+    /// Returns the original guard if the Fn returns None.
+    ///
+    pub fn try_map<U: ?Sized, F>(orig: RcORefMut<'a, T, C>, f: F) -> Result<RcORefMut<'a, U, C>, RcORefMut<'a, T, C>>
+        where F: FnOnce(&mut T) -> Option<&mut U>
+    {
+        let release = orig.release;
+        return match RefMut::filter_map(orig.value, f) {
+            Ok(value) => Ok(RcORefMut {value, release}),
+            Err(value) => Err(RcORefMut {value, release}),
+        };
+    }
+
+    ///
+    /// Splits the guard into guards for two disjoint components of the borrowed value, like RefMut::map_split.
+    /// The cell stays borrowed mutably until both guards are dropped, the guards can be split again.
+    ///
+    /// The halves support every function of RcORefMut except downgrade, which returns a half unchanged
+    /// since the cell cannot be borrowed shared while the other half still borrows it mutably.
+    /// This stays so after the other half was dropped.
+    ///
+    #[cfg_attr(feature = "debug-borrow-tracking", track_caller)]
+    pub fn map_split<U: ?Sized, V: ?Sized, F>(orig: RcORefMut<'a, T, C>, f: F) -> (RcORefMut<'a, U, C>, RcORefMut<'a, V, C>)
+        where F: FnOnce(&mut T) -> (&mut U, &mut V)
+    {
+        let RcORefMut {value, mut release} = orig;
+        let other = release.split();
+        let (a, b) = RefMut::map_split(value, f);
+        return (RcORefMut {value: a, release}, RcORefMut {value: b, release: other});
+    }
+
+    ///
+    /// Ends the borrow, same as dropping the guard.
+    ///
+    pub fn release(orig: RcORefMut<'a, T, C>) {
+        drop(orig);
+    }
+
+    ///
+    /// Converts the guard into the RefMut of the std library.
+    /// The returned RefMut does not poison the cell and does not apply a value stored with set_deferred when dropped.
+    ///
+    pub fn into_std(orig: RcORefMut<'a, T, C>) -> RefMut<'a, T> {
+        #[cfg_attr(not(feature = "poison"), allow(unused_mut))]
+        let RcORefMut {value, mut release} = orig;
+        #[cfg(feature = "poison")]
+        {
+            release.poison_on_unwind = false;
+        }
+        drop(release);
+        return value;
+    }
+
+    ///
+    /// Turns the mutable borrow into a shared borrow of the same value,
+    /// other shared borrows of the cell are possible afterwards while mutable borrows still fail.
+    /// Returns the guard unchanged if it is a half of a guard split with map_split.
+    ///
+    /// RefCell cannot do this in place, so the RefMut is released and the cell is borrowed again right away.
+    /// This is sound and leaves no window in which the cell could be modified:
+    /// the cell is not Send, so no other thread can access it, and nothing between the release and the new borrow
+    /// runs any code apart from RefCell itself. In particular a value stored with set_deferred is not applied
+    /// and the cell is not poisoned. The new borrow is of the same RefCell, which the guard keeps alive,
+    /// so the borrowed part of the value is still at the same address, the returned guard points to it again.
+    /// A guard that is not split is the only borrow of the cell, so borrowing it again cannot fail.
+    ///
+    pub fn downgrade(orig: RcORefMut<'a, T, C>) -> Result<RcORef<'a, T, C>, RcORefMut<'a, T, C>> {
+        if orig.release.split {
+            return Err(orig);
+        }
+
+        #[cfg_attr(not(feature = "poison"), allow(unused_mut))]
+        let RcORefMut {value, mut release} = orig;
+        let cell = release.cell;
+        let ptr: *const T = &*value;
+        drop(value);
+
+        let borrowed = cell.rc.try_borrow().expect("cell is borrowed by nothing else");
+        //Sound as explained above, the value did not move or change since the RefMut was released.
+        let value = Ref::map(borrowed, |_| unsafe { &*ptr });
+        if release.touch {
+            dirty::touched(cell);
+        }
+
+        #[cfg(feature = "debug-borrow-tracking")]
+        borrow_tracking::downgraded(cell, release.sequence);
+        release.exclusive = false;
+        release.touch = false;
+        #[cfg(feature = "poison")]
+        {
+            release.poison_on_unwind = false;
+        }
+        return Ok(RcORef {value, release});
+    }
+}
+
+impl <'a, T: ?Sized, C> From<RcORef<'a, T, C>> for Ref<'a, T> {
+    fn from(value: RcORef<'a, T, C>) -> Self {
+        return RcORef::into_std(value);
+    }
+}
+
+impl <'a, T: ?Sized, C> From<RcORefMut<'a, T, C>> for RefMut<'a, T> {
+    fn from(value: RcORefMut<'a, T, C>) -> Self {
+        return RcORefMut::into_std(value);
+    }
+}
+
+impl <T: ?Sized, C> Deref for RcORef<'_, T, C> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        return &self.value;
+    }
+}
+
+impl <T: ?Sized, C> Deref for RcORefMut<'_, T, C> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        return &self.value;
+    }
+}
+
+impl <T: ?Sized, C> DerefMut for RcORefMut<'_, T, C> {
+    fn deref_mut(&mut self) -> &mut T {
+        return &mut self.value;
+    }
+}
+
+impl <T: ?Sized + Debug, C> Debug for RcORef<'_, T, C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return Debug::fmt(&*self.value, f);
+    }
+}
+
+impl <T: ?Sized + Display, C> Display for RcORef<'_, T, C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return Display::fmt(&*self.value, f);
+    }
+}
+
+impl <T: ?Sized + Debug, C> Debug for RcORefMut<'_, T, C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return Debug::fmt(&*self.value, f);
+    }
+}
+
+impl <T: ?Sized + Display, C> Display for RcORefMut<'_, T, C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return Display::fmt(&*self.value, f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Ref, RefMut};
+    use crate::*;
+
+    #[test]
+    fn test_guard_borrow_rules() {
+        let x = RcOCell::from_value((1u32, String::from("a")));
+        let a = x.borrow();
+        let b = RcORef::map(x.borrow(), |v| &v.1);
+        assert_eq!(x.try_borrow_mut().is_err(), true);
+        assert_eq!(std::ptr::eq(RcORef::cell(&b), &x), true);
+        RcORef::release(a);
+        assert_eq!(x.try_borrow_mut().is_err(), true);
+        assert_eq!(*b, "a");
+        drop(b);
+
+        let mut c = x.borrow_mut();
+        c.0 = 2;
+        assert_eq!(x.try_borrow().is_err(), true);
+        assert_eq!(std::ptr::eq(RcORefMut::cell(&c), &x), true);
+        let mut c = RcORefMut::map(c, |v| &mut v.1);
+        c.push('b');
+        RcORefMut::release(c);
+        assert_eq!(x.get_and_clone(), (2, String::from("ab")));
+    }
+
+    #[test]
+    fn test_guard_duplicate() {
+        let x = RcOCell::from_value((1u32, String::from("a")));
+        let a = RcORef::map(x.borrow(), |v| &v.1);
+        let b = RcORef::duplicate(&a);
+        assert_eq!(std::ptr::eq(&*a, &*b), true);
+        drop(a);
+        assert_eq!(*b, "a");
+        assert_eq!(x.try_borrow_mut().is_err(), true);
+        assert_eq!(x.try_set((2, String::new())).is_err(), true);
+        drop(b);
+        assert_eq!(x.try_borrow_mut().is_ok(), true);
+
+        let a = x.borrow();
+        let b = RcORef::duplicate(&a);
+        x.set_deferred((3, String::from("c")));
+        drop(b);
+        assert_eq!(x.try_borrow_mut().is_err(), true);
+        assert_eq!(a.0, 1);
+        drop(a);
+        assert_eq!(x.get_and_clone(), (3, String::from("c")));
+    }
+
+    #[test]
+    fn test_guard_try_map() {
+        let x = RcOCell::from_value(vec![1u32, 2]);
+        let first = RcORef::try_map(x.borrow(), |v| v.first()).unwrap();
+        assert_eq!(*first, 1);
+        drop(first);
+
+        let brw = match RcORef::try_map(x.borrow(), |v| v.get(5)) {
+            Err(brw) => brw,
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(x.try_borrow_mut().is_err(), true);
+        drop(brw);
+
+        let mut last = RcORefMut::try_map(x.borrow_mut(), |v| v.last_mut()).unwrap();
+        *last = 3;
+        drop(last);
+        assert_eq!(RcORefMut::try_map(x.borrow_mut(), |v| v.get_mut(5)).is_err(), true);
+        assert_eq!(x.get_and_clone(), vec![1, 3]);
+    }
+
+    #[test]
+    fn test_guard_downgrade() {
+        let x = RcOCell::from_value((1u32, 2u32));
+        let mut brw = RcORefMut::map(x.borrow_mut(), |v| &mut v.1);
+        *brw = 3;
+        x.set_deferred((5, 5));
+        let brw = RcORefMut::downgrade(brw).unwrap_or_else(|_| panic!("unexpected"));
+        assert_eq!(*brw, 3);
+        assert_eq!(x.has_deferred(), true);
+        assert_eq!(x.borrow().0, 1);
+        assert_eq!(x.try_borrow_mut().is_err(), true);
+        assert_eq!(x.try_set((4, 4)).is_err(), true);
+        assert_eq!(*brw, 3);
+        drop(brw);
+        assert_eq!(x.get_and_clone(), (5, 5));
+        assert_eq!(x.try_borrow_mut().is_ok(), true);
+    }
+
+    #[test]
+    fn test_guard_downgrade_split() {
+        let x = RcOCell::from_value((1u32, 2u32));
+        let (a, b) = RcORefMut::map_split(x.borrow_mut(), |v| (&mut v.0, &mut v.1));
+        let mut a = match RcORefMut::downgrade(a) {
+            Err(a) => a,
+            Ok(_) => panic!("unexpected"),
+        };
+        *a = 3;
+        x.set_deferred((5, 5));
+        assert_eq!(x.try_borrow().is_err(), true);
+        drop(b);
+        let a = match RcORefMut::downgrade(a) {
+            Err(a) => a,
+            Ok(_) => panic!("unexpected"),
+        };
+        assert_eq!(x.try_borrow().is_err(), true);
+        assert_eq!(x.has_deferred(), true);
+        drop(a);
+        assert_eq!(x.get_and_clone(), (5, 5));
+    }
+
+    #[test]
+    fn test_guard_split_halves() {
+        let x = RcOCell::from_value((1u32, vec![2u32, 3]));
+        let (a, b) = RcORefMut::map_split(x.borrow_mut(), |v| (&mut v.0, &mut v.1));
+        assert_eq!(std::ptr::eq(RcORefMut::cell(&a), &x), true);
+        assert_eq!(std::ptr::eq(RcORefMut::cell(&b), &x), true);
+
+        let mut a = RcORefMut::map(a, |v| v);
+        *a = 4;
+        let mut last = RcORefMut::try_map(b, |v| v.last_mut()).unwrap();
+        *last = 5;
+        let last = match RcORefMut::try_map(last, |_| None::<&mut u32>) {
+            Err(last) => last,
+            Ok(_) => panic!("unexpected"),
+        };
+        assert_eq!(*last, 5);
+        let a = match RcORefMut::downgrade(a) {
+            Err(a) => a,
+            Ok(_) => panic!("unexpected"),
+        };
+
+        let mut a: RefMut<u32> = RcORefMut::into_std(a);
+        *a = 6;
+        assert_eq!(x.try_borrow().is_err(), true);
+        drop(a);
+        assert_eq!(x.try_borrow().is_err(), true);
+        RcORefMut::release(last);
+        assert_eq!(x.get_and_clone(), (6, vec![2, 5]));
+
+        let (a, b) = RcORefMut::map_split(x.borrow_mut(), |v| (&mut v.0, &mut v.1));
+        let (c, d) = RcORefMut::map_split(b, |v| v.split_at_mut(1));
+        drop((a, c));
+        assert_eq!(x.try_borrow_mut().is_err(), true);
+        drop(d);
+        assert_eq!(x.try_borrow_mut().is_ok(), true);
+    }
+
+    #[test]
+    fn test_guard_into_std() {
+        let x = RcOCell::from_value(1u32);
+        let brw: Ref<u32> = x.borrow().into();
+        assert_eq!(x.try_borrow_mut().is_err(), true);
+        assert_eq!(*brw, 1);
+        drop(brw);
+
+        let mut brw: RefMut<u32> = RcORefMut::into_std(x.borrow_mut());
+        *brw = 2;
+        assert_eq!(x.try_borrow().is_err(), true);
+        drop(brw);
+        assert_eq!(x.get_and_clone(), 2);
+    }
+}
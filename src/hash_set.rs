@@ -0,0 +1,266 @@
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::collections::HashSet;
+use std::hash::{BuildHasher, Hash};
+use crate::{RcOCell, RcOCellError, RcORefMut};
+
+///
+/// Operations for `RcOCell<HashSet<T>>`, also for sets with a custom hasher.
+///
+/// The modifying operations treat an empty cell like an empty set, insert_item and extend_items create the set on first use.
+/// Observers are notified if the set changed, a set that was created counts as a change.
+/// The reading operations never panic, they treat a cell whose value is borrowed mutably like an empty cell.
+///
+pub trait HashSetCellExt<T, S = RandomState> {
+    ///
+    /// Adds the value to the set and returns true if it was not present yet, an empty cell gets a new set.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn insert_item(&self, v: T) -> bool;
+
+    ///
+    /// Adds the value to the set and returns true if it was not present yet, an empty cell gets a new set.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    fn try_insert_item(&self, v: T) -> Result<bool, RcOCellError>;
+
+    ///
+    /// Removes the value from the set and returns true if it was present, false for an empty cell.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn remove_item<Q>(&self, v: &Q) -> bool
+        where T: Borrow<Q>, Q: Hash + Eq + ?Sized;
+
+    ///
+    /// Removes the value from the set and returns true if it was present, false for an empty cell.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    fn try_remove_item<Q>(&self, v: &Q) -> Result<bool, RcOCellError>
+        where T: Borrow<Q>, Q: Hash + Eq + ?Sized;
+
+    ///
+    /// Returns true if the set contains the value.
+    /// Never panics, returns false if the cell is empty or the value is borrowed mutably somewhere.
+    ///
+    fn contains_item<Q>(&self, v: &Q) -> bool
+        where T: Borrow<Q>, Q: Hash + Eq + ?Sized;
+
+    ///
+    /// Returns the number of values in the set.
+    /// Never panics, returns 0 if the cell is empty or the value is borrowed mutably somewhere.
+    ///
+    fn set_len(&self) -> usize;
+
+    ///
+    /// Takes the set out of the cell and leaves an empty set in its place.
+    /// Unlike get_and_clear the cell is populated afterwards, an empty cell returns an empty set.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn take_all(&self) -> HashSet<T, S>;
+
+    ///
+    /// Takes the set out of the cell and leaves an empty set in its place, see take_all.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    fn try_take_all(&self) -> Result<HashSet<T, S>, RcOCellError>;
+
+    ///
+    /// Adds all values of the iterator to the set, an empty cell gets a new set.
+    /// The iterator runs while the value is borrowed mutably, so it cannot access the cell.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn extend_items<I>(&self, iter: I)
+        where I: IntoIterator<Item = T>;
+
+    ///
+    /// Adds all values of the iterator to the set, see extend_items.
+    /// Fails without consuming the iterator if the value is borrowed somewhere.
+    ///
+    fn try_extend_items<I>(&self, iter: I) -> Result<(), RcOCellError>
+        where I: IntoIterator<Item = T>;
+}
+
+///
+/// Runs the Fn with the set, an empty cell gets a new set first.
+/// Notifies the observers if the set was created or the Fn returns true.
+///
+fn update<T, S: Default>(cell: &RcOCell<HashSet<T, S>>, mut value: RcORefMut<'_, Option<HashSet<T, S>>, HashSet<T, S>>, f: impl FnOnce(&mut HashSet<T, S>) -> bool) -> bool {
+    let created = value.is_none();
+    let changed = f(value.get_or_insert_with(HashSet::default));
+    drop(value);
+    if created || changed {
+        cell.changed();
+    }
+
+    return changed;
+}
+
+///
+/// Removes the value from the set, an empty cell stays empty.
+///
+fn remove<T, S, Q>(cell: &RcOCell<HashSet<T, S>>, mut value: RcORefMut<'_, Option<HashSet<T, S>>, HashSet<T, S>>, v: &Q) -> bool
+    where T: Hash + Eq + Borrow<Q>, S: BuildHasher, Q: Hash + Eq + ?Sized
+{
+    let removed = value.as_mut().is_some_and(|set| set.remove(v));
+    drop(value);
+    if removed {
+        cell.changed();
+    }
+
+    return removed;
+}
+
+///
+/// Takes the set and leaves an empty one in its place.
+///
+fn take<T, S: Default>(cell: &RcOCell<HashSet<T, S>>, mut value: RcORefMut<'_, Option<HashSet<T, S>>, HashSet<T, S>>) -> HashSet<T, S> {
+    let set = value.replace(HashSet::default()).unwrap_or_default();
+    drop(value);
+    cell.changed();
+    return set;
+}
+
+impl <T, S> HashSetCellExt<T, S> for RcOCell<HashSet<T, S>>
+    where T: Hash + Eq, S: BuildHasher + Default
+{
+    fn insert_item(&self, v: T) -> bool {
+        return update(self, self.raw_borrow_mut(), |set| set.insert(v));
+    }
+
+    fn try_insert_item(&self, v: T) -> Result<bool, RcOCellError> {
+        return Ok(update(self, self.try_raw_borrow_mut()?, |set| set.insert(v)));
+    }
+
+    fn remove_item<Q>(&self, v: &Q) -> bool
+        where T: Borrow<Q>, Q: Hash + Eq + ?Sized
+    {
+        return remove(self, self.raw_borrow_mut(), v);
+    }
+
+    fn try_remove_item<Q>(&self, v: &Q) -> Result<bool, RcOCellError>
+        where T: Borrow<Q>, Q: Hash + Eq + ?Sized
+    {
+        return Ok(remove(self, self.try_raw_borrow_mut()?, v));
+    }
+
+    fn contains_item<Q>(&self, v: &Q) -> bool
+        where T: Borrow<Q>, Q: Hash + Eq + ?Sized
+    {
+        return self.is_some_and(|set| set.contains(v));
+    }
+
+    fn set_len(&self) -> usize {
+        return self.map_or(0, |set| set.len());
+    }
+
+    fn take_all(&self) -> HashSet<T, S> {
+        return take(self, self.raw_borrow_mut());
+    }
+
+    fn try_take_all(&self) -> Result<HashSet<T, S>, RcOCellError> {
+        return Ok(take(self, self.try_raw_borrow_mut()?));
+    }
+
+    fn extend_items<I>(&self, iter: I)
+        where I: IntoIterator<Item = T>
+    {
+        update(self, self.raw_borrow_mut(), |set| extend(set, iter));
+    }
+
+    fn try_extend_items<I>(&self, iter: I) -> Result<(), RcOCellError>
+        where I: IntoIterator<Item = T>
+    {
+        update(self, self.try_raw_borrow_mut()?, |set| extend(set, iter));
+        return Ok(());
+    }
+}
+
+///
+/// Adds the values to the set, returns true if any value was not present yet.
+///
+fn extend<T: Hash + Eq, S: BuildHasher>(set: &mut HashSet<T, S>, iter: impl IntoIterator<Item = T>) -> bool {
+    let mut changed = false;
+    for v in iter {
+        changed |= set.insert(v);
+    }
+
+    return changed;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use crate::*;
+
+    #[test]
+    fn test_hash_set_lazy() {
+        let selected: RcOCell<HashSet<String>> = RcOCell::new();
+        assert_eq!(selected.set_len(), 0);
+        assert_eq!(selected.contains_item("a"), false);
+        assert_eq!(selected.remove_item("a"), false);
+        assert_eq!(selected.is_none(), true);
+
+        assert_eq!(selected.insert_item("a".to_string()), true);
+        assert_eq!(selected.insert_item("a".to_string()), false);
+        assert_eq!(selected.try_insert_item("b".to_string()).unwrap(), true);
+        assert_eq!(selected.set_len(), 2);
+        assert_eq!(selected.contains_item("a"), true);
+        assert_eq!(selected.contains_item(&"b".to_string()), true);
+
+        assert_eq!(selected.remove_item("a"), true);
+        assert_eq!(selected.try_remove_item("a").unwrap(), false);
+
+        selected.clear();
+        selected.extend_items(["c".to_string(), "d".to_string()]);
+        selected.try_extend_items(vec!["d".to_string(), "e".to_string()]).unwrap();
+        assert_eq!(selected.set_len(), 3);
+
+        let taken = selected.take_all();
+        assert_eq!(taken.len(), 3);
+        assert_eq!(taken.contains("e"), true);
+        assert_eq!(selected.is_some(), true);
+        assert_eq!(selected.set_len(), 0);
+        selected.clear();
+        assert_eq!(selected.try_take_all().unwrap().is_empty(), true);
+        assert_eq!(selected.is_some(), true);
+    }
+
+    #[test]
+    fn test_hash_set_notifies() {
+        let selected: RcOCell<HashSet<u32>> = RcOCell::new();
+        let changes = RcOCell::from_value(0u32);
+        let changes2 = changes.clone();
+        selected.on_set(move |_| { changes2.set(changes2.get_and_clone() + 1); });
+        selected.extend_items([]);
+        selected.insert_item(1);
+        selected.insert_item(1);
+        selected.remove_item(&2);
+        selected.extend_items([1]);
+        assert_eq!(changes.get_and_clone(), 2);
+        selected.remove_item(&1);
+        assert_eq!(changes.get_and_clone(), 3);
+    }
+
+    #[test]
+    fn test_hash_set_borrowed() {
+        let selected = RcOCell::from_value(HashSet::from([1u32, 2]));
+        let brw = selected.borrow();
+        assert_eq!(selected.contains_item(&1), true);
+        assert_eq!(selected.set_len(), 2);
+        match selected.try_insert_item(3) {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(selected.try_remove_item(&1).is_err(), true);
+        assert_eq!(selected.try_take_all().is_err(), true);
+        assert_eq!(selected.try_extend_items([4]).is_err(), true);
+        assert_eq!(selected.catch(|s| s.insert_item(3)).is_err(), true);
+        drop(brw);
+
+        let brw = selected.borrow_mut();
+        assert_eq!(selected.contains_item(&1), false);
+        assert_eq!(selected.set_len(), 0);
+        drop(brw);
+        assert_eq!(selected.get_and_clone(), HashSet::from([1, 2]));
+    }
+}
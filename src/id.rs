@@ -0,0 +1,378 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt::{Debug, Formatter, Pointer};
+use std::hash::{Hash, Hasher};
+use std::rc::{Rc, Weak};
+use crate::{shared, RcOCell, WeakRcOCell};
+
+///
+/// Identifies a cell, all clones and weak handles of a cell return the same id.
+///
+/// The id is derived from the address of the shared allocation, which stays in place as long as any handle
+/// (strong or weak) of the cell exists. A weak handle therefore keeps returning the id after the cell was dropped,
+/// and no other cell can get that id while the weak handle exists.
+///
+/// Ids of dropped cells can collide: once every handle of a cell is gone its allocation is freed
+/// and a new cell may be placed at the same address. A CellId that outlives all handles of its cell
+/// may then compare equal to the id of an unrelated cell. Keep a handle, for example a WeakByIdentity,
+/// instead of a bare CellId if the id has to stay unique.
+/// Weak handles that never belonged to a cell, like the ones made from Weak::new, all share one id.
+///
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CellId(usize);
+
+impl CellId {
+    pub(crate) fn from_key(key: usize) -> CellId {
+        return CellId(key);
+    }
+}
+
+impl Debug for CellId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return write!(f, "CellId({:#x})", self.0);
+    }
+}
+
+impl <T> RcOCell<T> {
+    ///
+    /// Returns the id of the cell.
+    ///
+    pub fn id(&self) -> CellId {
+        return CellId::from_key(shared::key(&self.rc));
+    }
+}
+
+impl <T> WeakRcOCell<T> {
+    ///
+    /// Returns the id of the cell, also after the cell was dropped.
+    /// The id stays unique while this handle exists, see CellId for when ids of dropped cells collide.
+    ///
+    pub fn id(&self) -> CellId {
+        return CellId(Weak::as_ptr(&self.rc) as *const () as usize);
+    }
+}
+
+///
+/// Cell that is compared and hashed by its id instead of its value, returned by by_identity.
+///
+/// Use it as key of a HashMap or HashSet to key by cell, the hash and equality do not change when the value changes.
+/// Distinct cells holding equal values are distinct keys. Borrow<CellId> allows lookups by id.
+/// Clippy's mutable_key_type lint does not know that the value is not hashed, allow it where such a map is declared.
+///
+pub struct ByIdentity<T> {
+    cell: RcOCell<T>,
+    id: CellId,
+}
+
+///
+/// Weak cell that is compared and hashed by its id instead of its value, returned by by_identity.
+///
+/// Like ByIdentity, the id stays the same after the cell was dropped, so the key keeps working.
+///
+pub struct WeakByIdentity<T> {
+    cell: WeakRcOCell<T>,
+    id: CellId,
+}
+
+///
+/// Cell that is ordered by the address of its allocation instead of its value, returned by by_address.
+///
+/// Use it as key of a BTreeMap or BTreeSet to key by cell, the order does not change when the value changes.
+/// The order is stable as long as the allocation exists but has no meaning otherwise and differs between runs.
+/// Borrow<CellId> allows lookups and range queries by id, CellId is ordered by the same address.
+///
+pub struct OrderedByAddress<T> {
+    cell: RcOCell<T>,
+    id: CellId,
+}
+
+///
+/// Weak cell that is ordered by the address of its allocation instead of its value, returned by by_address.
+///
+/// Like OrderedByAddress, the allocation stays in place after the cell was dropped, so the order stays the same.
+///
+pub struct WeakOrderedByAddress<T> {
+    cell: WeakRcOCell<T>,
+    id: CellId,
+}
+
+impl <T> RcOCell<T> {
+    ///
+    /// Returns a clone of the cell that is compared and hashed by its id.
+    ///
+    pub fn by_identity(&self) -> ByIdentity<T> {
+        return ByIdentity {cell: self.clone(), id: self.id()};
+    }
+
+    ///
+    /// Returns a clone of the cell that is ordered by the address of its allocation.
+    ///
+    pub fn by_address(&self) -> OrderedByAddress<T> {
+        return OrderedByAddress {cell: self.clone(), id: self.id()};
+    }
+}
+
+impl <T> WeakRcOCell<T> {
+    ///
+    /// Returns a clone of the weak cell that is compared and hashed by its id.
+    ///
+    pub fn by_identity(&self) -> WeakByIdentity<T> {
+        return WeakByIdentity {cell: self.clone(), id: self.id()};
+    }
+
+    ///
+    /// Returns a clone of the weak cell that is ordered by the address of its allocation.
+    ///
+    pub fn by_address(&self) -> WeakOrderedByAddress<T> {
+        return WeakOrderedByAddress {cell: self.clone(), id: self.id()};
+    }
+}
+
+macro_rules! identity_impl {
+    ($($t:ident($handle:ident)),*) => {$(
+        impl <T> $t<T> {
+            ///
+            /// Returns the cell.
+            ///
+            pub fn get(&self) -> &$handle<T> {
+                return &self.cell;
+            }
+
+            ///
+            /// Returns the cell.
+            ///
+            pub fn into_inner(self) -> $handle<T> {
+                return self.cell;
+            }
+        }
+
+        impl <T> Clone for $t<T> {
+            fn clone(&self) -> Self {
+                return $t {cell: self.cell.clone(), id: self.id};
+            }
+        }
+
+        impl <T> PartialEq for $t<T> {
+            fn eq(&self, other: &Self) -> bool {
+                return self.id == other.id;
+            }
+        }
+
+        impl <T> Eq for $t<T> {}
+
+        impl <T> Borrow<CellId> for $t<T> {
+            fn borrow(&self) -> &CellId {
+                return &self.id;
+            }
+        }
+
+        impl <T> Debug for $t<T> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                return f.debug_tuple(stringify!($t)).field(&self.id).finish();
+            }
+        }
+    )*};
+}
+
+identity_impl!(ByIdentity(RcOCell), WeakByIdentity(WeakRcOCell), OrderedByAddress(RcOCell), WeakOrderedByAddress(WeakRcOCell));
+
+macro_rules! hash_impl {
+    ($($t:ident),*) => {$(
+        ///
+        /// Hashes like the CellId of the cell, so lookups by CellId find the key.
+        ///
+        impl <T> Hash for $t<T> {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.id.hash(state);
+            }
+        }
+    )*};
+}
+
+hash_impl!(ByIdentity, WeakByIdentity);
+
+macro_rules! ord_impl {
+    ($($t:ident),*) => {$(
+        impl <T> PartialOrd for $t<T> {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                return Some(self.cmp(other));
+            }
+        }
+
+        ///
+        /// Orders like the CellId of the cell, so range queries by CellId work.
+        ///
+        impl <T> Ord for $t<T> {
+            fn cmp(&self, other: &Self) -> Ordering {
+                return self.id.cmp(&other.id);
+            }
+        }
+    )*};
+}
+
+ord_impl!(OrderedByAddress, WeakOrderedByAddress);
+
+macro_rules! convert_impl {
+    ($($from:ident => $to:ident),*) => {$(
+        impl <T> From<$from<T>> for $to<T> {
+            fn from(value: $from<T>) -> Self {
+                return $to {cell: value.cell, id: value.id};
+            }
+        }
+    )*};
+}
+
+convert_impl!(
+    ByIdentity => OrderedByAddress,
+    OrderedByAddress => ByIdentity,
+    WeakByIdentity => WeakOrderedByAddress,
+    WeakOrderedByAddress => WeakByIdentity
+);
+
+///
+/// Prints the address of the shared allocation.
+///
+impl <T> Pointer for RcOCell<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return Pointer::fmt(&Rc::as_ptr(&self.rc), f);
+    }
+}
+
+///
+/// Prints the address of the shared allocation.
+///
+impl <T> Pointer for WeakRcOCell<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return Pointer::fmt(&Weak::as_ptr(&self.rc), f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+    use crate::*;
+
+    #[test]
+    fn test_id_clones_and_weak() {
+        let x = RcOCell::from_value(1u32);
+        let y = x.clone();
+        let w = x.downgrade();
+        assert_eq!(x.id(), y.id());
+        assert_eq!(x.id(), w.id());
+        x.clear();
+        assert_eq!(x.id(), w.id());
+
+        let id = x.id();
+        drop(x);
+        drop(y);
+        assert_eq!(w.id(), id);
+    }
+
+    #[test]
+    fn test_id_dropped_cell_not_reused() {
+        let x = RcOCell::from_value(1u32);
+        let w = x.downgrade();
+        let id = w.id();
+        drop(x);
+
+        let cells: Vec<RcOCell<u32>> = (0..100).map(|_| {
+            let cell = RcOCell::from_value(2u32);
+            assert_ne!(cell.id(), id);
+            cell
+        }).collect();
+        assert_eq!(w.by_identity() == cells[0].downgrade().by_identity(), false);
+        assert_eq!(w.id(), id);
+
+        let a: WeakRcOCell<u32> = std::rc::Weak::new().into();
+        let b: WeakRcOCell<u32> = std::rc::Weak::new().into();
+        assert_eq!(a.id(), b.id());
+    }
+
+    #[test]
+    fn test_id_distinct() {
+        let cells: Vec<RcOCell<u32>> = (0..10).map(|_| RcOCell::new()).collect();
+        let ids: HashSet<CellId> = cells.iter().map(|c| c.id()).collect();
+        assert_eq!(ids.len(), 10);
+    }
+
+    #[test]
+    fn test_pointer() {
+        let x = RcOCell::from_value(1u32);
+        let w = x.downgrade();
+        assert_eq!(format!("{:p}", x), format!("{:p}", w));
+        assert_ne!(format!("{:p}", x), format!("{:p}", RcOCell::from_value(1u32)));
+        assert_eq!(format!("{:p}", x).starts_with("0x"), true);
+    }
+
+    #[derive(PartialEq, Hash)]
+    struct Node(u32);
+
+    #[test]
+    #[allow(clippy::mutable_key_type)]
+    fn test_by_identity() {
+        let a = RcOCell::from_value(Node(1));
+        let b = RcOCell::from_value(Node(1));
+        let mut map = HashMap::new();
+        map.insert(a.by_identity(), "a");
+        map.insert(b.by_identity(), "b");
+        assert_eq!(map.len(), 2);
+
+        a.borrow_mut().0 = 2;
+        b.clear();
+        assert_eq!(map.get(&a.clone().by_identity()), Some(&"a"));
+        assert_eq!(map.get(&b.id()), Some(&"b"));
+        assert_eq!(map.keys().any(|k| k.get().is_none()), true);
+        assert_eq!(a.by_identity() == b.by_identity(), false);
+        assert_eq!(a.by_identity().into_inner().is_some(), true);
+    }
+
+    #[test]
+    #[allow(clippy::mutable_key_type)]
+    fn test_weak_by_identity() {
+        let a = RcOCell::from_value(Node(1));
+        let weak = a.downgrade().by_identity();
+        let mut set = HashSet::new();
+        set.insert(weak.clone());
+        drop(a);
+        assert_eq!(set.contains(&weak), true);
+        assert_eq!(set.contains(&weak.get().id()), true);
+        assert_eq!(set.contains(&RcOCell::from_value(Node(1)).downgrade().by_identity()), false);
+        assert_eq!(format!("{:?}", weak).starts_with("WeakByIdentity(CellId(0x"), true);
+    }
+
+    #[test]
+    #[allow(clippy::mutable_key_type)]
+    fn test_ordered_by_address() {
+        let cells: Vec<RcOCell<u32>> = (0..5).map(RcOCell::from_value).collect();
+        let map: BTreeMap<OrderedByAddress<u32>, usize> = cells.iter().enumerate().map(|(i, c)| (c.by_address(), i)).collect();
+        let order: Vec<usize> = map.values().copied().collect();
+        for cell in cells.iter() {
+            cell.set(100 - cell.get_and_clone());
+        }
+        cells[0].clear();
+        assert_eq!(map.values().copied().collect::<Vec<_>>(), order);
+        assert_eq!(map.get(&cells[3].id()), Some(&3));
+
+        let ids: Vec<CellId> = map.keys().map(|k| k.get().id()).collect();
+        assert_eq!(map.range(ids[1]..ids[3]).map(|(_, i)| *i).collect::<Vec<_>>(), order[1..3].to_vec());
+        assert_eq!(map.range(ids[4]..).count(), 1);
+
+        let key = cells[2].by_address();
+        let identity: ByIdentity<u32> = key.clone().into();
+        assert_eq!(identity == cells[2].by_identity(), true);
+        assert_eq!(OrderedByAddress::from(identity) == key, true);
+    }
+
+    #[test]
+    fn test_weak_ordered_by_address() {
+        let cells: Vec<RcOCell<u32>> = (0..3).map(RcOCell::from_value).collect();
+        let set: BTreeSet<WeakOrderedByAddress<u32>> = cells.iter().map(|c| c.downgrade().by_address()).collect();
+        let before: Vec<CellId> = set.iter().map(|k| k.get().id()).collect();
+        let key = cells[1].downgrade().by_address();
+        drop(cells);
+        assert_eq!(set.iter().map(|k| k.get().id()).collect::<Vec<_>>(), before);
+        assert_eq!(set.contains(&key), true);
+        let identity: WeakByIdentity<u32> = key.into();
+        assert_eq!(WeakOrderedByAddress::from(identity).into_inner().try_upgrade().is_err(), true);
+    }
+}
@@ -0,0 +1,262 @@
+//!
+//! Helpers for cells holding readers and writers in code that returns `std::io::Result`.
+//!
+//! RcOCellError converts into io::Error with the following kinds:
+//! * NoValue → NotFound
+//! * Dropped → BrokenPipe
+//! * BorrowError → WouldBlock
+//! * WrongVariant → InvalidData
+//! * Poisoned → Other
+//! * IndexOutOfBounds → InvalidInput
+//! * DuplicateCell → InvalidInput
+//!
+//! IoCell implements the io traits itself, it reports an empty cell as NotConnected instead.
+//!
+use std::io::{BufRead, Error, ErrorKind, Read, Seek, SeekFrom, Write};
+use crate::{RcOCell, RcOCellError, WeakRcOCell};
+
+impl From<RcOCellError> for Error {
+    fn from(value: RcOCellError) -> Self {
+        let kind = match value {
+            RcOCellError::NoValue => ErrorKind::NotFound,
+            RcOCellError::Dropped => ErrorKind::BrokenPipe,
+            RcOCellError::BorrowError(_) => ErrorKind::WouldBlock,
+            RcOCellError::WrongVariant => ErrorKind::InvalidData,
+            RcOCellError::Poisoned => ErrorKind::Other,
+            RcOCellError::IndexOutOfBounds {..} => ErrorKind::InvalidInput,
+            RcOCellError::DuplicateCell => ErrorKind::InvalidInput,
+            RcOCellError::WrongThread {..} => ErrorKind::Other,
+        };
+
+        return Error::new(kind, value.to_string());
+    }
+}
+
+///
+/// Borrows the reader in the cell mutably and runs the Fn with it.
+/// Fails with an io::Error if the cell is empty or borrowed somewhere.
+///
+pub fn with_reader<R, X, F>(cell: &RcOCell<R>, f: F) -> std::io::Result<X>
+    where R: Read, F: FnOnce(&mut R) -> std::io::Result<X>
+{
+    return f(&mut *cell.try_borrow_mut()?);
+}
+
+///
+/// Borrows the writer in the cell mutably and runs the Fn with it.
+/// Fails with an io::Error if the cell is empty or borrowed somewhere.
+///
+pub fn with_writer<W, X, F>(cell: &RcOCell<W>, f: F) -> std::io::Result<X>
+    where W: Write, F: FnOnce(&mut W) -> std::io::Result<X>
+{
+    return f(&mut *cell.try_borrow_mut()?);
+}
+
+///
+/// Borrows the reader in the cell mutably and runs the Fn with it.
+/// Fails with an io::Error if the cell is empty, borrowed somewhere or was already dropped.
+///
+pub fn with_weak_reader<R, X, F>(cell: &WeakRcOCell<R>, f: F) -> std::io::Result<X>
+    where R: Read, F: FnOnce(&mut R) -> std::io::Result<X>
+{
+    return with_reader(&cell.try_upgrade()?, f);
+}
+
+///
+/// Borrows the writer in the cell mutably and runs the Fn with it.
+/// Fails with an io::Error if the cell is empty, borrowed somewhere or was already dropped.
+///
+pub fn with_weak_writer<W, X, F>(cell: &WeakRcOCell<W>, f: F) -> std::io::Result<X>
+    where W: Write, F: FnOnce(&mut W) -> std::io::Result<X>
+{
+    return with_writer(&cell.try_upgrade()?, f);
+}
+
+///
+/// Handle of a cell that implements Read, Write, Seek and BufRead if the value does, returned by RcOCell::as_io.
+///
+/// Every call borrows the value mutably for the duration of the call.
+/// An empty cell fails with ErrorKind::NotConnected and a value borrowed somewhere with ErrorKind::WouldBlock instead of panicking.
+/// BufRead copies the buffer of the value, since the borrow of the value ends with the call to fill_buf.
+/// The copy is discarded by consume and all other calls, the bytes stay in the buffer of the value until they are consumed.
+///
+pub struct IoCell<T> {
+    cell: RcOCell<T>,
+    buf: Vec<u8>,
+}
+
+impl <T> RcOCell<T> {
+    ///
+    /// Returns a handle of the cell that implements the io traits implemented by the value.
+    ///
+    pub fn as_io(&self) -> IoCell<T> {
+        return IoCell {cell: self.clone(), buf: Vec::new()};
+    }
+}
+
+impl <T> IoCell<T> {
+    ///
+    /// Returns the cell.
+    ///
+    pub fn cell(&self) -> &RcOCell<T> {
+        return &self.cell;
+    }
+
+    fn with<X>(&self, f: impl FnOnce(&mut T) -> std::io::Result<X>) -> std::io::Result<X> {
+        return match self.cell.try_borrow_mut() {
+            Ok(mut value) => f(&mut value),
+            Err(RcOCellError::NoValue) => Err(Error::new(ErrorKind::NotConnected, RcOCellError::NoValue.to_string())),
+            Err(e) => Err(e.into()),
+        };
+    }
+}
+
+impl <T> Clone for IoCell<T> {
+    fn clone(&self) -> Self {
+        return self.cell.as_io();
+    }
+}
+
+impl <T: Read> Read for IoCell<T> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.buf.clear();
+        return self.with(|value| value.read(buf));
+    }
+}
+
+impl <T: Write> Write for IoCell<T> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buf.clear();
+        return self.with(|value| value.write(buf));
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        return self.with(|value| value.flush());
+    }
+}
+
+impl <T: Seek> Seek for IoCell<T> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.buf.clear();
+        return self.with(|value| value.seek(pos));
+    }
+}
+
+impl <T: BufRead> BufRead for IoCell<T> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        if self.buf.is_empty() {
+            let mut buf = std::mem::take(&mut self.buf);
+            self.with(|value| {
+                buf.extend_from_slice(value.fill_buf()?);
+                return Ok(());
+            })?;
+            self.buf = buf;
+        }
+
+        return Ok(&self.buf);
+    }
+
+    fn consume(&mut self, amt: usize) {
+        let amt = usize::min(amt, self.buf.len());
+        self.buf.drain(..amt);
+        if let Ok(mut value) = self.cell.try_borrow_mut() {
+            value.consume(amt);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, Cursor, ErrorKind, Read, Seek, SeekFrom, Write};
+    use crate::*;
+    use crate::io::{with_reader, with_weak_writer, with_writer};
+
+    fn kind(cell: &RcOCell<u32>) -> ErrorKind {
+        let err: std::io::Error = cell.try_borrow_mut().map(|_| ()).unwrap_err().into();
+        return err.kind();
+    }
+
+    #[test]
+    fn test_error_kinds() {
+        let x: RcOCell<u32> = RcOCell::new();
+        assert_eq!(kind(&x), ErrorKind::NotFound);
+        x.set(1);
+        let brw = x.borrow();
+        assert_eq!(kind(&x), ErrorKind::WouldBlock);
+        drop(brw);
+
+        let w = x.downgrade();
+        drop(x);
+        let err: std::io::Error = w.try_upgrade().map(|_| ()).unwrap_err().into();
+        assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+        let err: std::io::Error = RcOCellError::WrongVariant.into();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_with_reader_writer() -> std::io::Result<()> {
+        let x = RcOCell::from_value(Cursor::new(Vec::new()));
+        with_writer(&x, |w| w.write_all(b"Baum"))?;
+        with_weak_writer(&x.downgrade(), |w| w.write_all(b"Nase"))?;
+        x.borrow_mut().set_position(0);
+        let mut text = String::new();
+        with_reader(&x, |r| r.read_to_string(&mut text))?;
+        assert_eq!(text, "BaumNase");
+
+        let brw = x.borrow();
+        let err = with_reader(&x, |r| r.read(&mut [0u8; 1])).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::WouldBlock);
+        drop(brw);
+
+        x.clear();
+        let err = with_writer(&x, |w| w.write(b"x")).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        return Ok(());
+    }
+
+    fn copy<R: Read, W: Write>(mut reader: R, mut writer: W) -> std::io::Result<u64> {
+        return std::io::copy(&mut reader, &mut writer);
+    }
+
+    #[test]
+    fn test_io_cell() -> std::io::Result<()> {
+        let source = RcOCell::from_value(Cursor::new(b"Baum".to_vec()));
+        let target = RcOCell::from_value(Cursor::new(Vec::new()));
+        assert_eq!(copy(source.as_io(), target.as_io())?, 4);
+        assert_eq!(target.borrow().get_ref(), b"Baum");
+
+        let mut io = target.as_io();
+        io.write_all(b"Nase")?;
+        io.flush()?;
+        io.seek(SeekFrom::Start(2))?;
+        let mut text = String::new();
+        io.read_to_string(&mut text)?;
+        assert_eq!(text, "umNase");
+
+        io.rewind()?;
+        let mut line = String::new();
+        assert_eq!(io.fill_buf()?, b"BaumNase");
+        io.consume(4);
+        io.read_line(&mut line)?;
+        assert_eq!(line, "Nase");
+        assert_eq!(target.borrow().position(), 8);
+        return Ok(());
+    }
+
+    #[test]
+    fn test_io_cell_errors() {
+        let cell: RcOCell<Cursor<Vec<u8>>> = RcOCell::new();
+        let mut io = cell.as_io();
+        assert_eq!(io.write(b"x").unwrap_err().kind(), ErrorKind::NotConnected);
+        assert_eq!(copy(io.clone(), Vec::new()).unwrap_err().kind(), ErrorKind::NotConnected);
+        assert_eq!(io.fill_buf().unwrap_err().kind(), ErrorKind::NotConnected);
+
+        cell.set(Cursor::new(Vec::new()));
+        let brw = cell.borrow();
+        assert_eq!(io.read(&mut [0u8; 1]).unwrap_err().kind(), ErrorKind::WouldBlock);
+        assert_eq!(io.seek(SeekFrom::End(0)).unwrap_err().kind(), ErrorKind::WouldBlock);
+        drop(brw);
+        assert_eq!(io.write(b"x").unwrap(), 1);
+        assert_eq!(io.cell().borrow().get_ref(), b"x");
+    }
+}
@@ -0,0 +1,140 @@
+use crate::{RcOCell, RcOCellError};
+
+///
+/// Pulls items from cells holding an iterator, so several components can share one iterator.
+///
+/// An empty cell counts as an exhausted iterator.
+/// The cell keeps the iterator when it is exhausted, it is not cleared, so observers are never notified by these methods.
+/// Pulling an item borrows the value mutably for the duration of the call to next.
+///
+pub trait IteratorCellExt<I: Iterator> {
+    ///
+    /// Returns the next item, None if the iterator is exhausted or the cell is empty.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn next_item(&self) -> Option<I::Item>;
+
+    ///
+    /// Returns the next item, None if the iterator is exhausted or the cell is empty.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    fn try_next_item(&self) -> Result<Option<I::Item>, RcOCellError>;
+
+    ///
+    /// Returns up to n items, fewer if the iterator is exhausted before.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn take_n(&self, n: usize) -> Vec<I::Item>;
+
+    ///
+    /// Returns up to n items, fewer if the iterator is exhausted before.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    fn try_take_n(&self, n: usize) -> Result<Vec<I::Item>, RcOCellError>;
+
+    ///
+    /// Returns a handle of the cell that implements Iterator, see CellIter.
+    ///
+    fn iter_handle(&self) -> CellIter<I>;
+}
+
+///
+/// Handle of a cell holding an iterator that implements Iterator itself, returned by iter_handle.
+///
+/// Every call to next pulls one item from the iterator in the cell.
+/// Caveat: next also returns None if the value is borrowed somewhere, so None does not always mean
+/// that the iterator is exhausted and a later call may return items again. Use try_next_item to tell the cases apart.
+///
+pub struct CellIter<I> {
+    cell: RcOCell<I>,
+}
+
+impl <I> CellIter<I> {
+    ///
+    /// Returns the cell.
+    ///
+    pub fn cell(&self) -> &RcOCell<I> {
+        return &self.cell;
+    }
+}
+
+impl <I> Clone for CellIter<I> {
+    fn clone(&self) -> Self {
+        return CellIter {cell: self.cell.clone()};
+    }
+}
+
+impl <I: Iterator> Iterator for CellIter<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        return self.cell.try_next_item().ok().flatten();
+    }
+}
+
+impl <I: Iterator> IteratorCellExt<I> for RcOCell<I> {
+    fn next_item(&self) -> Option<I::Item> {
+        return self.raw_borrow_mut().as_mut().and_then(|i| i.next());
+    }
+
+    fn try_next_item(&self) -> Result<Option<I::Item>, RcOCellError> {
+        return Ok(self.try_raw_borrow_mut()?.as_mut().and_then(|i| i.next()));
+    }
+
+    fn take_n(&self, n: usize) -> Vec<I::Item> {
+        return self.raw_borrow_mut().as_mut().map(|i| i.take(n).collect()).unwrap_or_default();
+    }
+
+    fn try_take_n(&self, n: usize) -> Result<Vec<I::Item>, RcOCellError> {
+        return Ok(self.try_raw_borrow_mut()?.as_mut().map(|i| i.take(n).collect()).unwrap_or_default());
+    }
+
+    fn iter_handle(&self) -> CellIter<I> {
+        return CellIter {cell: self.clone()};
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_shared_iterator() {
+        let jobs = RcOCell::from_value(vec![1u32, 2, 3, 4, 5, 6].into_iter());
+        let mut a = jobs.iter_handle();
+        let mut b = jobs.iter_handle();
+        assert_eq!(a.next(), Some(1));
+        assert_eq!(b.next(), Some(2));
+        assert_eq!(jobs.next_item(), Some(3));
+        assert_eq!(jobs.take_n(2), vec![4, 5]);
+        assert_eq!(a.collect::<Vec<_>>(), vec![6]);
+        assert_eq!(b.next(), None);
+        assert_eq!(jobs.try_next_item().unwrap(), None);
+        assert_eq!(jobs.take_n(2).is_empty(), true);
+        assert_eq!(jobs.is_some(), true);
+
+        jobs.clear();
+        assert_eq!(jobs.next_item(), None);
+        assert_eq!(jobs.try_take_n(1).unwrap().is_empty(), true);
+        jobs.set(vec![7].into_iter());
+        assert_eq!(b.next(), Some(7));
+    }
+
+    #[test]
+    fn test_iterator_borrowed() {
+        let jobs = RcOCell::from_value(0u32..10);
+        let mut handle = jobs.iter_handle();
+        let brw = jobs.borrow();
+        assert_eq!(handle.next(), None);
+        match jobs.try_next_item() {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(jobs.try_take_n(1).is_err(), true);
+        assert_eq!(jobs.catch(|j| j.next_item()).is_err(), true);
+        assert_eq!(jobs.catch(|j| j.take_n(1)).is_err(), true);
+        drop(brw);
+        assert_eq!(handle.next(), Some(0));
+        assert_eq!(handle.cell().try_take_n(3).unwrap(), vec![1, 2, 3]);
+    }
+}
@@ -0,0 +1,105 @@
+use std::borrow::Cow;
+use crate::{shared, RcOCell, WeakRcOCell};
+
+impl <T> RcOCell<T> {
+    ///
+    /// Sets a human readable label and returns the cell.
+    /// The label is shared by all clones and weak handles, it is shown in Debug output, the Display fallback
+    /// and in panic messages of the cell, i.e. "RcOCell[session_token]: No value present".
+    ///
+    pub fn with_label(self, label: impl Into<Cow<'static, str>>) -> RcOCell<T> {
+        self.set_label(label);
+        return self;
+    }
+
+    ///
+    /// Sets or replaces the label of the cell, see with_label.
+    ///
+    pub fn set_label(&self, label: impl Into<Cow<'static, str>>) {
+        let label = Some(label.into());
+        #[cfg(feature = "debug-thread-check")]
+        crate::thread_check::labeled(&self.rc, label.clone());
+        let old = shared::with_state(&self.rc, |state| std::mem::replace(&mut state.label, label));
+        drop(old);
+    }
+
+    ///
+    /// Returns the label of the cell, None if it has none.
+    ///
+    pub fn label(&self) -> Option<Cow<'static, str>> {
+        return shared::with_existing_state(&self.rc, |state| state.label.clone()).flatten();
+    }
+}
+
+impl <T> WeakRcOCell<T> {
+    ///
+    /// Returns the label of the cell, None if it has none or was dropped.
+    ///
+    pub fn label(&self) -> Option<Cow<'static, str>> {
+        return self.try_upgrade().ok()?.label();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic;
+    use std::panic::AssertUnwindSafe;
+    use crate::*;
+
+    fn panic_message<F: FnOnce()>(f: F) -> String {
+        let e = panic::catch_unwind(AssertUnwindSafe(f)).unwrap_err();
+        if let Some(s) = e.downcast_ref::<String>() {
+            return s.clone();
+        }
+        return e.downcast_ref::<&str>().unwrap().to_string();
+    }
+
+    #[test]
+    fn test_label_shared() {
+        let x = RcOCell::from_value(1u32);
+        assert_eq!(x.label(), None);
+        let x = x.with_label("score");
+        let y = x.clone();
+        let w = x.downgrade();
+        assert_eq!(y.label().as_deref(), Some("score"));
+        assert_eq!(w.label().as_deref(), Some("score"));
+        y.set_label(format!("score_{}", 2));
+        assert_eq!(x.label().as_deref(), Some("score_2"));
+        drop(x);
+        drop(y);
+        assert_eq!(w.label(), None);
+    }
+
+    #[test]
+    fn test_label_debug_display() {
+        let x = RcOCell::from_value(1u32).with_label("score");
+        assert_eq!(format!("{:?}", x), "RcOCell { label: \"score\", value: Some(1) }");
+        assert_eq!(format!("{}", x), "1");
+        x.clear();
+        assert_eq!(format!("{}", x), "RcOCell[score]: No value present");
+        let brw = x.raw_borrow_mut();
+        assert_eq!(format!("{:?}", x), "RcOCell { label: \"score\", value: <borrowed> }");
+        drop(brw);
+
+        let y: RcOCell<u32> = RcOCell::new();
+        assert_eq!(format!("{:?}", y), "RcOCell { value: None }");
+        assert_eq!(format!("{}", y), "No value present");
+    }
+
+    #[test]
+    fn test_label_panic() {
+        let x: RcOCell<u32> = RcOCell::new().with_label("session_token");
+        assert_eq!(panic_message(|| { x.borrow(); }), "RcOCell[session_token]: No value present");
+        assert_eq!(panic_message(|| { x.get_and_clear(); }), "RcOCell[session_token]: RcCell::get_and_clear on a cell without value");
+        assert_eq!(panic_message(|| { x.expect("not logged in"); }), "RcOCell[session_token]: not logged in: No value present");
+
+        x.set(1);
+        let brw = x.borrow();
+        assert_eq!(panic_message(|| { x.set(2); }).starts_with("RcOCell[session_token]: "), true);
+        assert_eq!(panic_message(|| { x.borrow_mut(); }).starts_with("RcOCell[session_token]: "), true);
+        drop(brw);
+
+        let y: RcOCell<u32> = RcOCell::new();
+        assert_eq!(panic_message(|| { y.borrow(); }), "No value present");
+    }
+}
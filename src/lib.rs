@@ -1,17 +1,254 @@
+#![allow(clippy::needless_return)]
+#![allow(clippy::bool_assert_comparison)]
+
 use core::fmt;
-use std::cell::{BorrowError, BorrowMutError, Ref, RefCell, RefMut};
+use std::cell::{BorrowError, BorrowMutError, RefCell};
 use std::fmt::{Debug, Display, Formatter};
-use std::ops::Deref;
+use std::panic::Location;
+use std::mem::ManuallyDrop;
+use std::ops::{Deref, DerefMut};
 use std::rc::{Rc, Weak};
+use std::borrow::Cow;
+use std::thread::ThreadId;
+use crate::guard::Release;
+
+mod shared;
+mod observe;
+mod thread_local;
+mod scope;
+mod clock;
+mod steal;
+mod flatten;
+mod result;
+mod numeric;
+mod boolean;
+mod ord;
+mod finalize;
+pub mod io;
+mod entry;
+mod transaction;
+mod id;
+mod builder;
+mod label;
+mod guard;
+mod poison;
+mod unwind;
+mod overrides;
+mod deferred;
+mod modify;
+mod tuple;
+mod elements;
+mod string;
+mod mapped;
+mod owned;
+mod iter;
+mod callback;
+mod multi;
+mod group;
+mod slice;
+mod weak_list;
+mod validated;
+mod dirty;
+mod batch;
+mod merge;
+mod drain;
+mod swap;
+mod query;
+mod display;
+mod hash_set;
+mod btree_map;
+mod bytes;
+mod split;
+mod parse;
+mod convert;
+mod secret;
+mod alive;
+mod tap;
+pub mod test_util;
+#[cfg(feature = "rc-dedup")]
+pub mod serde_shared;
+#[cfg(any(feature = "arbitrary", feature = "proptest"))]
+pub mod testing;
+#[cfg(feature = "diagnostics")]
+pub mod diagnostics;
+#[cfg(feature = "stats")]
+mod stats;
+#[cfg(feature = "debug-borrow-tracking")]
+mod borrow_tracking;
+#[cfg(feature = "debug-thread-check")]
+mod thread_check;
+#[cfg(feature = "tracing")]
+mod trace;
+#[cfg(feature = "expiring")]
+mod expiring;
+#[cfg(feature = "refreshing")]
+mod refreshing;
+#[cfg(feature = "spy")]
+mod spy;
+#[cfg(feature = "zeroize")]
+mod scrub;
+#[cfg(feature = "futures")]
+mod future;
+#[cfg(feature = "futures")]
+mod sink;
+
+pub use observe::{Binding, ObserverId};
+pub use scope::ScopeGuard;
+pub use clock::{Clock, ManualClock, SystemClock};
+pub use steal::StolenValue;
+pub use result::ResultCellExt;
+pub use numeric::{IntegerCellExt, NumericCellExt};
+pub use boolean::BoolCellExt;
+pub use string::StringCellExt;
+pub use mapped::{MappedRcOCell, WeakMappedRcOCell};
+pub use owned::{OwnedRef, OwnedRefMut};
+pub use iter::{CellIter, IteratorCellExt};
+pub use group::RcOCellGroup;
+pub use slice::CellSliceExt;
+pub use weak_list::{AliveCells, WeakCellList};
+pub use validated::{ValidatedRcOCell, ValidatedRefMut, ValidationError};
+pub use dirty::DirtyToken;
+pub use batch::BatchGuard;
+pub use merge::MergeOutcome;
+pub use modify::OwnedComputeResult;
+pub use drain::{DrainError, DrainOutcome};
+pub use swap::SwapOutcome;
+pub use parse::ParseSetError;
+pub use display::DisplayDebug;
+pub use hash_set::HashSetCellExt;
+pub use btree_map::BTreeMapCellExt;
+pub use bytes::BytesCellExt;
+pub use elements::{ElementCellExt, SliceCellError};
+pub use entry::{CellEntry, OccupiedEntry, VacantEntry};
+pub use transaction::{CellTransaction, CellTransactionError, Transaction};
+pub use id::{ByIdentity, CellId, OrderedByAddress, WeakByIdentity, WeakOrderedByAddress};
+pub use builder::RcOCellBuilder;
+pub use guard::{RcORef, RcORefMut};
+pub use overrides::OverrideGuard;
+pub use secret::SecretRcOCell;
+#[cfg(feature = "stats")]
+pub use stats::CellStats;
+#[cfg(feature = "debug-borrow-tracking")]
+pub use borrow_tracking::BorrowInfo;
+#[cfg(feature = "spy")]
+pub use spy::{CellOp, SpyRcOCell};
+#[cfg(feature = "futures")]
+pub use future::{BorrowFuture, BorrowMutFuture, CellFuture};
+#[cfg(feature = "futures")]
+pub use sink::CellSink;
+#[cfg(feature = "expiring")]
+pub use expiring::{ExpiringEntry, ExpiringRcOCell};
+#[cfg(feature = "refreshing")]
+pub use refreshing::RefreshingRcOCell;
 
-
+///
+/// What a compute Fn wants to happen to the value of the cell.
+///
+/// The enum is deliberately not `#[non_exhaustive]` so matches in compute closures and helpers stay exhaustive,
+/// adding a variant is a breaking change.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum RcOCellComputeResult<T> {
     //Replace the value
     Replace(T),
     //Remove the value (if exists, otherwise noop)
     Remove,
     //Do nothing
-    DoNothing
+    DoNothing,
+    //Remove the value and hand it back to the caller of compute_take or compute_if_present_take.
+    //Methods that cannot hand it back drop it like Remove.
+    Take,
+}
+
+impl <T> RcOCellComputeResult<T> {
+    ///
+    /// Replace for Some, Remove for None.
+    ///
+    pub fn from_option(value: Option<T>) -> RcOCellComputeResult<T> {
+        return match value {
+            Some(value) => RcOCellComputeResult::Replace(value),
+            None => RcOCellComputeResult::Remove,
+        };
+    }
+
+    ///
+    /// Replace if the condition is true, DoNothing otherwise.
+    ///
+    pub fn replace_if(condition: bool, value: T) -> RcOCellComputeResult<T> {
+        if condition {
+            return RcOCellComputeResult::Replace(value);
+        }
+
+        return RcOCellComputeResult::DoNothing;
+    }
+
+    ///
+    /// Alias for DoNothing that reads better at the end of a compute Fn.
+    ///
+    pub fn keep() -> RcOCellComputeResult<T> {
+        return RcOCellComputeResult::DoNothing;
+    }
+
+    ///
+    /// Maps the value of Replace, all other variants are kept as they are.
+    ///
+    pub fn map<U, F>(self, f: F) -> RcOCellComputeResult<U>
+        where F: FnOnce(T) -> U
+    {
+        return self.and_then(|value| RcOCellComputeResult::Replace(f(value)));
+    }
+
+    ///
+    /// Replaces Replace with the result of the Fn, all other variants are kept as they are.
+    ///
+    pub fn and_then<U, F>(self, f: F) -> RcOCellComputeResult<U>
+        where F: FnOnce(T) -> RcOCellComputeResult<U>
+    {
+        return match self {
+            RcOCellComputeResult::Replace(value) => f(value),
+            RcOCellComputeResult::Remove => RcOCellComputeResult::Remove,
+            RcOCellComputeResult::DoNothing => RcOCellComputeResult::DoNothing,
+            RcOCellComputeResult::Take => RcOCellComputeResult::Take,
+        };
+    }
+}
+
+///
+/// Outcome of compute_if_present_take.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RcOCellComputeOutcome<T> {
+    ///
+    /// There was no value, the Fn was not executed.
+    ///
+    NotExecuted,
+    ///
+    /// The Fn was executed and did not return Take.
+    ///
+    Executed,
+    ///
+    /// The Fn was executed and returned Take, this is the value that was removed from the cell.
+    ///
+    Taken(T),
+}
+
+impl <T> RcOCellComputeOutcome<T> {
+    ///
+    /// Returns true if the Fn was executed.
+    ///
+    pub fn was_executed(&self) -> bool {
+        return !matches!(self, RcOCellComputeOutcome::NotExecuted);
+    }
+
+    ///
+    /// Returns the value that was taken out of the cell, if any.
+    ///
+    pub fn taken(self) -> Option<T> {
+        return match self {
+            RcOCellComputeOutcome::Taken(value) => Some(value),
+            _ => None,
+        };
+    }
 }
 
 pub enum RcOCellBorrowError {
@@ -25,6 +262,26 @@ pub enum RcOCellBorrowError {
     Mut(BorrowMutError)
 }
 
+impl RcOCellBorrowError {
+    ///
+    /// Returns where the borrow that prevented the failed borrow was created.
+    /// If several borrows conflict the location of the oldest one that is still outstanding is reported.
+    ///
+    /// The std borrow errors have no room for the location, so it is kept per thread for the latest failed borrow of a cell
+    /// and belongs to that failure only: query it right after the call that failed.
+    /// An error that is kept around reports the location of a later failure on the same thread,
+    /// errors converted from the std borrow errors with From report None until a cell fails to borrow again.
+    ///
+    /// Always None without the `debug-borrow-tracking` feature or if the conflicting guard was converted with into_std.
+    ///
+    pub fn conflicting_location(&self) -> Option<&'static Location<'static>> {
+        #[cfg(feature = "debug-borrow-tracking")]
+        return borrow_tracking::last_conflict();
+        #[cfg(not(feature = "debug-borrow-tracking"))]
+        return None;
+    }
+}
+
 
 
 
@@ -42,7 +299,37 @@ pub enum RcOCellError {
     ///
     /// Failed to borrow the value in the cell
     ///
-    BorrowError(RcOCellBorrowError)
+    BorrowError(RcOCellBorrowError),
+    ///
+    /// There is a value in the cell but it is not the variant that was asked for,
+    /// i.e. the cell holds an Err when the Ok value was requested.
+    ///
+    WrongVariant,
+    ///
+    /// A panic unwound while the value was borrowed mutably, see RcOCell::is_poisoned.
+    /// Can only occur with the `poison` feature.
+    ///
+    Poisoned,
+    ///
+    /// The index of an element is not less than the number of elements in the cell.
+    ///
+    IndexOutOfBounds {
+        index: usize,
+        len: usize,
+    },
+    ///
+    /// The same cell was passed more than once to a function that borrows several cells mutably at once.
+    ///
+    DuplicateCell,
+    ///
+    /// The cell was accessed on a thread other than the one that created it, contains both threads and the label of the cell.
+    /// Can only occur with the `debug-thread-check` feature.
+    ///
+    WrongThread {
+        owner: ThreadId,
+        current: ThreadId,
+        label: Option<Cow<'static, str>>,
+    },
 }
 
 impl Debug for RcOCellError {
@@ -51,6 +338,11 @@ impl Debug for RcOCellError {
             RcOCellError::NoValue => f.write_str("No value present"),
             RcOCellError::BorrowError(e) => Debug::fmt(e, f),
             RcOCellError::Dropped => f.write_str("Cell already dropped"),
+            RcOCellError::WrongVariant => f.write_str("Value is not the requested variant"),
+            RcOCellError::Poisoned => f.write_str("Cell is poisoned because a panic occurred while its value was borrowed mutably"),
+            RcOCellError::IndexOutOfBounds {index, len} => write!(f, "Index {} is out of bounds for {} elements", index, len),
+            RcOCellError::DuplicateCell => f.write_str("The same cell was passed more than once to be borrowed mutably"),
+            RcOCellError::WrongThread {owner, current, label} => fmt_wrong_thread(f, owner, current, label),
         };
     }
 }
@@ -60,10 +352,22 @@ impl Display for RcOCellError {
             RcOCellError::NoValue => f.write_str("No value present"),
             RcOCellError::BorrowError(e) => Display::fmt(e, f),
             RcOCellError::Dropped => f.write_str("Cell already dropped"),
+            RcOCellError::WrongVariant => f.write_str("Value is not the requested variant"),
+            RcOCellError::Poisoned => f.write_str("Cell is poisoned because a panic occurred while its value was borrowed mutably"),
+            RcOCellError::IndexOutOfBounds {index, len} => write!(f, "Index {} is out of bounds for {} elements", index, len),
+            RcOCellError::DuplicateCell => f.write_str("The same cell was passed more than once to be borrowed mutably"),
+            RcOCellError::WrongThread {owner, current, label} => fmt_wrong_thread(f, owner, current, label),
         };
     }
 }
 
+fn fmt_wrong_thread(f: &mut Formatter<'_>, owner: &ThreadId, current: &ThreadId, label: &Option<Cow<'static, str>>) -> fmt::Result {
+    return match label {
+        Some(label) => write!(f, "Cell {:?} created on thread {:?} was accessed on thread {:?}", label, owner, current),
+        None => write!(f, "Cell created on thread {:?} was accessed on thread {:?}", owner, current),
+    };
+}
+
 unsafe impl Send for RcOCellError {
 
 }
@@ -75,45 +379,107 @@ unsafe impl Send for RcOCellBorrowError {
 
 impl Debug for RcOCellBorrowError {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        return match self {
-            RcOCellBorrowError::Normal(e) => Debug::fmt(e, f),
-            RcOCellBorrowError::Mut(e) => Debug::fmt(e, f)
+        match self {
+            RcOCellBorrowError::Normal(e) => Debug::fmt(e, f)?,
+            RcOCellBorrowError::Mut(e) => Debug::fmt(e, f)?
+        };
+
+        return match self.conflicting_location() {
+            Some(location) => write!(f, " (conflicting borrow at {})", location),
+            None => Ok(()),
         };
     }
 }
 impl Display for RcOCellBorrowError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            RcOCellBorrowError::Normal(e) => Display::fmt(e, f)?,
+            RcOCellBorrowError::Mut(e) => Display::fmt(e, f)?
+        };
+
+        return match self.conflicting_location() {
+            Some(location) => write!(f, " (conflicting borrow at {})", location),
+            None => Ok(()),
+        };
+    }
+}
+
+///
+/// Error of the fallible compute methods, either the cell could not be accessed or the Fn failed.
+///
+pub enum RcOCellComputeError<E> {
+    ///
+    /// The cell could not be accessed, the Fn was not called.
+    ///
+    Cell(RcOCellError),
+    ///
+    /// The Fn returned an error.
+    ///
+    Compute(E),
+}
+
+impl <E: Debug> Debug for RcOCellComputeError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        return match self {
+            RcOCellComputeError::Cell(e) => Debug::fmt(e, f),
+            RcOCellComputeError::Compute(e) => Debug::fmt(e, f),
+        };
+    }
+}
+
+impl <E: Display> Display for RcOCellComputeError<E> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         return match self {
-            RcOCellBorrowError::Normal(e) => Display::fmt(e, f),
-            RcOCellBorrowError::Mut(e) => Display::fmt(e, f)
+            RcOCellComputeError::Cell(e) => Display::fmt(e, f),
+            RcOCellComputeError::Compute(e) => Display::fmt(e, f),
         };
     }
 }
 
+///
+/// Some becomes Replace and None becomes Remove, so a None returned by a compute Fn clears the cell.
+///
+impl <T> From<Option<T>> for RcOCellComputeResult<T> {
+    fn from(value: Option<T>) -> Self {
+        return RcOCellComputeResult::from_option(value);
+    }
+}
+
+impl <E> From<RcOCellError> for RcOCellComputeError<E> {
+    fn from(value: RcOCellError) -> Self {
+        return RcOCellComputeError::Cell(value);
+    }
+}
+
 impl From<BorrowError> for RcOCellError {
     fn from(value: BorrowError) -> Self {
+        #[cfg(feature = "debug-borrow-tracking")]
+        borrow_tracking::clear_conflict();
         return RcOCellError::BorrowError(RcOCellBorrowError::Normal(value));
     }
 }
 
 impl From<BorrowMutError> for RcOCellError {
     fn from(value: BorrowMutError) -> Self {
+        #[cfg(feature = "debug-borrow-tracking")]
+        borrow_tracking::clear_conflict();
         return RcOCellError::BorrowError(RcOCellBorrowError::Mut(value));
     }
 }
 
 impl <T> From<Rc<RefCell<Option<T>>>> for RcOCell<T>
 {
+    #[track_caller]
     fn from(value: Rc<RefCell<Option<T>>>) -> Self {
-        return RcOCell{rc: value};
+        return RcOCell::wrap(value);
     }
 }
 
-impl <T> Into<Rc<RefCell<Option<T>>>> for RcOCell<T>
+impl <T> From<RcOCell<T>> for Rc<RefCell<Option<T>>>
 {
 
-    fn into(self) -> Rc<RefCell<Option<T>>> {
-        self.rc
+    fn from(value: RcOCell<T>) -> Self {
+        value.into_rc()
     }
 }
 
@@ -121,7 +487,7 @@ impl <T> TryInto<Rc<RefCell<Option<T>>>> for WeakRcOCell<T> {
     type Error = RcOCellError;
 
     fn try_into(self) -> Result<Rc<RefCell<Option<T>>>, Self::Error> {
-        Ok(self.try_upgrade()?.rc)
+        Ok(self.try_upgrade()?.into_rc())
     }
 }
 
@@ -131,11 +497,22 @@ impl <T> From<Rc<RefCell<Option<T>>>> for WeakRcOCell<T> {
     }
 }
 
+impl <T> From<Weak<RefCell<Option<T>>>> for WeakRcOCell<T> {
+    fn from(value: Weak<RefCell<Option<T>>>) -> Self {
+        return WeakRcOCell{rc: value};
+    }
+}
+
+impl <T> From<WeakRcOCell<T>> for Weak<RefCell<Option<T>>> {
+    fn from(value: WeakRcOCell<T>) -> Self {
+        return value.into_weak_raw();
+    }
+}
+
 ///
 /// This struct represents a mutable reference counted reference to a value that can be present or absent.
 /// It has the same borrow checking semantics as RefCell (i.e. Runtime borrow checking)
 ///
-#[derive(Debug)]
 pub struct RcOCell<T> where
 {
     rc: Rc<RefCell<Option<T>>>
@@ -150,6 +527,7 @@ pub struct WeakRcOCell<T> where
 impl <T> Default for RcOCell<T> where
     T: Default
 {
+    #[track_caller]
     fn default() -> Self {
         RcOCell::from(T::default())
     }
@@ -159,15 +537,65 @@ impl <T> Display for RcOCell<T> where
     T: Display
 {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let x = self.try_borrow();
-        if x.is_ok() {
-            return Display::fmt(x.unwrap().deref(), f);
+        if self.is_redacted() {
+            return f.write_str("[REDACTED]");
+        }
+
+        return match self.try_borrow() {
+            Ok(x) => Display::fmt(x.deref(), f),
+            Err(e) => self.fmt_inaccessible(e, f),
+        };
+    }
+}
+
+impl <T> RcOCell<T> {
+    ///
+    /// Writes the text the Display impl uses if the value cannot be borrowed, prefixed by the label (if any).
+    ///
+    fn fmt_inaccessible(&self, e: RcOCellError, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(label) = self.label() {
+            write!(f, "RcOCell[{}]: ", label)?;
         }
 
-        return match x.map(|_| ()).unwrap_err() {
+        return match e {
             RcOCellError::NoValue => f.write_str("No value present"),
             RcOCellError::BorrowError(_) => f.write_str("Value currently inaccessible because it is borrowed mutably somewhere"),
             RcOCellError::Dropped => f.write_str("Value already dropped"),
+            RcOCellError::WrongVariant => f.write_str("Value is not the requested variant"),
+            RcOCellError::Poisoned => f.write_str("Value is poisoned"),
+            e @ RcOCellError::IndexOutOfBounds {..} => Display::fmt(&e, f),
+            e @ RcOCellError::DuplicateCell => Display::fmt(&e, f),
+            e @ RcOCellError::WrongThread {..} => Display::fmt(&e, f),
+        };
+    }
+}
+
+///
+/// Prints the label (if any) and the value, `<borrowed>` if the value is borrowed mutably.
+///
+impl <T> Debug for RcOCell<T> where
+    T: Debug
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        struct Borrowed;
+        impl Debug for Borrowed {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                return f.write_str("<borrowed>");
+            }
+        }
+
+        if self.is_redacted() {
+            return f.write_str("[REDACTED]");
+        }
+
+        let mut d = f.debug_struct("RcOCell");
+        if let Some(label) = self.label() {
+            d.field("label", &label);
+        }
+
+        return match self.rc.try_borrow() {
+            Ok(value) => d.field("value", &*value).finish(),
+            Err(_) => d.field("value", &Borrowed).finish(),
         };
     }
 }
@@ -179,15 +607,26 @@ impl <T> Clone for RcOCell<T>
     }
 }
 
+impl <T> Drop for RcOCell<T> {
+    fn drop(&mut self) {
+        if Rc::strong_count(&self.rc) == 1 {
+            #[cfg(feature = "diagnostics")]
+            diagnostics::unregister(&self.rc);
+            shared::final_drop(&self.rc);
+        }
+    }
+}
+
 impl <T> From<T> for RcOCell<T> {
+    #[track_caller]
     fn from(value: T) -> Self {
         Self::from_value(value)
     }
 }
 
-impl <T> Into<Result<T, RcOCellError>> for RcOCell<T> {
-    fn into(self) -> Result<T, RcOCellError> {
-        self.try_get_and_clear()
+impl <T> From<RcOCell<T>> for Result<T, RcOCellError> {
+    fn from(value: RcOCell<T>) -> Self {
+        value.try_get_and_clear()
     }
 }
 
@@ -221,66 +660,339 @@ impl <T> RcOCell<T>
     ///
     /// Constructs a new empty/cleared RcOCell
     ///
+    #[track_caller]
     pub fn new() -> RcOCell<T> {
-        return RcOCell {rc: Rc::new(RefCell::new(None))}
+        return RcOCell::wrap(Rc::new(RefCell::new(None)));
     }
 
     ///
     /// Constructs a new RcOCell from a value.
     ///
+    #[track_caller]
     pub fn from_value(value: T) -> RcOCell<T> {
-        return RcOCell {rc: Rc::new(RefCell::new(Some(value)))}
+        return RcOCell::wrap(Rc::new(RefCell::new(Some(value))));
     }
 
     ///
     /// Constructs a new RcOCell from an option either with or without a value depending on the option.
     ///
+    #[track_caller]
     pub fn from_option(value: Option<T>) -> RcOCell<T> {
-        return RcOCell {rc: Rc::new(RefCell::new(value))}
+        return RcOCell::wrap(Rc::new(RefCell::new(value)));
+    }
+
+    ///
+    /// Constructs a new empty RcOCell together with a weak handle of it.
+    /// For the "owner holds the strong handle, child holds the weak handle" wiring.
+    ///
+    #[track_caller]
+    pub fn pair() -> (RcOCell<T>, WeakRcOCell<T>) {
+        let cell = RcOCell::new();
+        let weak = cell.downgrade();
+        return (cell, weak);
+    }
+
+    ///
+    /// Constructs a new RcOCell from a value together with a weak handle of it.
+    ///
+    #[track_caller]
+    pub fn pair_with(value: T) -> (RcOCell<T>, WeakRcOCell<T>) {
+        let cell = RcOCell::from_value(value);
+        let weak = cell.downgrade();
+        return (cell, weak);
+    }
+
+    ///
+    /// Constructs n new empty RcOCells, each together with a weak handle of it.
+    ///
+    #[track_caller]
+    pub fn pairs(n: usize) -> Vec<(RcOCell<T>, WeakRcOCell<T>)> {
+        let mut pairs = Vec::with_capacity(n);
+        for _ in 0..n {
+            pairs.push(RcOCell::pair());
+        }
+
+        return pairs;
+    }
+
+    ///
+    /// Wraps the Rc, registering it with the diagnostics registry if the feature is enabled.
+    ///
+    #[track_caller]
+    fn wrap(rc: Rc<RefCell<Option<T>>>) -> RcOCell<T> {
+        #[cfg(feature = "diagnostics")]
+        diagnostics::register(&rc, std::panic::Location::caller());
+        #[cfg(feature = "debug-thread-check")]
+        thread_check::register(&rc);
+        return RcOCell {rc};
+    }
+
+    ///
+    /// Unwraps the Rc without running the drop logic of the cell, the shared state stays attached to the allocation.
+    ///
+    fn into_rc(self) -> Rc<RefCell<Option<T>>> {
+        let this = ManuallyDrop::new(self);
+        return unsafe { std::ptr::read(&this.rc) };
+    }
+
+    ///
+    /// Panics with the message, prefixed with the label of the cell if it has one.
+    ///
+    #[cold]
+    #[track_caller]
+    fn fail(&self, msg: impl Display) -> ! {
+        #[cfg(feature = "tracing")]
+        trace::failing(self, &msg, std::panic::Location::caller());
+        match self.label() {
+            Some(label) => panic!("RcOCell[{}]: {}", label, msg),
+            None => panic!("{}", msg),
+        }
+    }
+
+    ///
+    /// Panics because borrowing the value failed,
+    /// lists the outstanding borrows of the cell if the debug-borrow-tracking feature is enabled.
+    ///
+    #[cold]
+    #[track_caller]
+    fn fail_borrow(&self, e: RcOCellError) -> ! {
+        #[cfg(feature = "debug-borrow-tracking")]
+        if let (RcOCellError::BorrowError(_), Some(borrows)) = (&e, borrow_tracking::describe(self)) {
+            self.fail(format!("{}, outstanding borrows: {}", e, borrows));
+        }
+
+        self.fail(e);
+    }
+
+    ///
+    /// Borrows the Option in the cell, panics with the label of the cell if it is borrowed mutably.
+    ///
+    #[track_caller]
+    #[inline]
+    fn raw_borrow(&self) -> RcORef<'_, Option<T>, T> {
+        return match self.try_raw_borrow() {
+            Ok(r) => r,
+            Err(e) => self.fail_borrow(e),
+        };
+    }
+
+    ///
+    /// Borrows the Option in the cell mutably, panics with the label of the cell if it is borrowed.
+    ///
+    #[track_caller]
+    #[inline]
+    fn raw_borrow_mut(&self) -> RcORefMut<'_, Option<T>, T> {
+        return match self.try_raw_borrow_mut() {
+            Ok(r) => r,
+            Err(e) => self.fail_borrow(e),
+        };
+    }
+
+    ///
+    /// Borrows the Option in the cell, fails if the cell is poisoned.
+    ///
+    #[cfg_attr(feature = "debug-borrow-tracking", track_caller)]
+    #[inline]
+    fn try_raw_borrow(&self) -> Result<RcORef<'_, Option<T>, T>, RcOCellError> {
+        self.check_poison()?;
+        return self.try_raw_borrow_ignore_poison();
+    }
+
+    ///
+    /// Borrows the Option in the cell mutably, fails if the cell is poisoned.
+    ///
+    #[cfg_attr(feature = "debug-borrow-tracking", track_caller)]
+    #[inline]
+    fn try_raw_borrow_mut(&self) -> Result<RcORefMut<'_, Option<T>, T>, RcOCellError> {
+        self.check_poison()?;
+        return self.try_raw_borrow_mut_ignore_poison();
+    }
+
+    ///
+    /// Borrows the Option in the cell, counted by the stats feature.
+    ///
+    #[cfg_attr(feature = "debug-borrow-tracking", track_caller)]
+    #[inline]
+    fn try_raw_borrow_ignore_poison(&self) -> Result<RcORef<'_, Option<T>, T>, RcOCellError> {
+        #[cfg(feature = "debug-thread-check")]
+        thread_check::check(self)?;
+        let result = self.rc.try_borrow().map_err(|e| self.borrow_failed(e));
+
+        #[cfg(feature = "stats")]
+        if result.is_ok() {
+            stats::record(&self.rc, |s| s.borrows += 1);
+        }
+
+        #[cfg(feature = "spy")]
+        if result.is_ok() {
+            spy::borrowed(self, false);
+        }
+
+        return Ok(RcORef::new(result?, Release::shared(self)));
+    }
+
+    ///
+    /// Borrows the Option in the cell mutably, counted by the stats feature.
+    /// The cell is poisoned if the returned guard is dropped by a panic.
+    ///
+    #[cfg_attr(feature = "debug-borrow-tracking", track_caller)]
+    #[inline]
+    fn try_raw_borrow_mut_ignore_poison(&self) -> Result<RcORefMut<'_, Option<T>, T>, RcOCellError> {
+        #[cfg(feature = "debug-thread-check")]
+        thread_check::check(self)?;
+        let result = self.rc.try_borrow_mut().map_err(|e| self.borrow_failed(e));
+
+        #[cfg(feature = "stats")]
+        if result.is_ok() {
+            stats::record(&self.rc, |s| s.borrows_mut += 1);
+        }
+
+        #[cfg(feature = "spy")]
+        if result.is_ok() {
+            spy::borrowed(self, true);
+        }
+
+        return Ok(RcORefMut::new(result?, Release::exclusive(self)));
+    }
+
+    ///
+    /// Fails if the value is borrowed somewhere, only the failure is counted by the stats feature.
+    ///
+    fn check_mut(&self) -> Result<(), RcOCellError> {
+        #[cfg(feature = "debug-thread-check")]
+        thread_check::check(self)?;
+        self.check_poison()?;
+        if let Err(e) = self.rc.try_borrow_mut() {
+            return Err(self.borrow_failed(e));
+        }
+
+        return Ok(());
+    }
+
+    ///
+    /// Called when borrowing the value failed because of another borrow, feeds the stats and tracing features.
+    /// Returns the error with the location of the conflicting borrow if the debug-borrow-tracking feature is enabled.
+    ///
+    #[inline]
+    fn borrow_failed(&self, e: impl Into<RcOCellError>) -> RcOCellError {
+        #[cfg(feature = "stats")]
+        stats::record(&self.rc, |s| s.borrow_failures += 1);
+        #[cfg(feature = "tracing")]
+        trace::borrow_failed(self);
+        let e = e.into();
+        #[cfg(feature = "debug-borrow-tracking")]
+        borrow_tracking::conflicted(self);
+        return e;
+    }
+
+    ///
+    /// Replaces the Option in the cell without notifying, panics with the label of the cell if it is borrowed.
+    ///
+    #[track_caller]
+    #[inline]
+    fn raw_replace(&self, value: Option<T>) -> Option<T> {
+        deferred::cancel(self);
+        return std::mem::replace(&mut *self.raw_borrow_mut(), value);
+    }
+
+    ///
+    /// Informs the observers of the cell about its new state.
+    ///
+    #[inline]
+    fn changed(&self) {
+        #[cfg(feature = "stats")]
+        stats::record(&self.rc, |s| s.mutations += 1);
+        #[cfg(feature = "spy")]
+        spy::changed(self);
+        dirty::touched(self);
+        shared::notify(&self.rc);
+        deferred::flush(self);
     }
 
     ///
     /// Borrows the value mutably.
     /// Panics if the value is already borrowed somewhere (either non mutably or mutably) or there is no value
     ///
-    pub fn try_borrow_mut(&self) -> Result<RefMut<T>, RcOCellError> {
-        let borrowed = self.rc.as_ref().try_borrow_mut()?;
+    #[cfg_attr(feature = "debug-borrow-tracking", track_caller)]
+    pub fn try_borrow_mut(&self) -> Result<RcORefMut<'_, T>, RcOCellError> {
+        let mut borrowed = self.try_raw_borrow_mut()?;
 
         if borrowed.is_none() {
             return Err(RcOCellError::NoValue);
         }
 
-        return Ok(RefMut::map(borrowed, |a| a.as_mut().unwrap()));
+        RcORefMut::marks_dirty(&mut borrowed);
+
+        return Ok(RcORefMut::map(borrowed, |a| a.as_mut().unwrap()));
     }
 
     ///
     /// Borrows the value.
     /// Fails if the value is already borrowed mutably somewhere or there is no value
     ///
-    pub fn try_borrow(&self) -> Result<Ref<T>, RcOCellError> {
-        let borrowed = self.rc.as_ref().try_borrow()?;
+    #[cfg_attr(feature = "debug-borrow-tracking", track_caller)]
+    pub fn try_borrow(&self) -> Result<RcORef<'_, T>, RcOCellError> {
+        let borrowed = self.try_raw_borrow()?;
 
         if borrowed.is_none() {
             return Err(RcOCellError::NoValue);
         }
 
-        return Ok(Ref::map(borrowed, |a| a.as_ref().unwrap()));
+        return Ok(RcORef::map(borrowed, |a| a.as_ref().unwrap()));
     }
 
     ///
     /// Borrows the value.
     /// Panics if the value is already borrowed mutably somewhere or there is no value
     ///
-    pub fn borrow(&self) -> Ref<T> {
-        Ref::map(self.rc.as_ref().borrow(), |a| a.as_ref().unwrap())
+    #[track_caller]
+    pub fn borrow(&self) -> RcORef<'_, T> {
+        let borrowed = self.raw_borrow();
+        if borrowed.is_none() {
+            self.fail(RcOCellError::NoValue);
+        }
+
+        return RcORef::map(borrowed, |a| a.as_ref().unwrap());
     }
 
     ///
     /// Borrows the value mutably.
     /// Panics if the value is already borrowed somewhere (either non mutably or mutably) or there is no value
     ///
-    pub fn borrow_mut(&self) -> RefMut<T> {
-        RefMut::map(self.rc.as_ref().borrow_mut(), |a| a.as_mut().unwrap())
+    #[track_caller]
+    pub fn borrow_mut(&self) -> RcORefMut<'_, T> {
+        let mut borrowed = self.raw_borrow_mut();
+        if borrowed.is_none() {
+            self.fail(RcOCellError::NoValue);
+        }
+
+        RcORefMut::marks_dirty(&mut borrowed);
+
+        return RcORefMut::map(borrowed, |a| a.as_mut().unwrap());
+    }
+
+    ///
+    /// Borrows the value.
+    /// Panics with the given message followed by the reason if the value is already borrowed mutably somewhere or there is no value
+    ///
+    #[track_caller]
+    pub fn expect(&self, msg: &str) -> RcORef<'_, T> {
+        return match self.try_borrow() {
+            Ok(r) => r,
+            Err(e) => self.fail(format_args!("{}: {}", msg, e)),
+        };
+    }
+
+    ///
+    /// Borrows the value mutably.
+    /// Panics with the given message followed by the reason if the value is already borrowed somewhere (either non mutably or mutably) or there is no value
+    ///
+    #[track_caller]
+    pub fn expect_mut(&self, msg: &str) -> RcORefMut<'_, T> {
+        return match self.try_borrow_mut() {
+            Ok(r) => r,
+            Err(e) => self.fail(format_args!("{}: {}", msg, e)),
+        };
     }
 
     ///
@@ -318,14 +1030,13 @@ impl <T> RcOCell<T>
     pub fn compute<F>(&self, f: F)
         where F: FnOnce(Option<&mut T>) -> RcOCellComputeResult<T>
     {
-        let mut x = self.rc.borrow_mut();
+        let mut x = self.raw_borrow_mut();
+        if x.is_some() {
+            RcORefMut::marks_dirty(&mut x);
+        }
         let result = f(x.as_mut());
         drop(x);
-        match result {
-            RcOCellComputeResult::Replace(t) => {self.set(t);}
-            RcOCellComputeResult::Remove => {self.clear();}
-            RcOCellComputeResult::DoNothing => {}
-        }
+        self.apply_compute_result(result);
     }
 
     ///
@@ -335,14 +1046,13 @@ impl <T> RcOCell<T>
     pub fn try_compute<F>(&self, f: F) -> Result<(), RcOCellError>
         where F: FnOnce(Option<&mut T>) -> RcOCellComputeResult<T>
     {
-        let mut x = self.rc.try_borrow_mut()?;
+        let mut x = self.try_raw_borrow_mut()?;
+        if x.is_some() {
+            RcORefMut::marks_dirty(&mut x);
+        }
         let result = f(x.as_mut());
         drop(x);
-        match result {
-            RcOCellComputeResult::Replace(t) => {self.set(t);}
-            RcOCellComputeResult::Remove => {self.clear();}
-            RcOCellComputeResult::DoNothing => {}
-        }
+        self.apply_compute_result(result);
 
         return Ok(());
     }
@@ -357,17 +1067,14 @@ impl <T> RcOCell<T>
     pub fn compute_if_present<F>(&self, f: F) -> bool
         where F: FnOnce(&mut T) -> RcOCellComputeResult<T>
     {
-        let mut x = self.rc.borrow_mut();
+        let mut x = self.raw_borrow_mut();
         if x.is_none() {
             return false;
         }
+        RcORefMut::marks_dirty(&mut x);
         let result = f(x.as_mut().unwrap());
         drop(x);
-        match result {
-            RcOCellComputeResult::Replace(t) => {self.set(t);}
-            RcOCellComputeResult::Remove => {self.clear();}
-            RcOCellComputeResult::DoNothing => {}
-        }
+        self.apply_compute_result(result);
 
         return true;
     }
@@ -381,21 +1088,285 @@ impl <T> RcOCell<T>
     pub fn try_compute_if_present<F>(&self, f: F) -> Result<bool, RcOCellError>
         where F: FnOnce(&mut T) -> RcOCellComputeResult<T>
     {
-        let mut x = self.rc.try_borrow_mut()?;
+        let mut x = self.try_raw_borrow_mut()?;
         if x.is_none() {
             return Ok(false);
         }
+        RcORefMut::marks_dirty(&mut x);
         let result = f(x.as_mut().unwrap());
         drop(x);
-        match result {
-            RcOCellComputeResult::Replace(t) => {self.set(t);}
-            RcOCellComputeResult::Remove => {self.clear();}
-            RcOCellComputeResult::DoNothing => {}
+        self.apply_compute_result(result);
+
+        return Ok(true);
+    }
+
+    ///
+    /// Runs the fallible Fn with the ref to the value (if present), conditionally creating/updating/removing it.
+    /// If the Fn returns an error the result is not applied and the error is returned.
+    /// Modifications the Fn made through the reference before returning the error are kept,
+    /// so the Fn should only modify the value once it can no longer fail.
+    /// Panics if the value was borrowed elsewhere.
+    ///
+    pub fn compute_result<E, F>(&self, f: F) -> Result<(), E>
+        where F: FnOnce(Option<&mut T>) -> Result<RcOCellComputeResult<T>, E>
+    {
+        let mut x = self.raw_borrow_mut();
+        if x.is_some() {
+            RcORefMut::marks_dirty(&mut x);
+        }
+        let result = f(x.as_mut());
+        drop(x);
+        self.apply_compute_result(result?);
+        return Ok(());
+    }
+
+    ///
+    /// Runs the fallible Fn with the ref to the value (if present), conditionally creating/updating/removing it.
+    /// If the Fn returns an error the result is not applied and the error is returned.
+    /// Modifications the Fn made through the reference before returning the error are kept.
+    /// Fails if the value was borrowed elsewhere.
+    ///
+    pub fn try_compute_result<E, F>(&self, f: F) -> Result<(), RcOCellComputeError<E>>
+        where F: FnOnce(Option<&mut T>) -> Result<RcOCellComputeResult<T>, E>
+    {
+        let mut x = self.try_raw_borrow_mut()?;
+        if x.is_some() {
+            RcORefMut::marks_dirty(&mut x);
+        }
+        let result = f(x.as_mut());
+        drop(x);
+        self.apply_compute_result(result.map_err(RcOCellComputeError::Compute)?);
+        return Ok(());
+    }
+
+    ///
+    /// Runs the fallible Fn if the value is present to perform a calculation on it, conditionally updating/removing it.
+    /// Returns true if the Fn was executed and succeeded.
+    /// False if the value was not present.
+    /// If the Fn returns an error the result is not applied and the error is returned.
+    /// Modifications the Fn made through the reference before returning the error are kept.
+    /// Panics if the value was borrowed elsewhere.
+    ///
+    pub fn compute_if_present_result<E, F>(&self, f: F) -> Result<bool, E>
+        where F: FnOnce(&mut T) -> Result<RcOCellComputeResult<T>, E>
+    {
+        let mut x = self.raw_borrow_mut();
+        if x.is_none() {
+            return Ok(false);
         }
+        RcORefMut::marks_dirty(&mut x);
+        let result = f(x.as_mut().unwrap());
+        drop(x);
+        self.apply_compute_result(result?);
+        return Ok(true);
+    }
 
+    ///
+    /// Runs the fallible Fn if the value is present to perform a calculation on it, conditionally updating/removing it.
+    /// Returns true if the Fn was executed and succeeded.
+    /// False if the value was not present.
+    /// If the Fn returns an error the result is not applied and the error is returned.
+    /// Modifications the Fn made through the reference before returning the error are kept.
+    /// Fails if the value was borrowed elsewhere.
+    ///
+    pub fn try_compute_if_present_result<E, F>(&self, f: F) -> Result<bool, RcOCellComputeError<E>>
+        where F: FnOnce(&mut T) -> Result<RcOCellComputeResult<T>, E>
+    {
+        let mut x = self.try_raw_borrow_mut()?;
+        if x.is_none() {
+            return Ok(false);
+        }
+        RcORefMut::marks_dirty(&mut x);
+        let result = f(x.as_mut().unwrap());
+        drop(x);
+        self.apply_compute_result(result.map_err(RcOCellComputeError::Compute)?);
         return Ok(true);
     }
 
+    ///
+    /// Runs the Fn with the ref to the value (if present), conditionally creating/updating/removing it.
+    /// Returns the removed value if the Fn returned Take, None for all other results.
+    /// Panics if the value was borrowed elsewhere.
+    ///
+    pub fn compute_take<F>(&self, f: F) -> Option<T>
+        where F: FnOnce(Option<&mut T>) -> RcOCellComputeResult<T>
+    {
+        let mut x = self.raw_borrow_mut();
+        if x.is_some() {
+            RcORefMut::marks_dirty(&mut x);
+        }
+        let result = f(x.as_mut());
+        drop(x);
+        return self.apply_compute_result(result);
+    }
+
+    ///
+    /// Runs the Fn with the ref to the value (if present), conditionally creating/updating/removing it.
+    /// Returns the removed value if the Fn returned Take, None for all other results.
+    /// Fails if the value was borrowed elsewhere.
+    ///
+    pub fn try_compute_take<F>(&self, f: F) -> Result<Option<T>, RcOCellError>
+        where F: FnOnce(Option<&mut T>) -> RcOCellComputeResult<T>
+    {
+        let mut x = self.try_raw_borrow_mut()?;
+        if x.is_some() {
+            RcORefMut::marks_dirty(&mut x);
+        }
+        let result = f(x.as_mut());
+        drop(x);
+        return Ok(self.apply_compute_result(result));
+    }
+
+    ///
+    /// Runs the Fn if the value is present to perform a calculation on it, conditionally updating/removing it.
+    /// Returns whether the Fn was executed together with the removed value if the Fn returned Take.
+    /// Panics if the value was borrowed elsewhere.
+    ///
+    pub fn compute_if_present_take<F>(&self, f: F) -> RcOCellComputeOutcome<T>
+        where F: FnOnce(&mut T) -> RcOCellComputeResult<T>
+    {
+        let mut x = self.raw_borrow_mut();
+        if x.is_none() {
+            return RcOCellComputeOutcome::NotExecuted;
+        }
+        RcORefMut::marks_dirty(&mut x);
+        let result = f(x.as_mut().unwrap());
+        drop(x);
+
+        return match self.apply_compute_result(result) {
+            Some(value) => RcOCellComputeOutcome::Taken(value),
+            None => RcOCellComputeOutcome::Executed,
+        };
+    }
+
+    ///
+    /// Runs the Fn if the value is present to perform a calculation on it, conditionally updating/removing it.
+    /// Returns whether the Fn was executed together with the removed value if the Fn returned Take.
+    /// Fails if the value was borrowed elsewhere.
+    ///
+    pub fn try_compute_if_present_take<F>(&self, f: F) -> Result<RcOCellComputeOutcome<T>, RcOCellError>
+        where F: FnOnce(&mut T) -> RcOCellComputeResult<T>
+    {
+        self.check_mut()?;
+        return Ok(self.compute_if_present_take(f));
+    }
+
+    ///
+    /// Runs the Fn with the ref to the value (if present) and stores the returned value.
+    /// **Returning None clears the cell**, it does not mean "keep the value".
+    /// Panics if the value was borrowed elsewhere.
+    ///
+    pub fn compute_from_option<F>(&self, f: F)
+        where F: FnOnce(Option<&mut T>) -> Option<T>
+    {
+        self.compute(|value| f(value).into());
+    }
+
+    ///
+    /// Runs the Fn with the ref to the value (if present) and stores the returned value.
+    /// **Returning None clears the cell**, it does not mean "keep the value".
+    /// Fails if the value was borrowed elsewhere.
+    ///
+    pub fn try_compute_from_option<F>(&self, f: F) -> Result<(), RcOCellError>
+        where F: FnOnce(Option<&mut T>) -> Option<T>
+    {
+        return self.try_compute(|value| f(value).into());
+    }
+
+    ///
+    /// Runs the Fn if the value is present and stores the returned value.
+    /// **Returning None clears the cell**, it does not mean "keep the value".
+    /// Returns true if the Fn was executed.
+    /// Panics if the value was borrowed elsewhere.
+    ///
+    pub fn compute_if_present_from_option<F>(&self, f: F) -> bool
+        where F: FnOnce(&mut T) -> Option<T>
+    {
+        return self.compute_if_present(|value| f(value).into());
+    }
+
+    ///
+    /// Runs the Fn if the value is present and stores the returned value.
+    /// **Returning None clears the cell**, it does not mean "keep the value".
+    /// Returns true if the Fn was executed.
+    /// Fails if the value was borrowed elsewhere.
+    ///
+    pub fn try_compute_if_present_from_option<F>(&self, f: F) -> Result<bool, RcOCellError>
+        where F: FnOnce(&mut T) -> Option<T>
+    {
+        return self.try_compute_if_present(|value| f(value).into());
+    }
+
+    ///
+    /// Runs the fallible Fn with the ref to the value (if present) and stores the returned value.
+    /// **Returning Ok(None) clears the cell**, an error leaves the cell as it is and is returned.
+    /// Modifications the Fn made through the reference before returning the error are kept.
+    /// Panics if the value was borrowed elsewhere.
+    ///
+    pub fn compute_from_result<E, F>(&self, f: F) -> Result<(), E>
+        where F: FnOnce(Option<&mut T>) -> Result<Option<T>, E>
+    {
+        return self.compute_result(|value| f(value).map(RcOCellComputeResult::from));
+    }
+
+    ///
+    /// Applies a compute result built outside of a compute Fn, i.e. by a helper function.
+    /// Returns the value that was replaced or removed by Replace, Remove and Take, None for DoNothing.
+    /// Observers are notified like for set and clear.
+    /// Panics if the value is borrowed somewhere, unless the result is DoNothing.
+    ///
+    #[track_caller]
+    pub fn apply(&self, result: RcOCellComputeResult<T>) -> Option<T> {
+        return match result {
+            RcOCellComputeResult::Replace(t) => self.set(t),
+            RcOCellComputeResult::Remove => self.clear(),
+            RcOCellComputeResult::DoNothing => None,
+            RcOCellComputeResult::Take => self.clear(),
+        };
+    }
+
+    ///
+    /// Applies a compute result built outside of a compute Fn, see apply.
+    /// Fails if the value is borrowed somewhere, the result is handed back with the error
+    /// so a value to store is not lost. DoNothing never fails.
+    ///
+    pub fn try_apply(&self, result: RcOCellComputeResult<T>) -> Result<Option<T>, (RcOCellError, RcOCellComputeResult<T>)> {
+        if matches!(result, RcOCellComputeResult::DoNothing) {
+            return Ok(None);
+        }
+
+        if let Err(e) = self.check_mut() {
+            return Err((e, result));
+        }
+
+        return Ok(self.apply(result));
+    }
+
+    ///
+    /// Applies the result of a compute Fn, returns the removed value if the result was Take.
+    ///
+    fn apply_compute_result(&self, result: RcOCellComputeResult<T>) -> Option<T> {
+        let take = matches!(result, RcOCellComputeResult::Take);
+        let old = self.apply(result);
+        if take {
+            return old;
+        }
+
+        self.discard(old);
+        return None;
+    }
+
+    ///
+    /// Drops a value the cell replaced or removed without handing it to the caller,
+    /// zeroizes it first if enabled with zeroize_discarded.
+    ///
+    fn discard(&self, value: Option<T>) {
+        #[cfg(feature = "zeroize")]
+        scrub::discarded(self, value);
+        #[cfg(not(feature = "zeroize"))]
+        drop(value);
+    }
+
     ///
     /// Runs the Fn if the value is absent to calculate a new value.
     /// Returns true if the Fn was executed.
@@ -405,7 +1376,7 @@ impl <T> RcOCell<T>
     pub fn compute_if_absent<F>(&self, f: F) -> bool
         where F: FnOnce() -> Option<T>
     {
-        let x = self.rc.try_borrow_mut();
+        let x = self.try_raw_borrow_mut();
         if x.is_err() {
             return false;
         }
@@ -416,13 +1387,132 @@ impl <T> RcOCell<T>
 
         let result = f();
         drop(x);
-        if result.is_some() {
-            self.set(result.unwrap());
+        if let Some(result) = result {
+            self.set(result);
         }
 
         return true;
     }
 
+    ///
+    /// Returns the value, if the cell is empty the fallible Fn is called first and its value is stored.
+    /// The Fn runs at most once and only if the cell is empty, it runs under the same mutable borrow
+    /// that checked the cell, so it cannot access the cell itself.
+    /// If the Fn returns an error the cell stays empty and the error is returned as RcOCellComputeError::Compute.
+    /// Observers are notified after the value was stored, an observer that clears the cell makes the call fail with NoValue.
+    /// Fails with RcOCellComputeError::Cell if the value is borrowed elsewhere, the Fn is not called then.
+    ///
+    pub fn get_or_try_insert_with<E, F>(&self, f: F) -> Result<RcORef<'_, T>, RcOCellComputeError<E>>
+        where F: FnOnce() -> Result<T, E>
+    {
+        let x = self.try_raw_borrow_mut()?;
+        if x.is_none() {
+            let value = f().map_err(RcOCellComputeError::Compute)?;
+            drop(x);
+            self.set(value);
+        } else {
+            drop(x);
+        }
+
+        return Ok(self.try_borrow()?);
+    }
+
+    ///
+    /// Returns a clone of the value, if the cell is empty the fallible Fn is called first, see get_or_try_insert_with.
+    ///
+    pub fn get_or_try_insert_with_cloned<E, F>(&self, f: F) -> Result<T, RcOCellComputeError<E>>
+        where F: FnOnce() -> Result<T, E>, T: Clone
+    {
+        return Ok(T::clone(&*self.get_or_try_insert_with(f)?));
+    }
+
+    ///
+    /// Borrows the value mutably, if the cell is empty the default value of T is stored first.
+    /// The default is stored under the same mutable borrow that is returned, so nothing can clear the cell in between.
+    /// Like with CellEntry::or_default storing the default is not reported to observers.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    #[track_caller]
+    pub fn get_or_insert_default(&self) -> RcORefMut<'_, T>
+        where T: Default
+    {
+        return match self.try_get_or_insert_default() {
+            Ok(value) => value,
+            Err(e) => self.fail(e),
+        };
+    }
+
+    ///
+    /// Borrows the value mutably, if the cell is empty the default value of T is stored first, see get_or_insert_default.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    #[cfg_attr(feature = "debug-borrow-tracking", track_caller)]
+    pub fn try_get_or_insert_default(&self) -> Result<RcORefMut<'_, T>, RcOCellError>
+        where T: Default
+    {
+        let mut value = self.try_raw_borrow_mut()?;
+        value.get_or_insert_with(T::default);
+        RcORefMut::marks_dirty(&mut value);
+        return Ok(RcORefMut::map(value, |v| v.as_mut().unwrap()));
+    }
+
+    ///
+    /// Borrows the value, if the cell is empty the default value of T is stored first, see get_or_insert_default.
+    /// A value that is present is borrowed like with borrow, so other shared borrows do not interfere.
+    /// Panics if the value is borrowed mutably somewhere, or borrowed at all if the cell is empty.
+    ///
+    #[track_caller]
+    pub fn borrow_or_default(&self) -> RcORef<'_, T>
+        where T: Default
+    {
+        return match self.try_borrow_or_default() {
+            Ok(value) => value,
+            Err(e) => self.fail(e),
+        };
+    }
+
+    ///
+    /// Borrows the value, if the cell is empty the default value of T is stored first, see borrow_or_default.
+    /// Fails if the value is borrowed mutably somewhere, or borrowed at all if the cell is empty.
+    ///
+    #[cfg_attr(feature = "debug-borrow-tracking", track_caller)]
+    pub fn try_borrow_or_default(&self) -> Result<RcORef<'_, T>, RcOCellError>
+        where T: Default
+    {
+        match self.try_borrow() {
+            Err(RcOCellError::NoValue) => {}
+            result => return result,
+        }
+
+        return Ok(RcORefMut::downgrade(self.try_get_or_insert_default()?).unwrap_or_else(|_| unreachable!("guard is not split")));
+    }
+
+    ///
+    /// Runs the Fn if the value is absent without storing anything in the cell.
+    /// Returns true if the Fn was executed.
+    /// False if the value was present or borrowed mutably elsewhere (it also exists in this case).
+    /// This function does not panic.
+    ///
+    pub fn if_absent<F>(&self, f: F) -> bool
+        where F: FnOnce() {
+        return self.try_if_absent(f).unwrap_or(false);
+    }
+
+    ///
+    /// Runs the Fn if the value is absent without storing anything in the cell.
+    /// Returns true if the Fn was executed, false if the value was present.
+    /// Fails if the value is borrowed mutably elsewhere.
+    ///
+    pub fn try_if_absent<F>(&self, f: F) -> Result<bool, RcOCellError>
+        where F: FnOnce() {
+        if self.try_raw_borrow()?.is_some() {
+            return Ok(false);
+        }
+
+        f();
+        return Ok(true);
+    }
+
     ///
     /// Runs the Fn if the value is present.
     /// Panics if the value is borrowed mutably elsewhere.
@@ -430,7 +1520,7 @@ impl <T> RcOCell<T>
     ///
     pub fn if_present<F>(&self, f: F) -> bool
         where F: FnOnce(&T) -> RcOCellComputeResult<T> {
-        let x = self.rc.borrow();
+        let x = self.raw_borrow();
         if x.is_none() {
             return false;
         }
@@ -444,10 +1534,11 @@ impl <T> RcOCell<T>
     ///
     pub fn if_present_mut<F>(&self, f: F) -> bool
         where F: FnOnce(&mut T) -> RcOCellComputeResult<T> {
-        let mut x = self.rc.borrow_mut();
+        let mut x = self.raw_borrow_mut();
         if x.is_none() {
             return false;
         }
+        RcORefMut::marks_dirty(&mut x);
         f(x.as_mut().unwrap());
         return true;
     }
@@ -459,7 +1550,7 @@ impl <T> RcOCell<T>
     ///
     pub fn try_if_present<F>(&self, f: F) -> Result<bool, RcOCellError>
         where F: FnOnce(&T) -> RcOCellComputeResult<T> {
-        let x = self.rc.try_borrow()?;
+        let x = self.try_raw_borrow()?;
         if x.is_none() {
             return Ok(false);
         }
@@ -474,10 +1565,11 @@ impl <T> RcOCell<T>
     ///
     pub fn try_if_present_mut<F>(&self, f: F) -> Result<bool, RcOCellError>
         where F: FnOnce(&mut T) -> RcOCellComputeResult<T> {
-        let mut x = self.rc.try_borrow_mut()?;
+        let mut x = self.try_raw_borrow_mut()?;
         if x.is_none() {
             return Ok(false);
         }
+        RcORefMut::marks_dirty(&mut x);
         f(x.as_mut().unwrap());
         return Ok(true);
     }
@@ -487,11 +1579,14 @@ impl <T> RcOCell<T>
     /// Panics if there is no value or the value is borrowed somewhere.
     ///
     pub fn get_and_clear(&self) -> T {
-        let r =  self.rc.replace(None);
+        let r =  self.raw_replace(None);
         if r.is_none() {
-            panic!("RcCell::get_and_clear on a cell without value");
+            self.fail("RcCell::get_and_clear on a cell without value");
         }
 
+        #[cfg(feature = "tracing")]
+        trace::changed(self, "get_and_clear");
+        self.changed();
         return r.unwrap();
     }
 
@@ -500,12 +1595,15 @@ impl <T> RcOCell<T>
     /// Fails if there is no value or if the value is borrowed somewhere.
     ///
     pub fn try_get_and_clear(&self) -> Result<T, RcOCellError> {
-        drop(self.rc.try_borrow_mut()?);
-        let old = self.rc.replace(None);
+        self.check_mut()?;
+        let old = self.raw_replace(None);
         if old.is_none() {
             return Err(RcOCellError::NoValue);
         }
 
+        #[cfg(feature = "tracing")]
+        trace::changed(self, "get_and_clear");
+        self.changed();
         return Ok(old.unwrap());
     }
 
@@ -515,9 +1613,16 @@ impl <T> RcOCell<T>
     /// Panics if there is no value or the value is borrowed somewhere.
     ///
     pub fn replace(&self, value: T) -> T {
-        let rep = self.get_and_clear();
-        self.rc.replace(Some(value));
-        return rep;
+        let rep =  self.raw_replace(None);
+        if rep.is_none() {
+            self.fail("RcCell::replace on a cell without value");
+        }
+
+        self.raw_replace(Some(value));
+        #[cfg(feature = "tracing")]
+        trace::changed(self, "replace");
+        self.changed();
+        return rep.unwrap();
     }
 
     ///
@@ -525,28 +1630,78 @@ impl <T> RcOCell<T>
     /// Fails if there is no value or the value is borrowed somewhere.
     ///
     pub fn try_replace(&self, value: T) -> Result<T, RcOCellError> {
-        drop(self.rc.try_borrow_mut()?);
-        let rep = self.rc.replace(None);
+        self.check_mut()?;
+        let rep = self.raw_replace(None);
         if rep.is_none() {
             return Err(RcOCellError::NoValue);
         }
-        self.rc.replace(Some(value));
+        self.raw_replace(Some(value));
+        #[cfg(feature = "tracing")]
+        trace::changed(self, "replace");
+        self.changed();
         return Ok(rep.unwrap());
     }
 
+    ///
+    /// Takes the value out of the cell and leaves T::default() in its place, like std::mem::take.
+    /// Unlike clear the cell still holds a value afterwards, so other holders keep working with i.e. an empty collection.
+    /// Panics if there is no value or the value is borrowed somewhere.
+    ///
+    #[track_caller]
+    pub fn take_replacing_default(&self) -> T
+        where T: Default
+    {
+        return self.replace(T::default());
+    }
+
+    ///
+    /// Takes the value out of the cell and leaves T::default() in its place, see take_replacing_default.
+    /// Fails if there is no value or the value is borrowed somewhere, the cell is not changed then.
+    ///
+    pub fn try_take_replacing_default(&self) -> Result<T, RcOCellError>
+        where T: Default
+    {
+        return self.try_replace(T::default());
+    }
+
+    ///
+    /// Takes the value out of the cell (if present) and leaves T::default() in its place, an empty cell gets the default too.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    #[track_caller]
+    pub fn replace_default(&self) -> Option<T>
+        where T: Default
+    {
+        return self.set(T::default());
+    }
+
+    ///
+    /// Takes the value out of the cell (if present) and leaves T::default() in its place, see replace_default.
+    /// Fails if the value is borrowed somewhere, the cell is not changed then.
+    ///
+    pub fn try_replace_default(&self) -> Result<Option<T>, RcOCellError>
+        where T: Default
+    {
+        return self.try_set(T::default());
+    }
+
     ///
     /// Sets the value returning the old value (if an old value existed)
     /// Panics if the value is borrowed somewhere.
     ///
     pub fn set(&self, value: T) -> Option<T> {
-        return self.rc.replace(Some(value));
+        let old = self.raw_replace(Some(value));
+        #[cfg(feature = "tracing")]
+        trace::changed(self, "set");
+        self.changed();
+        return old;
     }
     ///
     /// Sets the value returning the old value (if an old value existed)
     /// Fails if the value is borrowed somewhere
     ///
     pub fn try_set(&self, value: T) -> Result<Option<T>, RcOCellError> {
-        drop(self.rc.try_borrow_mut()?);
+        self.check_mut()?;
         return Ok(self.set(value));
     }
 
@@ -555,7 +1710,11 @@ impl <T> RcOCell<T>
     /// Panics if the value is borrowed somewhere
     ///
     pub fn clear(&self) -> Option<T> {
-        return self.rc.replace(None);
+        let old = self.raw_replace(None);
+        #[cfg(feature = "tracing")]
+        trace::changed(self, "clear");
+        self.changed();
+        return old;
     }
 
     ///
@@ -563,10 +1722,84 @@ impl <T> RcOCell<T>
     /// Fails if the value is borrowed somewhere
     ///
     pub fn try_clear(&self) -> Result<Option<T>, RcOCellError> {
-        drop(self.rc.try_borrow_mut()?);
+        self.check_mut()?;
         return Ok(self.clear());
     }
 
+    ///
+    /// Calls on_present with the value if present, otherwise calls on_absent, and returns the result.
+    /// Both the check and the Fn run under the same borrow, so the cell cannot change in between.
+    /// Consequently neither Fn can modify the cell.
+    /// Panics if the value is already borrowed mutably somewhere.
+    ///
+    pub fn match_value<R, P, A>(&self, on_present: P, on_absent: A) -> R where
+        P: FnOnce(&T) -> R,
+        A: FnOnce() -> R,
+    {
+        let brw = self.raw_borrow();
+        return match brw.as_ref() {
+            Some(value) => on_present(value),
+            None => on_absent(),
+        };
+    }
+
+    ///
+    /// Calls on_present with the value if present, otherwise calls on_absent, and returns the result.
+    /// Both the check and the Fn run under the same borrow, so the cell cannot change in between.
+    /// Fails without calling either Fn if the value is already borrowed mutably somewhere.
+    ///
+    pub fn try_match_value<R, P, A>(&self, on_present: P, on_absent: A) -> Result<R, RcOCellError> where
+        P: FnOnce(&T) -> R,
+        A: FnOnce() -> R,
+    {
+        let brw = self.try_raw_borrow()?;
+        return Ok(match brw.as_ref() {
+            Some(value) => on_present(value),
+            None => on_absent(),
+        });
+    }
+
+    ///
+    /// Calls on_present with the mut value if present, otherwise calls on_absent, and returns the result.
+    /// Both the check and the Fn run under the same mutable borrow, so the cell cannot change in between.
+    /// Consequently neither Fn can access the cell.
+    /// Panics if the value is already borrowed somewhere.
+    ///
+    pub fn match_value_mut<R, P, A>(&self, on_present: P, on_absent: A) -> R where
+        P: FnOnce(&mut T) -> R,
+        A: FnOnce() -> R,
+    {
+        let mut brw = self.raw_borrow_mut();
+        if brw.is_some() {
+            RcORefMut::marks_dirty(&mut brw);
+        }
+
+        return match brw.as_mut() {
+            Some(value) => on_present(value),
+            None => on_absent(),
+        };
+    }
+
+    ///
+    /// Calls on_present with the mut value if present, otherwise calls on_absent, and returns the result.
+    /// Both the check and the Fn run under the same mutable borrow, so the cell cannot change in between.
+    /// Fails without calling either Fn if the value is already borrowed somewhere.
+    ///
+    pub fn try_match_value_mut<R, P, A>(&self, on_present: P, on_absent: A) -> Result<R, RcOCellError> where
+        P: FnOnce(&mut T) -> R,
+        A: FnOnce() -> R,
+    {
+        let mut brw = self.try_raw_borrow_mut()?;
+        if brw.is_some() {
+            RcORefMut::marks_dirty(&mut brw);
+        }
+
+        return Ok(match brw.as_mut() {
+            Some(value) => on_present(value),
+            None => on_absent(),
+        });
+    }
+
     ///
     /// Calls the Fn with the value (if present) and returns the result as an option.
     /// Panics if the value is already borrowed mutably somewhere.
@@ -575,7 +1808,7 @@ impl <T> RcOCell<T>
     pub fn map<F, X>(&self, x: F) -> Option<X> where
         F: FnOnce(&T) -> X,
     {
-        let brw = self.rc.as_ref().borrow();
+        let brw = self.raw_borrow();
         if brw.is_none() {
             return None
         }
@@ -591,7 +1824,7 @@ impl <T> RcOCell<T>
     pub fn try_map<F, X>(&self, x: F) -> Result<Option<X>, RcOCellError> where
         F: FnOnce(&T) -> X,
     {
-        let brw = self.rc.as_ref().try_borrow()?;
+        let brw = self.try_raw_borrow()?;
         if brw.is_none() {
             return Ok(None)
         }
@@ -607,11 +1840,12 @@ impl <T> RcOCell<T>
     pub fn map_mut<F, X>(&self, x: F) -> Option<X> where
         F: FnOnce(&mut T) -> X,
     {
-        let mut brw = self.rc.as_ref().borrow_mut();
+        let mut brw = self.raw_borrow_mut();
         if brw.is_none() {
             return None
         }
 
+        RcORefMut::marks_dirty(&mut brw);
         return Some(x(brw.as_mut().unwrap()));
     }
 
@@ -623,11 +1857,12 @@ impl <T> RcOCell<T>
     pub fn try_map_mut<F, X>(&self, x: F) -> Result<Option<X>, RcOCellError> where
         F: FnOnce(&mut T) -> X,
     {
-        let mut brw = self.rc.as_ref().try_borrow_mut()?;
+        let mut brw = self.try_raw_borrow_mut()?;
         if brw.is_none() {
             return Ok(None);
         }
 
+        RcORefMut::marks_dirty(&mut brw);
         return Ok(Some(x(brw.as_mut().unwrap())));
     }
 
@@ -643,10 +1878,26 @@ impl <T> RcOCell<T>
     /// Panics if either cells value is borrowed
     ///
     pub fn swap(&self, other: &RcOCell<T>) {
+        #[cfg(feature = "debug-thread-check")]
+        if let Err(e) = thread_check::check(self).and_then(|_| thread_check::check(other)) {
+            self.fail(e);
+        }
         let r = self.rc.as_ref();
         let l = other.rc.as_ref();
         r.swap(l);
-    }
+        #[cfg(feature = "tracing")]
+        {
+            trace::changed(self, "swap");
+            trace::changed(other, "swap");
+        }
+        #[cfg(feature = "spy")]
+        {
+            spy::swapped(self);
+            spy::swapped(other);
+        }
+        self.changed();
+        other.changed();
+    }
 
     ///
     /// Swaps the values of both cells.
@@ -677,6 +1928,56 @@ impl <T> RcOCell<T>
         where T: Clone {
         Ok(T::clone(&*self.try_borrow()?))
     }
+
+    ///
+    /// Clones the value in the cell
+    /// Panics with the given message followed by the reason if the cell is empty or the value is currently mutably borrowed
+    ///
+    #[track_caller]
+    pub fn expect_clone(&self, msg: &str) -> T
+        where T: Clone {
+        T::clone(&*self.expect(msg))
+    }
+}
+
+impl <T: Deref> RcOCell<T> {
+    ///
+    /// Borrows the target the value derefs to, i.e. `str` for a String or `[T]` for a `Vec<T>`.
+    /// Panics if the value is already borrowed mutably somewhere or there is no value
+    ///
+    #[cfg_attr(feature = "debug-borrow-tracking", track_caller)]
+    pub fn borrow_deref(&self) -> RcORef<'_, T::Target, T> {
+        return RcORef::map(self.borrow(), |v| v.deref());
+    }
+
+    ///
+    /// Borrows the target the value derefs to, i.e. `str` for a String or `[T]` for a `Vec<T>`.
+    /// Fails if the value is already borrowed mutably somewhere or there is no value
+    ///
+    #[cfg_attr(feature = "debug-borrow-tracking", track_caller)]
+    pub fn try_borrow_deref(&self) -> Result<RcORef<'_, T::Target, T>, RcOCellError> {
+        return Ok(RcORef::map(self.try_borrow()?, |v| v.deref()));
+    }
+}
+
+impl <T: DerefMut> RcOCell<T> {
+    ///
+    /// Borrows the target the value derefs to mutably, i.e. `str` for a String or `[T]` for a `Vec<T>`.
+    /// Panics if the value is already borrowed somewhere (either non mutably or mutably) or there is no value
+    ///
+    #[cfg_attr(feature = "debug-borrow-tracking", track_caller)]
+    pub fn borrow_deref_mut(&self) -> RcORefMut<'_, T::Target, T> {
+        return RcORefMut::map(self.borrow_mut(), |v| v.deref_mut());
+    }
+
+    ///
+    /// Borrows the target the value derefs to mutably, i.e. `str` for a String or `[T]` for a `Vec<T>`.
+    /// Fails if the value is already borrowed somewhere (either non mutably or mutably) or there is no value
+    ///
+    #[cfg_attr(feature = "debug-borrow-tracking", track_caller)]
+    pub fn try_borrow_deref_mut(&self) -> Result<RcORefMut<'_, T::Target, T>, RcOCellError> {
+        return Ok(RcORefMut::map(self.try_borrow_mut()?, |v| v.deref_mut()));
+    }
 }
 
 impl <T> Clone for WeakRcOCell<T> {
@@ -702,6 +2003,40 @@ impl <T> TryFrom<WeakRcOCell<T>> for RcOCell<T> {
 
 
 impl <T> WeakRcOCell<T> {
+    ///
+    /// Returns the underlying Weak, i.e. to store it next to weak references that do not belong to a cell.
+    ///
+    pub fn as_weak_raw(&self) -> &Weak<RefCell<Option<T>>> {
+        return &self.rc;
+    }
+
+    ///
+    /// Unwraps the underlying Weak, the inverse of `WeakRcOCell::from`.
+    ///
+    pub fn into_weak_raw(self) -> Weak<RefCell<Option<T>>> {
+        return self.rc;
+    }
+
+    ///
+    /// Consumes the cell and returns the pointer of Weak::into_raw, the weak reference count is not decremented.
+    /// The pointer must be passed to from_raw exactly once to release the weak reference again.
+    /// The pointer is dangling if the cell was created from Weak::new.
+    ///
+    pub fn into_raw(self) -> *const RefCell<Option<T>> {
+        return Weak::into_raw(self.rc);
+    }
+
+    ///
+    /// Reconstructs the cell from a pointer returned by into_raw, see Weak::from_raw.
+    ///
+    /// # Safety
+    /// The pointer must have been returned by into_raw or Weak::into_raw for the same T
+    /// and must not have been passed to from_raw before.
+    ///
+    pub unsafe fn from_raw(ptr: *const RefCell<Option<T>>) -> WeakRcOCell<T> {
+        return WeakRcOCell{rc: unsafe { Weak::from_raw(ptr) }};
+    }
+
     pub fn upgrade(&self) -> RcOCell<T> {
         let x = self.rc.upgrade();
         if x.is_none() {
@@ -720,6 +2055,51 @@ impl <T> WeakRcOCell<T> {
         return Ok(RcOCell{rc: x.unwrap()});
     }
 
+    ///
+    /// Upgrades the cell.
+    /// Panics with the given message followed by the reason if the cell was already dropped.
+    ///
+    #[track_caller]
+    pub fn expect_upgrade(&self, msg: &str) -> RcOCell<T> {
+        return match self.try_upgrade() {
+            Ok(cell) => cell,
+            Err(e) => panic!("{}: {}", msg, e),
+        };
+    }
+
+    ///
+    /// Upgrades the cell if it is still alive, regardless of whether it currently holds a value.
+    /// If the cell was already dropped then a new empty cell is created,
+    /// this weak cell is changed to point at the new cell and the new cell is returned.
+    ///
+    pub fn upgrade_or_new(&mut self) -> RcOCell<T> {
+        return self.upgrade_or_insert_with_option(|| None);
+    }
+
+    ///
+    /// Upgrades the cell if it is still alive, regardless of whether it currently holds a value.
+    /// If the cell was already dropped then a new cell containing the value returned by the Fn is created,
+    /// this weak cell is changed to point at the new cell and the new cell is returned.
+    /// The Fn is only called if a new cell is created. An empty cell that is still alive is not populated.
+    ///
+    pub fn upgrade_or_insert_with<F>(&mut self, f: F) -> RcOCell<T>
+        where F: FnOnce() -> T
+    {
+        return self.upgrade_or_insert_with_option(|| Some(f()));
+    }
+
+    fn upgrade_or_insert_with_option<F>(&mut self, f: F) -> RcOCell<T>
+        where F: FnOnce() -> Option<T>
+    {
+        if let Some(rc) = self.rc.upgrade() {
+            return RcOCell{rc};
+        }
+
+        let cell = RcOCell::from_option(f());
+        self.rc = Rc::downgrade(&cell.rc);
+        return cell;
+    }
+
 
     ///
     /// Returns true if the value is set and the cell is not dropped
@@ -784,6 +2164,28 @@ impl <T> WeakRcOCell<T> {
             .try_compute(f)
     }
 
+    ///
+    /// Applies a compute result built outside of a compute Fn, see RcOCell::apply.
+    /// Panics if the cell was dropped or the value is borrowed somewhere, unless the result is DoNothing.
+    ///
+    #[track_caller]
+    pub fn apply(&self, result: RcOCellComputeResult<T>) -> Option<T> {
+        self.try_upgrade()
+            .expect("WeakRcOCell::apply called on a dropped cell")
+            .apply(result)
+    }
+
+    ///
+    /// Applies a compute result built outside of a compute Fn, see RcOCell::try_apply.
+    /// Fails if the cell was dropped or the value is borrowed somewhere, the result is handed back with the error.
+    ///
+    pub fn try_apply(&self, result: RcOCellComputeResult<T>) -> Result<Option<T>, (RcOCellError, RcOCellComputeResult<T>)> {
+        return match self.try_upgrade() {
+            Ok(cell) => cell.try_apply(result),
+            Err(e) => Err((e, result)),
+        };
+    }
+
 
     ///
     /// Runs the Fn if the value is present to perform a calculation on it, conditionally updating/removing it.
@@ -812,6 +2214,135 @@ impl <T> WeakRcOCell<T> {
             .try_compute_if_present(f)
     }
 
+    ///
+    /// Runs the Fn with the ref to the value (if present) and stores the returned value.
+    /// **Returning None clears the cell**, it does not mean "keep the value".
+    /// Panics if the value was borrowed elsewhere or the cell was already dropped.
+    ///
+    pub fn compute_from_option<F>(&self, f: F)
+        where F: FnOnce(Option<&mut T>) -> Option<T>
+    {
+        self.try_upgrade()
+            .expect("WeakRcOCell::compute_from_option called on a dropped cell")
+            .compute_from_option(f)
+    }
+
+    ///
+    /// Runs the Fn if the value is present and stores the returned value.
+    /// **Returning None clears the cell**, it does not mean "keep the value".
+    /// Returns true if the Fn was executed.
+    /// Panics if the value was borrowed elsewhere or the cell was already dropped.
+    ///
+    pub fn compute_if_present_from_option<F>(&self, f: F) -> bool
+        where F: FnOnce(&mut T) -> Option<T>
+    {
+        self.try_upgrade()
+            .expect("WeakRcOCell::compute_if_present_from_option called on a dropped cell")
+            .compute_if_present_from_option(f)
+    }
+
+    ///
+    /// Runs the Fn with the ref to the value (if present), conditionally creating/updating/removing it.
+    /// Returns the removed value if the Fn returned Take, None for all other results.
+    /// Panics if the value was borrowed elsewhere or the cell was already dropped.
+    ///
+    pub fn compute_take<F>(&self, f: F) -> Option<T>
+        where F: FnOnce(Option<&mut T>) -> RcOCellComputeResult<T>
+    {
+        self.try_upgrade()
+            .expect("WeakRcOCell::compute_take called on a dropped cell")
+            .compute_take(f)
+    }
+
+    ///
+    /// Runs the Fn with the ref to the value (if present), conditionally creating/updating/removing it.
+    /// Returns the removed value if the Fn returned Take, None for all other results.
+    /// Fails if the value was borrowed elsewhere or the cell was already dropped.
+    ///
+    pub fn try_compute_take<F>(&self, f: F) -> Result<Option<T>, RcOCellError>
+        where F: FnOnce(Option<&mut T>) -> RcOCellComputeResult<T>
+    {
+        self.try_upgrade()?.try_compute_take(f)
+    }
+
+    ///
+    /// Runs the Fn if the value is present to perform a calculation on it, conditionally updating/removing it.
+    /// Returns whether the Fn was executed together with the removed value if the Fn returned Take.
+    /// Panics if the value was borrowed elsewhere or the cell was already dropped.
+    ///
+    pub fn compute_if_present_take<F>(&self, f: F) -> RcOCellComputeOutcome<T>
+        where F: FnOnce(&mut T) -> RcOCellComputeResult<T>
+    {
+        self.try_upgrade()
+            .expect("WeakRcOCell::compute_if_present_take called on a dropped cell")
+            .compute_if_present_take(f)
+    }
+
+    ///
+    /// Runs the Fn if the value is present to perform a calculation on it, conditionally updating/removing it.
+    /// Returns whether the Fn was executed together with the removed value if the Fn returned Take.
+    /// Fails if the value was borrowed elsewhere or the cell was already dropped.
+    ///
+    pub fn try_compute_if_present_take<F>(&self, f: F) -> Result<RcOCellComputeOutcome<T>, RcOCellError>
+        where F: FnOnce(&mut T) -> RcOCellComputeResult<T>
+    {
+        self.try_upgrade()?.try_compute_if_present_take(f)
+    }
+
+    ///
+    /// Runs the fallible Fn with the ref to the value (if present), conditionally creating/updating/removing it.
+    /// If the Fn returns an error the result is not applied and the error is returned.
+    /// Modifications the Fn made through the reference before returning the error are kept.
+    /// Panics if the value was borrowed elsewhere or the cell was already dropped.
+    ///
+    pub fn compute_result<E, F>(&self, f: F) -> Result<(), E>
+        where F: FnOnce(Option<&mut T>) -> Result<RcOCellComputeResult<T>, E>
+    {
+        self.try_upgrade()
+            .expect("WeakRcOCell::compute_result called on a dropped cell")
+            .compute_result(f)
+    }
+
+    ///
+    /// Runs the fallible Fn with the ref to the value (if present), conditionally creating/updating/removing it.
+    /// If the Fn returns an error the result is not applied and the error is returned.
+    /// Modifications the Fn made through the reference before returning the error are kept.
+    /// Fails if the value was borrowed elsewhere or the cell was already dropped.
+    ///
+    pub fn try_compute_result<E, F>(&self, f: F) -> Result<(), RcOCellComputeError<E>>
+        where F: FnOnce(Option<&mut T>) -> Result<RcOCellComputeResult<T>, E>
+    {
+        self.try_upgrade()?
+            .try_compute_result(f)
+    }
+
+    ///
+    /// Runs the fallible Fn if the value is present to perform a calculation on it, conditionally updating/removing it.
+    /// Returns true if the Fn was executed and succeeded.
+    /// If the Fn returns an error the result is not applied and the error is returned.
+    /// Panics if the value was borrowed elsewhere or the cell was already dropped.
+    ///
+    pub fn compute_if_present_result<E, F>(&self, f: F) -> Result<bool, E>
+        where F: FnOnce(&mut T) -> Result<RcOCellComputeResult<T>, E>
+    {
+        self.try_upgrade()
+            .expect("WeakRcOCell::compute_if_present_result called on a dropped cell")
+            .compute_if_present_result(f)
+    }
+
+    ///
+    /// Runs the fallible Fn if the value is present to perform a calculation on it, conditionally updating/removing it.
+    /// Returns true if the Fn was executed and succeeded.
+    /// If the Fn returns an error the result is not applied and the error is returned.
+    /// Fails if the value was borrowed elsewhere or the cell was already dropped.
+    ///
+    pub fn try_compute_if_present_result<E, F>(&self, f: F) -> Result<bool, RcOCellComputeError<E>>
+        where F: FnOnce(&mut T) -> Result<RcOCellComputeResult<T>, E>
+    {
+        self.try_upgrade()?
+            .try_compute_if_present_result(f)
+    }
+
     ///
     /// Runs the Fn if the value is absent to calculate a new value.
     /// Returns true if the Fn was executed.
@@ -839,6 +2370,70 @@ impl <T> WeakRcOCell<T> {
             .compute_if_absent(f))
     }
 
+    ///
+    /// Returns the value, if the cell is empty the fallible Fn is called first and its value is stored,
+    /// see RcOCell::get_or_try_insert_with. The guard holds a strong handle of the cell until it is dropped.
+    /// Fails with RcOCellComputeError::Cell if the value is borrowed elsewhere or the cell was already dropped.
+    ///
+    pub fn get_or_try_insert_with<E, F>(&self, f: F) -> Result<OwnedRef<T>, RcOCellComputeError<E>>
+        where F: FnOnce() -> Result<T, E>, T: 'static
+    {
+        let cell = self.try_upgrade()?;
+        drop(cell.get_or_try_insert_with(f)?);
+        return Ok(cell.try_borrow_owned()?);
+    }
+
+    ///
+    /// Returns a clone of the value, if the cell is empty the fallible Fn is called first, see RcOCell::get_or_try_insert_with.
+    /// Fails with RcOCellComputeError::Cell if the value is borrowed elsewhere or the cell was already dropped.
+    ///
+    pub fn get_or_try_insert_with_cloned<E, F>(&self, f: F) -> Result<T, RcOCellComputeError<E>>
+        where F: FnOnce() -> Result<T, E>, T: Clone
+    {
+        self.try_upgrade()?
+            .get_or_try_insert_with_cloned(f)
+    }
+
+    ///
+    /// Runs the Fn if the value is absent without storing anything in the cell.
+    /// Returns true if the Fn was executed.
+    /// False if the value was present or borrowed mutably elsewhere (it also exists in this case).
+    /// Panics if the cell was already dropped, see if_absent_or_dropped.
+    ///
+    pub fn if_absent<F>(&self, f: F) -> bool
+        where F: FnOnce() {
+        self.try_upgrade()
+            .expect("WeakRcOCell::if_absent called on a dropped cell")
+            .if_absent(f)
+    }
+
+    ///
+    /// Runs the Fn if the value is absent without storing anything in the cell.
+    /// Returns true if the Fn was executed, false if the value was present.
+    /// Fails if the value is borrowed mutably elsewhere or the cell was already dropped.
+    ///
+    pub fn try_if_absent<F>(&self, f: F) -> Result<bool, RcOCellError>
+        where F: FnOnce() {
+        self.try_upgrade()?.try_if_absent(f)
+    }
+
+    ///
+    /// Runs the Fn if the value is absent or the cell was already dropped.
+    /// Returns true if the Fn was executed.
+    /// False if the value was present or borrowed mutably elsewhere (it also exists in this case).
+    /// This function does not panic.
+    ///
+    pub fn if_absent_or_dropped<F>(&self, f: F) -> bool
+        where F: FnOnce() {
+        return match self.try_upgrade() {
+            Ok(cell) => cell.if_absent(f),
+            Err(_) => {
+                f();
+                true
+            }
+        };
+    }
+
     ///
     /// Runs the Fn if the value is present.
     /// Panics if the value is borrowed mutably elsewhere.
@@ -921,6 +2516,52 @@ impl <T> WeakRcOCell<T> {
             .try_replace(value)
     }
 
+    ///
+    /// Takes the value out of the cell and leaves T::default() in its place, see RcOCell::take_replacing_default.
+    /// Panics if the cell was dropped, there is no value or the value is borrowed somewhere.
+    ///
+    pub fn take_replacing_default(&self) -> T
+        where T: Default
+    {
+        self.try_upgrade()
+            .expect("WeakRcOCell::take_replacing_default called on a dropped cell")
+            .take_replacing_default()
+    }
+
+    ///
+    /// Takes the value out of the cell and leaves T::default() in its place, see RcOCell::take_replacing_default.
+    /// Fails if the cell was dropped, there is no value or the value is borrowed somewhere.
+    ///
+    pub fn try_take_replacing_default(&self) -> Result<T, RcOCellError>
+        where T: Default
+    {
+        self.try_upgrade()?
+            .try_take_replacing_default()
+    }
+
+    ///
+    /// Takes the value out of the cell (if present) and leaves T::default() in its place, see RcOCell::replace_default.
+    /// Panics if the cell was dropped or the value is borrowed somewhere.
+    ///
+    pub fn replace_default(&self) -> Option<T>
+        where T: Default
+    {
+        self.try_upgrade()
+            .expect("WeakRcOCell::replace_default called on a dropped cell")
+            .replace_default()
+    }
+
+    ///
+    /// Takes the value out of the cell (if present) and leaves T::default() in its place, see RcOCell::replace_default.
+    /// Fails if the cell was dropped or the value is borrowed somewhere.
+    ///
+    pub fn try_replace_default(&self) -> Result<Option<T>, RcOCellError>
+        where T: Default
+    {
+        self.try_upgrade()?
+            .try_replace_default()
+    }
+
     ///
     /// Sets the value returning the old value (if an old value existed)
     /// Panics if the value is borrowed somewhere.
@@ -958,6 +2599,102 @@ impl <T> WeakRcOCell<T> {
             .try_clear()
     }
 
+    ///
+    /// Swaps the values of both cells, see RcOCell::swap.
+    /// Both cells are only upgraded for the duration of the swap.
+    /// Panics if either cell was dropped, the message tells which one, or if either value is borrowed somewhere.
+    ///
+    pub fn swap(&self, other: &WeakRcOCell<T>) {
+        let other = other.try_upgrade().expect("WeakRcOCell::swap called with a dropped cell");
+        self.swap_with_strong(&other);
+    }
+
+    ///
+    /// Swaps the values of both cells, see RcOCell::try_swap.
+    /// Fails with Dropped if either cell was dropped, nothing is swapped in that case.
+    /// This cell is upgraded first, so a failed try_upgrade on this cell tells which side was dropped.
+    /// Fails if either value is borrowed somewhere, both cells are checked before anything is swapped.
+    ///
+    pub fn try_swap(&self, other: &WeakRcOCell<T>) -> Result<(), RcOCellError> {
+        let this = self.try_upgrade()?;
+        return this.try_swap(&other.try_upgrade()?);
+    }
+
+    ///
+    /// Swaps the value of this cell with the value of a strong cell, see RcOCell::swap.
+    /// Panics if this cell was dropped or either value is borrowed somewhere.
+    ///
+    pub fn swap_with_strong(&self, other: &RcOCell<T>) {
+        self.try_upgrade()
+            .expect("WeakRcOCell::swap called on a dropped cell")
+            .swap(other)
+    }
+
+    ///
+    /// Swaps the value of this cell with the value of a strong cell, see RcOCell::try_swap.
+    /// Fails with Dropped if this cell was dropped and if either value is borrowed somewhere.
+    ///
+    pub fn try_swap_with_strong(&self, other: &RcOCell<T>) -> Result<(), RcOCellError> {
+        self.try_upgrade()?
+            .try_swap(other)
+    }
+
+    ///
+    /// Calls on_present with the value if present, otherwise calls on_absent, and returns the result.
+    /// A dropped cell counts as absent.
+    /// Both the check and the Fn run under the same borrow, so the cell cannot change in between.
+    /// Panics if the value is already borrowed mutably somewhere.
+    ///
+    pub fn match_value<R, P, A>(&self, on_present: P, on_absent: A) -> R where
+        P: FnOnce(&T) -> R,
+        A: FnOnce() -> R,
+    {
+        return match self.try_upgrade() {
+            Ok(cell) => cell.match_value(on_present, on_absent),
+            Err(_) => on_absent(),
+        };
+    }
+
+    ///
+    /// Calls on_present with the value if present, otherwise calls on_absent, and returns the result.
+    /// Both the check and the Fn run under the same borrow, so the cell cannot change in between.
+    /// Fails without calling either Fn if the value is already borrowed mutably somewhere or the cell was already dropped.
+    ///
+    pub fn try_match_value<R, P, A>(&self, on_present: P, on_absent: A) -> Result<R, RcOCellError> where
+        P: FnOnce(&T) -> R,
+        A: FnOnce() -> R,
+    {
+        self.try_upgrade()?.try_match_value(on_present, on_absent)
+    }
+
+    ///
+    /// Calls on_present with the mut value if present, otherwise calls on_absent, and returns the result.
+    /// A dropped cell counts as absent.
+    /// Both the check and the Fn run under the same mutable borrow, so the cell cannot change in between.
+    /// Panics if the value is already borrowed somewhere.
+    ///
+    pub fn match_value_mut<R, P, A>(&self, on_present: P, on_absent: A) -> R where
+        P: FnOnce(&mut T) -> R,
+        A: FnOnce() -> R,
+    {
+        return match self.try_upgrade() {
+            Ok(cell) => cell.match_value_mut(on_present, on_absent),
+            Err(_) => on_absent(),
+        };
+    }
+
+    ///
+    /// Calls on_present with the mut value if present, otherwise calls on_absent, and returns the result.
+    /// Both the check and the Fn run under the same mutable borrow, so the cell cannot change in between.
+    /// Fails without calling either Fn if the value is already borrowed somewhere or the cell was already dropped.
+    ///
+    pub fn try_match_value_mut<R, P, A>(&self, on_present: P, on_absent: A) -> Result<R, RcOCellError> where
+        P: FnOnce(&mut T) -> R,
+        A: FnOnce() -> R,
+    {
+        self.try_upgrade()?.try_match_value_mut(on_present, on_absent)
+    }
+
     ///
     /// Calls the Fn with the value (if present) and returns the result as an option.
     /// Panics if the value is already borrowed mutably somewhere.
@@ -1028,6 +2765,16 @@ impl <T> WeakRcOCell<T> {
         self.try_upgrade()?.try_get_and_clone()
     }
 
+    ///
+    /// Clones the value in the cell
+    /// Panics with the given message followed by the reason if the cell was dropped, is empty or the value is currently mutably borrowed
+    ///
+    #[track_caller]
+    pub fn expect_clone(&self, msg: &str) -> T
+        where T: Clone {
+        self.expect_upgrade(msg).expect_clone(msg)
+    }
+
 }
 
 
@@ -1045,7 +2792,7 @@ mod tests {
     use std::cell::RefCell;
     use std::panic;
     use std::panic::AssertUnwindSafe;
-    use std::rc::Rc;
+    use std::rc::{Rc, Weak};
     use crate::*;
     use crate::RcOCellComputeResult::Replace;
 
@@ -1145,9 +2892,9 @@ mod tests {
     fn test_replace_panic() {
         let x = RcOCell::from_value("Baum".to_string());
         let y = x.borrow();
-        let r = panic::catch_unwind(AssertUnwindSafe(|| {
+        let r = x.catch(|x| {
             x.replace("Nase".to_string());
-        }));
+        });
         assert_eq!(r.is_err(), true);
         assert_eq!(y.as_str(), "Baum");
         drop(y);
@@ -1159,9 +2906,9 @@ mod tests {
     fn test_get_empty_panic() {
         let x = RcOCell::from_value("Baum".to_string());
         x.clear();
-        let r = panic::catch_unwind(AssertUnwindSafe(|| {
+        let r = x.catch(|x| {
             x.get_and_clear();
-        }));
+        });
         assert_eq!(r.is_err(), true);
     }
 
@@ -1170,9 +2917,9 @@ mod tests {
     fn test_set_panic() {
         let x = RcOCell::from_value("Baum".to_string());
         let y = x.borrow();
-        let r = panic::catch_unwind(AssertUnwindSafe(|| {
+        let r = x.catch(|x| {
             x.set("Nase".to_string());
-        }));
+        });
         assert_eq!(r.is_err(), true);
         assert_eq!(y.as_str(), "Baum");
         drop(y);
@@ -1185,9 +2932,9 @@ mod tests {
     fn test_clear_panic() {
         let x = RcOCell::from_value("Baum".to_string());
         let y = x.borrow();
-        let r = panic::catch_unwind(AssertUnwindSafe(|| {
+        let r = x.catch(|x| {
             x.clear();
-        }));
+        });
         assert_eq!(r.is_err(), true);
         assert_eq!(y.as_str(), "Baum");
         drop(y);
@@ -1201,35 +2948,37 @@ mod tests {
     fn test_borrow_panic() {
         let x = RcOCell::from_value("Baum".to_string());
         let y = x.borrow_mut();
-        let r = panic::catch_unwind(AssertUnwindSafe(|| {
+        let r = x.catch(|x| {
             x.borrow_mut();
-        }));
+        });
         assert_eq!(r.is_err(), true);
         drop(y);
         let y = x.borrow_mut();
         drop(y);
 
-        let r = panic::catch_unwind(AssertUnwindSafe(|| {
+        let r = x.catch(|x| {
             let _m = x.borrow_mut();
             panic!("Oh no");
-        }));
+        });
         assert_eq!(r.is_err(), true);
+        assert_eq!(x.is_poisoned(), cfg!(feature = "poison"));
+        x.clear_poison();
         let y = x.borrow_mut();
         drop(y);
 
         let y = x.borrow();
-        let r = panic::catch_unwind(AssertUnwindSafe(|| {
+        let r = x.catch(|x| {
             let _m = x.borrow_mut();
-        }));
+        });
         assert_eq!(r.is_err(), true);
         drop(y);
         let y = x.borrow_mut();
         drop(y);
 
         let y = x.borrow_mut();
-        let r = panic::catch_unwind(AssertUnwindSafe(|| {
+        let r = x.catch(|x| {
             let _m = x.borrow();
-        }));
+        });
         assert_eq!(r.is_err(), true);
         drop(y);
         let y = x.borrow();
@@ -1326,4 +3075,750 @@ mod tests {
         assert_eq!(w.get_and_clone(), 1u32);
         assert_eq!(y.get_and_clone(), 1u32);
     }
+
+    #[test]
+    fn test_upgrade_or_new_dropped() {
+        let x = RcOCell::from_value(1u32);
+        let mut w = x.downgrade();
+        drop(x);
+        let y = w.upgrade_or_new();
+        assert_eq!(y.is_none(), true);
+        y.set(2u32);
+        assert_eq!(w.get_and_clone(), 2u32);
+        drop(y);
+        assert_eq!(w.try_upgrade().is_err(), true);
+    }
+
+    #[test]
+    fn test_upgrade_or_new_live_empty() {
+        let x: RcOCell<u32> = RcOCell::new();
+        let mut w = x.downgrade();
+        let y = w.upgrade_or_insert_with(|| panic!("must not be called"));
+        assert_eq!(y.is_none(), true);
+        x.set(3u32);
+        assert_eq!(y.get_and_clone(), 3u32);
+        assert_eq!(w.get_and_clone(), 3u32);
+    }
+
+    #[test]
+    fn test_upgrade_or_insert_with() {
+        let x = RcOCell::from_value(1u32);
+        let mut w = x.downgrade();
+        let y = w.upgrade_or_insert_with(|| 5u32);
+        assert_eq!(y.get_and_clone(), 1u32);
+        y.set(4u32);
+        assert_eq!(x.get_and_clone(), 4u32);
+        drop(x);
+        drop(y);
+
+        let y = w.upgrade_or_insert_with(|| 5u32);
+        assert_eq!(y.get_and_clone(), 5u32);
+        y.set(6u32);
+        assert_eq!(w.get_and_clone(), 6u32);
+    }
+
+    fn panic_message<F: FnOnce()>(f: F) -> String {
+        let r = panic::catch_unwind(AssertUnwindSafe(f));
+        let e = r.unwrap_err();
+        if let Some(s) = e.downcast_ref::<String>() {
+            return s.clone();
+        }
+        return e.downcast_ref::<&str>().unwrap().to_string();
+    }
+
+    #[test]
+    fn test_compute_result() {
+        let x = RcOCell::from_value(1u32);
+        let r: Result<(), String> = x.compute_result(|_| Err("parse error".to_string()));
+        assert_eq!(r.unwrap_err(), "parse error");
+        assert_eq!(x.get_and_clone(), 1u32);
+
+        let r: Result<(), String> = x.compute_result(|n| {
+            let n = n.unwrap();
+            *n += 1;
+            Err("after mutation".to_string())
+        });
+        assert_eq!(r.unwrap_err(), "after mutation");
+        assert_eq!(x.get_and_clone(), 2u32);
+
+        let r: Result<(), String> = x.compute_result(|n| Ok(Replace(*n.unwrap() * 10)));
+        assert_eq!(r.is_ok(), true);
+        assert_eq!(x.get_and_clone(), 20u32);
+    }
+
+    #[test]
+    fn test_try_compute_result() {
+        fn parse_into(x: &RcOCell<u32>, s: &str) -> Result<(), RcOCellComputeError<std::num::ParseIntError>> {
+            x.try_compute_result(|_| Ok(Replace(s.parse()?)))?;
+            return Ok(());
+        }
+
+        let x = RcOCell::new();
+        match parse_into(&x, "abc") {
+            Err(RcOCellComputeError::Compute(_)) => {}
+            _ => panic!("unexpected"),
+        }
+        assert_eq!(x.is_none(), true);
+        parse_into(&x, "12").unwrap();
+        assert_eq!(x.get_and_clone(), 12u32);
+        let brw = x.borrow();
+        match parse_into(&x, "13") {
+            Err(RcOCellComputeError::Cell(RcOCellError::BorrowError(_))) => {}
+            _ => panic!("unexpected"),
+        }
+        drop(brw);
+        assert_eq!(x.get_and_clone(), 12u32);
+    }
+
+    #[test]
+    fn test_compute_if_present_result() {
+        let x: RcOCell<u32> = RcOCell::new();
+        let r: Result<bool, ()> = x.compute_if_present_result(|_| panic!("must not be called"));
+        assert_eq!(r, Ok(false));
+        x.set(1);
+        let r: Result<bool, ()> = x.compute_if_present_result(|_| Err(()));
+        assert_eq!(r, Err(()));
+        assert_eq!(x.get_and_clone(), 1u32);
+        let r: Result<bool, ()> = x.compute_if_present_result(|_| Ok(RcOCellComputeResult::Remove));
+        assert_eq!(r, Ok(true));
+        assert_eq!(x.is_none(), true);
+        x.set(1);
+        let w = x.downgrade();
+        let r = w.try_compute_if_present_result(|n| { *n += 1; Err::<RcOCellComputeResult<u32>, _>("fail") });
+        match r {
+            Err(RcOCellComputeError::Compute("fail")) => {}
+            _ => panic!("unexpected"),
+        }
+        assert_eq!(x.get_and_clone(), 2u32);
+        drop(x);
+        match w.try_compute_if_present_result(|_| Err::<RcOCellComputeResult<u32>, _>("fail")) {
+            Err(RcOCellComputeError::Cell(RcOCellError::Dropped)) => {}
+            _ => panic!("unexpected"),
+        }
+    }
+
+    #[test]
+    fn test_if_absent() {
+        let x = RcOCell::from_value(1u32);
+        let mut ran = 0;
+        assert_eq!(x.if_absent(|| ran += 1), false);
+        assert_eq!(x.try_if_absent(|| ran += 1).unwrap(), false);
+        let brw = x.borrow();
+        assert_eq!(x.if_absent(|| ran += 1), false);
+        drop(brw);
+        let brw = x.borrow_mut();
+        assert_eq!(x.if_absent(|| ran += 1), false);
+        match x.try_if_absent(|| ran += 1) {
+            Err(RcOCellError::BorrowError(RcOCellBorrowError::Normal(_))) => {}
+            _ => panic!("unexpected"),
+        }
+        drop(brw);
+        assert_eq!(ran, 0);
+        x.clear();
+        assert_eq!(x.if_absent(|| ran += 1), true);
+        assert_eq!(x.try_if_absent(|| ran += 1).unwrap(), true);
+        assert_eq!(ran, 2);
+        assert_eq!(x.is_none(), true);
+    }
+
+    #[test]
+    fn test_if_absent_weak() {
+        let x: RcOCell<u32> = RcOCell::new();
+        let w = x.downgrade();
+        let mut ran = 0;
+        assert_eq!(w.if_absent(|| ran += 1), true);
+        assert_eq!(w.if_absent_or_dropped(|| ran += 1), true);
+        x.set(1);
+        assert_eq!(w.if_absent_or_dropped(|| ran += 1), false);
+        drop(x);
+        match w.try_if_absent(|| ran += 1) {
+            Err(RcOCellError::Dropped) => {}
+            _ => panic!("unexpected"),
+        }
+        assert_eq!(w.if_absent_or_dropped(|| ran += 1), true);
+        assert_eq!(ran, 3);
+        let r = panic::catch_unwind(AssertUnwindSafe(|| {
+            w.if_absent(|| {});
+        }));
+        assert_eq!(r.is_err(), true);
+    }
+
+    trait Shape {
+        fn area(&self) -> u32;
+        fn scale(&mut self, factor: u32);
+    }
+
+    struct Square(u32);
+
+    impl Shape for Square {
+        fn area(&self) -> u32 {
+            return self.0 * self.0;
+        }
+
+        fn scale(&mut self, factor: u32) {
+            self.0 *= factor;
+        }
+    }
+
+    #[test]
+    fn test_borrow_deref() {
+        let x = RcOCell::from_value("Baum".to_string());
+        let s: RcORef<str, String> = x.borrow_deref();
+        assert_eq!(&*s, "Baum");
+        drop(s);
+        x.borrow_deref_mut().make_ascii_uppercase();
+        assert_eq!(x.get_and_clone(), "BAUM");
+
+        let x: RcOCell<Box<dyn Shape>> = RcOCell::from_value(Box::new(Square(2)));
+        assert_eq!(x.borrow_deref().area(), 4);
+        x.borrow_deref_mut().scale(3);
+        assert_eq!(x.try_borrow_deref().unwrap().area(), 36);
+
+        let x = RcOCell::from_value(vec![3u32, 1, 2]);
+        x.try_borrow_deref_mut().unwrap().sort();
+        let slice: RcORef<[u32], Vec<u32>> = x.borrow_deref();
+        assert_eq!(&*slice, &[1, 2, 3]);
+        assert_eq!(x.try_borrow_deref_mut().is_err(), true);
+        drop(slice);
+
+        x.clear();
+        match x.try_borrow_deref() {
+            Err(RcOCellError::NoValue) => {}
+            _ => panic!("unexpected"),
+        };
+    }
+
+    #[test]
+    fn test_compute_take() {
+        let x = RcOCell::from_value("job".to_string());
+        assert_eq!(x.compute_take(|_| RcOCellComputeResult::Take), Some("job".to_string()));
+        assert_eq!(x.is_none(), true);
+        assert_eq!(x.compute_take(|_| RcOCellComputeResult::Take), None);
+        assert_eq!(x.compute_take(|_| Replace("next".to_string())), None);
+        assert_eq!(x.get_and_clone(), "next");
+        assert_eq!(x.compute_take(|_| RcOCellComputeResult::DoNothing), None);
+        assert_eq!(x.get_and_clone(), "next");
+        assert_eq!(x.compute_take(|_| RcOCellComputeResult::Remove), None);
+        assert_eq!(x.is_none(), true);
+
+        x.set("job".to_string());
+        let brw = x.borrow();
+        assert_eq!(x.try_compute_take(|_| RcOCellComputeResult::Take).is_err(), true);
+        drop(brw);
+        assert_eq!(x.try_compute_take(|_| RcOCellComputeResult::Take).unwrap(), Some("job".to_string()));
+
+        x.set("job".to_string());
+        x.compute(|_| RcOCellComputeResult::Take);
+        assert_eq!(x.is_none(), true);
+    }
+
+    #[test]
+    fn test_compute_if_present_take() {
+        let x: RcOCell<u32> = RcOCell::new();
+        assert_eq!(x.compute_if_present_take(|_| RcOCellComputeResult::Take), RcOCellComputeOutcome::NotExecuted);
+        x.set(1);
+        let outcome = x.compute_if_present_take(|v| Replace(*v + 1));
+        assert_eq!(outcome, RcOCellComputeOutcome::Executed);
+        assert_eq!(outcome.was_executed(), true);
+        assert_eq!(x.get_and_clone(), 2);
+        assert_eq!(x.compute_if_present_take(|_| RcOCellComputeResult::DoNothing).taken(), None);
+        assert_eq!(x.try_compute_if_present_take(|_| RcOCellComputeResult::Take).unwrap(), RcOCellComputeOutcome::Taken(2));
+        assert_eq!(x.is_none(), true);
+
+        x.set(3);
+        let w = x.downgrade();
+        assert_eq!(w.compute_if_present_take(|_| RcOCellComputeResult::Take).taken(), Some(3));
+        drop(x);
+        assert_eq!(w.try_compute_take(|_| RcOCellComputeResult::Take).is_err(), true);
+    }
+
+    #[test]
+    fn test_compute_result_adapters() {
+        assert_eq!(RcOCellComputeResult::from_option(Some(1)), Replace(1));
+        assert_eq!(RcOCellComputeResult::<u32>::from_option(None), RcOCellComputeResult::Remove);
+        assert_eq!(RcOCellComputeResult::replace_if(true, 1), Replace(1));
+        assert_eq!(RcOCellComputeResult::replace_if(false, 1), RcOCellComputeResult::DoNothing);
+        assert_eq!(RcOCellComputeResult::<u32>::keep(), RcOCellComputeResult::DoNothing);
+        assert_eq!(Replace(2).map(|v: u32| v.to_string()), Replace("2".to_string()));
+        assert_eq!(RcOCellComputeResult::<u32>::Take.map(|v| v + 1), RcOCellComputeResult::Take);
+        assert_eq!(Replace(0u32).and_then(|v| RcOCellComputeResult::replace_if(v > 0, v)), RcOCellComputeResult::DoNothing);
+        assert_eq!(RcOCellComputeResult::<u32>::Remove.and_then(|v| Replace(v + 1)), RcOCellComputeResult::Remove);
+    }
+
+    #[test]
+    fn test_compute_result_traits() {
+        let r = Replace(vec![1u32]);
+        assert_eq!(r.clone(), r);
+        assert_ne!(r, RcOCellComputeResult::DoNothing);
+        assert_eq!(format!("{:?}", r), "Replace([1])");
+
+        let x = RcOCell::from_value(1u32);
+        x.compute(|v| RcOCellComputeResult::from_option(v.map(|v| *v + 1)));
+        assert_eq!(x.get_and_clone(), 2);
+    }
+
+    #[test]
+    fn test_compute_from_option() {
+        let x: RcOCell<u32> = RcOCell::new();
+        let r: RcOCellComputeResult<u32> = Some(1).into();
+        assert_eq!(r, Replace(1));
+        x.compute_from_option(|v| Some(v.map(|v| *v).unwrap_or(0) + 1));
+        assert_eq!(x.get_and_clone(), 1);
+        assert_eq!(x.compute_if_present_from_option(|v| Some(*v * 10)), true);
+        assert_eq!(x.get_and_clone(), 10);
+        assert_eq!(x.try_compute_if_present_from_option(|_| None).unwrap(), true);
+        assert_eq!(x.is_none(), true);
+        assert_eq!(x.compute_if_present_from_option(|v| Some(*v)), false);
+        x.try_compute_from_option(|_| Some(5)).unwrap();
+        let w = x.downgrade();
+        w.compute_from_option(|_| None);
+        assert_eq!(x.is_none(), true);
+    }
+
+    #[test]
+    fn test_compute_from_result() {
+        let x = RcOCell::from_value(1u32);
+        let r: Result<(), String> = x.compute_from_result(|v| Ok(v.map(|v| *v + 1)));
+        assert_eq!(r, Ok(()));
+        assert_eq!(x.get_and_clone(), 2);
+        let r: Result<(), String> = x.compute_from_result(|_| Err("failed".to_string()));
+        assert_eq!(r, Err("failed".to_string()));
+        assert_eq!(x.get_and_clone(), 2);
+        let r: Result<(), String> = x.compute_from_result(|_| Ok(None));
+        assert_eq!(r, Ok(()));
+        assert_eq!(x.is_none(), true);
+    }
+
+    #[test]
+    fn test_apply() {
+        let x = RcOCell::from_value(1u32);
+        let changes = RcOCell::from_value(0u32);
+        let changes2 = changes.clone();
+        x.on_set(move |_| { changes2.set(changes2.get_and_clone() + 1); });
+
+        assert_eq!(x.apply(RcOCellComputeResult::Replace(2)), Some(1));
+        assert_eq!(x.apply(RcOCellComputeResult::DoNothing), None);
+        assert_eq!(changes.get_and_clone(), 1);
+        assert_eq!(x.apply(RcOCellComputeResult::Take), Some(2));
+        assert_eq!(x.apply(RcOCellComputeResult::Remove), None);
+        assert_eq!(x.try_apply(RcOCellComputeResult::Replace(3)).ok().unwrap(), None);
+        assert_eq!(x.try_apply(RcOCellComputeResult::Remove).ok().unwrap(), Some(3));
+        assert_eq!(changes.get_and_clone(), 2);
+
+        let weak = x.downgrade();
+        assert_eq!(weak.apply(RcOCellComputeResult::replace_if(true, 4)), None);
+        assert_eq!(weak.try_apply(RcOCellComputeResult::keep()).ok().unwrap(), None);
+        assert_eq!(x.get_and_clone(), 4);
+    }
+
+    #[test]
+    fn test_try_apply_borrowed() {
+        let x = RcOCell::from_value(String::from("a"));
+        let weak = x.downgrade();
+        let brw = x.borrow();
+        match x.try_apply(RcOCellComputeResult::Replace(String::from("b"))) {
+            Err((RcOCellError::BorrowError(_), RcOCellComputeResult::Replace(value))) => assert_eq!(value, "b"),
+            _ => panic!("unexpected"),
+        };
+        match weak.try_apply(RcOCellComputeResult::Take) {
+            Err((RcOCellError::BorrowError(_), RcOCellComputeResult::Take)) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(x.try_apply(RcOCellComputeResult::DoNothing).ok().unwrap(), None);
+        assert_eq!(x.catch(|x| x.apply(RcOCellComputeResult::Remove)).is_err(), true);
+        drop(brw);
+        assert_eq!(x.get_and_clone(), "a");
+
+        drop(x);
+        match weak.try_apply(RcOCellComputeResult::Replace(String::from("c"))) {
+            Err((RcOCellError::Dropped, RcOCellComputeResult::Replace(value))) => assert_eq!(value, "c"),
+            _ => panic!("unexpected"),
+        };
+    }
+
+    #[test]
+    fn test_match_value() {
+        let x = RcOCell::from_value(2u32);
+        assert_eq!(x.match_value(|n| *n * 2, || 0), 4);
+        assert_eq!(x.match_value_mut(|n| { *n += 1; *n }, || 0), 3);
+        x.clear();
+        assert_eq!(x.match_value(|n| *n * 2, || 0), 0);
+        assert_eq!(x.try_match_value_mut(|n| *n, || 7).unwrap(), 7);
+        x.set(1);
+        let brw = x.borrow_mut();
+        assert_eq!(x.try_match_value(|_| "present", || "absent").is_err(), true);
+        drop(brw);
+        let brw = x.borrow();
+        assert_eq!(x.try_match_value(|_| "present", || "absent").unwrap(), "present");
+        assert_eq!(x.try_match_value_mut(|_| "present", || "absent").is_err(), true);
+        drop(brw);
+    }
+
+    #[test]
+    fn test_match_value_reentrant() {
+        let x = RcOCell::from_value(1u32);
+        let r = x.match_value(|_| x.try_clear().is_err(), || false);
+        assert_eq!(r, true);
+        assert_eq!(x.get_and_clone(), 1u32);
+        let r = x.match_value_mut(|_| x.try_clear().is_err(), || false);
+        assert_eq!(r, true);
+        assert_eq!(x.get_and_clone(), 1u32);
+    }
+
+    #[test]
+    fn test_match_value_weak() {
+        let x = RcOCell::from_value(1u32);
+        let w = x.downgrade();
+        assert_eq!(w.match_value(|n| *n, || 0), 1);
+        drop(x);
+        assert_eq!(w.match_value(|n| *n, || 0), 0);
+        assert_eq!(w.match_value_mut(|n| *n, || 0), 0);
+        match w.try_match_value(|n| *n, || 0) {
+            Err(RcOCellError::Dropped) => {}
+            _ => panic!("unexpected"),
+        }
+    }
+
+    #[test]
+    fn test_expect() {
+        let x: RcOCell<String> = RcOCell::new();
+        let msg = panic_message(|| { x.expect("config not initialized"); });
+        assert_eq!(msg, "config not initialized: No value present");
+        x.set("Baum".to_string());
+        assert_eq!(x.expect("config not initialized").as_str(), "Baum");
+        let brw = x.borrow();
+        let msg = panic_message(|| { x.expect_mut("config in use"); });
+        assert_eq!(msg.starts_with("config in use: "), true);
+        assert_eq!(msg.contains("borrow"), true);
+        drop(brw);
+        x.expect_mut("config in use").push_str("Nase");
+        assert_eq!(x.expect_clone("config missing").as_str(), "BaumNase");
+    }
+
+    #[test]
+    fn test_expect_weak() {
+        let x = RcOCell::from_value(1u32);
+        let w = x.downgrade();
+        assert_eq!(w.expect_clone("counter gone"), 1u32);
+        x.clear();
+        let msg = panic_message(|| { w.expect_clone("counter gone"); });
+        assert_eq!(msg, "counter gone: No value present");
+        drop(x);
+        let msg = panic_message(|| { w.expect_clone("counter gone"); });
+        assert_eq!(msg, "counter gone: Cell already dropped");
+        let msg = panic_message(|| { w.expect_upgrade("counter gone"); });
+        assert_eq!(msg, "counter gone: Cell already dropped");
+        let msg = panic_message(|| { w.expect("counter gone"); });
+        assert_eq!(msg, "counter gone: Cell already dropped");
+        let msg = panic_message(|| { w.expect_mut("counter gone"); });
+        assert_eq!(msg, "counter gone: Cell already dropped");
+    }
+
+    #[test]
+    fn test_expect_weak_guards() {
+        let x = RcOCell::from_value(vec![1u32]);
+        let w = x.downgrade();
+        w.expect_mut("list gone").push(2);
+        let brw = w.expect("list gone");
+        assert_eq!(*brw, vec![1, 2]);
+        let msg = panic_message(|| { w.expect_mut("list in use"); });
+        assert_eq!(msg.starts_with("list in use: "), true);
+        assert_eq!(msg.contains("borrow"), true);
+        drop(x);
+        assert_eq!(w.expect("list gone").len(), 2);
+        drop(brw);
+
+        let x: RcOCell<Vec<u32>> = RcOCell::new();
+        let w = x.downgrade();
+        let msg = panic_message(|| { w.expect("list missing"); });
+        assert_eq!(msg, "list missing: No value present");
+        let msg = panic_message(|| { w.expect_mut("list missing"); });
+        assert_eq!(msg, "list missing: No value present");
+    }
+
+    #[test]
+    fn test_take_replacing_default() {
+        let queue = RcOCell::from_value(vec![1u32, 2]);
+        let changes = RcOCell::from_value(0u32);
+        let changes2 = changes.clone();
+        queue.on_set(move |_| { changes2.set(changes2.get_and_clone() + 1); });
+
+        assert_eq!(queue.take_replacing_default(), vec![1, 2]);
+        assert_eq!(queue.is_some(), true);
+        assert_eq!(queue.get_and_clone(), Vec::<u32>::new());
+        queue.set(vec![3]);
+        assert_eq!(queue.try_take_replacing_default().unwrap(), vec![3]);
+        assert_eq!(queue.replace_default(), Some(Vec::new()));
+        assert_eq!(changes.get_and_clone(), 4);
+
+        queue.clear();
+        match queue.try_take_replacing_default() {
+            Err(RcOCellError::NoValue) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(queue.is_none(), true);
+        assert_eq!(queue.try_replace_default().unwrap(), None);
+        assert_eq!(queue.get_and_clone(), Vec::<u32>::new());
+
+        let weak = queue.downgrade();
+        queue.set(vec![4]);
+        assert_eq!(weak.take_replacing_default(), vec![4]);
+        assert_eq!(weak.replace_default(), Some(Vec::new()));
+        drop(queue);
+        assert_eq!(weak.try_replace_default().is_err(), true);
+    }
+
+    #[test]
+    fn test_take_replacing_default_borrowed() {
+        let queue = RcOCell::from_value(vec![1u32, 2]);
+        let brw = queue.borrow();
+        match queue.try_take_replacing_default() {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(queue.try_replace_default().is_err(), true);
+        assert_eq!(queue.catch(|q| q.take_replacing_default()).is_err(), true);
+        assert_eq!(queue.catch(|q| q.replace_default()).is_err(), true);
+        drop(brw);
+        assert_eq!(queue.get_and_clone(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_weak_swap() {
+        let a = RcOCell::from_value(1u32);
+        let b = RcOCell::from_value(2u32);
+        let (wa, wb) = (a.downgrade(), b.downgrade());
+        wa.swap(&wb);
+        assert_eq!((a.get_and_clone(), b.get_and_clone()), (2, 1));
+        wb.try_swap(&wa).unwrap();
+        assert_eq!((a.get_and_clone(), b.get_and_clone()), (1, 2));
+        assert_eq!((Rc::strong_count(&a.rc), Rc::strong_count(&b.rc)), (1, 1));
+
+        let c: RcOCell<u32> = RcOCell::new();
+        wa.swap_with_strong(&c);
+        assert_eq!((a.is_none(), c.get_and_clone()), (true, 1));
+        wa.swap_with_strong(&c);
+        c.set(3);
+        wa.try_swap_with_strong(&c).unwrap();
+        assert_eq!((a.get_and_clone(), c.get_and_clone()), (3, 1));
+    }
+
+    #[test]
+    fn test_weak_swap_dropped() {
+        let a = RcOCell::from_value(1u32);
+        let b = RcOCell::from_value(2u32);
+        let (wa, wb) = (a.downgrade(), b.downgrade());
+        drop(b);
+        match wa.try_swap(&wb) {
+            Err(RcOCellError::Dropped) => {}
+            _ => panic!("unexpected"),
+        };
+        match wb.try_swap(&wa) {
+            Err(RcOCellError::Dropped) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(wa.try_upgrade().is_ok(), true);
+        assert_eq!(a.get_and_clone(), 1);
+
+        let msg = |r: std::thread::Result<()>| r.unwrap_err().downcast::<String>().map(|s| *s).unwrap_or_default();
+        let on = panic::catch_unwind(AssertUnwindSafe(|| wb.swap(&wa)));
+        let with = panic::catch_unwind(AssertUnwindSafe(|| wa.swap(&wb)));
+        assert_eq!(msg(on).contains("called on a dropped cell"), true);
+        assert_eq!(msg(with).contains("called with a dropped cell"), true);
+        match wb.try_swap_with_strong(&a) {
+            Err(RcOCellError::Dropped) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(a.get_and_clone(), 1);
+    }
+
+    #[test]
+    fn test_weak_swap_borrowed() {
+        let a = RcOCell::from_value(1u32);
+        let b = RcOCell::from_value(2u32);
+        let (wa, wb) = (a.downgrade(), b.downgrade());
+        let brw = b.borrow();
+        match wa.try_swap(&wb) {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        match wa.try_swap_with_strong(&b) {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(panic::catch_unwind(AssertUnwindSafe(|| wa.swap(&wb))).is_err(), true);
+        drop(brw);
+        assert_eq!((a.get_and_clone(), b.get_and_clone()), (1, 2));
+        assert_eq!((Rc::strong_count(&a.rc), Rc::strong_count(&b.rc)), (1, 1));
+    }
+
+    #[test]
+    fn test_conv_weak_raw() {
+        let cell = RcOCell::from_value(1u32);
+        let raw: Weak<RefCell<Option<u32>>> = cell.downgrade().into();
+        let weak = WeakRcOCell::from(raw);
+        assert_eq!(weak.as_weak_raw().upgrade().is_some(), true);
+        assert_eq!(weak.upgrade().get_and_clone(), 1);
+
+        let ptr = weak.clone().into_raw();
+        let restored = unsafe { WeakRcOCell::from_raw(ptr) };
+        assert_eq!(restored.into_weak_raw().ptr_eq(weak.as_weak_raw()), true);
+
+        drop(cell);
+        match weak.try_upgrade() {
+            Err(RcOCellError::Dropped) => {}
+            _ => panic!("unexpected"),
+        };
+    }
+
+    #[test]
+    fn test_conv_weak_raw_dangling() {
+        let weak: WeakRcOCell<u32> = Weak::new().into();
+        match weak.try_get_and_clone() {
+            Err(RcOCellError::Dropped) => {}
+            _ => panic!("unexpected"),
+        };
+
+        let weak = unsafe { WeakRcOCell::<u32>::from_raw(weak.into_raw()) };
+        match weak.try_upgrade() {
+            Err(RcOCellError::Dropped) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(weak.into_weak_raw().upgrade().is_none(), true);
+    }
+
+    #[test]
+    #[cfg(not(feature = "debug-thread-check"))]
+    fn test_thread_unchecked() {
+        let cell = RcOCell::from_value(1u32);
+        //The diagnostics feature registers every cell, without it a new cell has no shared state.
+        #[cfg(not(feature = "diagnostics"))]
+        assert_eq!(shared::may_have_state(&cell.rc), false);
+        assert_eq!(cell.try_set(2).unwrap(), Some(1));
+        assert_eq!(cell.try_get_and_clone().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_get_or_try_insert_with() {
+        let cell: RcOCell<String> = RcOCell::new();
+        let calls = std::cell::Cell::new(0);
+        let load = |result: Result<&str, &'static str>| {
+            calls.set(calls.get() + 1);
+            result.map(str::to_string)
+        };
+
+        match cell.get_or_try_insert_with(|| load(Err("io"))) {
+            Err(RcOCellComputeError::Compute("io")) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(cell.is_none(), true);
+        assert_eq!(calls.get(), 1);
+
+        assert_eq!(&*cell.get_or_try_insert_with(|| load(Ok("a"))).unwrap(), "a");
+        assert_eq!(calls.get(), 2);
+        assert_eq!(cell.get_or_try_insert_with_cloned(|| load(Ok("b"))).unwrap(), "a");
+        assert_eq!(cell.get_or_try_insert_with_cloned(|| load(Err("io"))).unwrap(), "a");
+        assert_eq!(calls.get(), 2);
+
+        let brw = cell.borrow();
+        match cell.get_or_try_insert_with(|| load(Ok("b"))) {
+            Err(RcOCellComputeError::Cell(RcOCellError::BorrowError(_))) => {}
+            _ => panic!("unexpected"),
+        };
+        drop(brw);
+        cell.clear();
+        let brw = cell.raw_borrow();
+        match cell.get_or_try_insert_with_cloned(|| load(Ok("b"))) {
+            Err(RcOCellComputeError::Cell(RcOCellError::BorrowError(_))) => {}
+            _ => panic!("unexpected"),
+        };
+        drop(brw);
+        assert_eq!(calls.get(), 2);
+
+        let weak = cell.downgrade();
+        assert_eq!(&*weak.get_or_try_insert_with(|| load(Ok("w"))).unwrap(), "w");
+        assert_eq!(weak.get_or_try_insert_with_cloned(|| load(Ok("x"))).unwrap(), "w");
+        assert_eq!(calls.get(), 3);
+        drop(cell);
+        match weak.get_or_try_insert_with(|| load(Ok("x"))) {
+            Err(RcOCellComputeError::Cell(RcOCellError::Dropped)) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[test]
+    #[cfg(not(feature = "debug-borrow-tracking"))]
+    fn test_conflicting_location_untracked() {
+        let cell = RcOCell::from_value(1u32);
+        let brw = cell.borrow();
+        match cell.try_borrow_mut() {
+            Err(RcOCellError::BorrowError(e)) => {
+                assert_eq!(e.conflicting_location().is_none(), true);
+                assert_eq!(format!("{}", e), "RefCell already borrowed");
+            }
+            _ => panic!("unexpected"),
+        };
+        drop(brw);
+    }
+
+    #[test]
+    fn test_get_or_insert_default() {
+        let cell: RcOCell<Vec<u32>> = RcOCell::new();
+        cell.get_or_insert_default().push(1);
+        cell.get_or_insert_default().push(2);
+        assert_eq!(cell.get_and_clone(), vec![1, 2]);
+        assert_eq!(cell.try_get_or_insert_default().unwrap().len(), 2);
+
+        let brw = cell.borrow();
+        match cell.try_get_or_insert_default() {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(cell.borrow_or_default().len(), 2);
+        drop(brw);
+
+        let cell: RcOCell<String> = RcOCell::new();
+        assert_eq!(cell.borrow_or_default().as_str(), "");
+        assert_eq!(cell.is_some(), true);
+        cell.set("abc".to_string());
+        assert_eq!(cell.try_borrow_or_default().unwrap().as_str(), "abc");
+
+        cell.clear();
+        let brw = cell.raw_borrow();
+        match cell.try_borrow_or_default() {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        drop(brw);
+
+        cell.set("abc".to_string());
+        let brw = cell.borrow_mut();
+        assert_eq!(cell.try_borrow_or_default().is_err(), true);
+        drop(brw);
+        assert_eq!(cell.get_and_clone(), "abc");
+    }
+
+    #[test]
+    fn test_pair() {
+        let (cell, weak): (RcOCell<u32>, WeakRcOCell<u32>) = RcOCell::pair();
+        assert_eq!(cell.id() == weak.id(), true);
+        assert_eq!(weak.is_none(), true);
+        cell.set(1);
+        assert_eq!(weak.get_and_clone(), 1);
+        drop(cell);
+        assert_eq!(weak.try_upgrade().is_err(), true);
+
+        let (cell, weak) = RcOCell::pair_with("a".to_string());
+        assert_eq!(weak.upgrade().id() == cell.id(), true);
+        assert_eq!(weak.get_and_clone(), "a");
+
+        let pairs: Vec<(RcOCell<u32>, WeakRcOCell<u32>)> = RcOCell::pairs(3);
+        assert_eq!(pairs.len(), 3);
+        for (cell, weak) in pairs.iter() {
+            assert_eq!(cell.id() == weak.id(), true);
+            assert_eq!(pairs.iter().filter(|(other, _)| other.id() == weak.id()).count(), 1);
+        }
+        assert_eq!(RcOCell::<u32>::pairs(0).is_empty(), true);
+    }
 }
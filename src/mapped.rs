@@ -0,0 +1,263 @@
+use std::fmt::{Debug, Formatter};
+use std::rc::Rc;
+use crate::{RcOCell, RcOCellError, RcORef, RcORefMut, WeakRcOCell};
+
+type Get<T, U> = Rc<dyn Fn(&T) -> &U>;
+type GetMut<T, U> = Rc<dyn Fn(&mut T) -> &mut U>;
+
+///
+/// Handle to a part of the value of a cell, see RcOCell::project.
+///
+/// Behaves like a cell holding the part, it is empty while the cell is empty.
+/// All borrows borrow the whole value of the cell, so the usual borrow rules apply to the cell and not only to the part.
+///
+pub struct MappedRcOCell<T, U: ?Sized> {
+    cell: RcOCell<T>,
+    get: Get<T, U>,
+    get_mut: GetMut<T, U>,
+}
+
+///
+/// Weak handle to a part of the value of a cell, see MappedRcOCell::downgrade.
+///
+pub struct WeakMappedRcOCell<T, U: ?Sized> {
+    cell: WeakRcOCell<T>,
+    get: Get<T, U>,
+    get_mut: GetMut<T, U>,
+}
+
+impl <T: 'static> RcOCell<T> {
+    ///
+    /// Returns a handle to the part of the value the two fns point to.
+    /// Both fns have to point to the same part of the value.
+    ///
+    pub fn project<U: ?Sized + 'static>(&self, get: fn(&T) -> &U, get_mut: fn(&mut T) -> &mut U) -> MappedRcOCell<T, U> {
+        return MappedRcOCell {cell: self.clone(), get: Rc::new(get), get_mut: Rc::new(get_mut)};
+    }
+
+    ///
+    /// Returns a handle to the part of the value the two Fns point to, unlike project the Fns may capture state.
+    /// Both Fns have to point to the same part of the value.
+    ///
+    pub fn project_with<U: ?Sized + 'static, G, M>(&self, get: G, get_mut: M) -> MappedRcOCell<T, U>
+        where G: Fn(&T) -> &U + 'static, M: Fn(&mut T) -> &mut U + 'static
+    {
+        return MappedRcOCell {cell: self.clone(), get: Rc::new(get), get_mut: Rc::new(get_mut)};
+    }
+}
+
+impl <T: 'static, U: ?Sized + 'static> MappedRcOCell<T, U> {
+    ///
+    /// Returns a handle to a part of this part, see RcOCell::project.
+    ///
+    pub fn project<V: ?Sized + 'static>(&self, get: fn(&U) -> &V, get_mut: fn(&mut U) -> &mut V) -> MappedRcOCell<T, V> {
+        return self.project_with(get, get_mut);
+    }
+
+    ///
+    /// Returns a handle to a part of this part, see RcOCell::project_with.
+    ///
+    pub fn project_with<V: ?Sized + 'static, G, M>(&self, get: G, get_mut: M) -> MappedRcOCell<T, V>
+        where G: Fn(&U) -> &V + 'static, M: Fn(&mut U) -> &mut V + 'static
+    {
+        let outer = self.get.clone();
+        let outer_mut = self.get_mut.clone();
+        return MappedRcOCell {
+            cell: self.cell.clone(),
+            get: Rc::new(move |t| get(outer(t))),
+            get_mut: Rc::new(move |t| get_mut(outer_mut(t))),
+        };
+    }
+}
+
+impl <T, U: ?Sized> MappedRcOCell<T, U> {
+    ///
+    /// Returns the cell the part belongs to.
+    ///
+    pub fn cell(&self) -> &RcOCell<T> {
+        return &self.cell;
+    }
+
+    ///
+    /// Borrows the part.
+    /// Panics if the value is already borrowed mutably somewhere or there is no value.
+    ///
+    #[track_caller]
+    pub fn borrow(&self) -> RcORef<'_, U, T> {
+        return RcORef::map(self.cell.borrow(), |v| (self.get)(v));
+    }
+
+    ///
+    /// Borrows the part.
+    /// Fails if the value is already borrowed mutably somewhere or there is no value.
+    ///
+    pub fn try_borrow(&self) -> Result<RcORef<'_, U, T>, RcOCellError> {
+        return Ok(RcORef::map(self.cell.try_borrow()?, |v| (self.get)(v)));
+    }
+
+    ///
+    /// Borrows the part mutably.
+    /// Panics if the value is already borrowed somewhere or there is no value.
+    ///
+    #[track_caller]
+    pub fn borrow_mut(&self) -> RcORefMut<'_, U, T> {
+        return RcORefMut::map(self.cell.borrow_mut(), |v| (self.get_mut)(v));
+    }
+
+    ///
+    /// Borrows the part mutably.
+    /// Fails if the value is already borrowed somewhere or there is no value.
+    ///
+    pub fn try_borrow_mut(&self) -> Result<RcORefMut<'_, U, T>, RcOCellError> {
+        return Ok(RcORefMut::map(self.cell.try_borrow_mut()?, |v| (self.get_mut)(v)));
+    }
+
+    ///
+    /// Calls the Fn with the part (if present) and returns the result as an option.
+    /// Panics if the value is already borrowed mutably somewhere.
+    /// Returns None if there is no value.
+    ///
+    pub fn map<F, X>(&self, f: F) -> Option<X>
+        where F: FnOnce(&U) -> X
+    {
+        return self.cell.map(|v| f((self.get)(v)));
+    }
+
+    ///
+    /// Returns true if the cell holds a value.
+    /// Never panics.
+    ///
+    pub fn is_some(&self) -> bool {
+        return self.cell.is_some();
+    }
+
+    ///
+    /// Returns true if the cell is empty.
+    /// Never panics.
+    ///
+    pub fn is_none(&self) -> bool {
+        return self.cell.is_none();
+    }
+
+    ///
+    /// Returns a weak handle to the same part.
+    ///
+    pub fn downgrade(&self) -> WeakMappedRcOCell<T, U> {
+        return WeakMappedRcOCell {cell: self.cell.downgrade(), get: self.get.clone(), get_mut: self.get_mut.clone()};
+    }
+}
+
+impl <T, U> MappedRcOCell<T, U> {
+    ///
+    /// Replaces the part and returns the previous part, observers of the cell are notified.
+    /// Fails if the value is borrowed somewhere or there is no value, a part cannot exist without the value.
+    ///
+    pub fn set(&self, value: U) -> Result<U, RcOCellError> {
+        let previous = std::mem::replace(&mut *self.try_borrow_mut()?, value);
+        self.cell.changed();
+        return Ok(previous);
+    }
+}
+
+impl <T, U: ?Sized> WeakMappedRcOCell<T, U> {
+    ///
+    /// Returns a handle to the part if the cell is still alive.
+    ///
+    pub fn try_upgrade(&self) -> Result<MappedRcOCell<T, U>, RcOCellError> {
+        return Ok(MappedRcOCell {cell: self.cell.try_upgrade()?, get: self.get.clone(), get_mut: self.get_mut.clone()});
+    }
+}
+
+impl <T, U: ?Sized> Clone for MappedRcOCell<T, U> {
+    fn clone(&self) -> Self {
+        return MappedRcOCell {cell: self.cell.clone(), get: self.get.clone(), get_mut: self.get_mut.clone()};
+    }
+}
+
+impl <T, U: ?Sized> Clone for WeakMappedRcOCell<T, U> {
+    fn clone(&self) -> Self {
+        return WeakMappedRcOCell {cell: self.cell.clone(), get: self.get.clone(), get_mut: self.get_mut.clone()};
+    }
+}
+
+impl <T, U: ?Sized + Debug> Debug for MappedRcOCell<T, U> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self.cell.rc.try_borrow() {
+            Ok(value) => f.debug_struct("MappedRcOCell").field("value", &value.as_ref().map(|v| (self.get)(v))).finish(),
+            Err(_) => f.debug_struct("MappedRcOCell").field("value", &format_args!("<borrowed>")).finish(),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Settings {
+        name: String,
+        volume: (u8, u8),
+    }
+
+    fn settings() -> RcOCell<Settings> {
+        return RcOCell::from_value(Settings {name: "a".to_string(), volume: (1, 2)});
+    }
+
+    #[test]
+    fn test_project() {
+        let cell = settings();
+        let name = cell.project(|s| &s.name, |s| &mut s.name);
+        name.borrow_mut().push('b');
+        assert_eq!(cell.borrow().name, "ab");
+        assert_eq!(name.set("c".to_string()).unwrap(), "ab");
+        assert_eq!(cell.borrow().name, "c");
+        assert_eq!(&*name.borrow(), "c");
+        assert_eq!(name.map(|n| n.len()), Some(1));
+
+        let brw = name.borrow();
+        assert_eq!(cell.try_borrow_mut().is_err(), true);
+        assert_eq!(name.try_borrow_mut().is_err(), true);
+        drop(brw);
+
+        let cloned = name.clone();
+        cell.clear();
+        assert_eq!(cloned.is_some(), false);
+        assert_eq!(cloned.is_none(), true);
+        assert_eq!(cloned.map(|n| n.len()), None);
+        match name.set("d".to_string()) {
+            Err(RcOCellError::NoValue) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(name.try_borrow().is_err(), true);
+        assert_eq!(format!("{:?}", name), "MappedRcOCell { value: None }");
+    }
+
+    #[test]
+    fn test_project_with() {
+        let cell = RcOCell::from_value(vec![1u32, 2, 3]);
+        let index = 2;
+        let last = cell.project_with(move |v| &v[index], move |v| &mut v[index]);
+        *last.borrow_mut() = 4;
+        assert_eq!(cell.get_and_clone(), vec![1, 2, 4]);
+
+        let settings = settings();
+        let volume = settings.project(|s| &s.volume, |s| &mut s.volume);
+        let left = volume.project(|v| &v.0, |v| &mut v.0);
+        left.set(5).unwrap();
+        assert_eq!(settings.borrow().volume, (5, 2));
+        assert_eq!(format!("{:?}", left), "MappedRcOCell { value: Some(5) }");
+    }
+
+    #[test]
+    fn test_weak_projection() {
+        let cell = settings();
+        let name = cell.project(|s| &s.name, |s| &mut s.name).downgrade();
+        name.try_upgrade().unwrap().set("b".to_string()).unwrap();
+        assert_eq!(cell.borrow().name, "b");
+        drop(cell);
+        match name.try_upgrade() {
+            Err(RcOCellError::Dropped) => {}
+            _ => panic!("unexpected"),
+        };
+    }
+}
@@ -0,0 +1,277 @@
+use crate::{RcOCell, RcOCellError};
+#[cfg(feature = "tracing")]
+use crate::trace;
+
+///
+/// What merge_with and its variants did, depending on which of the two cells held a value.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeOutcome {
+    ///
+    /// Both cells held a value, the value of the other cell was merged into the value of this cell.
+    ///
+    Merged,
+    ///
+    /// Only the other cell held a value, it was converted by the init Fn and stored in this cell.
+    ///
+    Moved,
+    ///
+    /// Only the other cell held a value and there is no init Fn, nothing was changed.
+    ///
+    SelfEmpty,
+    ///
+    /// Only this cell held a value, nothing was changed.
+    ///
+    OtherEmpty,
+    ///
+    /// Neither cell held a value, nothing was changed.
+    ///
+    BothEmpty,
+}
+
+impl <T> RcOCell<T> {
+    ///
+    /// Takes the value of the other cell, leaving it empty, and merges it into the value of this cell with the Fn.
+    /// Nothing is changed if either cell is empty, see MergeOutcome.
+    /// Both cells are checked before the other cell is drained, observers of both cells are notified after the Fn ran.
+    /// If the Fn panics the value of the other cell is lost.
+    ///
+    /// Panics if either value is borrowed somewhere or both are the same cell.
+    ///
+    #[track_caller]
+    pub fn merge_with<U, F>(&self, other: &RcOCell<U>, f: F) -> MergeOutcome
+        where F: FnOnce(&mut T, U)
+    {
+        return match self.merge(other, None::<fn(U) -> T>, f) {
+            Ok(outcome) => outcome,
+            Err(e) => self.fail(e),
+        };
+    }
+
+    ///
+    /// Takes the value of the other cell and merges it into the value of this cell, see merge_with.
+    /// Fails without draining the other cell if either value is borrowed somewhere,
+    /// fails with RcOCellError::DuplicateCell if both are the same cell.
+    ///
+    pub fn try_merge_with<U, F>(&self, other: &RcOCell<U>, f: F) -> Result<MergeOutcome, RcOCellError>
+        where F: FnOnce(&mut T, U)
+    {
+        return self.merge(other, None::<fn(U) -> T>, f);
+    }
+
+    ///
+    /// Takes the value of the other cell and merges it into the value of this cell, see merge_with.
+    /// If this cell is empty the value of the other cell is converted with the init Fn and stored instead.
+    ///
+    /// Panics if either value is borrowed somewhere or both are the same cell.
+    ///
+    #[track_caller]
+    pub fn merge_with_init<U, I, F>(&self, other: &RcOCell<U>, init: I, f: F) -> MergeOutcome
+        where I: FnOnce(U) -> T, F: FnOnce(&mut T, U)
+    {
+        return match self.merge(other, Some(init), f) {
+            Ok(outcome) => outcome,
+            Err(e) => self.fail(e),
+        };
+    }
+
+    ///
+    /// Takes the value of the other cell and merges it into the value of this cell, see merge_with_init.
+    /// Fails without draining the other cell if either value is borrowed somewhere,
+    /// fails with RcOCellError::DuplicateCell if both are the same cell.
+    ///
+    pub fn try_merge_with_init<U, I, F>(&self, other: &RcOCell<U>, init: I, f: F) -> Result<MergeOutcome, RcOCellError>
+        where I: FnOnce(U) -> T, F: FnOnce(&mut T, U)
+    {
+        return self.merge(other, Some(init), f);
+    }
+
+    ///
+    /// Merges a clone of the value of the other cell into the value of this cell with the Fn, the other cell keeps its value.
+    /// Nothing is changed if either cell is empty, see MergeOutcome.
+    ///
+    /// Panics if this value is borrowed somewhere, the other value is borrowed mutably somewhere or both are the same cell.
+    ///
+    #[track_caller]
+    pub fn merge_cloned<U, F>(&self, other: &RcOCell<U>, f: F) -> MergeOutcome
+        where F: FnOnce(&mut T, U), U: Clone
+    {
+        return match self.try_merge_cloned(other, f) {
+            Ok(outcome) => outcome,
+            Err(e) => self.fail(e),
+        };
+    }
+
+    ///
+    /// Merges a clone of the value of the other cell into the value of this cell, see merge_cloned.
+    /// Fails if this value is borrowed somewhere, the other value is borrowed mutably somewhere or both are the same cell.
+    ///
+    pub fn try_merge_cloned<U, F>(&self, other: &RcOCell<U>, f: F) -> Result<MergeOutcome, RcOCellError>
+        where F: FnOnce(&mut T, U), U: Clone
+    {
+        if self.id() == other.id() {
+            return Err(RcOCellError::DuplicateCell);
+        }
+
+        self.check_mut()?;
+        let Some(value) = other.try_raw_borrow()?.clone() else {
+            return Ok(if self.is_some() { MergeOutcome::OtherEmpty } else { MergeOutcome::BothEmpty });
+        };
+
+        let mut target = self.try_raw_borrow_mut()?;
+        let Some(target_value) = target.as_mut() else {
+            return Ok(MergeOutcome::SelfEmpty);
+        };
+
+        f(target_value, value);
+        drop(target);
+        #[cfg(feature = "tracing")]
+        trace::changed(self, "merge");
+        self.changed();
+        return Ok(MergeOutcome::Merged);
+    }
+
+    fn merge<U, I, F>(&self, other: &RcOCell<U>, init: Option<I>, f: F) -> Result<MergeOutcome, RcOCellError>
+        where I: FnOnce(U) -> T, F: FnOnce(&mut T, U)
+    {
+        if self.id() == other.id() {
+            return Err(RcOCellError::DuplicateCell);
+        }
+
+        self.check_mut()?;
+        other.check_mut()?;
+        let self_present = self.is_some();
+        let other_present = other.is_some();
+        if !other_present {
+            return Ok(if self_present { MergeOutcome::OtherEmpty } else { MergeOutcome::BothEmpty });
+        }
+
+        let outcome = match (self_present, init) {
+            (true, _) => {
+                let value = other.raw_replace(None).unwrap();
+                f(self.raw_borrow_mut().as_mut().unwrap(), value);
+                MergeOutcome::Merged
+            }
+            (false, Some(init)) => {
+                let value = other.raw_replace(None).unwrap();
+                self.raw_replace(Some(init(value)));
+                MergeOutcome::Moved
+            }
+            (false, None) => return Ok(MergeOutcome::SelfEmpty),
+        };
+
+        #[cfg(feature = "tracing")]
+        {
+            trace::changed(self, "merge");
+            trace::changed(other, "merge");
+        }
+        self.changed();
+        other.changed();
+        return Ok(outcome);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn add(total: &mut u64, delta: u32) {
+        *total += delta as u64;
+    }
+
+    #[test]
+    fn test_merge_presence() {
+        let totals = RcOCell::from_value(10u64);
+        let delta = RcOCell::from_value(5u32);
+        assert_eq!(totals.merge_with(&delta, add), MergeOutcome::Merged);
+        assert_eq!(totals.get_and_clone(), 15);
+        assert_eq!(delta.is_none(), true);
+
+        assert_eq!(totals.merge_with(&delta, add), MergeOutcome::OtherEmpty);
+        assert_eq!(totals.get_and_clone(), 15);
+
+        totals.clear();
+        assert_eq!(totals.try_merge_with(&delta, add).unwrap(), MergeOutcome::BothEmpty);
+
+        delta.set(7);
+        assert_eq!(totals.merge_with(&delta, add), MergeOutcome::SelfEmpty);
+        assert_eq!(delta.get_and_clone(), 7);
+        assert_eq!(totals.is_none(), true);
+
+        assert_eq!(totals.merge_with_init(&delta, |d| d as u64 * 2, add), MergeOutcome::Moved);
+        assert_eq!(totals.get_and_clone(), 14);
+        assert_eq!(delta.is_none(), true);
+
+        delta.set(1);
+        assert_eq!(totals.try_merge_with_init(&delta, |d| d as u64, add).unwrap(), MergeOutcome::Merged);
+        assert_eq!(totals.get_and_clone(), 15);
+    }
+
+    #[test]
+    fn test_merge_notifies() {
+        let totals = RcOCell::from_value(0u64);
+        let delta = RcOCell::from_value(3u32);
+        let seen = RcOCell::from_value(Vec::new());
+        let seen2 = seen.clone();
+        totals.on_set(move |v| { seen2.borrow_mut().push(format!("total {}", v)); });
+        let seen3 = seen.clone();
+        delta.on_clear(move || { seen3.borrow_mut().push("delta cleared".to_string()); });
+        totals.merge_with(&delta, add);
+        assert_eq!(seen.get_and_clone(), vec!["total 3".to_string(), "delta cleared".to_string()]);
+    }
+
+    #[test]
+    fn test_merge_borrowed_does_not_drain() {
+        let totals = RcOCell::from_value(0u64);
+        let delta = RcOCell::from_value(3u32);
+        let brw = totals.borrow();
+        match totals.try_merge_with(&delta, add) {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(totals.try_merge_with_init(&delta, |d| d as u64, add).is_err(), true);
+        assert_eq!(totals.catch(|t| t.merge_with(&delta, add)).is_err(), true);
+        drop(brw);
+        assert_eq!(delta.get_and_clone(), 3);
+
+        let brw = delta.borrow();
+        match totals.try_merge_with(&delta, add) {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        drop(brw);
+        assert_eq!(delta.get_and_clone(), 3);
+        assert_eq!(totals.get_and_clone(), 0);
+
+        let alias = totals.clone();
+        match totals.try_merge_with(&alias, |a, b| *a += b) {
+            Err(RcOCellError::DuplicateCell) => {}
+            _ => panic!("unexpected"),
+        };
+        match totals.try_merge_cloned(&alias, |a, b| *a += b) {
+            Err(RcOCellError::DuplicateCell) => {}
+            _ => panic!("unexpected"),
+        };
+    }
+
+    #[test]
+    fn test_merge_cloned() {
+        let totals = RcOCell::from_value(1u64);
+        let delta = RcOCell::from_value(2u32);
+        assert_eq!(totals.merge_cloned(&delta, add), MergeOutcome::Merged);
+        assert_eq!(totals.merge_cloned(&delta, add), MergeOutcome::Merged);
+        assert_eq!(totals.get_and_clone(), 5);
+        assert_eq!(delta.get_and_clone(), 2);
+
+        let brw = delta.borrow();
+        assert_eq!(totals.try_merge_cloned(&delta, add).unwrap(), MergeOutcome::Merged);
+        drop(brw);
+
+        totals.clear();
+        assert_eq!(totals.merge_cloned(&delta, add), MergeOutcome::SelfEmpty);
+        delta.clear();
+        assert_eq!(totals.merge_cloned(&delta, add), MergeOutcome::BothEmpty);
+        totals.set(1);
+        assert_eq!(totals.try_merge_cloned(&delta, add).unwrap(), MergeOutcome::OtherEmpty);
+    }
+}
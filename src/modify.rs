@@ -0,0 +1,626 @@
+use crate::{RcOCell, RcOCellError, RcORefMut, WeakRcOCell};
+#[cfg(feature = "tracing")]
+use crate::trace;
+
+///
+/// What the Fn of compute_owned wants to be stored in the cell.
+///
+/// Unlike RcOCellComputeResult there is no variant to keep the value, the Fn owns it
+/// and has to hand it back with Store to keep it.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OwnedComputeResult<T> {
+    ///
+    /// Store the value in the cell.
+    ///
+    Store(T),
+    ///
+    /// Leave the cell empty.
+    ///
+    Empty,
+}
+
+///
+/// Some becomes Store and None becomes Empty.
+///
+impl <T> From<Option<T>> for OwnedComputeResult<T> {
+    fn from(value: Option<T>) -> Self {
+        return match value {
+            Some(value) => OwnedComputeResult::Store(value),
+            None => OwnedComputeResult::Empty,
+        };
+    }
+}
+
+impl <T> RcOCell<T> {
+    ///
+    /// Takes the value out of the cell, calls the Fn with it and stores the result in the cell.
+    /// This allows updates of values that can only be changed by consuming them.
+    /// The cell stays borrowed mutably while the Fn runs, so the Fn cannot access the cell and nobody observes it empty.
+    ///
+    /// If the Fn panics the value is lost since the Fn owns it, the cell is left empty.
+    /// With the `poison` feature the cell is also poisoned.
+    ///
+    /// Panics if there is no value or the value is borrowed somewhere.
+    ///
+    #[track_caller]
+    pub fn modify<F>(&self, f: F)
+        where F: FnOnce(T) -> T
+    {
+        let mut value = self.raw_borrow_mut();
+        let Some(old) = value.take() else {
+            drop(value);
+            self.fail("RcOCell::modify on a cell without value");
+        };
+
+        *value = Some(f(old));
+        drop(value);
+        self.modified();
+    }
+
+    ///
+    /// Takes the value out of the cell, calls the Fn with it and stores the result in the cell, see modify.
+    /// Fails if there is no value or the value is borrowed somewhere.
+    ///
+    pub fn try_modify<F>(&self, f: F) -> Result<(), RcOCellError>
+        where F: FnOnce(T) -> T
+    {
+        let mut value = self.try_raw_borrow_mut()?;
+        let old = value.take().ok_or(RcOCellError::NoValue)?;
+        *value = Some(f(old));
+        drop(value);
+        self.modified();
+        return Ok(());
+    }
+
+    ///
+    /// Takes the value out of the cell, calls the Fn with it and stores the result in the cell, see modify.
+    /// If the cell is empty the Fn is called with the value returned by the default Fn instead, so the Fn always runs.
+    /// Unlike upsert the Fn gets the value by value, use upsert to change it in place
+    /// and to store the value of the default Fn without passing it through the Fn.
+    ///
+    /// Panics if the value is borrowed somewhere.
+    ///
+    #[track_caller]
+    pub fn modify_or_insert<F, D>(&self, f: F, default: D)
+        where F: FnOnce(T) -> T, D: FnOnce() -> T
+    {
+        let mut value = self.raw_borrow_mut();
+        let old = value.take().unwrap_or_else(default);
+        *value = Some(f(old));
+        drop(value);
+        self.modified();
+    }
+
+    ///
+    /// Takes the value out of the cell and returns what the Fn makes of it, the cell is empty afterwards.
+    /// The cell is cleared before the Fn is called, so it stays empty if the Fn panics.
+    ///
+    /// Panics if there is no value or the value is borrowed somewhere, the cell is not modified then.
+    ///
+    #[track_caller]
+    pub fn take_map<U, F>(&self, f: F) -> U
+        where F: FnOnce(T) -> U
+    {
+        if self.is_none() {
+            self.fail("RcOCell::take_map on a cell without value");
+        }
+
+        return f(self.get_and_clear());
+    }
+
+    ///
+    /// Takes the value out of the cell and returns what the Fn makes of it, see take_map.
+    /// Fails if there is no value or the value is borrowed somewhere, the cell is not modified then.
+    ///
+    pub fn try_take_map<U, F>(&self, f: F) -> Result<U, RcOCellError>
+        where F: FnOnce(T) -> U
+    {
+        return Ok(f(self.try_get_and_clear()?));
+    }
+
+    ///
+    /// Takes the value out of the cell and returns what the Fn makes of it, see take_map.
+    /// Returns the default without calling the Fn if the cell is empty.
+    ///
+    /// Panics if the value is borrowed somewhere.
+    ///
+    #[track_caller]
+    pub fn take_map_or<U, F>(&self, default: U, f: F) -> U
+        where F: FnOnce(T) -> U
+    {
+        return match self.clear() {
+            Some(value) => f(value),
+            None => default,
+        };
+    }
+
+    ///
+    /// Takes the value out of the cell (if present), passes it to the Fn by value and stores what the Fn returns.
+    /// Unlike compute the Fn owns the old value, so it can consume it to build the new one.
+    /// The cell stays borrowed mutably while the Fn runs, so the Fn cannot access the cell and nobody observes it empty.
+    /// Observers are notified unless the cell was empty and stays empty.
+    ///
+    /// If the Fn panics the value is lost since the Fn owns it, the cell is left empty.
+    /// With the `poison` feature the cell is also poisoned.
+    ///
+    /// Panics if the value is borrowed somewhere.
+    ///
+    #[track_caller]
+    pub fn compute_owned<F>(&self, f: F)
+        where F: FnOnce(Option<T>) -> OwnedComputeResult<T>
+    {
+        self.compute_owned_in(self.raw_borrow_mut(), f);
+    }
+
+    ///
+    /// Takes the value out of the cell and stores what the Fn makes of it, see compute_owned.
+    /// Fails without calling the Fn if the value is borrowed somewhere.
+    ///
+    pub fn try_compute_owned<F>(&self, f: F) -> Result<(), RcOCellError>
+        where F: FnOnce(Option<T>) -> OwnedComputeResult<T>
+    {
+        self.compute_owned_in(self.try_raw_borrow_mut()?, f);
+        return Ok(());
+    }
+
+    fn compute_owned_in<F>(&self, mut value: RcORefMut<'_, Option<T>, T>, f: F)
+        where F: FnOnce(Option<T>) -> OwnedComputeResult<T>
+    {
+        let old = value.take();
+        let was_some = old.is_some();
+        if let OwnedComputeResult::Store(new) = f(old) {
+            *value = Some(new);
+        }
+
+        let changed = was_some || value.is_some();
+        drop(value);
+        if changed {
+            self.modified();
+        }
+    }
+
+    ///
+    /// Calls the modify Fn with the value if there is one, otherwise stores the value of the insert Fn,
+    /// like `entry().and_modify().or_insert_with()` of HashMap.
+    /// Exactly one Fn runs, under the same mutable borrow that checked the cell, so neither can access the cell.
+    /// The modify Fn changes the value in place, use modify_or_insert for values that can only be changed by consuming them.
+    ///
+    /// Panics if the value is borrowed somewhere.
+    ///
+    #[track_caller]
+    pub fn upsert<F, G>(&self, modify: F, insert: G)
+        where F: FnOnce(&mut T), G: FnOnce() -> T
+    {
+        self.upsert_with(modify, || (insert(), ()));
+    }
+
+    ///
+    /// Modifies the value or inserts a new one, see upsert.
+    /// Fails without calling either Fn if the value is borrowed somewhere.
+    ///
+    pub fn try_upsert<F, G>(&self, modify: F, insert: G) -> Result<(), RcOCellError>
+        where F: FnOnce(&mut T), G: FnOnce() -> T
+    {
+        return self.try_upsert_with(modify, || (insert(), ()));
+    }
+
+    ///
+    /// Modifies the value or inserts a new one like upsert and returns what the Fn that ran returned.
+    /// The insert Fn returns the value to store together with its result.
+    ///
+    /// Panics if the value is borrowed somewhere.
+    ///
+    #[track_caller]
+    pub fn upsert_with<R, F, G>(&self, modify: F, insert: G) -> R
+        where F: FnOnce(&mut T) -> R, G: FnOnce() -> (T, R)
+    {
+        return self.upsert_in(self.raw_borrow_mut(), modify, insert);
+    }
+
+    ///
+    /// Modifies the value or inserts a new one and returns what the Fn that ran returned, see upsert_with.
+    /// Fails without calling either Fn if the value is borrowed somewhere.
+    ///
+    pub fn try_upsert_with<R, F, G>(&self, modify: F, insert: G) -> Result<R, RcOCellError>
+        where F: FnOnce(&mut T) -> R, G: FnOnce() -> (T, R)
+    {
+        return Ok(self.upsert_in(self.try_raw_borrow_mut()?, modify, insert));
+    }
+
+    fn upsert_in<R, F, G>(&self, mut value: RcORefMut<'_, Option<T>, T>, modify: F, insert: G) -> R
+        where F: FnOnce(&mut T) -> R, G: FnOnce() -> (T, R)
+    {
+        let result = match value.as_mut() {
+            Some(value) => modify(value),
+            None => {
+                let (inserted, result) = insert();
+                *value = Some(inserted);
+                result
+            }
+        };
+
+        drop(value);
+        self.modified();
+        return result;
+    }
+
+    fn modified(&self) {
+        #[cfg(feature = "tracing")]
+        trace::changed(self, "modify");
+        self.changed();
+    }
+}
+
+impl <T> WeakRcOCell<T> {
+    ///
+    /// Takes the value out of the cell, calls the Fn with it and stores the result in the cell, see RcOCell::modify.
+    /// Panics if the cell was dropped, there is no value or the value is borrowed somewhere.
+    ///
+    pub fn modify<F>(&self, f: F)
+        where F: FnOnce(T) -> T
+    {
+        self.try_upgrade()
+            .expect("WeakRcOCell::modify called on a dropped cell")
+            .modify(f)
+    }
+
+    ///
+    /// Takes the value out of the cell, calls the Fn with it and stores the result in the cell, see RcOCell::modify.
+    /// Fails if the cell was dropped, there is no value or the value is borrowed somewhere.
+    ///
+    pub fn try_modify<F>(&self, f: F) -> Result<(), RcOCellError>
+        where F: FnOnce(T) -> T
+    {
+        self.try_upgrade()?.try_modify(f)
+    }
+
+    ///
+    /// Takes the value out of the cell, calls the Fn with it and stores the result in the cell, see RcOCell::modify_or_insert.
+    /// Panics if the cell was dropped or the value is borrowed somewhere.
+    ///
+    pub fn modify_or_insert<F, D>(&self, f: F, default: D)
+        where F: FnOnce(T) -> T, D: FnOnce() -> T
+    {
+        self.try_upgrade()
+            .expect("WeakRcOCell::modify_or_insert called on a dropped cell")
+            .modify_or_insert(f, default)
+    }
+
+    ///
+    /// Takes the value out of the cell and returns what the Fn makes of it, see RcOCell::take_map.
+    /// Panics if the cell was dropped, there is no value or the value is borrowed somewhere.
+    ///
+    pub fn take_map<U, F>(&self, f: F) -> U
+        where F: FnOnce(T) -> U
+    {
+        self.try_upgrade()
+            .expect("WeakRcOCell::take_map called on a dropped cell")
+            .take_map(f)
+    }
+
+    ///
+    /// Takes the value out of the cell and returns what the Fn makes of it, see RcOCell::take_map.
+    /// Fails if the cell was dropped, there is no value or the value is borrowed somewhere.
+    ///
+    pub fn try_take_map<U, F>(&self, f: F) -> Result<U, RcOCellError>
+        where F: FnOnce(T) -> U
+    {
+        self.try_upgrade()?.try_take_map(f)
+    }
+
+    ///
+    /// Takes the value out of the cell and returns what the Fn makes of it, see RcOCell::take_map_or.
+    /// Panics if the cell was dropped or the value is borrowed somewhere.
+    ///
+    pub fn take_map_or<U, F>(&self, default: U, f: F) -> U
+        where F: FnOnce(T) -> U
+    {
+        self.try_upgrade()
+            .expect("WeakRcOCell::take_map_or called on a dropped cell")
+            .take_map_or(default, f)
+    }
+
+    ///
+    /// Takes the value out of the cell and stores what the Fn makes of it, see RcOCell::compute_owned.
+    /// Panics if the cell was dropped or the value is borrowed somewhere.
+    ///
+    pub fn compute_owned<F>(&self, f: F)
+        where F: FnOnce(Option<T>) -> OwnedComputeResult<T>
+    {
+        self.try_upgrade()
+            .expect("WeakRcOCell::compute_owned called on a dropped cell")
+            .compute_owned(f)
+    }
+
+    ///
+    /// Takes the value out of the cell and stores what the Fn makes of it, see RcOCell::compute_owned.
+    /// Fails if the cell was dropped or the value is borrowed somewhere.
+    ///
+    pub fn try_compute_owned<F>(&self, f: F) -> Result<(), RcOCellError>
+        where F: FnOnce(Option<T>) -> OwnedComputeResult<T>
+    {
+        self.try_upgrade()?.try_compute_owned(f)
+    }
+
+    ///
+    /// Modifies the value or inserts a new one, see RcOCell::upsert.
+    /// Panics if the cell was dropped or the value is borrowed somewhere.
+    ///
+    pub fn upsert<F, G>(&self, modify: F, insert: G)
+        where F: FnOnce(&mut T), G: FnOnce() -> T
+    {
+        self.try_upgrade()
+            .expect("WeakRcOCell::upsert called on a dropped cell")
+            .upsert(modify, insert)
+    }
+
+    ///
+    /// Modifies the value or inserts a new one, see RcOCell::upsert.
+    /// Fails if the cell was dropped or the value is borrowed somewhere.
+    ///
+    pub fn try_upsert<F, G>(&self, modify: F, insert: G) -> Result<(), RcOCellError>
+        where F: FnOnce(&mut T), G: FnOnce() -> T
+    {
+        self.try_upgrade()?.try_upsert(modify, insert)
+    }
+
+    ///
+    /// Modifies the value or inserts a new one and returns what the Fn that ran returned, see RcOCell::upsert_with.
+    /// Panics if the cell was dropped or the value is borrowed somewhere.
+    ///
+    pub fn upsert_with<R, F, G>(&self, modify: F, insert: G) -> R
+        where F: FnOnce(&mut T) -> R, G: FnOnce() -> (T, R)
+    {
+        self.try_upgrade()
+            .expect("WeakRcOCell::upsert_with called on a dropped cell")
+            .upsert_with(modify, insert)
+    }
+
+    ///
+    /// Modifies the value or inserts a new one and returns what the Fn that ran returned, see RcOCell::upsert_with.
+    /// Fails if the cell was dropped or the value is borrowed somewhere.
+    ///
+    pub fn try_upsert_with<R, F, G>(&self, modify: F, insert: G) -> Result<R, RcOCellError>
+        where F: FnOnce(&mut T) -> R, G: FnOnce() -> (T, R)
+    {
+        self.try_upgrade()?.try_upsert_with(modify, insert)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[derive(Debug, PartialEq)]
+    enum Door {
+        Open(String),
+        Closed(String),
+    }
+
+    impl Door {
+        fn toggle(self) -> Door {
+            return match self {
+                Door::Open(name) => Door::Closed(name),
+                Door::Closed(name) => Door::Open(name),
+            };
+        }
+    }
+
+    #[test]
+    fn test_modify() {
+        let door = RcOCell::from_value(Door::Closed("front".to_string()));
+        door.modify(Door::toggle);
+        assert_eq!(*door.borrow(), Door::Open("front".to_string()));
+
+        let weak = door.downgrade();
+        weak.modify(Door::toggle);
+        assert_eq!(*door.borrow(), Door::Closed("front".to_string()));
+        assert_eq!(weak.try_modify(Door::toggle).is_ok(), true);
+        assert_eq!(*door.borrow(), Door::Open("front".to_string()));
+
+        let brw = door.borrow();
+        match door.try_modify(Door::toggle) {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        drop(brw);
+
+        door.clear();
+        match door.try_modify(Door::toggle) {
+            Err(RcOCellError::NoValue) => {}
+            _ => panic!("unexpected"),
+        };
+        door.modify_or_insert(Door::toggle, || Door::Closed("back".to_string()));
+        assert_eq!(*door.borrow(), Door::Open("back".to_string()));
+        weak.modify_or_insert(Door::toggle, || panic!("unexpected"));
+        assert_eq!(*door.borrow(), Door::Closed("back".to_string()));
+
+        drop(door);
+        match weak.try_modify(Door::toggle) {
+            Err(RcOCellError::Dropped) => {}
+            _ => panic!("unexpected"),
+        };
+    }
+
+    #[test]
+    fn test_upsert() {
+        let hits: RcOCell<u32> = RcOCell::new();
+        hits.upsert(|n| *n += 1, || 1);
+        assert_eq!(hits.get_and_clone(), 1);
+        hits.upsert(|n| *n += 1, || panic!("unexpected"));
+        hits.try_upsert(|n| *n += 1, || panic!("unexpected")).unwrap();
+        assert_eq!(hits.get_and_clone(), 3);
+
+        let weak = hits.downgrade();
+        weak.upsert(|n| *n *= 2, || panic!("unexpected"));
+        hits.clear();
+        weak.try_upsert(|_| panic!("unexpected"), || 7).unwrap();
+        assert_eq!(hits.get_and_clone(), 7);
+    }
+
+    #[test]
+    fn test_upsert_with() {
+        let hits: RcOCell<u32> = RcOCell::new();
+        let created = hits.upsert_with(|_| false, || (1, true));
+        assert_eq!((created, hits.get_and_clone()), (true, 1));
+        let previous = hits.upsert_with(|n| { *n += 1; *n - 1 }, || panic!("unexpected"));
+        assert_eq!((previous, hits.get_and_clone()), (1, 2));
+        assert_eq!(hits.try_upsert_with(|n| *n, || (0, 0)).unwrap(), 2);
+
+        let weak = hits.downgrade();
+        hits.clear();
+        assert_eq!(weak.upsert_with(|_| "modified", || (5, "inserted")), "inserted");
+        assert_eq!(weak.try_upsert_with(|_| "modified", || (5, "inserted")).unwrap(), "modified");
+        assert_eq!(hits.get_and_clone(), 5);
+    }
+
+    #[test]
+    fn test_upsert_borrowed() {
+        let hits = RcOCell::from_value(1u32);
+        let changes = RcOCell::from_value(0u32);
+        let changes2 = changes.clone();
+        hits.on_set(move |_| { changes2.set(changes2.get_and_clone() + 1); });
+
+        let brw = hits.borrow();
+        match hits.try_upsert(|_| panic!("unexpected"), || panic!("unexpected")) {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(hits.try_upsert_with(|_| panic!("unexpected"), || -> (u32, ()) { panic!("unexpected") }).is_err(), true);
+        assert_eq!(hits.catch(|h| h.upsert(|n| *n += 1, || 1)).is_err(), true);
+        drop(brw);
+        assert_eq!(changes.get_and_clone(), 0);
+
+        hits.upsert(|n| *n += 1, || 1);
+        assert_eq!((hits.get_and_clone(), changes.get_and_clone()), (2, 1));
+
+        let weak = hits.downgrade();
+        drop(hits);
+        match weak.try_upsert(|n| *n += 1, || 1) {
+            Err(RcOCellError::Dropped) => {}
+            _ => panic!("unexpected"),
+        };
+    }
+
+    #[test]
+    fn test_modify_observed() {
+        let door = RcOCell::from_value(Door::Closed("front".to_string()));
+        let seen = RcOCell::new();
+        let seen2 = seen.clone();
+        door.on_set(move |d: &Door| { seen2.set(format!("{:?}", d)); });
+        door.modify(|d| {
+            assert_eq!(door.try_borrow().is_err(), true);
+            d.toggle()
+        });
+        assert_eq!(seen.get_and_clone(), "Open(\"front\")");
+    }
+
+    #[test]
+    fn test_modify_panic() {
+        let door = RcOCell::from_value(Door::Closed("front".to_string()));
+        let r = door.catch(|door| door.modify(|_| panic!("Oh no")));
+        assert_eq!(r.is_err(), true);
+        assert_eq!(door.is_poisoned(), cfg!(feature = "poison"));
+        door.clear_poison();
+        assert_eq!(door.is_none(), true);
+
+        let r = door.catch(|door| door.modify(Door::toggle));
+        assert_eq!(r.is_err(), true);
+    }
+
+    #[test]
+    fn test_compute_owned() {
+        let door: RcOCell<Door> = RcOCell::new();
+        let changes = RcOCell::from_value(0u32);
+        let changes2 = changes.clone();
+        door.on_set(move |_| { changes2.set(changes2.get_and_clone() + 1); });
+
+        door.compute_owned(|d| {
+            assert_eq!(d, None);
+            OwnedComputeResult::Empty
+        });
+        assert_eq!((door.is_none(), changes.get_and_clone()), (true, 0));
+        door.compute_owned(|_| OwnedComputeResult::Store(Door::Closed("front".to_string())));
+        door.try_compute_owned(|d| OwnedComputeResult::Store(d.unwrap().toggle())).unwrap();
+        assert_eq!(*door.borrow(), Door::Open("front".to_string()));
+        assert_eq!(changes.get_and_clone(), 2);
+
+        let weak = door.downgrade();
+        weak.compute_owned(|d| d.map(Door::toggle).into());
+        assert_eq!(*door.borrow(), Door::Closed("front".to_string()));
+        weak.try_compute_owned(|_| OwnedComputeResult::Empty).unwrap();
+        assert_eq!(door.is_none(), true);
+
+        door.set(Door::Open("back".to_string()));
+        let brw = door.borrow();
+        match door.try_compute_owned(|_| panic!("unexpected")) {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        drop(brw);
+        assert_eq!(*door.borrow(), Door::Open("back".to_string()));
+
+        drop(door);
+        match weak.try_compute_owned(|_| panic!("unexpected")) {
+            Err(RcOCellError::Dropped) => {}
+            _ => panic!("unexpected"),
+        };
+    }
+
+    #[test]
+    fn test_compute_owned_panic() {
+        let door = RcOCell::from_value(Door::Closed("front".to_string()));
+        let r = door.catch(|door| door.compute_owned(|d| {
+            assert_eq!(door.try_borrow().is_err(), true);
+            drop(d);
+            panic!("Oh no")
+        }));
+        assert_eq!(r.is_err(), true);
+        assert_eq!(door.is_poisoned(), cfg!(feature = "poison"));
+        door.clear_poison();
+        assert_eq!(door.is_none(), true);
+    }
+
+    #[test]
+    fn test_take_map() {
+        let log = RcOCell::from_value(vec!["a", "b"]);
+        assert_eq!(log.take_map(|v| v.join(",")), "a,b");
+        assert_eq!(log.is_none(), true);
+        match log.try_take_map(|v| v.len()) {
+            Err(RcOCellError::NoValue) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(log.take_map_or(0, |v| v.len()), 0);
+
+        log.set(vec!["c"]);
+        let brw = log.borrow();
+        match log.try_take_map(|v| v.len()) {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        drop(brw);
+        assert_eq!(log.get_and_clone(), vec!["c"]);
+
+        let weak = log.downgrade();
+        assert_eq!(weak.take_map_or(0, |v| v.len()), 1);
+        assert_eq!(log.is_none(), true);
+        log.set(vec!["d", "e"]);
+        assert_eq!(weak.try_take_map(|v| v.len()).unwrap(), 2);
+        log.set(vec![]);
+        assert_eq!(weak.take_map(|v| v.is_empty()), true);
+    }
+
+    #[test]
+    fn test_take_map_failure() {
+        let log = RcOCell::from_value(vec!["a"]);
+        let brw = log.borrow();
+        let r = log.catch(|log| log.take_map(|v| v.len()));
+        assert_eq!(r.is_err(), true);
+        drop(brw);
+        assert_eq!(log.get_and_clone(), vec!["a"]);
+
+        let empty: RcOCell<Vec<&str>> = RcOCell::new();
+        assert_eq!(empty.catch(|empty| empty.take_map(|v| v.len())).is_err(), true);
+    }
+}
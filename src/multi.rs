@@ -0,0 +1,192 @@
+use std::collections::HashSet;
+use crate::{CellId, RcOCell, RcOCellError, RcORef, RcORefMut};
+
+impl <T> RcOCell<T> {
+    ///
+    /// Borrows the values of both cells, either both borrows succeed or none is held.
+    /// Panics if either value is already borrowed mutably somewhere or either cell is empty.
+    ///
+    #[track_caller]
+    pub fn borrow_both<'a, U>(&'a self, other: &'a RcOCell<U>) -> (RcORef<'a, T>, RcORef<'a, U>) {
+        return match self.try_borrow_both(other) {
+            Ok(guards) => guards,
+            Err(e) => self.fail(e),
+        };
+    }
+
+    ///
+    /// Borrows the values of both cells, either both borrows succeed or none is held.
+    /// Fails if either value is already borrowed mutably somewhere or either cell is empty.
+    ///
+    pub fn try_borrow_both<'a, U>(&'a self, other: &'a RcOCell<U>) -> Result<(RcORef<'a, T>, RcORef<'a, U>), RcOCellError> {
+        let first = self.try_borrow()?;
+        let second = other.try_borrow()?;
+        return Ok((first, second));
+    }
+
+    ///
+    /// Borrows the values of both cells mutably, either both borrows succeed or none is held.
+    /// Panics if both are the same cell, either value is already borrowed somewhere or either cell is empty.
+    ///
+    #[track_caller]
+    pub fn borrow_mut_both<'a, U>(&'a self, other: &'a RcOCell<U>) -> (RcORefMut<'a, T>, RcORefMut<'a, U>) {
+        return match self.try_borrow_mut_both(other) {
+            Ok(guards) => guards,
+            Err(e) => self.fail(e),
+        };
+    }
+
+    ///
+    /// Borrows the values of both cells mutably, either both borrows succeed or none is held.
+    /// Fails with RcOCellError::DuplicateCell if both are the same cell, nothing is borrowed then.
+    /// Fails if either value is already borrowed somewhere or either cell is empty.
+    ///
+    pub fn try_borrow_mut_both<'a, U>(&'a self, other: &'a RcOCell<U>) -> Result<(RcORefMut<'a, T>, RcORefMut<'a, U>), RcOCellError> {
+        if self.id() == other.id() {
+            return Err(RcOCellError::DuplicateCell);
+        }
+
+        let first = self.try_borrow_mut()?;
+        let second = other.try_borrow_mut()?;
+        return Ok((first, second));
+    }
+
+    ///
+    /// Borrows the values of all cells, either all borrows succeed or none is held.
+    /// The same cell may occur more than once.
+    /// Fails with the index of the first cell that could not be borrowed, the guards taken before are released.
+    ///
+    pub fn try_borrow_all<'a>(cells: &[&'a RcOCell<T>]) -> Result<Vec<RcORef<'a, T>>, (usize, RcOCellError)> {
+        let mut guards = Vec::with_capacity(cells.len());
+        for (index, cell) in cells.iter().enumerate() {
+            guards.push(cell.try_borrow().map_err(|e| (index, e))?);
+        }
+
+        return Ok(guards);
+    }
+
+    ///
+    /// Borrows the values of all cells mutably, either all borrows succeed or none is held.
+    /// Fails with the index of the first cell that could not be borrowed, the guards taken before are released.
+    ///
+    /// If a cell occurs more than once nothing is borrowed, the call fails with RcOCellError::DuplicateCell
+    /// and the index of the second occurrence.
+    ///
+    pub fn try_borrow_mut_all<'a>(cells: &[&'a RcOCell<T>]) -> Result<Vec<RcORefMut<'a, T>>, (usize, RcOCellError)> {
+        let mut seen: HashSet<CellId> = HashSet::with_capacity(cells.len());
+        for (index, cell) in cells.iter().enumerate() {
+            if !seen.insert(cell.id()) {
+                return Err((index, RcOCellError::DuplicateCell));
+            }
+        }
+
+        let mut guards = Vec::with_capacity(cells.len());
+        for (index, cell) in cells.iter().enumerate() {
+            guards.push(cell.try_borrow_mut().map_err(|e| (index, e))?);
+        }
+
+        return Ok(guards);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_borrow_both() {
+        let a = RcOCell::from_value(1u32);
+        let b = RcOCell::from_value("b".to_string());
+        let (x, y) = a.borrow_both(&b);
+        assert_eq!((*x, y.as_str()), (1, "b"));
+        drop((x, y));
+
+        let (mut x, mut y) = a.borrow_mut_both(&b);
+        *x += 1;
+        y.push('c');
+        drop((x, y));
+        assert_eq!(a.get_and_clone(), 2);
+        assert_eq!(b.get_and_clone(), "bc");
+
+        let (x, y) = a.try_borrow_both(&a).unwrap();
+        assert_eq!(*x + *y, 4);
+        drop((x, y));
+
+        let brw = b.borrow();
+        match a.try_borrow_mut_both(&b) {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(a.try_borrow_mut().is_ok(), true);
+        assert_eq!(a.catch(|a| drop(a.borrow_mut_both(&b))).is_err(), true);
+        drop(brw);
+
+        b.clear();
+        match a.try_borrow_both(&b) {
+            Err(RcOCellError::NoValue) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(a.try_borrow_mut().is_ok(), true);
+    }
+
+    #[test]
+    fn test_borrow_mut_both_same_cell() {
+        let a = RcOCell::from_value(1u32);
+        let alias = a.clone();
+        match a.try_borrow_mut_both(&alias) {
+            Err(RcOCellError::DuplicateCell) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(a.catch(|a| drop(a.borrow_mut_both(&alias))).is_err(), true);
+        assert_eq!(a.try_borrow_mut().is_ok(), true);
+    }
+
+    #[test]
+    fn test_borrow_all() {
+        let cells: Vec<RcOCell<u32>> = (0..4).map(RcOCell::from_value).collect();
+        let refs: Vec<&RcOCell<u32>> = cells.iter().collect();
+        let guards = RcOCell::try_borrow_all(&[&cells[1], &cells[1], &cells[3]]).unwrap();
+        assert_eq!(guards.iter().map(|g| **g).collect::<Vec<_>>(), vec![1, 1, 3]);
+        drop(guards);
+
+        for mut guard in RcOCell::try_borrow_mut_all(&refs).unwrap() {
+            *guard *= 10;
+        }
+        assert_eq!(cells.iter().map(|c| c.get_and_clone()).collect::<Vec<_>>(), vec![0, 10, 20, 30]);
+
+        let brw = cells[2].borrow_mut();
+        match RcOCell::try_borrow_all(&refs) {
+            Err((2, RcOCellError::BorrowError(_))) => {}
+            _ => panic!("unexpected"),
+        };
+        drop(brw);
+        let brw = cells[2].borrow();
+        match RcOCell::try_borrow_mut_all(&refs) {
+            Err((2, RcOCellError::BorrowError(_))) => {}
+            _ => panic!("unexpected"),
+        };
+        drop(brw);
+        assert_eq!(cells.iter().all(|c| c.try_borrow_mut().is_ok()), true);
+
+        cells[3].clear();
+        match RcOCell::try_borrow_mut_all(&refs) {
+            Err((3, RcOCellError::NoValue)) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(cells[0].try_borrow_mut().is_ok(), true);
+    }
+
+    #[test]
+    fn test_borrow_mut_all_duplicate() {
+        let a = RcOCell::from_value(1u32);
+        let b = RcOCell::from_value(2u32);
+        let alias = a.clone();
+        match RcOCell::try_borrow_mut_all(&[&a, &b, &alias]) {
+            Err((2, RcOCellError::DuplicateCell)) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(a.try_borrow_mut().is_ok(), true);
+        assert_eq!(b.try_borrow_mut().is_ok(), true);
+        assert_eq!(RcOCell::try_borrow_mut_all(&[&b, &a]).unwrap().len(), 2);
+    }
+}
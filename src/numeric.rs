@@ -0,0 +1,397 @@
+use crate::{RcOCell, RcOCellError, RcORefMut, WeakRcOCell};
+
+///
+/// Counter operations for cells holding a primitive number.
+///
+/// An empty cell counts as zero, so adding to an empty cell stores the added amount.
+/// All operations overflow like the corresponding operator would, see IntegerCellExt for checked, saturating and wrapping variants.
+/// Observers are notified about every change.
+///
+pub trait NumericCellExt<T> {
+    ///
+    /// Adds n to the value and returns the previous value.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn fetch_add(&self, n: T) -> T;
+
+    ///
+    /// Adds n to the value and returns the previous value.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    fn try_fetch_add(&self, n: T) -> Result<T, RcOCellError>;
+
+    ///
+    /// Subtracts n from the value and returns the previous value.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn fetch_sub(&self, n: T) -> T;
+
+    ///
+    /// Subtracts n from the value and returns the previous value.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    fn try_fetch_sub(&self, n: T) -> Result<T, RcOCellError>;
+
+    ///
+    /// Adds one to the value and returns the new value.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn increment(&self) -> T;
+
+    ///
+    /// Adds one to the value and returns the new value.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    fn try_increment(&self) -> Result<T, RcOCellError>;
+
+    ///
+    /// Subtracts one from the value and returns the new value.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn decrement(&self) -> T;
+
+    ///
+    /// Subtracts one from the value and returns the new value.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    fn try_decrement(&self) -> Result<T, RcOCellError>;
+}
+
+///
+/// Overflow aware counter operations for cells holding a primitive integer.
+/// An empty cell counts as zero.
+///
+pub trait IntegerCellExt<T>: NumericCellExt<T> {
+    ///
+    /// Adds n to the value and returns the previous value.
+    /// Returns None and leaves the cell untouched if the addition overflows.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn checked_fetch_add(&self, n: T) -> Option<T>;
+
+    ///
+    /// Adds n to the value and returns the previous value.
+    /// Returns None and leaves the cell untouched if the addition overflows.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    fn try_checked_fetch_add(&self, n: T) -> Result<Option<T>, RcOCellError>;
+
+    ///
+    /// Subtracts n from the value and returns the previous value.
+    /// Returns None and leaves the cell untouched if the subtraction overflows.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn checked_fetch_sub(&self, n: T) -> Option<T>;
+
+    ///
+    /// Subtracts n from the value and returns the previous value.
+    /// Returns None and leaves the cell untouched if the subtraction overflows.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    fn try_checked_fetch_sub(&self, n: T) -> Result<Option<T>, RcOCellError>;
+
+    ///
+    /// Adds n to the value, stopping at the maximum, and returns the previous value.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn saturating_fetch_add(&self, n: T) -> T;
+
+    ///
+    /// Subtracts n from the value, stopping at the minimum, and returns the previous value.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn saturating_fetch_sub(&self, n: T) -> T;
+
+    ///
+    /// Adds n to the value, wrapping around at the maximum, and returns the previous value.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn wrapping_fetch_add(&self, n: T) -> T;
+
+    ///
+    /// Subtracts n from the value, wrapping around at the minimum, and returns the previous value.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn wrapping_fetch_sub(&self, n: T) -> T;
+}
+
+///
+/// Replaces the value with the result of the Fn unless it returns None and returns the previous value.
+/// An empty cell is passed to the Fn as zero.
+///
+fn update<T: Copy + Default>(cell: &RcOCell<T>, mut value: RcORefMut<'_, Option<T>, T>, f: impl FnOnce(T) -> Option<T>) -> Option<T> {
+    let previous = value.unwrap_or_default();
+    let next = f(previous)?;
+    *value = Some(next);
+    drop(value);
+    cell.changed();
+    return Some(previous);
+}
+
+fn apply<T: Copy + Default>(cell: &RcOCell<T>, f: impl FnOnce(T) -> Option<T>) -> Option<T> {
+    return update(cell, cell.raw_borrow_mut(), f);
+}
+
+fn try_apply<T: Copy + Default>(cell: &RcOCell<T>, f: impl FnOnce(T) -> Option<T>) -> Result<Option<T>, RcOCellError> {
+    return Ok(update(cell, cell.try_raw_borrow_mut()?, f));
+}
+
+macro_rules! numeric_impl {
+    ($($t:ty),*) => {$(
+        impl NumericCellExt<$t> for RcOCell<$t> {
+            fn fetch_add(&self, n: $t) -> $t {
+                return apply(self, |v| Some(v + n)).unwrap();
+            }
+
+            fn try_fetch_add(&self, n: $t) -> Result<$t, RcOCellError> {
+                return Ok(try_apply(self, |v| Some(v + n))?.unwrap());
+            }
+
+            fn fetch_sub(&self, n: $t) -> $t {
+                return apply(self, |v| Some(v - n)).unwrap();
+            }
+
+            fn try_fetch_sub(&self, n: $t) -> Result<$t, RcOCellError> {
+                return Ok(try_apply(self, |v| Some(v - n))?.unwrap());
+            }
+
+            fn increment(&self) -> $t {
+                return self.fetch_add(1 as $t) + 1 as $t;
+            }
+
+            fn try_increment(&self) -> Result<$t, RcOCellError> {
+                return Ok(self.try_fetch_add(1 as $t)? + 1 as $t);
+            }
+
+            fn decrement(&self) -> $t {
+                return self.fetch_sub(1 as $t) - 1 as $t;
+            }
+
+            fn try_decrement(&self) -> Result<$t, RcOCellError> {
+                return Ok(self.try_fetch_sub(1 as $t)? - 1 as $t);
+            }
+        }
+    )*};
+}
+
+macro_rules! integer_impl {
+    ($($t:ty),*) => {$(
+        numeric_impl!($t);
+
+        impl IntegerCellExt<$t> for RcOCell<$t> {
+            fn checked_fetch_add(&self, n: $t) -> Option<$t> {
+                return apply(self, |v| v.checked_add(n));
+            }
+
+            fn try_checked_fetch_add(&self, n: $t) -> Result<Option<$t>, RcOCellError> {
+                return try_apply(self, |v| v.checked_add(n));
+            }
+
+            fn checked_fetch_sub(&self, n: $t) -> Option<$t> {
+                return apply(self, |v| v.checked_sub(n));
+            }
+
+            fn try_checked_fetch_sub(&self, n: $t) -> Result<Option<$t>, RcOCellError> {
+                return try_apply(self, |v| v.checked_sub(n));
+            }
+
+            fn saturating_fetch_add(&self, n: $t) -> $t {
+                return apply(self, |v| Some(v.saturating_add(n))).unwrap();
+            }
+
+            fn saturating_fetch_sub(&self, n: $t) -> $t {
+                return apply(self, |v| Some(v.saturating_sub(n))).unwrap();
+            }
+
+            fn wrapping_fetch_add(&self, n: $t) -> $t {
+                return apply(self, |v| Some(v.wrapping_add(n))).unwrap();
+            }
+
+            fn wrapping_fetch_sub(&self, n: $t) -> $t {
+                return apply(self, |v| Some(v.wrapping_sub(n))).unwrap();
+            }
+        }
+    )*};
+}
+
+integer_impl!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+numeric_impl!(f32, f64);
+
+impl <T> NumericCellExt<T> for WeakRcOCell<T> where RcOCell<T>: NumericCellExt<T> {
+    fn fetch_add(&self, n: T) -> T {
+        self.try_upgrade()
+            .expect("WeakRcOCell::fetch_add called on a dropped cell")
+            .fetch_add(n)
+    }
+
+    fn try_fetch_add(&self, n: T) -> Result<T, RcOCellError> {
+        self.try_upgrade()?.try_fetch_add(n)
+    }
+
+    fn fetch_sub(&self, n: T) -> T {
+        self.try_upgrade()
+            .expect("WeakRcOCell::fetch_sub called on a dropped cell")
+            .fetch_sub(n)
+    }
+
+    fn try_fetch_sub(&self, n: T) -> Result<T, RcOCellError> {
+        self.try_upgrade()?.try_fetch_sub(n)
+    }
+
+    fn increment(&self) -> T {
+        self.try_upgrade()
+            .expect("WeakRcOCell::increment called on a dropped cell")
+            .increment()
+    }
+
+    fn try_increment(&self) -> Result<T, RcOCellError> {
+        self.try_upgrade()?.try_increment()
+    }
+
+    fn decrement(&self) -> T {
+        self.try_upgrade()
+            .expect("WeakRcOCell::decrement called on a dropped cell")
+            .decrement()
+    }
+
+    fn try_decrement(&self) -> Result<T, RcOCellError> {
+        self.try_upgrade()?.try_decrement()
+    }
+}
+
+impl <T> IntegerCellExt<T> for WeakRcOCell<T> where RcOCell<T>: IntegerCellExt<T> {
+    fn checked_fetch_add(&self, n: T) -> Option<T> {
+        self.try_upgrade()
+            .expect("WeakRcOCell::checked_fetch_add called on a dropped cell")
+            .checked_fetch_add(n)
+    }
+
+    fn try_checked_fetch_add(&self, n: T) -> Result<Option<T>, RcOCellError> {
+        self.try_upgrade()?.try_checked_fetch_add(n)
+    }
+
+    fn checked_fetch_sub(&self, n: T) -> Option<T> {
+        self.try_upgrade()
+            .expect("WeakRcOCell::checked_fetch_sub called on a dropped cell")
+            .checked_fetch_sub(n)
+    }
+
+    fn try_checked_fetch_sub(&self, n: T) -> Result<Option<T>, RcOCellError> {
+        self.try_upgrade()?.try_checked_fetch_sub(n)
+    }
+
+    fn saturating_fetch_add(&self, n: T) -> T {
+        self.try_upgrade()
+            .expect("WeakRcOCell::saturating_fetch_add called on a dropped cell")
+            .saturating_fetch_add(n)
+    }
+
+    fn saturating_fetch_sub(&self, n: T) -> T {
+        self.try_upgrade()
+            .expect("WeakRcOCell::saturating_fetch_sub called on a dropped cell")
+            .saturating_fetch_sub(n)
+    }
+
+    fn wrapping_fetch_add(&self, n: T) -> T {
+        self.try_upgrade()
+            .expect("WeakRcOCell::wrapping_fetch_add called on a dropped cell")
+            .wrapping_fetch_add(n)
+    }
+
+    fn wrapping_fetch_sub(&self, n: T) -> T {
+        self.try_upgrade()
+            .expect("WeakRcOCell::wrapping_fetch_sub called on a dropped cell")
+            .wrapping_fetch_sub(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use crate::*;
+
+    #[test]
+    fn test_fetch_add_sub() {
+        let x = RcOCell::from_value(5i32);
+        assert_eq!(x.fetch_add(3), 5);
+        assert_eq!(x.fetch_sub(10), 8);
+        assert_eq!(x.get_and_clone(), -2);
+        assert_eq!(x.increment(), -1);
+        assert_eq!(x.decrement(), -2);
+
+        let f = RcOCell::from_value(1.5f64);
+        assert_eq!(f.fetch_add(1.0), 1.5);
+        assert_eq!(f.increment(), 3.5);
+    }
+
+    #[test]
+    fn test_empty_counts_as_zero() {
+        let x: RcOCell<u32> = RcOCell::new();
+        assert_eq!(x.fetch_add(2), 0);
+        assert_eq!(x.get_and_clone(), 2);
+        x.clear();
+        assert_eq!(x.increment(), 1);
+        x.clear();
+        assert_eq!(x.checked_fetch_sub(1), None);
+        assert_eq!(x.is_none(), true);
+    }
+
+    #[test]
+    fn test_overflow_variants() {
+        let x = RcOCell::from_value(250u8);
+        assert_eq!(x.checked_fetch_add(10), None);
+        assert_eq!(x.get_and_clone(), 250);
+        assert_eq!(x.checked_fetch_add(5), Some(250));
+        assert_eq!(x.saturating_fetch_add(10), 255);
+        assert_eq!(x.get_and_clone(), 255);
+        assert_eq!(x.wrapping_fetch_add(2), 255);
+        assert_eq!(x.get_and_clone(), 1);
+        assert_eq!(x.wrapping_fetch_sub(2), 1);
+        assert_eq!(x.get_and_clone(), 255);
+        x.set(1);
+        assert_eq!(x.saturating_fetch_sub(2), 1);
+        assert_eq!(x.get_and_clone(), 0);
+    }
+
+    #[test]
+    fn test_try_numeric() {
+        let x = RcOCell::from_value(1u64);
+        let brw = x.borrow();
+        assert_eq!(x.try_fetch_add(1).is_err(), true);
+        assert_eq!(x.try_increment().is_err(), true);
+        assert_eq!(x.try_checked_fetch_sub(1).is_err(), true);
+        drop(brw);
+        assert_eq!(x.try_fetch_add(1).unwrap(), 1);
+        assert_eq!(x.try_decrement().unwrap(), 1);
+        assert_eq!(x.try_checked_fetch_sub(2).unwrap(), None);
+    }
+
+    #[test]
+    fn test_numeric_notifies() {
+        let x = RcOCell::from_value(0u32);
+        let seen = Rc::new(Cell::new(0u32));
+        let seen_clone = seen.clone();
+        x.on_set(move |v| seen_clone.set(*v));
+        x.increment();
+        x.fetch_add(4);
+        assert_eq!(seen.get(), 5);
+        x.set(u32::MAX);
+        seen.set(0);
+        assert_eq!(x.checked_fetch_add(1), None);
+        assert_eq!(seen.get(), 0);
+    }
+
+    #[test]
+    fn test_numeric_weak() {
+        let x = RcOCell::from_value(1i64);
+        let w = x.downgrade();
+        assert_eq!(w.increment(), 2);
+        assert_eq!(w.checked_fetch_add(i64::MAX), None);
+        drop(x);
+        match w.try_fetch_add(1) {
+            Err(RcOCellError::Dropped) => {}
+            _ => panic!("unexpected"),
+        };
+    }
+}
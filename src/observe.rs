@@ -0,0 +1,622 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use crate::{shared, RcOCell, WeakRcOCell};
+
+///
+/// Identifies an observer registered on a cell, used to unsubscribe it again.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObserverId(u64);
+
+///
+/// Keeps two cells synchronized, see RcOCell::bind.
+/// The synchronization stops when this is dropped.
+///
+#[must_use = "the cells are unbound again when the Binding is dropped"]
+pub struct Binding {
+    unbind: Option<Box<dyn FnOnce()>>,
+}
+
+impl Binding {
+    ///
+    /// Stops the synchronization, same as dropping the Binding.
+    ///
+    pub fn unbind(mut self) {
+        self.run_unbind();
+    }
+
+    fn run_unbind(&mut self) {
+        if let Some(unbind) = self.unbind.take() {
+            unbind();
+        }
+    }
+}
+
+impl Drop for Binding {
+    fn drop(&mut self) {
+        self.run_unbind();
+    }
+}
+
+impl std::fmt::Debug for Binding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(if self.unbind.is_some() { "Binding" } else { "Binding(unbound)" })
+    }
+}
+
+///
+/// Resets the propagation flag of a binding even if the propagation panics.
+///
+struct Propagating<'a>(&'a Cell<bool>);
+
+impl <'a> Propagating<'a> {
+    fn enter(flag: &'a Cell<bool>) -> Option<Propagating<'a>> {
+        if flag.replace(true) {
+            return None;
+        }
+
+        return Some(Propagating(flag));
+    }
+}
+
+impl Drop for Propagating<'_> {
+    fn drop(&mut self) {
+        self.0.set(false);
+    }
+}
+
+///
+/// Registers the observers that propagate the changes of source to target.
+///
+fn propagate<S: 'static, D: 'static, F>(source: &RcOCell<S>, target: &RcOCell<D>, flag: &Rc<Cell<bool>>, convert: F) -> (ObserverId, ObserverId)
+    where F: Fn(&S) -> D + 'static
+{
+    let weak = target.downgrade();
+    let f = flag.clone();
+    let set_id = source.on_set_while(move |value| {
+        let Ok(target) = weak.try_upgrade() else {
+            return false;
+        };
+        if let Some(_guard) = Propagating::enter(&f) {
+            target.set(convert(value));
+        }
+        true
+    });
+
+    let weak = target.downgrade();
+    let f = flag.clone();
+    let clear_id = source.on_clear_while(move || {
+        let Ok(target) = weak.try_upgrade() else {
+            return false;
+        };
+        if let Some(_guard) = Propagating::enter(&f) {
+            target.clear();
+        }
+        true
+    });
+
+    return (set_id, clear_id);
+}
+
+impl <T: 'static> RcOCell<T> {
+
+    ///
+    /// Registers a Fn that is called with the new value whenever a value is stored in the cell.
+    /// This happens on set, replace, compute, swap and all other methods that store a value.
+    /// In place modifications through borrow_mut, map_mut and similar methods are not observed.
+    ///
+    /// The Fn is called while the value is borrowed, it must not modify this cell except with set_deferred.
+    /// Observers are called in the order they were registered.
+    ///
+    pub fn on_set<F>(&self, f: F) -> ObserverId
+        where F: Fn(&T) + 'static
+    {
+        return self.on_set_while(move |value| {
+            f(value);
+            true
+        });
+    }
+
+    ///
+    /// Registers a Fn that is called whenever the cell is cleared.
+    /// This happens on clear, get_and_clear, compute and all other methods that remove the value.
+    ///
+    /// The Fn must not modify this cell except with set_deferred.
+    /// Observers are called in the order they were registered.
+    ///
+    pub fn on_clear<F>(&self, f: F) -> ObserverId
+        where F: Fn() + 'static
+    {
+        return self.on_clear_while(move || {
+            f();
+            true
+        });
+    }
+
+    ///
+    /// Registers a Fn that is called with the upgraded target cell and the new value whenever a value is stored in the cell.
+    /// The observer only references the target weakly, so capturing another cell this way does not create a strong cycle.
+    /// It is removed by the first notification after the target was dropped, see on_set.
+    ///
+    pub fn on_set_weak<U, F>(&self, target: &WeakRcOCell<U>, f: F) -> ObserverId
+        where U: 'static, F: Fn(&RcOCell<U>, &T) + 'static
+    {
+        let target = target.clone();
+        return self.on_set_while(move |value| {
+            let Ok(target) = target.try_upgrade() else {
+                return false;
+            };
+            f(&target, value);
+            true
+        });
+    }
+
+    ///
+    /// Registers a Fn that is called with the anchor and the new value whenever a value is stored in the cell.
+    /// The observer only references the anchor weakly and does not keep it alive.
+    /// It is removed by the first notification after the anchor was dropped, see on_set.
+    ///
+    pub fn on_set_while_alive<A, F>(&self, anchor: &Rc<A>, f: F) -> ObserverId
+        where A: ?Sized + 'static, F: Fn(&Rc<A>, &T) + 'static
+    {
+        let anchor = Rc::downgrade(anchor);
+        return self.on_set_while(move |value| {
+            let Some(anchor) = anchor.upgrade() else {
+                return false;
+            };
+            f(&anchor, value);
+            true
+        });
+    }
+
+    ///
+    /// Removes an observer registered with on_set or on_clear.
+    /// Returns true if the observer was registered on this cell.
+    ///
+    pub fn unsubscribe(&self, id: ObserverId) -> bool {
+        return shared::remove_observer(&self.rc, id.0).is_some();
+    }
+
+    ///
+    /// Returns the number of observers currently registered on this cell.
+    ///
+    pub fn observer_count(&self) -> usize {
+        return shared::with_existing_typed(&self.rc, |typed| typed.on_set.len() + typed.on_clear.len()).unwrap_or(0);
+    }
+
+    ///
+    /// Like on_set but the observer is removed once the Fn returns false.
+    ///
+    pub(crate) fn on_set_while<F>(&self, f: F) -> ObserverId
+        where F: Fn(&T) -> bool + 'static
+    {
+        let id = shared::next_id();
+        shared::with_typed(&self.rc, |typed| typed.on_set.push((id, Rc::new(f))));
+        return ObserverId(id);
+    }
+
+    ///
+    /// Like on_clear but the observer is removed once the Fn returns false.
+    ///
+    pub(crate) fn on_clear_while<F>(&self, f: F) -> ObserverId
+        where F: Fn() -> bool + 'static
+    {
+        let id = shared::next_id();
+        shared::with_typed(&self.rc, |typed| typed.on_clear.push((id, Rc::new(f))));
+        return ObserverId(id);
+    }
+
+    ///
+    /// Registers a Fn that runs once after the last strong handle of this cell was dropped.
+    /// Returns an id to remove the Fn again with remove_cleanup.
+    ///
+    pub(crate) fn add_cleanup<F>(&self, f: F) -> u64
+        where F: FnOnce() + 'static
+    {
+        let id = shared::next_id();
+        shared::with_state(&self.rc, |state| state.cleanups.push((id, Box::new(f))));
+        return id;
+    }
+
+    ///
+    /// Removes a Fn registered with add_cleanup without running it.
+    ///
+    pub(crate) fn remove_cleanup(&self, id: u64) {
+        let removed = shared::with_existing_state(&self.rc, |state| {
+            let index = state.cleanups.iter().position(|(i, _)| *i == id)?;
+            Some(state.cleanups.remove(index))
+        });
+
+        //Dropped outside of the shared state, the Fn may own handles of cells.
+        drop(removed);
+    }
+
+    ///
+    /// Creates a new cell whose value is calculated from the value of this cell.
+    /// The derived cell is recalculated whenever a value is stored in this cell and cleared whenever this cell is cleared.
+    ///
+    /// This cell only references the derived cell weakly, the subscription is removed once the derived cell is dropped.
+    /// Values stored in the derived cell directly are overwritten by the next change of this cell.
+    ///
+    pub fn derive<U, F>(&self, f: F) -> RcOCell<U>
+        where U: 'static, F: Fn(&T) -> U + 'static
+    {
+        let derived = RcOCell::from_option(self.map(&f));
+        let weak = derived.downgrade();
+        let set_id = self.on_set_while(move |value| {
+            let Ok(derived) = weak.try_upgrade() else {
+                return false;
+            };
+            derived.set(f(value));
+            true
+        });
+
+        let weak = derived.downgrade();
+        let clear_id = self.on_clear_while(move || {
+            let Ok(derived) = weak.try_upgrade() else {
+                return false;
+            };
+            derived.clear();
+            true
+        });
+
+        let source = self.downgrade();
+        derived.add_cleanup(move || {
+            if let Ok(source) = source.try_upgrade() {
+                source.unsubscribe(set_id);
+                source.unsubscribe(clear_id);
+            }
+        });
+
+        return derived;
+    }
+
+    ///
+    /// Creates a new cell whose value is calculated from the values of two cells.
+    /// The derived cell is recalculated whenever one of the cells changes
+    /// and is empty while either cell is empty.
+    ///
+    /// The cells only reference the derived cell weakly, the subscriptions are removed once the derived cell is dropped.
+    ///
+    pub fn derive2<A, B, F>(a: &RcOCell<A>, b: &RcOCell<B>, f: F) -> RcOCell<T>
+        where A: 'static, B: 'static, F: Fn(&A, &B) -> T + 'static
+    {
+        let derived = RcOCell::new();
+        let weak = derived.downgrade();
+        let weak_a = a.downgrade();
+        let weak_b = b.downgrade();
+        let recompute = Rc::new(move || {
+            let Ok(derived) = weak.try_upgrade() else {
+                return false;
+            };
+            let (Ok(a), Ok(b)) = (weak_a.try_upgrade(), weak_b.try_upgrade()) else {
+                derived.clear();
+                return true;
+            };
+
+            let value = match (a.try_borrow(), b.try_borrow()) {
+                (Ok(a), Ok(b)) => Some(f(&a, &b)),
+                _ => None,
+            };
+
+            match value {
+                Some(value) => { derived.set(value); }
+                None => { derived.clear(); }
+            }
+            true
+        });
+
+        recompute();
+        let r = recompute.clone();
+        let a_set = a.on_set_while(move |_| r());
+        let r = recompute.clone();
+        let a_clear = a.on_clear_while(move || r());
+        let r = recompute.clone();
+        let b_set = b.on_set_while(move |_| r());
+        let b_clear = b.on_clear_while(move || recompute());
+
+        let weak_a = a.downgrade();
+        let weak_b = b.downgrade();
+        derived.add_cleanup(move || {
+            if let Ok(a) = weak_a.try_upgrade() {
+                a.unsubscribe(a_set);
+                a.unsubscribe(a_clear);
+            }
+            if let Ok(b) = weak_b.try_upgrade() {
+                b.unsubscribe(b_set);
+                b.unsubscribe(b_clear);
+            }
+        });
+
+        return derived;
+    }
+
+    ///
+    /// Keeps this cell and the other cell synchronized until the returned Binding is dropped.
+    /// Storing a value in either cell stores a clone of it in the other cell, clearing either cell clears the other cell.
+    /// The other cell is updated to the state of this cell right away.
+    ///
+    /// The changes made by the synchronization itself are not propagated back.
+    /// The Binding only references both cells weakly.
+    ///
+    pub fn bind(&self, other: &RcOCell<T>) -> Binding
+        where T: Clone
+    {
+        return self.bind_map(other, T::clone, T::clone);
+    }
+
+    ///
+    /// Keeps this cell and the other cell synchronized until the returned Binding is dropped.
+    /// Storing a value in this cell stores the value converted by `to` in the other cell,
+    /// storing a value in the other cell stores the value converted by `from` in this cell.
+    /// Clearing either cell clears the other cell.
+    /// The other cell is updated to the state of this cell right away.
+    ///
+    /// The changes made by the synchronization itself are not propagated back.
+    /// The Binding only references both cells weakly.
+    ///
+    pub fn bind_map<U, TO, FROM>(&self, other: &RcOCell<U>, to: TO, from: FROM) -> Binding
+        where U: 'static, TO: Fn(&T) -> U + 'static, FROM: Fn(&U) -> T + 'static
+    {
+        match self.map(&to) {
+            Some(value) => { other.set(value); }
+            None => { other.clear(); }
+        }
+
+        let flag = Rc::new(Cell::new(false));
+        let (self_set, self_clear) = propagate(self, other, &flag, to);
+        let (other_set, other_clear) = propagate(other, self, &flag, from);
+
+        let this = self.downgrade();
+        let other_cleanup = other.add_cleanup(move || {
+            if let Ok(this) = this.try_upgrade() {
+                this.unsubscribe(self_set);
+                this.unsubscribe(self_clear);
+            }
+        });
+        let weak_other = other.downgrade();
+        let self_cleanup = self.add_cleanup(move || {
+            if let Ok(other) = weak_other.try_upgrade() {
+                other.unsubscribe(other_set);
+                other.unsubscribe(other_clear);
+            }
+        });
+
+        let this = self.downgrade();
+        let other = other.downgrade();
+        return Binding {
+            unbind: Some(Box::new(move || {
+                if let Ok(this) = this.try_upgrade() {
+                    this.unsubscribe(self_set);
+                    this.unsubscribe(self_clear);
+                    this.remove_cleanup(self_cleanup);
+                }
+                if let Ok(other) = other.try_upgrade() {
+                    other.unsubscribe(other_set);
+                    other.unsubscribe(other_clear);
+                    other.remove_cleanup(other_cleanup);
+                }
+            })),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+    use crate::*;
+    use crate::shared;
+
+    #[test]
+    fn test_observers() {
+        let x = RcOCell::from_value(1u32);
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let l = log.clone();
+        let set = x.on_set(move |v| l.borrow_mut().push(Some(*v)));
+        let l = log.clone();
+        x.on_clear(move || l.borrow_mut().push(None));
+        x.set(2);
+        x.clear();
+        x.compute(|_| RcOCellComputeResult::Replace(3));
+        x.replace(4);
+        assert_eq!(x.unsubscribe(set), true);
+        assert_eq!(x.unsubscribe(set), false);
+        x.set(5);
+        x.get_and_clear();
+        assert_eq!(*log.borrow(), vec![Some(2), None, Some(3), Some(4), None]);
+    }
+
+    #[test]
+    fn test_derive() {
+        let count = RcOCell::from_value(1u32);
+        let label = count.derive(|n| format!("{n} items"));
+        assert_eq!(label.borrow().as_str(), "1 items");
+        count.set(5);
+        assert_eq!(label.borrow().as_str(), "5 items");
+        count.clear();
+        assert_eq!(label.is_none(), true);
+        count.set(2);
+        assert_eq!(label.borrow().as_str(), "2 items");
+    }
+
+    #[test]
+    fn test_derive_order() {
+        let source = RcOCell::from_value(1u32);
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let l = log.clone();
+        source.on_set(move |v| l.borrow_mut().push(format!("first {v}")));
+        let doubled = source.derive(|n| n * 2);
+        let l = log.clone();
+        doubled.on_set(move |v| l.borrow_mut().push(format!("doubled {v}")));
+        let text = doubled.derive(|n| format!("{n}"));
+        let l = log.clone();
+        text.on_set(move |v| l.borrow_mut().push(format!("text {v}")));
+        let l = log.clone();
+        source.on_set(move |v| l.borrow_mut().push(format!("last {v}")));
+
+        source.set(3);
+        assert_eq!(*log.borrow(), vec!["first 3", "doubled 6", "text 6", "last 3"]);
+        assert_eq!(text.borrow().as_str(), "6");
+    }
+
+    #[test]
+    fn test_derive_no_leak() {
+        let source = RcOCell::from_value(1u32);
+        let payload = Rc::new(());
+        let p = payload.clone();
+        let derived = source.derive(move |n| (*n, p.clone()));
+        assert_eq!(source.observer_count(), 2);
+        assert_eq!(Rc::strong_count(&payload), 3);
+        let weak = derived.downgrade();
+        drop(derived);
+        assert_eq!(weak.try_upgrade().is_err(), true);
+        assert_eq!(source.observer_count(), 0);
+        assert_eq!(Rc::strong_count(&payload), 1);
+        source.set(2);
+    }
+
+    #[test]
+    fn test_derive2() {
+        let a = RcOCell::from_value(2u32);
+        let b = RcOCell::from_value("x".to_string());
+        let c = RcOCell::derive2(&a, &b, |n, s| s.repeat(*n as usize));
+        assert_eq!(c.borrow().as_str(), "xx");
+        a.set(3);
+        assert_eq!(c.borrow().as_str(), "xxx");
+        b.set("y".to_string());
+        assert_eq!(c.borrow().as_str(), "yyy");
+        b.clear();
+        assert_eq!(c.is_none(), true);
+        b.set("z".to_string());
+        assert_eq!(c.borrow().as_str(), "zzz");
+        drop(c);
+        assert_eq!(a.observer_count(), 0);
+        assert_eq!(b.observer_count(), 0);
+    }
+
+    #[test]
+    fn test_bind() {
+        let a = RcOCell::from_value(1u32);
+        let b = RcOCell::new();
+        let count = Rc::new(Cell::new(0));
+        let c = count.clone();
+        b.on_set(move |_| c.set(c.get() + 1));
+        let binding = a.bind(&b);
+        assert_eq!(b.get_and_clone(), 1);
+        a.set(2);
+        assert_eq!(b.get_and_clone(), 2);
+        b.set(3);
+        assert_eq!(a.get_and_clone(), 3);
+        assert_eq!(count.get(), 3);
+        a.clear();
+        assert_eq!(b.is_none(), true);
+        b.set(4);
+        assert_eq!(a.get_and_clone(), 4);
+
+        drop(binding);
+        assert_eq!(a.observer_count(), 0);
+        assert_eq!(b.observer_count(), 1);
+        a.set(5);
+        assert_eq!(b.get_and_clone(), 4);
+        b.clear();
+        assert_eq!(a.get_and_clone(), 5);
+    }
+
+    #[test]
+    fn test_bind_map() {
+        let number = RcOCell::from_value(1u32);
+        let text = RcOCell::new();
+        let binding = number.bind_map(&text, |n| n.to_string(), |s| s.parse().unwrap_or(0));
+        assert_eq!(text.borrow().as_str(), "1");
+        text.set("42".to_string());
+        assert_eq!(number.get_and_clone(), 42);
+        number.set(7);
+        assert_eq!(text.borrow().as_str(), "7");
+        binding.unbind();
+        number.set(8);
+        assert_eq!(text.borrow().as_str(), "7");
+    }
+
+    #[test]
+    fn test_bind_weak() {
+        let a = RcOCell::from_value(1u32);
+        let b = RcOCell::new();
+        let _binding = a.bind(&b);
+        let weak = b.downgrade();
+        drop(b);
+        assert_eq!(weak.try_upgrade().is_err(), true);
+        a.set(2);
+        assert_eq!(a.observer_count(), 0);
+    }
+
+    #[test]
+    fn test_bind_repeated() {
+        let a = RcOCell::from_value(0u32);
+        let b = RcOCell::new();
+        let cleanups = |cell: &RcOCell<u32>| shared::with_existing_state(&cell.rc, |state| state.cleanups.len()).unwrap_or(0);
+        for i in 1..100 {
+            let binding = a.bind(&b);
+            a.set(i);
+            assert_eq!(b.get_and_clone(), i);
+            assert_eq!(cleanups(&a), 1);
+            assert_eq!(cleanups(&b), 1);
+            if i % 2 == 0 {
+                binding.unbind();
+            } else {
+                drop(binding);
+            }
+        }
+
+        assert_eq!(a.observer_count(), 0);
+        assert_eq!(b.observer_count(), 0);
+        assert_eq!(cleanups(&a), 0);
+        assert_eq!(cleanups(&b), 0);
+    }
+
+    #[test]
+    fn test_on_set_weak() {
+        let source = RcOCell::from_value(0u32);
+        let target = RcOCell::from_value(Vec::new());
+        source.on_set_weak(&target.downgrade(), |target, value| target.borrow_mut().push(*value));
+        source.on_set(|_| {});
+        assert_eq!(Rc::strong_count(&target.rc), 1);
+        assert_eq!(source.observer_count(), 2);
+
+        source.set(1);
+        source.set(2);
+        assert_eq!(target.get_and_clone(), vec![1, 2]);
+
+        drop(target);
+        assert_eq!(source.observer_count(), 2);
+        source.set(3);
+        assert_eq!(source.observer_count(), 1);
+    }
+
+    #[test]
+    fn test_on_set_while_alive() {
+        let source = RcOCell::from_value(0u32);
+        let anchor = Rc::new(RefCell::new(Vec::new()));
+        let other = Rc::new(());
+        source.on_set_while_alive(&anchor, |anchor, value| anchor.borrow_mut().push(*value));
+        source.on_set_while_alive(&other, |_, _| {});
+        assert_eq!(Rc::strong_count(&anchor), 1);
+
+        source.set(1);
+        assert_eq!(*anchor.borrow(), vec![1]);
+
+        let seen = anchor.clone();
+        drop(anchor);
+        assert_eq!(source.observer_count(), 2);
+        source.set(2);
+        assert_eq!(*seen.borrow(), vec![1, 2]);
+        drop(seen);
+        source.set(3);
+        assert_eq!(source.observer_count(), 1);
+        drop(other);
+        source.clear();
+        assert_eq!(source.observer_count(), 1);
+        source.set(4);
+        assert_eq!(source.observer_count(), 0);
+    }
+}
@@ -0,0 +1,199 @@
+use crate::{RcOCell, RcOCellError, RcORefMut, WeakRcOCell};
+
+impl <T: Ord> RcOCell<T> {
+    ///
+    /// Stores the candidate if it beats the current value, returns the replaced value (None if the cell was empty).
+    /// Hands the candidate back as Err if it does not beat the current value.
+    ///
+    fn store_if<F>(&self, mut value: RcORefMut<'_, Option<T>, T>, candidate: T, beats: F) -> Result<Option<T>, T>
+        where F: FnOnce(&T, &T) -> bool
+    {
+        if let Some(current) = value.as_ref() {
+            if !beats(&candidate, current) {
+                return Err(candidate);
+            }
+        }
+
+        let old = value.replace(candidate);
+        drop(value);
+        self.changed();
+        return Ok(old);
+    }
+
+    ///
+    /// Stores the candidate if the cell is empty or the candidate is greater than the value.
+    /// Returns true if the candidate was stored, an equal candidate is not stored.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    pub fn update_max(&self, candidate: T) -> bool {
+        return self.update_max_returning(candidate).is_ok();
+    }
+
+    ///
+    /// Stores the candidate if the cell is empty or the candidate is greater than the value.
+    /// Returns true if the candidate was stored, an equal candidate is not stored.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    pub fn try_update_max(&self, candidate: T) -> Result<bool, RcOCellError> {
+        return Ok(self.store_if(self.try_raw_borrow_mut()?, candidate, |c, v| c > v).is_ok());
+    }
+
+    ///
+    /// Stores the candidate if the cell is empty or the candidate is greater than the value.
+    /// Returns Ok with the replaced value if the candidate was stored or Err with the candidate if it was not stored.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    pub fn update_max_returning(&self, candidate: T) -> Result<Option<T>, T> {
+        return self.store_if(self.raw_borrow_mut(), candidate, |c, v| c > v);
+    }
+
+    ///
+    /// Stores the candidate if the cell is empty or the candidate is less than the value.
+    /// Returns true if the candidate was stored, an equal candidate is not stored.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    pub fn update_min(&self, candidate: T) -> bool {
+        return self.update_min_returning(candidate).is_ok();
+    }
+
+    ///
+    /// Stores the candidate if the cell is empty or the candidate is less than the value.
+    /// Returns true if the candidate was stored, an equal candidate is not stored.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    pub fn try_update_min(&self, candidate: T) -> Result<bool, RcOCellError> {
+        return Ok(self.store_if(self.try_raw_borrow_mut()?, candidate, |c, v| c < v).is_ok());
+    }
+
+    ///
+    /// Stores the candidate if the cell is empty or the candidate is less than the value.
+    /// Returns Ok with the replaced value if the candidate was stored or Err with the candidate if it was not stored.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    pub fn update_min_returning(&self, candidate: T) -> Result<Option<T>, T> {
+        return self.store_if(self.raw_borrow_mut(), candidate, |c, v| c < v);
+    }
+}
+
+impl <T: Ord> WeakRcOCell<T> {
+    ///
+    /// Stores the candidate if the cell is empty or the candidate is greater than the value.
+    /// Returns true if the candidate was stored, an equal candidate is not stored.
+    /// Panics if the value is borrowed somewhere or the cell was already dropped.
+    ///
+    pub fn update_max(&self, candidate: T) -> bool {
+        self.try_upgrade()
+            .expect("WeakRcOCell::update_max called on a dropped cell")
+            .update_max(candidate)
+    }
+
+    ///
+    /// Stores the candidate if the cell is empty or the candidate is greater than the value.
+    /// Returns true if the candidate was stored, an equal candidate is not stored.
+    /// Fails if the value is borrowed somewhere or the cell was already dropped.
+    ///
+    pub fn try_update_max(&self, candidate: T) -> Result<bool, RcOCellError> {
+        self.try_upgrade()?.try_update_max(candidate)
+    }
+
+    ///
+    /// Stores the candidate if the cell is empty or the candidate is greater than the value.
+    /// Returns Ok with the replaced value if the candidate was stored or Err with the candidate if it was not stored.
+    /// Panics if the value is borrowed somewhere or the cell was already dropped.
+    ///
+    pub fn update_max_returning(&self, candidate: T) -> Result<Option<T>, T> {
+        self.try_upgrade()
+            .expect("WeakRcOCell::update_max_returning called on a dropped cell")
+            .update_max_returning(candidate)
+    }
+
+    ///
+    /// Stores the candidate if the cell is empty or the candidate is less than the value.
+    /// Returns true if the candidate was stored, an equal candidate is not stored.
+    /// Panics if the value is borrowed somewhere or the cell was already dropped.
+    ///
+    pub fn update_min(&self, candidate: T) -> bool {
+        self.try_upgrade()
+            .expect("WeakRcOCell::update_min called on a dropped cell")
+            .update_min(candidate)
+    }
+
+    ///
+    /// Stores the candidate if the cell is empty or the candidate is less than the value.
+    /// Returns true if the candidate was stored, an equal candidate is not stored.
+    /// Fails if the value is borrowed somewhere or the cell was already dropped.
+    ///
+    pub fn try_update_min(&self, candidate: T) -> Result<bool, RcOCellError> {
+        self.try_upgrade()?.try_update_min(candidate)
+    }
+
+    ///
+    /// Stores the candidate if the cell is empty or the candidate is less than the value.
+    /// Returns Ok with the replaced value if the candidate was stored or Err with the candidate if it was not stored.
+    /// Panics if the value is borrowed somewhere or the cell was already dropped.
+    ///
+    pub fn update_min_returning(&self, candidate: T) -> Result<Option<T>, T> {
+        self.try_upgrade()
+            .expect("WeakRcOCell::update_min_returning called on a dropped cell")
+            .update_min_returning(candidate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+    struct Ticket(u32);
+
+    #[test]
+    fn test_update_max() {
+        let x: RcOCell<u32> = RcOCell::new();
+        assert_eq!(x.update_max(5), true);
+        assert_eq!(x.update_max(3), false);
+        assert_eq!(x.update_max(5), false);
+        assert_eq!(x.update_max(7), true);
+        assert_eq!(x.get_and_clone(), 7);
+    }
+
+    #[test]
+    fn test_update_min() {
+        let x: RcOCell<u32> = RcOCell::new();
+        assert_eq!(x.update_min(5), true);
+        assert_eq!(x.update_min(7), false);
+        assert_eq!(x.update_min(5), false);
+        assert_eq!(x.update_min(3), true);
+        assert_eq!(x.get_and_clone(), 3);
+    }
+
+    #[test]
+    fn test_update_returning() {
+        let x: RcOCell<Ticket> = RcOCell::new();
+        assert_eq!(x.update_max_returning(Ticket(2)), Ok(None));
+        assert_eq!(x.update_max_returning(Ticket(1)), Err(Ticket(1)));
+        assert_eq!(x.update_max_returning(Ticket(2)), Err(Ticket(2)));
+        assert_eq!(x.update_max_returning(Ticket(3)), Ok(Some(Ticket(2))));
+        assert_eq!(x.update_min_returning(Ticket(4)), Err(Ticket(4)));
+        assert_eq!(x.update_min_returning(Ticket(1)), Ok(Some(Ticket(3))));
+        assert_eq!(*x.borrow(), Ticket(1));
+    }
+
+    #[test]
+    fn test_try_update() {
+        let x = RcOCell::from_value(1u32);
+        let brw = x.borrow();
+        assert_eq!(x.try_update_max(2).is_err(), true);
+        assert_eq!(x.try_update_min(0).is_err(), true);
+        drop(brw);
+        assert_eq!(x.try_update_max(2).unwrap(), true);
+        assert_eq!(x.try_update_min(3).unwrap(), false);
+
+        let w = x.downgrade();
+        assert_eq!(w.update_min(0), true);
+        drop(x);
+        match w.try_update_max(1) {
+            Err(RcOCellError::Dropped) => {}
+            _ => panic!("unexpected"),
+        };
+    }
+}
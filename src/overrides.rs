@@ -0,0 +1,246 @@
+use std::fmt::{Debug, Formatter};
+use crate::{shared, RcOCell, RcOCellError};
+
+///
+/// Restores the previous contents of a cell when dropped, see RcOCell::override_with.
+///
+#[must_use = "the previous contents are restored when the guard is dropped"]
+pub struct OverrideGuard<T> {
+    cell: RcOCell<T>,
+    previous: Option<Option<T>>,
+    depth: usize,
+    version: u64,
+    version_before: u64,
+}
+
+impl <T> RcOCell<T> {
+    ///
+    /// Stores the value in the cell until the returned guard is dropped,
+    /// the guard then restores the previous contents, including an empty cell.
+    /// This also happens if the guard is dropped while a panic unwinds,
+    /// unless the value is still borrowed or the panic poisoned the cell, the cell is then left as it is.
+    ///
+    /// Overrides nest like push_scope, guards have to be dropped in reverse order of their creation.
+    /// Unlike push_scope the previous contents are not restored if the cell was modified by other code
+    /// while the override was active, see OverrideGuard::was_clobbered.
+    /// Modifications are detected for all methods that notify observers, in place modifications through borrow_mut are not.
+    ///
+    /// Panics if the value is borrowed somewhere.
+    ///
+    pub fn override_with(&self, value: T) -> OverrideGuard<T> {
+        return self.push_override(Some(value));
+    }
+
+    ///
+    /// Clears the cell until the returned guard is dropped, see override_with.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    pub fn override_clear(&self) -> OverrideGuard<T> {
+        return self.push_override(None);
+    }
+
+    ///
+    /// Stores the value in the cell until the returned guard is dropped, see override_with.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    pub fn try_override_with(&self, value: T) -> Result<OverrideGuard<T>, RcOCellError> {
+        self.check_mut()?;
+        return Ok(self.push_override(Some(value)));
+    }
+
+    ///
+    /// Clears the cell until the returned guard is dropped, see override_with.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    pub fn try_override_clear(&self) -> Result<OverrideGuard<T>, RcOCellError> {
+        self.check_mut()?;
+        return Ok(self.push_override(None));
+    }
+
+    fn push_override(&self, value: Option<T>) -> OverrideGuard<T> {
+        let (depth, version_before) = shared::with_state(&self.rc, |state| {
+            state.scope_depth += 1;
+            (state.scope_depth, state.version)
+        });
+
+        let previous = match value {
+            Some(value) => self.set(value),
+            None => self.clear(),
+        };
+
+        return OverrideGuard {
+            cell: self.clone(),
+            previous: Some(previous),
+            depth,
+            version: self.version(),
+            version_before,
+        };
+    }
+
+    fn version(&self) -> u64 {
+        return shared::with_existing_state(&self.rc, |state| state.version).unwrap_or(0);
+    }
+}
+
+impl <T> OverrideGuard<T> {
+    ///
+    /// Returns the cell the override applies to.
+    ///
+    pub fn cell(&self) -> &RcOCell<T> {
+        return &self.cell;
+    }
+
+    ///
+    /// Returns the value the cell held before the override.
+    ///
+    pub fn previous(&self) -> Option<&T> {
+        return self.previous.as_ref().and_then(|p| p.as_ref());
+    }
+
+    ///
+    /// Returns true if the cell was modified by other code since the override,
+    /// dropping the guard then leaves the cell as it is.
+    /// Overrides nested inside this one that were dropped again do not count.
+    ///
+    pub fn was_clobbered(&self) -> bool {
+        return self.cell.version() != self.version;
+    }
+}
+
+impl <T> Drop for OverrideGuard<T> {
+    fn drop(&mut self) {
+        let in_order = shared::with_existing_state(&self.cell.rc, |state| {
+            if state.scope_depth != self.depth {
+                return false;
+            }
+            state.scope_depth -= 1;
+            true
+        }).unwrap_or(false);
+
+        if !in_order {
+            if std::thread::panicking() {
+                return;
+            }
+            panic!("OverrideGuard dropped while an override that was made after it is still active");
+        }
+
+        let previous = self.previous.take().unwrap();
+        if self.was_clobbered() {
+            return;
+        }
+
+        if std::thread::panicking() && (self.cell.check_poison().is_err() || self.cell.rc.try_borrow_mut().is_err()) {
+            return;
+        }
+
+        match previous {
+            Some(value) => { self.cell.set(value); }
+            None => { self.cell.clear(); }
+        }
+
+        //The cell is back in the state it had before the override, so an enclosing override is not clobbered.
+        let version_before = self.version_before;
+        shared::with_existing_state(&self.cell.rc, |state| state.version = version_before);
+    }
+}
+
+impl <T: Debug> Debug for OverrideGuard<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OverrideGuard")
+            .field("previous", &self.previous())
+            .field("clobbered", &self.was_clobbered())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_override_nested() {
+        let theme = RcOCell::from_value("light");
+        let a = theme.override_with("dark");
+        {
+            let b = theme.override_clear();
+            assert_eq!(b.previous(), Some(&"dark"));
+            assert_eq!(theme.is_none(), true);
+            {
+                let _c = theme.override_with("contrast");
+                assert_eq!(theme.get_and_clone(), "contrast");
+            }
+            assert_eq!(theme.is_none(), true);
+            assert_eq!(b.was_clobbered(), false);
+        }
+        assert_eq!(theme.get_and_clone(), "dark");
+        assert_eq!(a.was_clobbered(), false);
+        drop(a);
+        assert_eq!(theme.get_and_clone(), "light");
+    }
+
+    #[test]
+    fn test_override_panic() {
+        let theme = RcOCell::from_value("light");
+        let r = theme.catch(|theme| {
+            let _guard = theme.override_with("dark");
+            panic!("Oh no");
+        });
+        assert_eq!(r.is_err(), true);
+        assert_eq!(theme.get_and_clone(), "light");
+
+        let empty: RcOCell<u32> = RcOCell::new();
+        let r = empty.catch(|empty| {
+            let _guard = empty.override_with(1);
+            panic!("Oh no");
+        });
+        assert_eq!(r.is_err(), true);
+        assert_eq!(empty.is_none(), true);
+    }
+
+    #[test]
+    fn test_override_clobbered() {
+        let theme = RcOCell::from_value("light");
+        let guard = theme.override_with("dark");
+        theme.set("sepia");
+        assert_eq!(guard.was_clobbered(), true);
+        drop(guard);
+        assert_eq!(theme.get_and_clone(), "sepia");
+
+        let guard = theme.override_with("dark");
+        *theme.borrow_mut() = "in place";
+        assert_eq!(guard.was_clobbered(), false);
+        drop(guard);
+        assert_eq!(theme.get_and_clone(), "sepia");
+    }
+
+    #[test]
+    fn test_try_override() {
+        let theme = RcOCell::from_value("light");
+        let brw = theme.borrow();
+        assert_eq!(theme.try_override_with("dark").is_err(), true);
+        assert_eq!(theme.try_override_clear().is_err(), true);
+        drop(brw);
+        let guard = theme.try_override_clear().unwrap();
+        assert_eq!(theme.is_none(), true);
+        drop(guard);
+        assert_eq!(theme.get_and_clone(), "light");
+    }
+
+    #[cfg(feature = "poison")]
+    #[test]
+    fn test_override_poisoned() {
+        let theme = RcOCell::from_value("light");
+        let r = theme.catch(|theme| {
+            let _guard = theme.override_with("dark");
+            let mut brw = theme.borrow_mut();
+            *brw = "half";
+            panic!("Oh no");
+        });
+        assert_eq!(r.is_err(), true);
+        assert_eq!(theme.is_poisoned(), true);
+        theme.clear_poison();
+        assert_eq!(theme.get_and_clone(), "half");
+        drop(theme.override_clear());
+        assert_eq!(theme.get_and_clone(), "half");
+    }
+}
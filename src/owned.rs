@@ -0,0 +1,351 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::ptr::NonNull;
+use crate::{RcOCell, RcOCellError, RcORef, RcORefMut, WeakRcOCell};
+
+///
+/// Borrow of the value of a cell that owns a handle of the cell, returned by borrow_owned.
+///
+/// Unlike RcORef the guard does not borrow the cell it was created from, it can be returned from functions
+/// and stored in structs. The value stays alive while the guard exists, even if all other handles are dropped.
+/// WeakRcOCell::borrow returns this guard too, the strong handle of the cell is held exactly as long as the guard.
+/// Otherwise it behaves like RcORef, the borrow ends when the guard is dropped.
+///
+pub struct OwnedRef<T: 'static> {
+    guard: RcORef<'static, T>,
+    handle: Handle<T>,
+}
+
+///
+/// Mutable borrow of the value of a cell that owns a handle of the cell, returned by borrow_mut_owned.
+///
+/// Unlike RcORefMut the guard does not borrow the cell it was created from, see OwnedRef.
+/// Otherwise it behaves like RcORefMut, the borrow ends when the guard is dropped.
+///
+pub struct OwnedRefMut<T: 'static> {
+    guard: RcORefMut<'static, T>,
+    handle: Handle<T>,
+}
+
+///
+/// Handle of the cell in its own allocation, so borrows of it stay valid while the guard moves.
+///
+/// The guards borrow the handle for 'static although it only lives as long as the guard.
+/// This is sound because the borrow never leaves the guard, the allocation never moves
+/// and the guard field is declared before the handle field, so the borrow is released before the handle is freed.
+/// The handle is a strong reference, so the RefCell the borrow points into is alive for the whole time as well.
+///
+struct Handle<T>(NonNull<RcOCell<T>>);
+
+impl <T> Handle<T> {
+    fn new(cell: RcOCell<T>) -> Handle<T> {
+        return Handle(NonNull::from(Box::leak(Box::new(cell))));
+    }
+
+    ///
+    /// Safety: the returned reference must not outlive the handle.
+    ///
+    unsafe fn get(&self) -> &'static RcOCell<T> {
+        return self.0.as_ref();
+    }
+}
+
+impl <T> Drop for Handle<T> {
+    fn drop(&mut self) {
+        drop(unsafe { Box::from_raw(self.0.as_ptr()) });
+    }
+}
+
+impl <T: 'static> RcOCell<T> {
+    ///
+    /// Borrows the value, the guard keeps the cell alive and does not borrow this handle.
+    /// Panics if the value is already borrowed mutably somewhere or there is no value.
+    ///
+    #[track_caller]
+    pub fn borrow_owned(&self) -> OwnedRef<T> {
+        let handle = Handle::new(self.clone());
+        let guard = unsafe { handle.get() }.borrow();
+        return OwnedRef {guard, handle};
+    }
+
+    ///
+    /// Borrows the value, the guard keeps the cell alive and does not borrow this handle.
+    /// Fails if the value is already borrowed mutably somewhere or there is no value.
+    ///
+    #[cfg_attr(feature = "debug-borrow-tracking", track_caller)]
+    pub fn try_borrow_owned(&self) -> Result<OwnedRef<T>, RcOCellError> {
+        let handle = Handle::new(self.clone());
+        let guard = unsafe { handle.get() }.try_borrow()?;
+        return Ok(OwnedRef {guard, handle});
+    }
+
+    ///
+    /// Borrows the value mutably, the guard keeps the cell alive and does not borrow this handle.
+    /// Panics if the value is already borrowed somewhere or there is no value.
+    ///
+    #[track_caller]
+    pub fn borrow_mut_owned(&self) -> OwnedRefMut<T> {
+        let handle = Handle::new(self.clone());
+        let guard = unsafe { handle.get() }.borrow_mut();
+        return OwnedRefMut {guard, handle};
+    }
+
+    ///
+    /// Borrows the value mutably, the guard keeps the cell alive and does not borrow this handle.
+    /// Fails if the value is already borrowed somewhere or there is no value.
+    ///
+    #[cfg_attr(feature = "debug-borrow-tracking", track_caller)]
+    pub fn try_borrow_mut_owned(&self) -> Result<OwnedRefMut<T>, RcOCellError> {
+        let handle = Handle::new(self.clone());
+        let guard = unsafe { handle.get() }.try_borrow_mut()?;
+        return Ok(OwnedRefMut {guard, handle});
+    }
+}
+
+impl <T: 'static> WeakRcOCell<T> {
+    ///
+    /// Borrows the value, the guard holds a strong handle of the cell until it is dropped.
+    /// Panics if the cell was dropped, the value is already borrowed mutably somewhere or there is no value.
+    ///
+    #[track_caller]
+    pub fn borrow(&self) -> OwnedRef<T> {
+        let handle = Handle::new(self.try_upgrade().expect("WeakRcOCell::borrow called on a dropped cell"));
+        let guard = unsafe { handle.get() }.borrow();
+        return OwnedRef {guard, handle};
+    }
+
+    ///
+    /// Borrows the value, the guard holds a strong handle of the cell until it is dropped.
+    /// Fails with RcOCellError::Dropped if the cell was dropped,
+    /// otherwise if the value is already borrowed mutably somewhere or there is no value.
+    ///
+    #[cfg_attr(feature = "debug-borrow-tracking", track_caller)]
+    pub fn try_borrow(&self) -> Result<OwnedRef<T>, RcOCellError> {
+        let handle = Handle::new(self.try_upgrade()?);
+        let guard = unsafe { handle.get() }.try_borrow()?;
+        return Ok(OwnedRef {guard, handle});
+    }
+
+    ///
+    /// Borrows the value mutably, the guard holds a strong handle of the cell until it is dropped.
+    /// Panics if the cell was dropped, the value is already borrowed somewhere or there is no value.
+    ///
+    #[track_caller]
+    pub fn borrow_mut(&self) -> OwnedRefMut<T> {
+        let handle = Handle::new(self.try_upgrade().expect("WeakRcOCell::borrow_mut called on a dropped cell"));
+        let guard = unsafe { handle.get() }.borrow_mut();
+        return OwnedRefMut {guard, handle};
+    }
+
+    ///
+    /// Borrows the value mutably, the guard holds a strong handle of the cell until it is dropped.
+    /// Fails with RcOCellError::Dropped if the cell was dropped,
+    /// otherwise if the value is already borrowed somewhere or there is no value.
+    ///
+    #[cfg_attr(feature = "debug-borrow-tracking", track_caller)]
+    pub fn try_borrow_mut(&self) -> Result<OwnedRefMut<T>, RcOCellError> {
+        let handle = Handle::new(self.try_upgrade()?);
+        let guard = unsafe { handle.get() }.try_borrow_mut()?;
+        return Ok(OwnedRefMut {guard, handle});
+    }
+
+    ///
+    /// Borrows the value, the guard holds a strong handle of the cell until it is dropped.
+    /// Panics with the given message followed by the reason if the cell was dropped,
+    /// the value is already borrowed mutably somewhere or there is no value.
+    ///
+    #[track_caller]
+    pub fn expect(&self, msg: &str) -> OwnedRef<T> {
+        let handle = Handle::new(self.expect_upgrade(msg));
+        let guard = unsafe { handle.get() }.expect(msg);
+        return OwnedRef {guard, handle};
+    }
+
+    ///
+    /// Borrows the value mutably, the guard holds a strong handle of the cell until it is dropped.
+    /// Panics with the given message followed by the reason if the cell was dropped,
+    /// the value is already borrowed somewhere or there is no value.
+    ///
+    #[track_caller]
+    pub fn expect_mut(&self, msg: &str) -> OwnedRefMut<T> {
+        let handle = Handle::new(self.expect_upgrade(msg));
+        let guard = unsafe { handle.get() }.expect_mut(msg);
+        return OwnedRefMut {guard, handle};
+    }
+}
+
+impl <T: 'static> OwnedRef<T> {
+    ///
+    /// Returns the cell the value belongs to.
+    ///
+    pub fn cell(orig: &OwnedRef<T>) -> &RcOCell<T> {
+        return unsafe { orig.handle.get() };
+    }
+}
+
+impl <T: 'static> OwnedRefMut<T> {
+    ///
+    /// Returns the cell the value belongs to.
+    ///
+    pub fn cell(orig: &OwnedRefMut<T>) -> &RcOCell<T> {
+        return unsafe { orig.handle.get() };
+    }
+}
+
+impl <T: 'static> Deref for OwnedRef<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        return &self.guard;
+    }
+}
+
+impl <T: 'static> Deref for OwnedRefMut<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        return &self.guard;
+    }
+}
+
+impl <T: 'static> DerefMut for OwnedRefMut<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        return &mut self.guard;
+    }
+}
+
+impl <T: Debug + 'static> Debug for OwnedRef<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return Debug::fmt(&*self.guard, f);
+    }
+}
+
+impl <T: Display + 'static> Display for OwnedRef<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return Display::fmt(&*self.guard, f);
+    }
+}
+
+impl <T: Debug + 'static> Debug for OwnedRefMut<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return Debug::fmt(&*self.guard, f);
+    }
+}
+
+impl <T: Display + 'static> Display for OwnedRefMut<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return Display::fmt(&*self.guard, f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn local_guard() -> OwnedRef<String> {
+        let cell = RcOCell::from_value("local".to_string());
+        return cell.borrow_owned();
+    }
+
+    struct Holder {
+        name: OwnedRefMut<String>,
+    }
+
+    #[test]
+    fn test_owned_returned() {
+        let guard = local_guard();
+        assert_eq!(&*guard, "local");
+        assert_eq!(OwnedRef::cell(&guard).is_some(), true);
+        assert_eq!(format!("{:?} {}", guard, guard), "\"local\" local");
+
+        let dropped = RcOCell::from_value(0u32);
+        let dropped2 = dropped.clone();
+        let cell = RcOCell::from_value(1u32);
+        cell.on_final_drop(move |_| { dropped2.set(1); });
+        let guard = cell.borrow_owned();
+        let weak = cell.downgrade();
+        drop(cell);
+        assert_eq!(*guard, 1);
+        assert_eq!(weak.is_some(), true);
+        assert_eq!(dropped.get_and_clone(), 0);
+        drop(guard);
+        assert_eq!(weak.try_upgrade().is_err(), true);
+        assert_eq!(dropped.get_and_clone(), 1);
+    }
+
+    #[test]
+    fn test_owned_stored() {
+        let cell = RcOCell::from_value("a".to_string());
+        let mut holder = Holder {name: cell.borrow_mut_owned()};
+        holder.name.push('b');
+        match cell.try_borrow() {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(cell.try_borrow_owned().is_err(), true);
+        assert_eq!(cell.catch(|c| c.borrow_owned()).is_err(), true);
+        drop(holder);
+        assert_eq!(cell.get_and_clone(), "ab");
+    }
+
+    #[test]
+    fn test_owned_conflicts() {
+        let cell = RcOCell::from_value(1u32);
+        let a = cell.borrow_owned();
+        let b = cell.try_borrow_owned().unwrap();
+        assert_eq!(cell.try_borrow_mut_owned().is_err(), true);
+        assert_eq!(cell.try_set(2).is_err(), true);
+        cell.set_deferred(2);
+        drop(a);
+        assert_eq!(*b, 1);
+        drop(b);
+        assert_eq!(cell.get_and_clone(), 2);
+
+        let mut guard = cell.try_borrow_mut_owned().unwrap();
+        *guard = 3;
+        assert_eq!(OwnedRefMut::cell(&guard).try_borrow().is_err(), true);
+        drop(guard);
+        assert_eq!(cell.get_and_clone(), 3);
+
+        cell.clear();
+        match cell.try_borrow_owned() {
+            Err(RcOCellError::NoValue) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(cell.try_borrow_mut_owned().is_err(), true);
+    }
+
+    #[test]
+    fn test_weak_borrow() {
+        let cell = RcOCell::from_value(vec![1u32]);
+        let weak = cell.downgrade();
+        weak.borrow_mut().push(2);
+        assert_eq!(*weak.borrow(), vec![1, 2]);
+
+        let guard = weak.try_borrow().unwrap();
+        match weak.try_borrow_mut() {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        drop(cell);
+        assert_eq!(*guard, vec![1, 2]);
+        assert_eq!(weak.is_some(), true);
+        assert_eq!(weak.try_borrow().unwrap().len(), 2);
+        drop(guard);
+
+        match weak.try_borrow() {
+            Err(RcOCellError::Dropped) => {}
+            _ => panic!("unexpected"),
+        };
+        match weak.try_borrow_mut() {
+            Err(RcOCellError::Dropped) => {}
+            _ => panic!("unexpected"),
+        };
+
+        let cell: RcOCell<u32> = RcOCell::new();
+        match cell.downgrade().try_borrow() {
+            Err(RcOCellError::NoValue) => {}
+            _ => panic!("unexpected"),
+        };
+    }
+}
@@ -0,0 +1,205 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::str::FromStr;
+use crate::{RcOCell, RcOCellError};
+
+///
+/// Error of the setters that parse their value, separates text that could not be parsed from a cell that could not be accessed.
+/// The cell was not changed if this error is returned.
+///
+pub enum ParseSetError<E> {
+    ///
+    /// The text could not be parsed, contains the error of FromStr.
+    ///
+    Parse(E),
+    ///
+    /// The text was parsed but the cell could not be accessed, the parsed value was dropped.
+    ///
+    Cell(RcOCellError),
+}
+
+impl <E: Debug> Debug for ParseSetError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            ParseSetError::Parse(e) => write!(f, "Parse({:?})", e),
+            ParseSetError::Cell(e) => write!(f, "Cell({:?})", e),
+        };
+    }
+}
+
+impl <E: Display> Display for ParseSetError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            ParseSetError::Parse(e) => write!(f, "Invalid value: {}", e),
+            ParseSetError::Cell(e) => Display::fmt(e, f),
+        };
+    }
+}
+
+impl <E> From<RcOCellError> for ParseSetError<E> {
+    fn from(value: RcOCellError) -> Self {
+        return ParseSetError::Cell(value);
+    }
+}
+
+impl <T: FromStr> RcOCell<T> {
+    ///
+    /// Constructs a new RcOCell with the value parsed from the text.
+    /// Fails with the error of FromStr if the text cannot be parsed.
+    ///
+    pub fn parse_from(s: &str) -> Result<RcOCell<T>, T::Err> {
+        return Ok(RcOCell::from_value(s.parse()?));
+    }
+
+    ///
+    /// Parses the text and sets the value, returning the old value (if an old value existed).
+    /// Fails with ParseSetError::Parse without touching the cell if the text cannot be parsed.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    #[track_caller]
+    pub fn set_parsed(&self, s: &str) -> Result<Option<T>, ParseSetError<T::Err>> {
+        let value = s.parse().map_err(ParseSetError::Parse)?;
+        return Ok(self.set(value));
+    }
+
+    ///
+    /// Parses the text and sets the value, returning the old value (if an old value existed).
+    /// Fails with ParseSetError::Parse if the text cannot be parsed
+    /// and with ParseSetError::Cell if the value is borrowed somewhere, the cell is not changed in either case.
+    ///
+    pub fn try_set_parsed(&self, s: &str) -> Result<Option<T>, ParseSetError<T::Err>> {
+        let value = s.parse().map_err(ParseSetError::Parse)?;
+        return Ok(self.try_set(value)?);
+    }
+}
+
+impl <T: FromStr + Display> RcOCell<T> {
+    ///
+    /// Calls the Fn with the current value formatted with Display, or an empty String if there is no value,
+    /// then parses the text the Fn returns and sets the value, returning the old value (if an old value existed).
+    /// This is meant for edit in place, i.e. a text field that starts with the current value.
+    /// The value is not borrowed while the Fn runs, so the Fn may access the cell.
+    ///
+    /// Fails with ParseSetError::Parse without touching the cell if the text cannot be parsed.
+    /// Panics if the value is borrowed mutably somewhere, or borrowed at all when the parsed value is set.
+    ///
+    #[track_caller]
+    pub fn compute_parsed<F>(&self, f: F) -> Result<Option<T>, ParseSetError<T::Err>>
+        where F: FnOnce(String) -> String
+    {
+        let text = self.raw_borrow().as_ref().map(ToString::to_string).unwrap_or_default();
+        return self.set_parsed(&f(text));
+    }
+
+    ///
+    /// Calls the Fn with the current value as text and sets the value parsed from the text it returns, see compute_parsed.
+    /// Fails with ParseSetError::Cell without calling the Fn if the value is borrowed mutably somewhere,
+    /// with ParseSetError::Parse if the text cannot be parsed
+    /// and with ParseSetError::Cell if the value is borrowed at all when the parsed value is set.
+    ///
+    pub fn try_compute_parsed<F>(&self, f: F) -> Result<Option<T>, ParseSetError<T::Err>>
+        where F: FnOnce(String) -> String
+    {
+        let text = self.try_raw_borrow()?.as_ref().map(ToString::to_string).unwrap_or_default();
+        return self.try_set_parsed(&f(text));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::{Display, Formatter};
+    use std::num::ParseIntError;
+    use std::str::FromStr;
+    use crate::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Point(i32, i32);
+
+    impl FromStr for Point {
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            let Some((x, y)) = s.split_once(',') else {
+                return Err(format!("missing ',' in {:?}", s));
+            };
+
+            let x = x.trim().parse().map_err(|e: ParseIntError| e.to_string())?;
+            let y = y.trim().parse().map_err(|e: ParseIntError| e.to_string())?;
+            return Ok(Point(x, y));
+        }
+    }
+
+    impl Display for Point {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            return write!(f, "{},{}", self.0, self.1);
+        }
+    }
+
+    #[test]
+    fn test_set_parsed_int() {
+        let port: RcOCell<u16> = RcOCell::parse_from("8080").unwrap();
+        assert_eq!(port.get_and_clone(), 8080);
+        assert_eq!(RcOCell::<u16>::parse_from("80a").is_err(), true);
+
+        assert_eq!(port.set_parsed("443").unwrap(), Some(8080));
+        match port.set_parsed("70000") {
+            Err(ParseSetError::Parse(e)) => assert_eq!(e.to_string(), "number too large to fit in target type"),
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(port.get_and_clone(), 443);
+
+        port.clear();
+        assert_eq!(port.try_set_parsed(" 1").is_err(), true);
+        assert_eq!(port.try_set_parsed("1").unwrap(), None);
+        assert_eq!(port.get_and_clone(), 1);
+    }
+
+    #[test]
+    fn test_set_parsed_custom() {
+        let pos = RcOCell::<Point>::parse_from("1, 2").unwrap();
+        match RcOCell::<Point>::parse_from("1 2") {
+            Err(e) => assert_eq!(e, "missing ',' in \"1 2\""),
+            _ => panic!("unexpected"),
+        };
+
+        let brw = pos.borrow();
+        match pos.try_set_parsed("3,4") {
+            Err(ParseSetError::Cell(RcOCellError::BorrowError(_))) => {}
+            _ => panic!("unexpected"),
+        };
+        match pos.try_set_parsed("3,x") {
+            Err(ParseSetError::Parse(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(pos.catch(|p| { let _ = p.set_parsed("3,4"); }).is_err(), true);
+        drop(brw);
+        assert_eq!(pos.get_and_clone(), Point(1, 2));
+
+        let err = pos.try_set_parsed("x").unwrap_err();
+        assert_eq!(format!("{}", err), "Invalid value: missing ',' in \"x\"");
+    }
+
+    #[test]
+    fn test_compute_parsed() {
+        let pos = RcOCell::from_value(Point(1, 2));
+        let old = pos.compute_parsed(|text| {
+            assert_eq!(text, "1,2");
+            return text.replace('2', "5");
+        });
+        assert_eq!(old.unwrap(), Some(Point(1, 2)));
+        assert_eq!(pos.get_and_clone(), Point(1, 5));
+
+        assert_eq!(pos.try_compute_parsed(|_| String::from("oops")).is_err(), true);
+        assert_eq!(pos.get_and_clone(), Point(1, 5));
+
+        let empty: RcOCell<u32> = RcOCell::new();
+        empty.compute_parsed(|text| format!("{}7", text)).unwrap();
+        assert_eq!(empty.get_and_clone(), 7);
+
+        let brw = empty.borrow_mut();
+        match empty.try_compute_parsed(|_| panic!("unexpected")) {
+            Err(ParseSetError::Cell(RcOCellError::BorrowError(_))) => {}
+            _ => panic!("unexpected"),
+        };
+        drop(brw);
+    }
+}
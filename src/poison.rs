@@ -0,0 +1,169 @@
+//!
+//! Mutex style poisoning (feature `poison`).
+//!
+//! Every mutable access the crate hands out, either as a guard or by calling a Fn with the value,
+//! is an RcORefMut. If it is dropped by a panic unwinding, the value may have been left half modified,
+//! so the cell is marked as poisoned and further accesses fail with RcOCellError::Poisoned until clear_poison is called.
+//! Without the feature the cell is never poisoned.
+//!
+use crate::{RcOCell, RcOCellError, RcORef, RcORefMut, WeakRcOCell};
+#[cfg(feature = "poison")]
+use crate::shared;
+
+///
+/// Marks the cell as poisoned, called by a mutable guard dropped by a panic that started while it existed.
+///
+#[cfg(feature = "poison")]
+pub(crate) fn poison<T>(cell: &RcOCell<T>) {
+    shared::with_state(&cell.rc, |state| state.poisoned = true);
+}
+
+impl <T> RcOCell<T> {
+    ///
+    /// Returns true if a panic unwound while the value was borrowed mutably.
+    /// Always false without the `poison` feature.
+    ///
+    pub fn is_poisoned(&self) -> bool {
+        #[cfg(feature = "poison")]
+        return shared::with_existing_state(&self.rc, |state| state.poisoned).unwrap_or(false);
+        #[cfg(not(feature = "poison"))]
+        return false;
+    }
+
+    ///
+    /// Removes the poisoning, the value is accessible again.
+    ///
+    pub fn clear_poison(&self) {
+        #[cfg(feature = "poison")]
+        shared::with_existing_state(&self.rc, |state| state.poisoned = false);
+    }
+
+    ///
+    /// Borrows the value even if the cell is poisoned, the poisoning stays in place.
+    /// Fails if the value is already borrowed mutably somewhere or there is no value.
+    ///
+    pub fn try_borrow_ignore_poison(&self) -> Result<RcORef<'_, T>, RcOCellError> {
+        let borrowed = self.try_raw_borrow_ignore_poison()?;
+        if borrowed.is_none() {
+            return Err(RcOCellError::NoValue);
+        }
+
+        return Ok(RcORef::map(borrowed, |a| a.as_ref().unwrap()));
+    }
+
+    ///
+    /// Borrows the value mutably even if the cell is poisoned, the poisoning stays in place.
+    /// Fails if the value is already borrowed somewhere or there is no value.
+    ///
+    pub fn try_borrow_mut_ignore_poison(&self) -> Result<RcORefMut<'_, T>, RcOCellError> {
+        let mut borrowed = self.try_raw_borrow_mut_ignore_poison()?;
+        if borrowed.is_none() {
+            return Err(RcOCellError::NoValue);
+        }
+
+        RcORefMut::marks_dirty(&mut borrowed);
+
+        return Ok(RcORefMut::map(borrowed, |a| a.as_mut().unwrap()));
+    }
+
+    ///
+    /// Fails with RcOCellError::Poisoned if the cell is poisoned.
+    ///
+    #[inline]
+    pub(crate) fn check_poison(&self) -> Result<(), RcOCellError> {
+        if self.is_poisoned() {
+            return Err(RcOCellError::Poisoned);
+        }
+
+        return Ok(());
+    }
+}
+
+impl <T> WeakRcOCell<T> {
+    ///
+    /// Returns true if the cell is poisoned, false if it was dropped.
+    ///
+    pub fn is_poisoned(&self) -> bool {
+        return self.try_upgrade().map(|cell| cell.is_poisoned()).unwrap_or(false);
+    }
+
+    ///
+    /// Removes the poisoning of the cell if it is still alive.
+    ///
+    pub fn clear_poison(&self) {
+        if let Ok(cell) = self.try_upgrade() {
+            cell.clear_poison();
+        }
+    }
+}
+
+#[cfg(all(test, feature = "poison"))]
+mod tests {
+    use std::panic;
+    use std::panic::AssertUnwindSafe;
+    use crate::*;
+
+    #[test]
+    fn test_poison_closure() {
+        let x = RcOCell::from_value(vec![1u32]);
+        let r = panic::catch_unwind(AssertUnwindSafe(|| {
+            x.compute_if_present(|v| {
+                v.push(2);
+                panic!("Oh no");
+            });
+        }));
+        assert_eq!(r.is_err(), true);
+        assert_eq!(x.is_poisoned(), true);
+        assert_eq!(x.downgrade().is_poisoned(), true);
+        match x.try_borrow() {
+            Err(RcOCellError::Poisoned) => {}
+            _ => panic!("unexpected"),
+        };
+        match x.try_set(vec![]) {
+            Err(RcOCellError::Poisoned) => {}
+            _ => panic!("unexpected"),
+        };
+
+        let r = panic::catch_unwind(AssertUnwindSafe(|| { x.borrow(); }));
+        let msg = r.unwrap_err().downcast_ref::<String>().unwrap().clone();
+        assert_eq!(msg.contains("poisoned"), true);
+
+        assert_eq!(*x.try_borrow_ignore_poison().unwrap(), vec![1, 2]);
+        x.try_borrow_mut_ignore_poison().unwrap().pop();
+        x.clear_poison();
+        assert_eq!(x.get_and_clone(), vec![1]);
+    }
+
+    #[test]
+    fn test_poison_guard() {
+        let x = RcOCell::from_value(1u32);
+        let r = panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut brw = x.borrow_mut();
+            *brw = 2;
+            panic!("Oh no");
+        }));
+        assert_eq!(r.is_err(), true);
+        assert_eq!(x.is_poisoned(), true);
+        x.clear_poison();
+        assert_eq!(x.get_and_clone(), 2);
+    }
+
+    #[test]
+    fn test_no_poison() {
+        let x = RcOCell::from_value(1u32);
+        let r = panic::catch_unwind(AssertUnwindSafe(|| {
+            let _brw = x.borrow();
+            panic!("Oh no");
+        }));
+        assert_eq!(r.is_err(), true);
+        let r = panic::catch_unwind(AssertUnwindSafe(|| {
+            x.map(|_| -> u32 { panic!("Oh no") });
+        }));
+        assert_eq!(r.is_err(), true);
+        let brw = x.borrow();
+        assert_eq!(x.try_borrow_mut().is_err(), true);
+        drop(brw);
+        assert_eq!(x.is_poisoned(), false);
+        assert_eq!(x.get_and_clone(), 1);
+    }
+}
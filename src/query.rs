@@ -0,0 +1,417 @@
+use crate::{RcOCell, RcOCellError, WeakRcOCell};
+
+impl <T> RcOCell<T> {
+    ///
+    /// Returns true if the cell holds a value equal to x, like Option::contains.
+    /// Returns false if the cell is empty or the value is borrowed mutably somewhere.
+    ///
+    pub fn contains<U>(&self, x: &U) -> bool
+        where T: PartialEq<U>
+    {
+        return self.try_contains(x).unwrap_or(false);
+    }
+
+    ///
+    /// Returns true if the cell holds a value equal to x, false if the cell is empty.
+    /// Fails if the value is borrowed mutably somewhere.
+    ///
+    pub fn try_contains<U>(&self, x: &U) -> Result<bool, RcOCellError>
+        where T: PartialEq<U>
+    {
+        return self.try_is_some_and(|value| value == x);
+    }
+
+    ///
+    /// Returns true if the cell holds a value that matches the predicate, like Option::is_some_and.
+    /// Returns false if the cell is empty or the value is borrowed mutably somewhere.
+    /// The value is borrowed while the predicate runs, so the predicate cannot modify the cell.
+    ///
+    pub fn is_some_and<F>(&self, pred: F) -> bool
+        where F: FnOnce(&T) -> bool
+    {
+        return self.try_is_some_and(pred).unwrap_or(false);
+    }
+
+    ///
+    /// Returns true if the cell holds a value that matches the predicate, false if the cell is empty.
+    /// Fails if the value is borrowed mutably somewhere.
+    ///
+    pub fn try_is_some_and<F>(&self, pred: F) -> Result<bool, RcOCellError>
+        where F: FnOnce(&T) -> bool
+    {
+        return Ok(self.try_raw_borrow()?.as_ref().is_some_and(pred));
+    }
+
+    ///
+    /// Returns true if the cell is empty or holds a value that matches the predicate, like Option::is_none_or.
+    /// Returns false if the value is borrowed mutably somewhere, as the value cannot be checked.
+    /// The value is borrowed while the predicate runs, so the predicate cannot modify the cell.
+    ///
+    pub fn is_none_or<F>(&self, pred: F) -> bool
+        where F: FnOnce(&T) -> bool
+    {
+        return self.try_is_none_or(pred).unwrap_or(false);
+    }
+
+    ///
+    /// Returns true if the cell is empty or holds a value that matches the predicate.
+    /// Fails if the value is borrowed mutably somewhere.
+    ///
+    pub fn try_is_none_or<F>(&self, pred: F) -> Result<bool, RcOCellError>
+        where F: FnOnce(&T) -> bool
+    {
+        return Ok(self.try_raw_borrow()?.as_ref().is_none_or(pred));
+    }
+    ///
+    /// Calls the Fn with the value and returns its result, returns the default if the cell is empty, like Option::map_or.
+    /// Unlike map this never panics: the default is also returned if the value is borrowed mutably somewhere.
+    ///
+    pub fn map_or<R, F>(&self, default: R, f: F) -> R
+        where F: FnOnce(&T) -> R
+    {
+        return match self.try_raw_borrow() {
+            Ok(value) => value.as_ref().map_or(default, f),
+            Err(_) => default,
+        };
+    }
+
+    ///
+    /// Calls the Fn with the value and returns its result, returns the default if the cell is empty.
+    /// Fails if the value is borrowed mutably somewhere.
+    ///
+    pub fn try_map_or<R, F>(&self, default: R, f: F) -> Result<R, RcOCellError>
+        where F: FnOnce(&T) -> R
+    {
+        return Ok(self.try_raw_borrow()?.as_ref().map_or(default, f));
+    }
+
+    ///
+    /// Calls the Fn with the value and returns its result, calls the default Fn if the cell is empty, like Option::map_or_else.
+    /// Unlike map this never panics: the default Fn is also called if the value is borrowed mutably somewhere.
+    ///
+    pub fn map_or_else<R, D, F>(&self, default_fn: D, f: F) -> R
+        where D: FnOnce() -> R, F: FnOnce(&T) -> R
+    {
+        return match self.try_raw_borrow() {
+            Ok(value) => value.as_ref().map_or_else(default_fn, f),
+            Err(_) => default_fn(),
+        };
+    }
+
+    ///
+    /// Calls the Fn with the value and returns its result, calls the default Fn if the cell is empty.
+    /// Fails without calling either Fn if the value is borrowed mutably somewhere.
+    ///
+    pub fn try_map_or_else<R, D, F>(&self, default_fn: D, f: F) -> Result<R, RcOCellError>
+        where D: FnOnce() -> R, F: FnOnce(&T) -> R
+    {
+        return Ok(self.try_raw_borrow()?.as_ref().map_or_else(default_fn, f));
+    }
+
+    ///
+    /// Returns a clone of the value, never panics.
+    /// None means the cell is empty or the value is borrowed mutably somewhere,
+    /// use try_get_and_clone to tell these cases apart.
+    ///
+    pub fn clone_value(&self) -> Option<T>
+        where T: Clone
+    {
+        return self.map_or(None, |value| Some(value.clone()));
+    }
+
+    ///
+    /// Returns a clone of the value, same as clone_value.
+    ///
+    pub fn cloned(&self) -> Option<T>
+        where T: Clone
+    {
+        return self.clone_value();
+    }
+}
+
+impl <T> WeakRcOCell<T> {
+    ///
+    /// Returns true if the cell holds a value equal to x, see RcOCell::contains.
+    /// Returns false if the cell was dropped.
+    ///
+    pub fn contains<U>(&self, x: &U) -> bool
+        where T: PartialEq<U>
+    {
+        return self.try_contains(x).unwrap_or(false);
+    }
+
+    ///
+    /// Returns true if the cell holds a value equal to x, see RcOCell::try_contains.
+    /// A dropped cell counts as empty.
+    ///
+    pub fn try_contains<U>(&self, x: &U) -> Result<bool, RcOCellError>
+        where T: PartialEq<U>
+    {
+        return self.try_is_some_and(|value| value == x);
+    }
+
+    ///
+    /// Returns true if the cell holds a value that matches the predicate, see RcOCell::is_some_and.
+    /// Returns false if the cell was dropped.
+    ///
+    pub fn is_some_and<F>(&self, pred: F) -> bool
+        where F: FnOnce(&T) -> bool
+    {
+        return self.try_is_some_and(pred).unwrap_or(false);
+    }
+
+    ///
+    /// Returns true if the cell holds a value that matches the predicate, see RcOCell::try_is_some_and.
+    /// A dropped cell counts as empty.
+    ///
+    pub fn try_is_some_and<F>(&self, pred: F) -> Result<bool, RcOCellError>
+        where F: FnOnce(&T) -> bool
+    {
+        return match self.try_upgrade() {
+            Ok(cell) => cell.try_is_some_and(pred),
+            Err(RcOCellError::Dropped) => Ok(false),
+            Err(e) => Err(e),
+        };
+    }
+
+    ///
+    /// Returns true if the cell is empty or holds a value that matches the predicate, see RcOCell::is_none_or.
+    /// Returns true if the cell was dropped.
+    ///
+    pub fn is_none_or<F>(&self, pred: F) -> bool
+        where F: FnOnce(&T) -> bool
+    {
+        return self.try_is_none_or(pred).unwrap_or(false);
+    }
+
+    ///
+    /// Returns true if the cell is empty or holds a value that matches the predicate, see RcOCell::try_is_none_or.
+    /// A dropped cell counts as empty.
+    ///
+    pub fn try_is_none_or<F>(&self, pred: F) -> Result<bool, RcOCellError>
+        where F: FnOnce(&T) -> bool
+    {
+        return match self.try_upgrade() {
+            Ok(cell) => cell.try_is_none_or(pred),
+            Err(RcOCellError::Dropped) => Ok(true),
+            Err(e) => Err(e),
+        };
+    }
+    ///
+    /// Calls the Fn with the value and returns its result, see RcOCell::map_or.
+    /// Returns the default if the cell was dropped.
+    ///
+    pub fn map_or<R, F>(&self, default: R, f: F) -> R
+        where F: FnOnce(&T) -> R
+    {
+        return match self.try_upgrade() {
+            Ok(cell) => cell.map_or(default, f),
+            Err(_) => default,
+        };
+    }
+
+    ///
+    /// Calls the Fn with the value and returns its result, see RcOCell::try_map_or.
+    /// A dropped cell counts as empty.
+    ///
+    pub fn try_map_or<R, F>(&self, default: R, f: F) -> Result<R, RcOCellError>
+        where F: FnOnce(&T) -> R
+    {
+        return match self.try_upgrade() {
+            Ok(cell) => cell.try_map_or(default, f),
+            Err(RcOCellError::Dropped) => Ok(default),
+            Err(e) => Err(e),
+        };
+    }
+
+    ///
+    /// Calls the Fn with the value and returns its result, see RcOCell::map_or_else.
+    /// Calls the default Fn if the cell was dropped.
+    ///
+    pub fn map_or_else<R, D, F>(&self, default_fn: D, f: F) -> R
+        where D: FnOnce() -> R, F: FnOnce(&T) -> R
+    {
+        return match self.try_upgrade() {
+            Ok(cell) => cell.map_or_else(default_fn, f),
+            Err(_) => default_fn(),
+        };
+    }
+
+    ///
+    /// Calls the Fn with the value and returns its result, see RcOCell::try_map_or_else.
+    /// A dropped cell counts as empty.
+    ///
+    pub fn try_map_or_else<R, D, F>(&self, default_fn: D, f: F) -> Result<R, RcOCellError>
+        where D: FnOnce() -> R, F: FnOnce(&T) -> R
+    {
+        return match self.try_upgrade() {
+            Ok(cell) => cell.try_map_or_else(default_fn, f),
+            Err(RcOCellError::Dropped) => Ok(default_fn()),
+            Err(e) => Err(e),
+        };
+    }
+
+    ///
+    /// Returns a clone of the value, never panics, see RcOCell::clone_value.
+    /// None means the cell was dropped, is empty or the value is borrowed mutably somewhere,
+    /// use try_get_and_clone to tell these cases apart.
+    ///
+    pub fn clone_value(&self) -> Option<T>
+        where T: Clone
+    {
+        return self.map_or(None, |value| Some(value.clone()));
+    }
+
+    ///
+    /// Returns a clone of the value, same as clone_value.
+    ///
+    pub fn cloned(&self) -> Option<T>
+        where T: Clone
+    {
+        return self.clone_value();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_query_present_and_empty() {
+        let cell = RcOCell::from_value(String::from("abc"));
+        assert_eq!(cell.contains(&"abc"), true);
+        assert_eq!(cell.contains(&String::from("x")), false);
+        assert_eq!(cell.is_some_and(|v| v.len() == 3), true);
+        assert_eq!(cell.is_none_or(|v| v.is_empty()), false);
+        assert_eq!(cell.try_contains(&"abc").unwrap(), true);
+
+        cell.clear();
+        assert_eq!(cell.contains(&"abc"), false);
+        assert_eq!(cell.is_some_and(|_| true), false);
+        assert_eq!(cell.is_none_or(|_| false), true);
+        assert_eq!(cell.try_is_some_and(|_| true).unwrap(), false);
+        assert_eq!(cell.try_is_none_or(|_| false).unwrap(), true);
+    }
+
+    #[test]
+    fn test_query_borrowed() {
+        let cell = RcOCell::from_value(1u32);
+        let brw = cell.borrow();
+        assert_eq!(cell.contains(&1), true);
+        drop(brw);
+
+        let weak = cell.downgrade();
+        let guard = cell.borrow_mut();
+        assert_eq!(cell.contains(&1), false);
+        assert_eq!(cell.is_some_and(|_| true), false);
+        assert_eq!(cell.is_none_or(|_| true), false);
+        match cell.try_contains(&1) {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        match cell.try_is_none_or(|_| true) {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(weak.is_some_and(|_| true), false);
+        match weak.try_is_some_and(|_| true) {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        drop(guard);
+        assert_eq!(weak.contains(&1), true);
+    }
+
+    #[test]
+    fn test_query_weak_dropped() {
+        let cell = RcOCell::from_value(1u32);
+        let weak = cell.downgrade();
+        assert_eq!(weak.contains(&1), true);
+        assert_eq!(weak.is_none_or(|v| *v == 2), false);
+
+        drop(cell);
+        assert_eq!(weak.contains(&1), false);
+        assert_eq!(weak.is_some_and(|_| true), false);
+        assert_eq!(weak.is_none_or(|_| false), true);
+        assert_eq!(weak.try_contains(&1).unwrap(), false);
+        assert_eq!(weak.try_is_none_or(|_| false).unwrap(), true);
+    }
+
+    #[test]
+    fn test_map_or() {
+        let cell = RcOCell::from_value(2u32);
+        assert_eq!(cell.map_or(0, |v| v * 10), 20);
+        assert_eq!(cell.map_or_else(|| 0, |v| v * 10), 20);
+        assert_eq!(cell.try_map_or(0, |v| v * 10).unwrap(), 20);
+        assert_eq!(cell.try_map_or_else(|| 0, |v| v * 10).unwrap(), 20);
+
+        let guard = cell.borrow_mut();
+        assert_eq!(cell.map_or(0, |v| v * 10), 0);
+        assert_eq!(cell.map_or_else(|| 1, |v| v * 10), 1);
+        match cell.try_map_or(0, |v| v * 10) {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        match cell.try_map_or_else(|| panic!("unexpected"), |v| v * 10) {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        match cell.downgrade().try_map_or(0, |v| v * 10) {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        drop(guard);
+
+        cell.clear();
+        assert_eq!(cell.map_or(0, |v| v * 10), 0);
+        assert_eq!(cell.map_or_else(|| 1, |v| v * 10), 1);
+        assert_eq!(cell.try_map_or(0, |v| v * 10).unwrap(), 0);
+        assert_eq!(cell.try_map_or_else(|| 1, |v| v * 10).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_map_or_weak() {
+        let cell = RcOCell::from_value(2u32);
+        let weak = cell.downgrade();
+        assert_eq!(weak.map_or(0, |v| v * 10), 20);
+        assert_eq!(weak.try_map_or_else(|| 1, |v| v * 10).unwrap(), 20);
+
+        let guard = cell.borrow_mut();
+        assert_eq!(weak.map_or_else(|| 1, |v| v * 10), 1);
+        drop(guard);
+
+        drop(cell);
+        assert_eq!(weak.map_or(0, |v| v * 10), 0);
+        assert_eq!(weak.map_or_else(|| 1, |v| v * 10), 1);
+        assert_eq!(weak.try_map_or(0, |v| v * 10).unwrap(), 0);
+        assert_eq!(weak.try_map_or_else(|| 1, |v| v * 10).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_clone_value() {
+        let cell = RcOCell::from_value(String::from("a"));
+        let weak = cell.downgrade();
+        assert_eq!(cell.clone_value(), Some(String::from("a")));
+        assert_eq!(weak.cloned(), Some(String::from("a")));
+
+        let guard = cell.borrow();
+        assert_eq!(cell.cloned(), Some(String::from("a")));
+        assert_eq!(weak.clone_value(), Some(String::from("a")));
+        drop(guard);
+
+        let guard = cell.borrow_mut();
+        assert_eq!(cell.clone_value(), None);
+        assert_eq!(weak.clone_value(), None);
+        assert_eq!(cell.try_get_and_clone().is_err(), true);
+        drop(guard);
+
+        cell.clear();
+        assert_eq!(cell.cloned(), None);
+        assert_eq!(weak.cloned(), None);
+
+        cell.set(String::from("b"));
+        drop(cell);
+        assert_eq!(weak.clone_value(), None);
+        assert_eq!(weak.cloned(), None);
+    }
+}
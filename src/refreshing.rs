@@ -0,0 +1,273 @@
+use std::cell::Ref;
+use std::fmt::{Debug, Formatter};
+use std::time::{Duration, Instant};
+use crate::{Clock, RcOCell, RcOCellComputeError, RcOCellError, RcORef, SystemClock};
+
+struct Entry<T> {
+    value: T,
+    produced: Option<Instant>,
+}
+
+///
+/// A read through cache that refreshes its value once it is older than the maximum age passed by the reader.
+///
+/// Unlike ExpiringRcOCell the value never disappears on its own, an old value is only replaced by a fresh one.
+/// A value is fresh while its age is less than the maximum age, so a value whose age is exactly the maximum age is stale.
+/// get_or_try_refresh keeps serving the stale value if the supplier fails.
+/// The supplier runs while the value is not borrowed, so it may read the cell.
+///
+/// Clones share the value, each clone keeps its own copy of the clock.
+///
+pub struct RefreshingRcOCell<T, C: Clock = SystemClock> {
+    cell: RcOCell<Entry<T>>,
+    clock: C,
+}
+
+impl <T> RefreshingRcOCell<T, SystemClock> {
+    ///
+    /// Constructs a new empty cell, the first read calls the supplier.
+    ///
+    pub fn new() -> RefreshingRcOCell<T, SystemClock> {
+        return RefreshingRcOCell::with_clock(SystemClock);
+    }
+}
+
+impl <T> Default for RefreshingRcOCell<T, SystemClock> {
+    fn default() -> Self {
+        return RefreshingRcOCell::new();
+    }
+}
+
+impl <T, C: Clock> RefreshingRcOCell<T, C> {
+    ///
+    /// Constructs a new empty cell that uses the clock to determine the age of values.
+    ///
+    pub fn with_clock(clock: C) -> RefreshingRcOCell<T, C> {
+        return RefreshingRcOCell {cell: RcOCell::new(), clock};
+    }
+
+    ///
+    /// Returns true if there is a value that was produced less than max_age ago and was not invalidated.
+    /// Fails if the value is borrowed mutably somewhere.
+    ///
+    fn is_fresh(&self, max_age: Duration) -> Result<bool, RcOCellError> {
+        let entry = self.cell.try_raw_borrow()?;
+        let Some(produced) = entry.as_ref().and_then(|e| e.produced) else {
+            return Ok(false);
+        };
+
+        return Ok(produced.checked_add(max_age).is_none_or(|deadline| self.clock.now() < deadline));
+    }
+
+    fn store(&self, value: T) -> Result<(), RcOCellError> {
+        let produced = Some(self.clock.now());
+        self.cell.try_set(Entry {value, produced})?;
+        return Ok(());
+    }
+
+    fn value(&self) -> Result<Ref<'_, T>, RcOCellError> {
+        return Ok(RcORef::into_std(RcORef::map(self.cell.try_borrow()?, |e| &e.value)));
+    }
+
+    ///
+    /// Borrows the value if it was produced less than max_age ago,
+    /// otherwise calls the supplier, stores its value with the current time and borrows that.
+    /// Panics if the value is borrowed mutably somewhere, or borrowed at all when it has to be replaced.
+    ///
+    pub fn get_or_refresh<F>(&self, max_age: Duration, supplier: F) -> Ref<'_, T>
+        where F: FnOnce() -> T
+    {
+        return match self.try_get_or_refresh(max_age, supplier) {
+            Ok(r) => r,
+            Err(e) => panic!("RefreshingRcOCell::get_or_refresh failed: {}", e),
+        };
+    }
+
+    ///
+    /// Borrows the value if it is fresh, otherwise refreshes it first, see get_or_refresh.
+    /// Fails if the value is borrowed mutably somewhere, or borrowed at all when it has to be replaced.
+    /// The value of the supplier is dropped if it cannot be stored.
+    ///
+    pub fn try_get_or_refresh<F>(&self, max_age: Duration, supplier: F) -> Result<Ref<'_, T>, RcOCellError>
+        where F: FnOnce() -> T
+    {
+        if !self.is_fresh(max_age)? {
+            self.store(supplier())?;
+        }
+
+        return self.value();
+    }
+
+    ///
+    /// Borrows the value if it is fresh, otherwise calls the fallible supplier to refresh it, see get_or_refresh.
+    /// If the supplier fails the stale value is borrowed instead and stays stale, so the next call tries again.
+    /// Fails with RcOCellComputeError::Compute only if the supplier fails and there is no stale value.
+    /// Fails with RcOCellComputeError::Cell if the value is borrowed mutably somewhere,
+    /// or borrowed at all when a fresh value has to be stored.
+    ///
+    pub fn get_or_try_refresh<E, F>(&self, max_age: Duration, supplier: F) -> Result<Ref<'_, T>, RcOCellComputeError<E>>
+        where F: FnOnce() -> Result<T, E>
+    {
+        if !self.is_fresh(max_age).map_err(RcOCellComputeError::Cell)? {
+            match supplier() {
+                Ok(value) => self.store(value).map_err(RcOCellComputeError::Cell)?,
+                Err(e) => if self.cell.is_none() {
+                    return Err(RcOCellComputeError::Compute(e));
+                },
+            }
+        }
+
+        return self.value().map_err(RcOCellComputeError::Cell);
+    }
+
+    ///
+    /// Forces the next read to refresh the value, the value is kept to be served if the refresh fails.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    pub fn invalidate(&self) {
+        if let Err(e) = self.try_invalidate() {
+            panic!("RefreshingRcOCell::invalidate failed: {}", e);
+        }
+    }
+
+    ///
+    /// Forces the next read to refresh the value, see invalidate.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    pub fn try_invalidate(&self) -> Result<(), RcOCellError> {
+        if let Some(entry) = self.cell.try_raw_borrow_mut()?.as_mut() {
+            entry.produced = None;
+        }
+
+        return Ok(());
+    }
+
+    ///
+    /// Returns how long ago the value was produced.
+    /// None if there is no value, the value was invalidated or is borrowed mutably.
+    ///
+    pub fn age(&self) -> Option<Duration> {
+        let produced = self.cell.map_or(None, |e| e.produced)?;
+        return Some(self.clock.now().saturating_duration_since(produced));
+    }
+
+    ///
+    /// Borrows the value regardless of its age.
+    /// Fails if there is no value or the value is borrowed mutably somewhere.
+    ///
+    pub fn try_borrow(&self) -> Result<Ref<'_, T>, RcOCellError> {
+        return self.value();
+    }
+
+    ///
+    /// Returns true if there is a value regardless of its age.
+    /// A mutably borrowed value counts as set.
+    /// Never panics.
+    ///
+    pub fn is_some(&self) -> bool {
+        return self.cell.is_some();
+    }
+
+    ///
+    /// Removes the value, the next read calls the supplier and has no stale value to fall back on.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    pub fn clear(&self) -> Option<T> {
+        return self.cell.clear().map(|e| e.value);
+    }
+}
+
+impl <T, C: Clock + Clone> Clone for RefreshingRcOCell<T, C> {
+    fn clone(&self) -> Self {
+        return RefreshingRcOCell {cell: self.cell.clone(), clock: self.clock.clone()};
+    }
+}
+
+impl <T: Debug, C: Clock> Debug for RefreshingRcOCell<T, C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self.try_borrow() {
+            Ok(value) => f.debug_struct("RefreshingRcOCell").field("value", &*value).field("age", &self.age()).finish(),
+            Err(e) => f.debug_struct("RefreshingRcOCell").field("value", &e).finish(),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use std::time::Duration;
+    use crate::*;
+
+    const MAX_AGE: Duration = Duration::from_secs(10);
+
+    fn cell() -> (RefreshingRcOCell<String, Rc<ManualClock>>, Rc<ManualClock>) {
+        let clock = Rc::new(ManualClock::new());
+        return (RefreshingRcOCell::with_clock(clock.clone()), clock);
+    }
+
+    #[test]
+    fn test_fresh_hit_and_stale_refresh() {
+        let (x, clock) = cell();
+        let calls = std::cell::Cell::new(0);
+        let supplier = || { calls.set(calls.get() + 1); format!("v{}", calls.get()) };
+
+        assert_eq!(x.get_or_refresh(MAX_AGE, supplier).as_str(), "v1");
+        clock.advance(Duration::from_secs(9));
+        assert_eq!(x.get_or_refresh(MAX_AGE, supplier).as_str(), "v1");
+        assert_eq!(x.age(), Some(Duration::from_secs(9)));
+        assert_eq!(calls.get(), 1);
+
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(x.get_or_refresh(MAX_AGE, supplier).as_str(), "v2");
+        assert_eq!(x.age(), Some(Duration::ZERO));
+        assert_eq!(x.try_get_or_refresh(Duration::from_secs(1), supplier).unwrap().as_str(), "v2");
+        assert_eq!(x.try_get_or_refresh(Duration::ZERO, supplier).unwrap().as_str(), "v3");
+
+        x.invalidate();
+        assert_eq!(x.age(), None);
+        assert_eq!(x.try_borrow().unwrap().as_str(), "v3");
+        assert_eq!(x.get_or_refresh(MAX_AGE, supplier).as_str(), "v4");
+        clock.advance(Duration::from_secs(1000));
+        assert_eq!(x.get_or_refresh(Duration::MAX, supplier).as_str(), "v4");
+        assert_eq!(calls.get(), 4);
+    }
+
+    #[test]
+    fn test_supplier_failure() {
+        let (x, clock) = cell();
+        match x.get_or_try_refresh(MAX_AGE, || Err::<String, _>("offline")) {
+            Err(RcOCellComputeError::Compute("offline")) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(x.is_some(), false);
+
+        assert_eq!(x.get_or_try_refresh(MAX_AGE, || Ok::<_, &str>("v1".to_string())).unwrap().as_str(), "v1");
+        clock.advance(MAX_AGE);
+        assert_eq!(x.get_or_try_refresh(MAX_AGE, || Err("offline")).unwrap().as_str(), "v1");
+        assert_eq!(x.age(), Some(MAX_AGE));
+        assert_eq!(x.get_or_try_refresh(MAX_AGE, || Ok::<_, &str>("v2".to_string())).unwrap().as_str(), "v2");
+
+        x.invalidate();
+        assert_eq!(x.get_or_try_refresh(MAX_AGE, || Err("offline")).unwrap().as_str(), "v2");
+        x.clear();
+        assert_eq!(x.get_or_try_refresh(MAX_AGE, || Err("offline")).is_err(), true);
+    }
+
+    #[test]
+    fn test_refresh_borrowed() {
+        let (x, clock) = cell();
+        let brw = x.get_or_refresh(MAX_AGE, || "v1".to_string());
+        assert_eq!(x.get_or_refresh(MAX_AGE, || panic!("unexpected")).as_str(), "v1");
+        clock.advance(MAX_AGE);
+        match x.try_get_or_refresh(MAX_AGE, || "v2".to_string()) {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(x.try_invalidate().is_err(), true);
+        drop(brw);
+
+        let reader = x.clone();
+        let value = x.get_or_refresh(MAX_AGE, || format!("{} refreshed", reader.try_borrow().unwrap()));
+        assert_eq!(value.as_str(), "v1 refreshed");
+    }
+}
@@ -0,0 +1,214 @@
+use crate::{RcOCell, RcOCellError, RcORef};
+
+///
+/// Helpers for cells that hold a Result, i.e. the outcome of loading a resource.
+///
+/// Methods that project through a variant fail with RcOCellError::WrongVariant if the cell holds the other variant
+/// and with RcOCellError::NoValue if the cell is empty.
+///
+pub trait ResultCellExt<T, E> {
+    ///
+    /// Returns true if the cell holds an Ok.
+    /// Returns false if the cell is empty or the value is borrowed mutably somewhere.
+    /// Never panics.
+    ///
+    fn is_ok_value(&self) -> bool;
+
+    ///
+    /// Returns true if the cell holds an Err.
+    /// Returns false if the cell is empty or the value is borrowed mutably somewhere.
+    /// Never panics.
+    ///
+    fn is_err_value(&self) -> bool;
+
+    ///
+    /// Borrows the Ok value.
+    /// Fails if the value is already borrowed mutably somewhere, there is no value or the value is an Err.
+    ///
+    fn borrow_ok(&self) -> Result<RcORef<'_, T, Result<T, E>>, RcOCellError>;
+
+    ///
+    /// Borrows the Err value.
+    /// Fails if the value is already borrowed mutably somewhere, there is no value or the value is an Ok.
+    ///
+    fn borrow_err(&self) -> Result<RcORef<'_, E, Result<T, E>>, RcOCellError>;
+
+    ///
+    /// Takes the value out of the cell if it is an Ok, an Err stays in the cell.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn take_ok(&self) -> Option<T>;
+
+    ///
+    /// Takes the value out of the cell if it is an Err, an Ok stays in the cell.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn take_err(&self) -> Option<E>;
+
+    ///
+    /// Moves the value into a new Ok or Err cell depending on its variant and leaves this cell empty.
+    /// The cell of the other variant is empty, both are empty if this cell was empty.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn split(&self) -> (RcOCell<T>, RcOCell<E>);
+}
+
+impl <T, E> RcOCell<Result<T, E>> {
+    fn borrow_variant<X, F>(&self, project: F) -> Result<RcORef<'_, X, Result<T, E>>, RcOCellError>
+        where F: Fn(&Result<T, E>) -> Option<&X>
+    {
+        let borrowed = self.try_borrow()?;
+        if project(&borrowed).is_none() {
+            return Err(RcOCellError::WrongVariant);
+        }
+
+        return Ok(RcORef::map(borrowed, |v| project(v).unwrap()));
+    }
+
+    fn take_variant(&self, is_variant: bool) -> Option<Result<T, E>> {
+        let matches = self.raw_borrow_mut().as_ref().map(|v| v.is_ok() == is_variant).unwrap_or(false);
+        if !matches {
+            return None;
+        }
+
+        return self.clear();
+    }
+}
+
+impl <T, E> ResultCellExt<T, E> for RcOCell<Result<T, E>> {
+    fn is_ok_value(&self) -> bool {
+        return self.try_map(|v| v.is_ok()).ok().flatten().unwrap_or(false);
+    }
+
+    fn is_err_value(&self) -> bool {
+        return self.try_map(|v| v.is_err()).ok().flatten().unwrap_or(false);
+    }
+
+    fn borrow_ok(&self) -> Result<RcORef<'_, T, Result<T, E>>, RcOCellError> {
+        return self.borrow_variant(|v| v.as_ref().ok());
+    }
+
+    fn borrow_err(&self) -> Result<RcORef<'_, E, Result<T, E>>, RcOCellError> {
+        return self.borrow_variant(|v| v.as_ref().err());
+    }
+
+    fn take_ok(&self) -> Option<T> {
+        return self.take_variant(true).and_then(|v| v.ok());
+    }
+
+    fn take_err(&self) -> Option<E> {
+        return self.take_variant(false).and_then(|v| v.err());
+    }
+
+    fn split(&self) -> (RcOCell<T>, RcOCell<E>) {
+        return match self.clear() {
+            Some(Ok(value)) => (RcOCell::from_value(value), RcOCell::new()),
+            Some(Err(err)) => (RcOCell::new(), RcOCell::from_value(err)),
+            None => (RcOCell::new(), RcOCell::new()),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic;
+    use std::panic::AssertUnwindSafe;
+    use crate::*;
+
+    type Loaded = RcOCell<Result<u32, String>>;
+
+    fn states() -> (Loaded, Loaded, Loaded) {
+        return (RcOCell::from_value(Ok(1)), RcOCell::from_value(Err("failed".to_string())), RcOCell::new());
+    }
+
+    fn panics<R>(f: impl FnOnce() -> R) -> bool {
+        return panic::catch_unwind(AssertUnwindSafe(f)).is_err();
+    }
+
+    #[test]
+    fn test_is_ok_err_value() {
+        let (ok, err, empty) = states();
+        assert_eq!(ok.is_ok_value(), true);
+        assert_eq!(ok.is_err_value(), false);
+        assert_eq!(err.is_ok_value(), false);
+        assert_eq!(err.is_err_value(), true);
+        assert_eq!(empty.is_ok_value(), false);
+        assert_eq!(empty.is_err_value(), false);
+        let brw = ok.borrow_mut();
+        assert_eq!(ok.is_ok_value(), false);
+        assert_eq!(ok.is_err_value(), false);
+        drop(brw);
+    }
+
+    #[test]
+    fn test_borrow_ok_err() {
+        let (ok, err, empty) = states();
+        assert_eq!(*ok.borrow_ok().unwrap(), 1);
+        assert_eq!(err.borrow_err().unwrap().as_str(), "failed");
+        match ok.borrow_err() {
+            Err(RcOCellError::WrongVariant) => {}
+            _ => panic!("unexpected"),
+        };
+        match err.borrow_ok() {
+            Err(RcOCellError::WrongVariant) => {}
+            _ => panic!("unexpected"),
+        };
+        match empty.borrow_ok() {
+            Err(RcOCellError::NoValue) => {}
+            _ => panic!("unexpected"),
+        };
+        match empty.borrow_err() {
+            Err(RcOCellError::NoValue) => {}
+            _ => panic!("unexpected"),
+        };
+        let brw = ok.borrow_mut();
+        match ok.borrow_ok() {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        drop(brw);
+    }
+
+    #[test]
+    fn test_take_ok_err() {
+        let (ok, err, empty) = states();
+        assert_eq!(ok.take_err(), None);
+        assert_eq!(ok.is_ok_value(), true);
+        assert_eq!(ok.take_ok(), Some(1));
+        assert_eq!(ok.is_none(), true);
+        assert_eq!(err.take_ok(), None);
+        assert_eq!(err.is_err_value(), true);
+        assert_eq!(err.take_err(), Some("failed".to_string()));
+        assert_eq!(err.is_none(), true);
+        assert_eq!(empty.take_ok(), None);
+        assert_eq!(empty.take_err(), None);
+
+        let (ok, _, _) = states();
+        let brw = ok.borrow();
+        assert_eq!(panics(|| ok.take_ok()), true);
+        assert_eq!(panics(|| ok.take_err()), true);
+        drop(brw);
+        assert_eq!(ok.take_ok(), Some(1));
+    }
+
+    #[test]
+    fn test_split() {
+        let (ok, err, empty) = states();
+        let (value, error) = ok.split();
+        assert_eq!(value.get_and_clone(), 1);
+        assert_eq!(error.is_none(), true);
+        assert_eq!(ok.is_none(), true);
+        let (value, error) = err.split();
+        assert_eq!(value.is_none(), true);
+        assert_eq!(error.get_and_clone(), "failed");
+        let (value, error) = empty.split();
+        assert_eq!(value.is_none(), true);
+        assert_eq!(error.is_none(), true);
+
+        let (ok, _, _) = states();
+        let brw = ok.borrow();
+        assert_eq!(panics(|| ok.split()), true);
+        drop(brw);
+        assert_eq!(ok.is_ok_value(), true);
+    }
+}
@@ -0,0 +1,193 @@
+use std::fmt::{Debug, Formatter};
+use crate::{shared, RcOCell};
+
+///
+/// Restores the previous state of a cell when dropped, see RcOCell::push_scope.
+///
+#[must_use = "the previous value is restored when the guard is dropped"]
+pub struct ScopeGuard<T> {
+    cell: RcOCell<T>,
+    previous: Option<Option<T>>,
+    depth: usize,
+}
+
+impl <T> RcOCell<T> {
+    ///
+    /// Stores the value in the cell until the returned guard is dropped,
+    /// the guard then restores the previous value or clears the cell if it was empty before.
+    /// This also happens if the guard is dropped while a panic unwinds,
+    /// unless the value is still borrowed or the panic poisoned the cell, the cell is then left as it is.
+    ///
+    /// Scopes can be nested to arbitrary depth but the guards have to be dropped in reverse order of their creation.
+    /// Dropping a guard while a guard that was created after it is still alive is a bug and panics without restoring anything.
+    /// Modifications of the cell made while a scope is active are discarded when the scope ends.
+    ///
+    /// Panics if the value is borrowed somewhere.
+    ///
+    pub fn push_scope(&self, value: T) -> ScopeGuard<T> {
+        let previous = self.set(value);
+        let depth = shared::with_state(&self.rc, |state| {
+            state.scope_depth += 1;
+            state.scope_depth
+        });
+
+        return ScopeGuard {
+            cell: self.clone(),
+            previous: Some(previous),
+            depth,
+        };
+    }
+
+    ///
+    /// Stores the value in the cell until the returned guard is dropped, see push_scope.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    pub fn try_push_scope(&self, value: T) -> Result<ScopeGuard<T>, crate::RcOCellError> {
+        self.check_mut()?;
+        return Ok(self.push_scope(value));
+    }
+}
+
+impl <T> ScopeGuard<T> {
+    ///
+    /// Returns the cell the scope was pushed on.
+    ///
+    pub fn cell(&self) -> &RcOCell<T> {
+        return &self.cell;
+    }
+
+    ///
+    /// Returns the value the cell held before this scope was pushed.
+    ///
+    pub fn previous(&self) -> Option<&T> {
+        return self.previous.as_ref().and_then(|p| p.as_ref());
+    }
+}
+
+impl <T> Drop for ScopeGuard<T> {
+    fn drop(&mut self) {
+        let in_order = shared::with_existing_state(&self.cell.rc, |state| {
+            if state.scope_depth != self.depth {
+                return false;
+            }
+            state.scope_depth -= 1;
+            true
+        }).unwrap_or(false);
+
+        if !in_order {
+            if std::thread::panicking() {
+                return;
+            }
+            panic!("ScopeGuard dropped while a scope that was pushed after it is still active");
+        }
+
+        let previous = self.previous.take().unwrap();
+        if std::thread::panicking() && (self.cell.check_poison().is_err() || self.cell.rc.try_borrow_mut().is_err()) {
+            return;
+        }
+
+        match previous {
+            Some(value) => { self.cell.set(value); }
+            None => { self.cell.clear(); }
+        }
+    }
+}
+
+impl <T: Debug> Debug for ScopeGuard<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScopeGuard")
+            .field("previous", &self.previous())
+            .field("depth", &self.depth)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic;
+    use std::panic::AssertUnwindSafe;
+    use crate::*;
+
+    rco_thread_local! {
+        fn request_id() -> RcOCell<u32>;
+    }
+
+    fn current_request() -> Option<u32> {
+        return request_id().map(|id| *id);
+    }
+
+    #[test]
+    fn test_nested_scopes() {
+        assert_eq!(current_request(), None);
+        let a = request_id().push_scope(1);
+        assert_eq!(current_request(), Some(1));
+        {
+            let _b = request_id().push_scope(2);
+            assert_eq!(current_request(), Some(2));
+            {
+                let c = request_id().push_scope(3);
+                assert_eq!(c.previous(), Some(&2));
+                assert_eq!(current_request(), Some(3));
+            }
+            assert_eq!(current_request(), Some(2));
+        }
+        assert_eq!(current_request(), Some(1));
+        drop(a);
+        assert_eq!(current_request(), None);
+    }
+
+    #[test]
+    fn test_scope_panic() {
+        let cell = RcOCell::from_value("outer".to_string());
+        let r = panic::catch_unwind(AssertUnwindSafe(|| {
+            let _guard = cell.push_scope("inner".to_string());
+            assert_eq!(cell.borrow().as_str(), "inner");
+            panic!("Oh no");
+        }));
+        assert_eq!(r.is_err(), true);
+        assert_eq!(cell.borrow().as_str(), "outer");
+    }
+
+    #[test]
+    fn test_scope_out_of_order() {
+        let cell = RcOCell::from_value(0u32);
+        let a = cell.push_scope(1);
+        let b = cell.push_scope(2);
+        let r = panic::catch_unwind(AssertUnwindSafe(|| drop(a)));
+        assert_eq!(r.is_err(), true);
+        assert_eq!(cell.get_and_clone(), 2);
+        drop(b);
+        assert_eq!(cell.get_and_clone(), 1);
+    }
+
+    #[test]
+    fn test_try_push_scope() {
+        let cell = RcOCell::from_value(0u32);
+        let brw = cell.borrow();
+        assert_eq!(cell.try_push_scope(1).is_err(), true);
+        drop(brw);
+        let guard = cell.try_push_scope(1).unwrap();
+        assert_eq!(cell.get_and_clone(), 1);
+        drop(guard);
+        assert_eq!(cell.get_and_clone(), 0);
+    }
+
+    #[cfg(feature = "poison")]
+    #[test]
+    fn test_scope_poisoned() {
+        let cell = RcOCell::from_value(0u32);
+        let r = cell.catch(|cell| {
+            let _guard = cell.push_scope(1);
+            *cell.borrow_mut() = 2;
+            let _brw = cell.borrow_mut();
+            panic!("Oh no");
+        });
+        assert_eq!(r.is_err(), true);
+        assert_eq!(cell.is_poisoned(), true);
+        cell.clear_poison();
+        assert_eq!(cell.get_and_clone(), 2);
+        let guard = cell.push_scope(3);
+        drop(guard);
+        assert_eq!(cell.get_and_clone(), 2);
+    }
+}
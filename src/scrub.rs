@@ -0,0 +1,253 @@
+use zeroize::Zeroize;
+use crate::{shared, RcOCell, RcOCellError, WeakRcOCell};
+
+///
+/// Zeroizes a value the cell discarded if enabled with zeroize_discarded, then drops it.
+///
+pub(crate) fn discarded<T>(cell: &RcOCell<T>, value: Option<T>) {
+    let Some(mut value) = value else {
+        return;
+    };
+
+    if let Some(zeroize) = shared::with_existing_typed(&cell.rc, |typed| typed.zeroize).flatten() {
+        zeroize(&mut value);
+    }
+    drop(value);
+}
+
+impl <T: Zeroize + 'static> RcOCell<T> {
+    ///
+    /// Zeroizes the values the cell drops itself before they are dropped (feature `zeroize`).
+    /// These are the old value when a compute Fn returns Replace or Remove, the old value replaced by a deferred value
+    /// and the value still in the cell when its last handle is dropped.
+    /// Disabled by default.
+    ///
+    /// Values that are returned to the caller, i.e. by set, replace, clear and get_and_clear, are not zeroized,
+    /// the caller is responsible for them. Use set_zeroizing and clear_zeroizing to discard the old value instead.
+    /// The value is not zeroized on the final drop if the last handle is a plain Rc
+    /// or finalizers registered with on_final_drop receive the value.
+    ///
+    pub fn zeroize_discarded(&self, enabled: bool) {
+        let zeroize: Option<fn(&mut T)> = if enabled { Some(T::zeroize) } else { None };
+        shared::with_typed(&self.rc, |typed| typed.zeroize = zeroize);
+    }
+
+    ///
+    /// Zeroizes the value in the cell and clears the cell, the zeroized value is dropped instead of returned.
+    /// Works regardless of zeroize_discarded.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    #[track_caller]
+    pub fn clear_zeroizing(&self) {
+        if let Err(e) = self.try_clear_zeroizing() {
+            self.fail(e);
+        }
+    }
+
+    ///
+    /// Zeroizes the value in the cell and clears the cell, see clear_zeroizing.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    pub fn try_clear_zeroizing(&self) -> Result<(), RcOCellError> {
+        self.zeroize_in_place()?;
+        drop(self.clear());
+        return Ok(());
+    }
+
+    ///
+    /// Zeroizes the old value in the cell (if an old value existed) and sets the new value, the old value is dropped instead of returned.
+    /// Works regardless of zeroize_discarded.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    #[track_caller]
+    pub fn set_zeroizing(&self, value: T) {
+        if let Err(e) = self.try_set_zeroizing(value) {
+            self.fail(e);
+        }
+    }
+
+    ///
+    /// Zeroizes the old value in the cell and sets the new value, see set_zeroizing.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    pub fn try_set_zeroizing(&self, value: T) -> Result<(), RcOCellError> {
+        self.zeroize_in_place()?;
+        drop(self.set(value));
+        return Ok(());
+    }
+
+    ///
+    /// Zeroizes the value where it is stored, so no copy is left in the allocation of the cell.
+    ///
+    fn zeroize_in_place(&self) -> Result<(), RcOCellError> {
+        self.check_mut()?;
+        if let Some(value) = self.rc.borrow_mut().as_mut() {
+            value.zeroize();
+        }
+
+        return Ok(());
+    }
+}
+
+impl <T: Zeroize + 'static> WeakRcOCell<T> {
+    ///
+    /// Zeroizes the value in the cell and clears the cell, see RcOCell::clear_zeroizing.
+    /// Panics if the cell was dropped or the value is borrowed somewhere.
+    ///
+    pub fn clear_zeroizing(&self) {
+        self.try_upgrade()
+            .expect("WeakRcOCell::clear_zeroizing called on a dropped cell")
+            .clear_zeroizing()
+    }
+
+    ///
+    /// Zeroizes the value in the cell and clears the cell, see RcOCell::clear_zeroizing.
+    /// Fails if the cell was dropped or the value is borrowed somewhere.
+    ///
+    pub fn try_clear_zeroizing(&self) -> Result<(), RcOCellError> {
+        self.try_upgrade()?
+            .try_clear_zeroizing()
+    }
+
+    ///
+    /// Zeroizes the old value in the cell and sets the new value, see RcOCell::set_zeroizing.
+    /// Panics if the cell was dropped or the value is borrowed somewhere.
+    ///
+    pub fn set_zeroizing(&self, value: T) {
+        self.try_upgrade()
+            .expect("WeakRcOCell::set_zeroizing called on a dropped cell")
+            .set_zeroizing(value)
+    }
+
+    ///
+    /// Zeroizes the old value in the cell and sets the new value, see RcOCell::set_zeroizing.
+    /// Fails if the cell was dropped or the value is borrowed somewhere.
+    ///
+    pub fn try_set_zeroizing(&self, value: T) -> Result<(), RcOCellError> {
+        self.try_upgrade()?
+            .try_set_zeroizing(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use zeroize::Zeroize;
+    use crate::*;
+
+    type Log = Rc<RefCell<Vec<[u8; 4]>>>;
+
+    ///
+    /// Records its bytes when it is dropped, so the tests can tell whether it was zeroized before.
+    ///
+    struct Secret {
+        bytes: [u8; 4],
+        log: Log,
+    }
+
+    impl Zeroize for Secret {
+        fn zeroize(&mut self) {
+            self.bytes.zeroize();
+        }
+    }
+
+    impl Drop for Secret {
+        fn drop(&mut self) {
+            self.log.borrow_mut().push(self.bytes);
+        }
+    }
+
+    fn secret(log: &Log, b: u8) -> Secret {
+        return Secret {bytes: [b; 4], log: log.clone()};
+    }
+
+    fn dropped(log: &Log) -> Vec<[u8; 4]> {
+        return std::mem::take(&mut *log.borrow_mut());
+    }
+
+    #[test]
+    fn test_zeroize_discarded() {
+        let log = Log::default();
+        let cell = RcOCell::from_value(secret(&log, 1));
+        cell.compute(|_| RcOCellComputeResult::Replace(secret(&log, 2)));
+        assert_eq!(dropped(&log), vec![[1; 4]]);
+
+        cell.zeroize_discarded(true);
+        cell.compute(|_| RcOCellComputeResult::Replace(secret(&log, 3)));
+        cell.compute_if_present(|_| RcOCellComputeResult::Remove);
+        assert_eq!(dropped(&log), vec![[0; 4], [0; 4]]);
+
+        cell.set(secret(&log, 4));
+        let returned = cell.set(secret(&log, 5)).unwrap();
+        assert_eq!(returned.bytes, [4; 4]);
+        drop(returned);
+        assert_eq!(cell.get_and_clear().bytes, [5; 4]);
+        assert_eq!(dropped(&log), vec![[4; 4], [5; 4]]);
+
+        cell.set(secret(&log, 6));
+        drop(cell);
+        assert_eq!(dropped(&log), vec![[0; 4]]);
+    }
+
+    #[test]
+    fn test_zeroize_deferred_and_final_drop() {
+        let log = Log::default();
+        let cell = RcOCell::from_value(secret(&log, 1));
+        cell.zeroize_discarded(true);
+        let brw = cell.borrow();
+        cell.set_deferred(secret(&log, 2));
+        drop(brw);
+        assert_eq!(dropped(&log), vec![[0; 4]]);
+        assert_eq!(cell.borrow().bytes, [2; 4]);
+
+        let log2 = log.clone();
+        cell.on_final_drop(move |value| {
+            let bytes = value.unwrap().bytes;
+            log2.borrow_mut().push(bytes);
+        });
+        drop(cell);
+        assert_eq!(dropped(&log), vec![[2; 4], [2; 4]]);
+
+        let cell = RcOCell::from_value(secret(&log, 3));
+        cell.zeroize_discarded(true);
+        cell.zeroize_discarded(false);
+        drop(cell);
+        assert_eq!(dropped(&log), vec![[3; 4]]);
+    }
+
+    #[test]
+    fn test_clear_zeroizing() {
+        let log = Log::default();
+        let cell = RcOCell::from_value(secret(&log, 1));
+        cell.set_zeroizing(secret(&log, 2));
+        assert_eq!(dropped(&log), vec![[0; 4]]);
+        cell.clear_zeroizing();
+        assert_eq!(dropped(&log), vec![[0; 4]]);
+        assert_eq!(cell.is_none(), true);
+        cell.clear_zeroizing();
+        assert_eq!(dropped(&log).is_empty(), true);
+
+        let weak = cell.downgrade();
+        weak.set_zeroizing(secret(&log, 3));
+        let brw = cell.borrow();
+        match weak.try_clear_zeroizing() {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        match cell.try_set_zeroizing(secret(&log, 4)) {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(cell.catch(|c| c.clear_zeroizing()).is_err(), true);
+        drop(brw);
+        assert_eq!(dropped(&log), vec![[4; 4]]);
+        assert_eq!(cell.borrow().bytes, [3; 4]);
+
+        drop(cell);
+        match weak.try_set_zeroizing(secret(&log, 5)) {
+            Err(RcOCellError::Dropped) => {}
+            _ => panic!("unexpected"),
+        };
+    }
+}
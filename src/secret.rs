@@ -0,0 +1,243 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::Deref;
+use crate::{shared, RcOCell, RcOCellError, RcORef, RcORefMut};
+
+///
+/// Cell for sensitive values that never formats its value.
+///
+/// Debug and Display print `[REDACTED]` regardless of T, the value can only be read with expose_secret or with_exposed,
+/// so it cannot end up in a log by accident, i.e. through the Debug of a struct containing the cell.
+///
+/// Wrapping a cell marks the cell itself as redacted, this cannot be undone.
+/// The Debug and Display of every RcOCell handle of the cell print `[REDACTED]` too,
+/// and the tracing feature does not log its value even if log_values was enabled.
+///
+pub struct SecretRcOCell<T> {
+    cell: RcOCell<T>,
+}
+
+impl <T> SecretRcOCell<T> {
+    ///
+    /// Constructs a new empty SecretRcOCell.
+    ///
+    #[track_caller]
+    pub fn new() -> SecretRcOCell<T> {
+        return SecretRcOCell::wrap(RcOCell::new());
+    }
+
+    ///
+    /// Constructs a new SecretRcOCell with a value.
+    ///
+    #[track_caller]
+    pub fn from_value(value: T) -> SecretRcOCell<T> {
+        return SecretRcOCell::wrap(RcOCell::from_value(value));
+    }
+
+    ///
+    /// Wraps the cell and marks it as redacted, including all other handles of it.
+    /// This is the security boundary: the value may already have been formatted through the other handles before.
+    ///
+    pub fn wrap(cell: RcOCell<T>) -> SecretRcOCell<T> {
+        shared::with_state(&cell.rc, |state| state.redacted = true);
+        return SecretRcOCell {cell};
+    }
+
+    ///
+    /// Unwraps the cell. The cell stays redacted, its Debug and Display keep printing `[REDACTED]`,
+    /// but the value can be read without expose_secret from now on.
+    ///
+    pub fn into_inner(self) -> RcOCell<T> {
+        return self.cell;
+    }
+
+    ///
+    /// Borrows the secret value.
+    /// Panics if the value is already borrowed mutably somewhere or there is no value.
+    ///
+    #[track_caller]
+    pub fn expose_secret(&self) -> RcORef<'_, T> {
+        return self.cell.borrow();
+    }
+
+    ///
+    /// Borrows the secret value.
+    /// Fails if the value is already borrowed mutably somewhere or there is no value.
+    ///
+    #[cfg_attr(feature = "debug-borrow-tracking", track_caller)]
+    pub fn try_expose_secret(&self) -> Result<RcORef<'_, T>, RcOCellError> {
+        return self.cell.try_borrow();
+    }
+
+    ///
+    /// Borrows the secret value mutably.
+    /// Panics if the value is already borrowed somewhere or there is no value.
+    ///
+    #[track_caller]
+    pub fn expose_secret_mut(&self) -> RcORefMut<'_, T> {
+        return self.cell.borrow_mut();
+    }
+
+    ///
+    /// Borrows the secret value mutably.
+    /// Fails if the value is already borrowed somewhere or there is no value.
+    ///
+    #[cfg_attr(feature = "debug-borrow-tracking", track_caller)]
+    pub fn try_expose_secret_mut(&self) -> Result<RcORefMut<'_, T>, RcOCellError> {
+        return self.cell.try_borrow_mut();
+    }
+
+    ///
+    /// Calls the Fn with the secret value and returns the result.
+    /// Panics if the value is already borrowed mutably somewhere or there is no value.
+    ///
+    #[track_caller]
+    pub fn with_exposed<R, F>(&self, f: F) -> R
+        where F: FnOnce(&T) -> R
+    {
+        return f(self.expose_secret().deref());
+    }
+
+    ///
+    /// Calls the Fn with the secret value and returns the result.
+    /// Fails if the value is already borrowed mutably somewhere or there is no value.
+    ///
+    pub fn try_with_exposed<R, F>(&self, f: F) -> Result<R, RcOCellError>
+        where F: FnOnce(&T) -> R
+    {
+        let exposed = self.try_expose_secret()?;
+        return Ok(f(exposed.deref()));
+    }
+
+    ///
+    /// Sets the secret value returning the old value (if an old value existed).
+    /// Panics if the value is borrowed somewhere.
+    ///
+    #[track_caller]
+    pub fn set(&self, value: T) -> Option<T> {
+        return self.cell.set(value);
+    }
+
+    ///
+    /// Clears the secret value returning the old value (if an old value existed).
+    /// Panics if the value is borrowed somewhere.
+    ///
+    #[track_caller]
+    pub fn clear(&self) -> Option<T> {
+        return self.cell.clear();
+    }
+
+    ///
+    /// Returns true if a secret value is set, never panics.
+    ///
+    pub fn is_some(&self) -> bool {
+        return self.cell.is_some();
+    }
+}
+
+impl <T> RcOCell<T> {
+    ///
+    /// Returns true if the cell was wrapped in a SecretRcOCell, its value is never formatted then.
+    ///
+    pub fn is_redacted(&self) -> bool {
+        return shared::with_existing_state(&self.rc, |state| state.redacted).unwrap_or(false);
+    }
+}
+
+impl <T> Clone for SecretRcOCell<T> {
+    fn clone(&self) -> Self {
+        return SecretRcOCell {cell: self.cell.clone()};
+    }
+}
+
+impl <T> Default for SecretRcOCell<T> {
+    #[track_caller]
+    fn default() -> Self {
+        return SecretRcOCell::new();
+    }
+}
+
+impl <T> Debug for SecretRcOCell<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return f.write_str("[REDACTED]");
+    }
+}
+
+impl <T> Display for SecretRcOCell<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return f.write_str("[REDACTED]");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[derive(Debug)]
+    #[allow(dead_code)]
+    struct Credentials {
+        user: String,
+        password: SecretRcOCell<String>,
+    }
+
+    #[test]
+    fn test_secret_redacted() {
+        let secret = SecretRcOCell::from_value(String::from("hunter2"));
+        assert_eq!(format!("{}", secret), "[REDACTED]");
+        assert_eq!(format!("{:?}", secret), "[REDACTED]");
+        assert_eq!(format!("{:#?}", SecretRcOCell::<u32>::new()), "[REDACTED]");
+
+        let credentials = Credentials {user: String::from("admin"), password: secret.clone()};
+        let text = format!("{:?}", credentials);
+        assert_eq!(text, "Credentials { user: \"admin\", password: [REDACTED] }");
+        assert_eq!(format!("{:#?}", credentials).contains("hunter2"), false);
+
+        assert_eq!(*secret.expose_secret(), "hunter2");
+        assert_eq!(secret.with_exposed(|s| s.len()), 7);
+    }
+
+    #[test]
+    fn test_secret_wrap_marks_cell() {
+        let cell = RcOCell::from_value(String::from("token"));
+        let other = cell.clone();
+        assert_eq!(format!("{}", other), "token");
+        assert_eq!(cell.is_redacted(), false);
+
+        let secret = SecretRcOCell::wrap(cell);
+        assert_eq!(other.is_redacted(), true);
+        assert_eq!(format!("{}", other), "[REDACTED]");
+        assert_eq!(format!("{:?}", other), "[REDACTED]");
+
+        let cell = secret.into_inner();
+        assert_eq!(format!("{:?}", cell), "[REDACTED]");
+        assert_eq!(cell.get_and_clone(), "token");
+    }
+
+    #[test]
+    fn test_secret_borrow_rules() {
+        let secret = SecretRcOCell::from_value(1u32);
+        let exposed = secret.expose_secret();
+        match secret.try_expose_secret_mut() {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(*secret.try_expose_secret().unwrap(), 1);
+        drop(exposed);
+
+        let exposed = secret.expose_secret_mut();
+        match secret.try_with_exposed(|v| *v) {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| secret.set(2))).is_err(), true);
+        drop(exposed);
+
+        *secret.expose_secret_mut() += 1;
+        assert_eq!(secret.set(5), Some(2));
+        assert_eq!(secret.clear(), Some(5));
+        assert_eq!(secret.is_some(), false);
+        match secret.try_expose_secret() {
+            Err(RcOCellError::NoValue) => {}
+            _ => panic!("unexpected"),
+        };
+    }
+}
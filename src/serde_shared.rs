@@ -0,0 +1,351 @@
+//!
+//! Serialization that preserves the sharing of cells, enabled by the `rc-dedup` feature.
+//!
+//! Every distinct cell is serialized once together with an id, further occurrences of the same cell only serialize the id.
+//! Deserialization rebuilds the sharing, fields that pointed at the same cell point at one cell again.
+//! Cycles are supported as long as they are broken by a WeakRcOCell.
+//!
+//! Cell fields are annotated with `#[serde(with = "rco_cell::serde_shared")]`,
+//! weak cell fields with `#[serde(with = "rco_cell::serde_shared::weak")]`,
+//! and the root value is (de)serialized wrapped in Shared which holds the id table.
+//! Annotated fields fail to (de)serialize outside of Shared.
+//!
+//! ```
+//! use rco_cell::RcOCell;
+//! use rco_cell::serde_shared::Shared;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Model {
+//!     #[serde(with = "rco_cell::serde_shared")]
+//!     a: RcOCell<String>,
+//!     #[serde(with = "rco_cell::serde_shared")]
+//!     b: RcOCell<String>,
+//! }
+//!
+//! let cell = RcOCell::from_value("Baum".to_string());
+//! let json = serde_json::to_string(&Shared(Model {a: cell.clone(), b: cell})).unwrap();
+//! let Shared(model): Shared<Model> = serde_json::from_str(&json).unwrap();
+//! model.a.set("Nase".to_string());
+//! assert_eq!(model.b.get_and_clone(), "Nase");
+//! ```
+//!
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Formatter;
+use std::marker::PhantomData;
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::{EnumAccess, SeqAccess, VariantAccess, Visitor};
+use serde::ser::SerializeTupleVariant;
+use crate::{shared, RcOCell, WeakRcOCell};
+
+const NAME: &str = "RcOCell";
+const VARIANTS: &[&str] = &["Def", "Ref", "Dropped"];
+const OUTSIDE: &str = "serde_shared used outside of serde_shared::Shared";
+
+thread_local! {
+    static SERIALIZE: RefCell<Option<HashMap<usize, u64>>> = const { RefCell::new(None) };
+    static DESERIALIZE: RefCell<Option<HashMap<u64, Box<dyn Any>>>> = const { RefCell::new(None) };
+}
+
+///
+/// Wraps the root value of a (de)serialization, the sharing of cells inside it is preserved.
+/// Cells deserialized only through weak references are dropped once the deserialization of the Shared is done.
+///
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Shared<T>(pub T);
+
+///
+/// Installs a fresh id table for the duration of a (de)serialization and restores the previous one afterwards.
+///
+struct Scope<M: 'static> {
+    table: &'static std::thread::LocalKey<RefCell<Option<M>>>,
+    previous: Option<M>,
+}
+
+impl <M: 'static> Scope<M> {
+    fn enter(table: &'static std::thread::LocalKey<RefCell<Option<M>>>, fresh: M) -> Scope<M> {
+        let previous = table.with(|t| t.replace(Some(fresh)));
+        return Scope {table, previous};
+    }
+}
+
+impl <M: 'static> Drop for Scope<M> {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        let current = self.table.with(|t| t.replace(previous));
+        drop(current);
+    }
+}
+
+impl <T: Serialize> Serialize for Shared<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let _scope = Scope::enter(&SERIALIZE, HashMap::new());
+        return self.0.serialize(serializer);
+    }
+}
+
+impl <'de, T: Deserialize<'de>> Deserialize<'de> for Shared<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let _scope = Scope::enter(&DESERIALIZE, HashMap::new());
+        return T::deserialize(deserializer).map(Shared);
+    }
+}
+
+///
+/// Serializes the cell, or a dropped marker for None.
+///
+fn serialize_cell<T: Serialize, S: Serializer>(cell: Option<&RcOCell<T>>, serializer: S) -> Result<S::Ok, S::Error> {
+    let Some(cell) = cell else {
+        return serializer.serialize_unit_variant(NAME, 2, "Dropped");
+    };
+
+    let key = shared::key(&cell.rc);
+    let lookup = SERIALIZE.with(|table| {
+        let mut table = table.borrow_mut();
+        let table = table.as_mut()?;
+        if let Some(id) = table.get(&key) {
+            return Some((*id, false));
+        }
+
+        let id = table.len() as u64;
+        table.insert(key, id);
+        return Some((id, true));
+    });
+
+    let Some((id, first)) = lookup else {
+        return Err(ser::Error::custom(OUTSIDE));
+    };
+
+    if !first {
+        return serializer.serialize_newtype_variant(NAME, 1, "Ref", &id);
+    }
+
+    let value = cell.rc.try_borrow().map_err(|_| ser::Error::custom("cell is borrowed mutably"))?;
+    let mut variant = serializer.serialize_tuple_variant(NAME, 0, "Def", 2)?;
+    variant.serialize_field(&id)?;
+    variant.serialize_field(&*value)?;
+    return variant.end();
+}
+
+enum Variant {
+    Def,
+    Ref,
+    Dropped,
+}
+
+struct VariantVisitor;
+
+impl <'de> Visitor<'de> for VariantVisitor {
+    type Value = Variant;
+
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_str("cell variant")
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Variant, E> {
+        return match v {
+            0 => Ok(Variant::Def),
+            1 => Ok(Variant::Ref),
+            2 => Ok(Variant::Dropped),
+            _ => Err(E::invalid_value(de::Unexpected::Unsigned(v), &self)),
+        };
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Variant, E> {
+        return match v {
+            "Def" => Ok(Variant::Def),
+            "Ref" => Ok(Variant::Ref),
+            "Dropped" => Ok(Variant::Dropped),
+            _ => Err(E::unknown_variant(v, VARIANTS)),
+        };
+    }
+}
+
+impl <'de> Deserialize<'de> for Variant {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        return deserializer.deserialize_identifier(VariantVisitor);
+    }
+}
+
+///
+/// Deserializes a cell, None for the dropped marker.
+///
+struct CellVisitor<T>(PhantomData<T>);
+
+impl <'de, T: Deserialize<'de> + 'static> Visitor<'de> for CellVisitor<T> {
+    type Value = Option<RcOCell<T>>;
+
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_str("shared cell")
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+        let (variant, access) = data.variant::<Variant>()?;
+        return match variant {
+            Variant::Def => access.tuple_variant(2, self),
+            Variant::Ref => {
+                let id: u64 = access.newtype_variant()?;
+                let found = DESERIALIZE.with(|table| {
+                    let table = table.borrow();
+                    let table = table.as_ref().ok_or(OUTSIDE)?;
+                    let cell = table.get(&id).ok_or("reference to a cell that was not defined before")?;
+                    return cell.downcast_ref::<RcOCell<T>>().cloned().ok_or("reference to a cell of a different type");
+                });
+                found.map(Some).map_err(de::Error::custom)
+            }
+            Variant::Dropped => {
+                access.unit_variant()?;
+                Ok(None)
+            }
+        };
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let id: u64 = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let cell: RcOCell<T> = RcOCell::new();
+        let replaced = DESERIALIZE.with(|table| {
+            let mut table = table.borrow_mut();
+            let table = table.as_mut().ok_or(OUTSIDE)?;
+            let cell: Box<dyn Any> = Box::new(cell.clone());
+            return Ok::<_, &str>(table.insert(id, cell));
+        });
+        match replaced {
+            Ok(None) => {}
+            Ok(Some(_)) => return Err(de::Error::custom("cell defined twice")),
+            Err(e) => return Err(de::Error::custom(e)),
+        };
+
+        let value: Option<T> = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        *cell.rc.borrow_mut() = value;
+        return Ok(Some(cell));
+    }
+}
+
+fn deserialize_cell<'de, T: Deserialize<'de> + 'static, D: Deserializer<'de>>(deserializer: D) -> Result<Option<RcOCell<T>>, D::Error> {
+    return deserializer.deserialize_enum(NAME, VARIANTS, CellVisitor(PhantomData));
+}
+
+///
+/// Serializes the cell, see the module documentation.
+///
+pub fn serialize<T: Serialize, S: Serializer>(cell: &RcOCell<T>, serializer: S) -> Result<S::Ok, S::Error> {
+    return serialize_cell(Some(cell), serializer);
+}
+
+///
+/// Deserializes the cell, see the module documentation.
+///
+pub fn deserialize<'de, T: Deserialize<'de> + 'static, D: Deserializer<'de>>(deserializer: D) -> Result<RcOCell<T>, D::Error> {
+    return deserialize_cell(deserializer)?.ok_or_else(|| de::Error::custom("dropped weak cell where a cell was expected"));
+}
+
+///
+/// (De)serialization of WeakRcOCell fields, a dropped weak cell deserializes as a dropped weak cell.
+///
+pub mod weak {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use crate::WeakRcOCell;
+
+    ///
+    /// Serializes the weak cell, see the module documentation.
+    ///
+    pub fn serialize<T: Serialize, S: Serializer>(cell: &WeakRcOCell<T>, serializer: S) -> Result<S::Ok, S::Error> {
+        return super::serialize_cell(cell.try_upgrade().ok().as_ref(), serializer);
+    }
+
+    ///
+    /// Deserializes the weak cell, see the module documentation.
+    ///
+    pub fn deserialize<'de, T: Deserialize<'de> + 'static, D: Deserializer<'de>>(deserializer: D) -> Result<WeakRcOCell<T>, D::Error> {
+        return Ok(match super::deserialize_cell(deserializer)? {
+            Some(cell) => cell.downgrade(),
+            None => super::dropped(),
+        });
+    }
+}
+
+fn dropped<T>() -> WeakRcOCell<T> {
+    return WeakRcOCell {rc: std::rc::Weak::new()};
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use crate::*;
+    use crate::serde_shared::Shared;
+
+    #[derive(Serialize, Deserialize)]
+    struct Aliased {
+        #[serde(with = "crate::serde_shared")]
+        a: RcOCell<Vec<u32>>,
+        #[serde(with = "crate::serde_shared")]
+        b: RcOCell<Vec<u32>>,
+        #[serde(with = "crate::serde_shared")]
+        c: RcOCell<Vec<u32>>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Parent {
+        name: String,
+        #[serde(with = "crate::serde_shared")]
+        child: RcOCell<Child>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Child {
+        #[serde(with = "crate::serde_shared::weak")]
+        parent: WeakRcOCell<Parent>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Tree {
+        #[serde(with = "crate::serde_shared")]
+        root: RcOCell<Parent>,
+    }
+
+    #[test]
+    fn test_aliased_round_trip() {
+        let shared = RcOCell::from_value(vec![1u32]);
+        let model = Aliased {a: shared.clone(), b: shared, c: RcOCell::new()};
+        let json = serde_json::to_string(&Shared(model)).unwrap();
+        let Shared(model): Shared<Aliased> = serde_json::from_str(&json).unwrap();
+        model.a.borrow_mut().push(2);
+        assert_eq!(model.b.get_and_clone(), vec![1, 2]);
+        assert_eq!(model.c.is_none(), true);
+        model.c.set(vec![3]);
+        assert_eq!(model.a.get_and_clone(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_weak_cycle_round_trip() {
+        let root: RcOCell<Parent> = RcOCell::new();
+        let child = RcOCell::from_value(Child {parent: root.downgrade()});
+        root.set(Parent {name: "root".to_string(), child});
+        let json = serde_json::to_string(&Shared(Tree {root: root.clone()})).unwrap();
+        root.borrow_mut().child.clear();
+
+        let Shared(tree): Shared<Tree> = serde_json::from_str(&json).unwrap();
+        let parent = tree.root.borrow().child.borrow().parent.upgrade();
+        assert_eq!(parent.borrow().name, "root");
+        parent.borrow_mut().name = "renamed".to_string();
+        assert_eq!(tree.root.borrow().name, "renamed");
+        drop(parent);
+        tree.root.borrow_mut().child.clear();
+    }
+
+    #[test]
+    fn test_dropped_weak() {
+        let weak = RcOCell::from_value(Parent {name: "gone".to_string(), child: RcOCell::new()}).downgrade();
+        let json = serde_json::to_string(&Shared(Child {parent: weak})).unwrap();
+        let Shared(child): Shared<Child> = serde_json::from_str(&json).unwrap();
+        assert_eq!(child.parent.try_upgrade().is_err(), true);
+    }
+
+    #[test]
+    fn test_outside_shared() {
+        let model = Aliased {a: RcOCell::new(), b: RcOCell::new(), c: RcOCell::new()};
+        assert_eq!(serde_json::to_string(&model).is_err(), true);
+    }
+}
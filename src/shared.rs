@@ -0,0 +1,442 @@
+//!
+//! State that is shared between all handles of a cell but is not part of the value itself.
+//!
+//! RcOCell converts losslessly to and from a plain `Rc<RefCell<Option<T>>>`, so this state cannot live in the allocation.
+//! It is kept in a thread local table keyed by the address of the allocation instead.
+//! Every entry holds a weak reference to its allocation, so the address cannot be reused by another allocation while the entry exists.
+//! Entries are removed when the last RcOCell of a cell is dropped, entries of cells whose last strong reference
+//! was a plain Rc are pruned lazily.
+//!
+//! Since every entry holds a weak reference, the weak count in the allocation doubles as a flag that is stored right next to the RefCell:
+//! a cell without weak references has no entry, so the lookups for such cells are skipped without touching the table.
+//! Cells that never had observers, labels, weak handles or any other state pay nothing for this table.
+//!
+//! Nothing in here may call user code while the table is borrowed,
+//! this includes dropping user closures or values since those may own cells themselves.
+//!
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::{Rc, Weak};
+
+pub(crate) type Observer<T> = Rc<dyn Fn(&T) -> bool>;
+pub(crate) type ClearObserver = Rc<dyn Fn() -> bool>;
+pub(crate) type Finalizer<T> = Box<dyn FnOnce(Option<T>)>;
+
+///
+/// The part of the shared state that depends on the type of the value.
+///
+pub(crate) struct TypedState<T> {
+    pub(crate) on_set: Vec<(u64, Observer<T>)>,
+    pub(crate) on_clear: Vec<(u64, ClearObserver)>,
+    pub(crate) on_final_drop: Vec<Finalizer<T>>,
+    ///
+    /// Formats values for the events of the tracing feature, None unless enabled with log_values.
+    ///
+    #[cfg(feature = "tracing")]
+    pub(crate) debug_value: Option<fn(&T) -> String>,
+    ///
+    /// Recording of the spy feature, None unless a SpyRcOCell wraps the cell.
+    ///
+    #[cfg(feature = "spy")]
+    pub(crate) spy: Option<Rc<crate::spy::Recorder<T>>>,
+    ///
+    /// Zeroizes values the cell discards, None unless enabled with zeroize_discarded.
+    ///
+    #[cfg(feature = "zeroize")]
+    pub(crate) zeroize: Option<fn(&mut T)>,
+}
+
+impl <T> Default for TypedState<T> {
+    fn default() -> Self {
+        return TypedState {
+            on_set: Vec::new(),
+            on_clear: Vec::new(),
+            on_final_drop: Vec::new(),
+            #[cfg(feature = "tracing")]
+            debug_value: None,
+            #[cfg(feature = "spy")]
+            spy: None,
+            #[cfg(feature = "zeroize")]
+            zeroize: None,
+        };
+    }
+}
+
+pub(crate) struct CellState {
+    anchor: *const (),
+    anchor_strong_count: unsafe fn(*const ()) -> usize,
+    anchor_drop: unsafe fn(*const ()),
+    typed: *mut (),
+    typed_drop: unsafe fn(*mut ()),
+    ///
+    /// Runs once after the last strong handle was dropped.
+    ///
+    pub(crate) cleanups: Vec<(u64, Box<dyn FnOnce()>)>,
+    ///
+    /// Number of scopes currently pushed with push_scope.
+    ///
+    pub(crate) scope_depth: usize,
+    ///
+    /// Incremented whenever observers are notified, only counts while the state exists.
+    ///
+    pub(crate) version: u64,
+    ///
+    /// Human readable name of the cell used in Debug output and panic messages.
+    ///
+    pub(crate) label: Option<Cow<'static, str>>,
+    ///
+    /// Value stored with set_deferred that waits for the borrows of the cell to end.
+    ///
+    pub(crate) deferred: Option<crate::deferred::Pending>,
+    ///
+    /// Counters of the stats feature.
+    ///
+    #[cfg(feature = "stats")]
+    pub(crate) stats: crate::CellStats,
+    ///
+    /// Set by the poison feature when a panic unwound during a mutable access.
+    ///
+    #[cfg(feature = "poison")]
+    pub(crate) poisoned: bool,
+    ///
+    /// Guards of the value that were not dropped yet, recorded by the debug-borrow-tracking feature.
+    ///
+    #[cfg(feature = "debug-borrow-tracking")]
+    pub(crate) borrows: Vec<crate::BorrowInfo>,
+    ///
+    /// Tasks waiting in borrow_async or borrow_mut_async, in the order they started waiting.
+    ///
+    #[cfg(feature = "futures")]
+    pub(crate) waiters: std::collections::VecDeque<crate::future::Waiter>,
+    ///
+    /// Set on the thread that created the cell, the entry of the debug-thread-check feature is removed with this state.
+    ///
+    #[cfg(feature = "debug-thread-check")]
+    pub(crate) thread_owner: bool,
+    ///
+    /// Set when the cell was wrapped in a SecretRcOCell, its value is never formatted afterwards.
+    ///
+    pub(crate) redacted: bool,
+    ///
+    /// Number of batches currently open with begin_batch.
+    ///
+    pub(crate) batch_depth: usize,
+    ///
+    /// Set when observers were due while a batch was open, they are notified when the outermost batch ends.
+    ///
+    pub(crate) batch_pending: bool,
+    ///
+    /// Generations for the dirty flags, None until the cell is tracked.
+    ///
+    pub(crate) dirty: Option<crate::dirty::Tracking>,
+}
+
+impl CellState {
+    fn is_dead(&self) -> bool {
+        return unsafe { (self.anchor_strong_count)(self.anchor) } == 0;
+    }
+}
+
+impl Drop for CellState {
+    fn drop(&mut self) {
+        #[cfg(feature = "debug-thread-check")]
+        if self.thread_owner {
+            crate::thread_check::forget(self.anchor as usize);
+        }
+
+        unsafe {
+            (self.typed_drop)(self.typed);
+            (self.anchor_drop)(self.anchor);
+        }
+    }
+}
+
+unsafe fn anchor_strong_count<T>(ptr: *const ()) -> usize {
+    let weak = std::mem::ManuallyDrop::new(Weak::from_raw(ptr as *const RefCell<Option<T>>));
+    return weak.strong_count();
+}
+
+unsafe fn anchor_drop<T>(ptr: *const ()) {
+    drop(Weak::from_raw(ptr as *const RefCell<Option<T>>));
+}
+
+unsafe fn typed_drop<T>(ptr: *mut ()) {
+    drop(Box::from_raw(ptr as *mut TypedState<T>));
+}
+
+struct Table {
+    cells: HashMap<usize, CellState>,
+    next_prune: usize,
+}
+
+thread_local! {
+    static TABLE: RefCell<Table> = RefCell::new(Table {cells: HashMap::new(), next_prune: 16});
+    static NEXT_ID: Cell<u64> = const { Cell::new(1) };
+}
+
+///
+/// Returns false if the cell has no state for sure, see the module documentation.
+///
+#[inline]
+pub(crate) fn may_have_state<T>(rc: &Rc<RefCell<Option<T>>>) -> bool {
+    return Rc::weak_count(rc) != 0;
+}
+
+pub(crate) fn key<T>(rc: &Rc<RefCell<Option<T>>>) -> usize {
+    return Rc::as_ptr(rc) as *const () as usize;
+}
+
+///
+/// Returns a new id that is unique for this thread.
+///
+pub(crate) fn next_id() -> u64 {
+    return NEXT_ID.with(|n| {
+        let id = n.get();
+        n.set(id + 1);
+        id
+    });
+}
+
+///
+/// Runs the Fn with the state of the cell, creating the state if it does not exist yet.
+///
+pub(crate) fn with_state<T, R>(rc: &Rc<RefCell<Option<T>>>, f: impl FnOnce(&mut CellState) -> R) -> R {
+    let key = key(rc);
+    let (result, pruned) = TABLE.with(|table| {
+        let mut table = table.borrow_mut();
+        let mut pruned = Vec::new();
+        if !table.cells.contains_key(&key) && table.cells.len() >= table.next_prune {
+            let dead: Vec<usize> = table.cells.iter().filter(|(_, s)| s.is_dead()).map(|(k, _)| *k).collect();
+            for k in dead {
+                pruned.push(table.cells.remove(&k).unwrap());
+            }
+            table.next_prune = usize::max(16, table.cells.len() * 2);
+        }
+
+        let state = table.cells.entry(key).or_insert_with(|| CellState {
+            anchor: Weak::into_raw(Rc::downgrade(rc)) as *const (),
+            anchor_strong_count: anchor_strong_count::<T>,
+            anchor_drop: anchor_drop::<T>,
+            typed: Box::into_raw(Box::new(TypedState::<T>::default())) as *mut (),
+            typed_drop: typed_drop::<T>,
+            cleanups: Vec::new(),
+            scope_depth: 0,
+            version: 0,
+            label: None,
+            deferred: None,
+            #[cfg(feature = "stats")]
+            stats: crate::CellStats::default(),
+            #[cfg(feature = "poison")]
+            poisoned: false,
+            #[cfg(feature = "debug-borrow-tracking")]
+            borrows: Vec::new(),
+            #[cfg(feature = "futures")]
+            waiters: std::collections::VecDeque::new(),
+            #[cfg(feature = "debug-thread-check")]
+            thread_owner: false,
+            redacted: false,
+            batch_depth: 0,
+            batch_pending: false,
+            dirty: None,
+        });
+
+        (f(state), pruned)
+    });
+
+    drop(pruned);
+    return result;
+}
+
+///
+/// Runs the Fn with the state of the cell if the cell has any state.
+/// Returns None without calling the Fn if it has none.
+///
+#[inline]
+pub(crate) fn with_existing_state<T, R>(rc: &Rc<RefCell<Option<T>>>, f: impl FnOnce(&mut CellState) -> R) -> Option<R> {
+    if !may_have_state(rc) {
+        return None;
+    }
+
+    return with_existing_key(key(rc), f);
+}
+
+///
+/// Like with_existing_state but looks the state up by the key of the cell.
+///
+pub(crate) fn with_existing_key<R>(key: usize, f: impl FnOnce(&mut CellState) -> R) -> Option<R> {
+    return TABLE.try_with(|table| {
+        let mut table = table.borrow_mut();
+        return table.cells.get_mut(&key).map(f);
+    }).ok().flatten();
+}
+
+///
+/// Runs the Fn with the typed state of the cell, creating the state if it does not exist yet.
+///
+pub(crate) fn with_typed<T: 'static, R>(rc: &Rc<RefCell<Option<T>>>, f: impl FnOnce(&mut TypedState<T>) -> R) -> R {
+    return with_state(rc, |state| f(unsafe { typed(state) }));
+}
+
+///
+/// Runs the Fn with the typed state of the cell if the cell has any state.
+///
+/// The typed state is created together with the state for the T of the cell at the address.
+/// The address cannot be reused while the state exists and RefCell is invariant in T,
+/// so every caller that finds the state uses the same T the state was created with.
+///
+pub(crate) fn with_existing_typed<T, R>(rc: &Rc<RefCell<Option<T>>>, f: impl FnOnce(&mut TypedState<T>) -> R) -> Option<R> {
+    return with_existing_state(rc, |state| f(unsafe { typed(state) }));
+}
+
+unsafe fn typed<T>(state: &mut CellState) -> &mut TypedState<T> {
+    return &mut *(state.typed as *mut TypedState<T>);
+}
+
+///
+/// Called by the last strong RcOCell handle right before it drops its Rc.
+/// Removes the state of the cell and runs the finalizers and cleanups registered for it.
+///
+/// A panicking finalizer does not stop the remaining finalizers and cleanups,
+/// the first panic is resumed once all of them ran unless the thread is already panicking.
+///
+#[inline]
+pub(crate) fn final_drop<T>(rc: &Rc<RefCell<Option<T>>>) {
+    if !may_have_state(rc) {
+        return;
+    }
+
+    let key = key(rc);
+    let state = TABLE.try_with(|table| table.borrow_mut().cells.remove(&key)).ok().flatten();
+    let Some(mut state) = state else {
+        return;
+    };
+
+    let cleanups = std::mem::take(&mut state.cleanups);
+    let finalizers = std::mem::take(&mut unsafe { typed::<T>(&mut state) }.on_final_drop);
+    #[cfg(feature = "zeroize")]
+    let zeroize = unsafe { typed::<T>(&mut state) }.zeroize;
+    drop(state);
+
+    #[cfg(feature = "zeroize")]
+    if let (Some(zeroize), true) = (zeroize, finalizers.is_empty()) {
+        if let Some(value) = rc.try_borrow_mut().ok().as_mut().and_then(|value| value.as_mut()) {
+            zeroize(value);
+        }
+    }
+
+    let mut panic = None;
+    if !finalizers.is_empty() {
+        let mut value = rc.try_borrow_mut().ok().and_then(|mut value| value.take());
+        for finalizer in finalizers {
+            let value = value.take();
+            if let Err(payload) = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| finalizer(value))) {
+                panic.get_or_insert(payload);
+            }
+        }
+    }
+
+    for (_, cleanup) in cleanups {
+        cleanup();
+    }
+
+    if let Some(payload) = panic {
+        if !std::thread::panicking() {
+            std::panic::resume_unwind(payload);
+        }
+    }
+}
+
+///
+/// Notifies all observers of the cell about its current state and increments its version.
+/// While a batch is open the observers are only marked as due, see begin_batch.
+/// Observers that report that they are no longer interested are removed afterwards.
+///
+#[inline]
+pub(crate) fn notify<T>(rc: &Rc<RefCell<Option<T>>>) {
+    if !may_have_state(rc) {
+        return;
+    }
+
+    let observers = with_existing_state(rc, |state| {
+        state.version += 1;
+        if state.batch_depth > 0 {
+            state.batch_pending = true;
+            return None;
+        }
+        return observers::<T>(state);
+    }).flatten();
+
+    if let Some((on_set, on_clear)) = observers {
+        deliver(rc, on_set, on_clear);
+    }
+}
+
+///
+/// Notifies all observers of the cell about its current state without incrementing its version, used when a batch ends.
+///
+pub(crate) fn notify_batched<T>(rc: &Rc<RefCell<Option<T>>>) {
+    let observers = with_existing_state(rc, |state| observers::<T>(state)).flatten();
+    if let Some((on_set, on_clear)) = observers {
+        deliver(rc, on_set, on_clear);
+    }
+}
+
+type Observers<T> = (Vec<(u64, Observer<T>)>, Vec<(u64, ClearObserver)>);
+
+fn observers<T>(state: &mut CellState) -> Option<Observers<T>> {
+    let typed = unsafe { typed::<T>(state) };
+    if typed.on_set.is_empty() && typed.on_clear.is_empty() {
+        return None;
+    }
+    return Some((typed.on_set.clone(), typed.on_clear.clone()));
+}
+
+fn deliver<T>(rc: &Rc<RefCell<Option<T>>>, on_set: Vec<(u64, Observer<T>)>, on_clear: Vec<(u64, ClearObserver)>) {
+    let mut finished = Vec::new();
+    {
+        let Ok(value) = rc.try_borrow() else {
+            return;
+        };
+
+        match value.as_ref() {
+            Some(value) => {
+                for (id, observer) in on_set.iter() {
+                    if !observer(value) {
+                        finished.push(*id);
+                    }
+                }
+            }
+            None => {
+                for (id, observer) in on_clear.iter() {
+                    if !observer() {
+                        finished.push(*id);
+                    }
+                }
+            }
+        }
+    }
+
+    drop(on_set);
+    drop(on_clear);
+    if !finished.is_empty() {
+        for id in finished {
+            drop(remove_observer(rc, id));
+        }
+    }
+}
+
+///
+/// Removes the observer with the given id, returns what was removed so the caller can drop it outside the table borrow.
+///
+pub(crate) fn remove_observer<T>(rc: &Rc<RefCell<Option<T>>>, id: u64) -> Option<(Option<Observer<T>>, Option<ClearObserver>)> {
+    return with_existing_typed(rc, |typed| {
+        let set = typed.on_set.iter().position(|(i, _)| *i == id).map(|i| typed.on_set.remove(i).1);
+        let clear = typed.on_clear.iter().position(|(i, _)| *i == id).map(|i| typed.on_clear.remove(i).1);
+        if set.is_none() && clear.is_none() {
+            return None;
+        }
+
+        return Some((set, clear));
+    }).flatten();
+}
@@ -0,0 +1,156 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use futures_sink::Sink;
+use crate::{RcOCell, RcOCellError};
+
+///
+/// Sink that stores every item in a cell (feature `futures`), returned by sink.
+///
+/// Each item replaces the previous one, so the cell always holds the latest item.
+/// The sink is not ready while the value is borrowed somewhere, the task waits like in borrow_mut_async
+/// and is woken when the guards of the cell are dropped.
+///
+pub struct CellSink<T> {
+    cell: RcOCell<T>,
+    clear_on_close: bool,
+    waiter: Option<u64>,
+}
+
+impl <T> CellSink<T> {
+    ///
+    /// Constructs a sink for the cell.
+    /// If clear_on_close is true the cell is cleared when the sink is closed, otherwise it keeps the last item.
+    ///
+    pub fn new(cell: &RcOCell<T>, clear_on_close: bool) -> CellSink<T> {
+        return CellSink {cell: cell.clone(), clear_on_close, waiter: None};
+    }
+
+    ///
+    /// Returns the cell the items are stored in.
+    ///
+    pub fn cell(&self) -> &RcOCell<T> {
+        return &self.cell;
+    }
+
+    ///
+    /// Waits until the value is not borrowed anymore.
+    ///
+    fn poll_unborrowed(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), RcOCellError>> {
+        let cell = &self.cell;
+        // The sink does not keep a borrow once it is ready, so the next waiting task is woken like after a shared borrow.
+        return cell.poll_borrow(&mut self.waiter, false, cx, || cell.rc.try_borrow_mut().map(drop).map_err(RcOCellError::from));
+    }
+}
+
+impl <T> RcOCell<T> {
+    ///
+    /// Returns a sink that stores every item in this cell (feature `futures`), the cell keeps the last item when the sink is closed.
+    /// Use CellSink::new to clear the cell on close instead.
+    ///
+    pub fn sink(&self) -> CellSink<T> {
+        return CellSink::new(self, false);
+    }
+}
+
+impl <T> Sink<T> for CellSink<T> {
+    type Error = RcOCellError;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        return self.get_mut().poll_unborrowed(cx);
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
+        self.cell.try_set(item)?;
+        return Ok(());
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        return self.get_mut().poll_unborrowed(cx);
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.get_mut();
+        if this.poll_unborrowed(cx)?.is_pending() {
+            return Poll::Pending;
+        }
+
+        if this.clear_on_close {
+            this.cell.try_clear()?;
+        }
+        return Poll::Ready(Ok(()));
+    }
+}
+
+impl <T> Drop for CellSink<T> {
+    fn drop(&mut self) {
+        self.cell.stop_waiting(&mut self.waiter, true);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::pin::Pin;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+    use futures::executor::block_on;
+    use futures::{stream, StreamExt};
+    use futures_sink::Sink;
+    use crate::*;
+
+    #[derive(Default)]
+    struct Flag(AtomicBool);
+
+    impl Wake for Flag {
+        fn wake(self: Arc<Self>) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_forward_into_sink() {
+        let cell: RcOCell<u32> = RcOCell::new();
+        block_on(stream::iter(1..=5).map(Ok).forward(cell.sink())).unwrap();
+        assert_eq!(cell.get_and_clone(), 5);
+
+        let seen = RcOCell::from_value(Vec::new());
+        let seen2 = seen.clone();
+        cell.on_set(move |v| seen2.borrow_mut().push(*v));
+        block_on(stream::iter(vec![7, 8]).map(Ok).forward(CellSink::new(&cell, true))).unwrap();
+        assert_eq!(cell.is_none(), true);
+        assert_eq!(seen.get_and_clone(), vec![7, 8]);
+    }
+
+    #[test]
+    fn test_sink_borrowed() {
+        let cell = RcOCell::from_value(0u32);
+        let flag = Arc::new(Flag::default());
+        let waker = Waker::from(flag.clone());
+        let mut cx = Context::from_waker(&waker);
+        let mut sink = cell.sink();
+
+        let guard = cell.borrow();
+        assert_eq!(Pin::new(&mut sink).poll_ready(&mut cx).is_pending(), true);
+        match Pin::new(&mut sink).start_send(1) {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(flag.0.load(Ordering::SeqCst), false);
+        drop(guard);
+        assert_eq!(flag.0.load(Ordering::SeqCst), true);
+
+        match Pin::new(&mut sink).poll_ready(&mut cx) {
+            Poll::Ready(Ok(())) => {}
+            _ => panic!("unexpected"),
+        };
+        Pin::new(&mut sink).start_send(2).unwrap();
+        let guard = cell.borrow_mut();
+        assert_eq!(Pin::new(&mut sink).poll_close(&mut cx).is_pending(), true);
+        drop(guard);
+        match Pin::new(&mut sink).poll_close(&mut cx) {
+            Poll::Ready(Ok(())) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(cell.get_and_clone(), 2);
+    }
+}
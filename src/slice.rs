@@ -0,0 +1,168 @@
+use crate::{RcOCell, RcOCellError};
+
+///
+/// Queries over a collection of cells, implemented for slices so they work for Vec and arrays of cells.
+///
+/// Methods that borrow values come in two flavors:
+/// the normal flavor skips cells whose value is borrowed mutably somewhere,
+/// the try_ flavor fails with the index of the first such cell.
+/// Empty cells are skipped by both flavors.
+///
+pub trait CellSliceExt<T> {
+    ///
+    /// Returns clones of the values of the cells that hold a value, in order.
+    /// Cells whose value is borrowed mutably somewhere are skipped.
+    ///
+    fn values_cloned(&self) -> Vec<T> where T: Clone;
+
+    ///
+    /// Returns clones of the values of the cells that hold a value, in order.
+    /// Fails with the index of the first cell whose value is borrowed mutably somewhere.
+    ///
+    fn try_values_cloned(&self) -> Result<Vec<T>, (usize, RcOCellError)> where T: Clone;
+
+    ///
+    /// Returns true if every cell holds a value, true for an empty slice.
+    /// Never panics, a borrowed value counts as present.
+    ///
+    fn all_present(&self) -> bool;
+
+    ///
+    /// Returns the number of cells that hold a value.
+    /// Never panics, a borrowed value counts as present.
+    ///
+    fn count_present(&self) -> usize;
+
+    ///
+    /// Returns the first cell that holds a value.
+    /// Never panics, a borrowed value counts as present.
+    ///
+    fn first_present(&self) -> Option<&RcOCell<T>>;
+
+    ///
+    /// Returns the index of the first cell whose value matches the predicate.
+    /// Each cell is borrowed while the predicate runs, so the predicate cannot modify it.
+    /// Cells whose value is borrowed mutably somewhere are skipped.
+    ///
+    fn position_by<F>(&self, pred: F) -> Option<usize>
+        where F: FnMut(&T) -> bool;
+
+    ///
+    /// Returns the index of the first cell whose value matches the predicate, see position_by.
+    /// Fails with the index of the first cell whose value is borrowed mutably somewhere before a match was found.
+    ///
+    fn try_position_by<F>(&self, pred: F) -> Result<Option<usize>, (usize, RcOCellError)>
+        where F: FnMut(&T) -> bool;
+}
+
+impl <T> CellSliceExt<T> for [RcOCell<T>] {
+    fn values_cloned(&self) -> Vec<T> where T: Clone {
+        return self.iter().filter_map(|cell| cell.try_get_and_clone().ok()).collect();
+    }
+
+    fn try_values_cloned(&self) -> Result<Vec<T>, (usize, RcOCellError)> where T: Clone {
+        let mut values = Vec::new();
+        for (index, cell) in self.iter().enumerate() {
+            match cell.try_get_and_clone() {
+                Ok(value) => values.push(value),
+                Err(RcOCellError::NoValue) => {}
+                Err(e) => return Err((index, e)),
+            }
+        }
+
+        return Ok(values);
+    }
+
+    fn all_present(&self) -> bool {
+        return self.iter().all(|cell| cell.is_some());
+    }
+
+    fn count_present(&self) -> usize {
+        return self.iter().filter(|cell| cell.is_some()).count();
+    }
+
+    fn first_present(&self) -> Option<&RcOCell<T>> {
+        return self.iter().find(|cell| cell.is_some());
+    }
+
+    fn position_by<F>(&self, mut pred: F) -> Option<usize>
+        where F: FnMut(&T) -> bool
+    {
+        return self.iter().position(|cell| cell.try_borrow().is_ok_and(|value| pred(&value)));
+    }
+
+    fn try_position_by<F>(&self, mut pred: F) -> Result<Option<usize>, (usize, RcOCellError)>
+        where F: FnMut(&T) -> bool
+    {
+        for (index, cell) in self.iter().enumerate() {
+            match cell.try_borrow() {
+                Ok(value) => if pred(&value) {
+                    return Ok(Some(index));
+                },
+                Err(RcOCellError::NoValue) => {}
+                Err(e) => return Err((index, e)),
+            }
+        }
+
+        return Ok(None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn mixed() -> [RcOCell<u32>; 5] {
+        return [
+            RcOCell::new(),
+            RcOCell::from_value(1),
+            RcOCell::from_value(2),
+            RcOCell::new(),
+            RcOCell::from_value(3),
+        ];
+    }
+
+    #[test]
+    fn test_slice_queries() {
+        let cells = mixed();
+        assert_eq!(cells.values_cloned(), vec![1, 2, 3]);
+        assert_eq!(cells.try_values_cloned().unwrap(), vec![1, 2, 3]);
+        assert_eq!(cells.all_present(), false);
+        assert_eq!(cells.count_present(), 3);
+        assert_eq!(cells.first_present().unwrap().id() == cells[1].id(), true);
+        assert_eq!(cells.position_by(|v| *v > 1), Some(2));
+        assert_eq!(cells.try_position_by(|v| *v > 5).unwrap(), None);
+
+        let present: Vec<RcOCell<u32>> = cells.iter().filter(|c| c.is_some()).cloned().collect();
+        assert_eq!(present.all_present(), true);
+        let empty: Vec<RcOCell<u32>> = Vec::new();
+        assert_eq!(empty.all_present(), true);
+        assert_eq!(empty.first_present().is_none(), true);
+    }
+
+    #[test]
+    fn test_slice_borrowed() {
+        let cells = mixed();
+        let mut brw = cells[2].borrow_mut();
+        assert_eq!(cells.values_cloned(), vec![1, 3]);
+        match cells.try_values_cloned() {
+            Err((2, RcOCellError::BorrowError(_))) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(cells.count_present(), 3);
+
+        let mut scanned = Vec::new();
+        assert_eq!(cells.position_by(|v| { scanned.push(*v); *v > 1 }), Some(4));
+        assert_eq!(scanned, vec![1, 3]);
+        match cells.try_position_by(|v| *v > 1) {
+            Err((2, RcOCellError::BorrowError(_))) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(cells.try_position_by(|v| *v == 1).unwrap(), Some(1));
+
+        *brw = 10;
+        drop(brw);
+        assert_eq!(cells.position_by(|v| *v > 1), Some(2));
+        assert_eq!(cells.values_cloned(), vec![1, 10, 3]);
+    }
+}
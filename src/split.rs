@@ -0,0 +1,159 @@
+use crate::{RcOCell, RcOCellError, RcORefMut, WeakRcOCell};
+
+type Halves<'a, U, V, T> = (RcORefMut<'a, U, T>, RcORefMut<'a, V, T>);
+
+impl <T> RcOCell<T> {
+    ///
+    /// Borrows two disjoint components of the value mutably at the same time, like RefMut::map_split.
+    /// This allows i.e. moving data from one field of the value to another, which fails with two calls to borrow_mut.
+    /// The cell stays borrowed mutably until both guards are dropped.
+    /// Either guard can be split again with RcORefMut::map_split, i.e. to borrow three fields.
+    ///
+    /// The Fn must return references that do not overlap, the borrow checker rejects a Fn that does not:
+    /// ```compile_fail
+    /// use rco_cell::RcOCell;
+    ///
+    /// let cell = RcOCell::from_value((1u32, 2u32));
+    /// let (a, b) = cell.borrow_mut_split(|v| (&mut v.0, &mut v.0));
+    /// ```
+    ///
+    /// Panics if the value is already borrowed somewhere (either non mutably or mutably) or there is no value
+    ///
+    #[track_caller]
+    pub fn borrow_mut_split<U: ?Sized, V: ?Sized, F>(&self, f: F) -> Halves<'_, U, V, T>
+        where F: FnOnce(&mut T) -> (&mut U, &mut V)
+    {
+        return RcORefMut::map_split(self.borrow_mut(), f);
+    }
+
+    ///
+    /// Borrows two disjoint components of the value mutably at the same time, see borrow_mut_split.
+    /// Fails if the value is already borrowed somewhere (either non mutably or mutably) or there is no value,
+    /// the Fn is only called if there is a value.
+    ///
+    #[cfg_attr(feature = "debug-borrow-tracking", track_caller)]
+    pub fn try_borrow_mut_split<U: ?Sized, V: ?Sized, F>(&self, f: F) -> Result<Halves<'_, U, V, T>, RcOCellError>
+        where F: FnOnce(&mut T) -> (&mut U, &mut V)
+    {
+        return Ok(RcORefMut::map_split(self.try_borrow_mut()?, f));
+    }
+}
+
+impl <T> WeakRcOCell<T> {
+    ///
+    /// Borrows two disjoint components of the value mutably and calls the Fn with both, see RcOCell::borrow_mut_split.
+    /// The guards cannot be returned since the cell is only upgraded for the duration of the call.
+    /// Panics if the cell was dropped, there is no value or the value is borrowed somewhere.
+    ///
+    pub fn with_mut_split<U: ?Sized, V: ?Sized, R, S, F>(&self, split: S, f: F) -> R
+        where S: FnOnce(&mut T) -> (&mut U, &mut V), F: FnOnce(&mut U, &mut V) -> R
+    {
+        let cell = self.try_upgrade().expect("WeakRcOCell::with_mut_split called on a dropped cell");
+        let (mut a, mut b) = cell.borrow_mut_split(split);
+        return f(&mut a, &mut b);
+    }
+
+    ///
+    /// Borrows two disjoint components of the value mutably and calls the Fn with both, see RcOCell::borrow_mut_split.
+    /// Fails if the cell was dropped, there is no value or the value is borrowed somewhere.
+    ///
+    pub fn try_with_mut_split<U: ?Sized, V: ?Sized, R, S, F>(&self, split: S, f: F) -> Result<R, RcOCellError>
+        where S: FnOnce(&mut T) -> (&mut U, &mut V), F: FnOnce(&mut U, &mut V) -> R
+    {
+        let cell = self.try_upgrade()?;
+        let (mut a, mut b) = cell.try_borrow_mut_split(split)?;
+        return Ok(f(&mut a, &mut b));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    struct Queues {
+        pending: Vec<u32>,
+        done: Vec<u32>,
+    }
+
+    fn queues() -> RcOCell<Queues> {
+        return RcOCell::from_value(Queues {pending: vec![1, 2, 3], done: Vec::new()});
+    }
+
+    #[test]
+    fn test_borrow_mut_split() {
+        let cell = queues();
+        let (mut pending, mut done) = cell.borrow_mut_split(|q| (&mut q.pending, &mut q.done));
+        done.extend(pending.drain(..2));
+        pending.push(4);
+        assert_eq!(cell.try_borrow().is_err(), true);
+        assert_eq!(cell.try_borrow_mut_split(|q| (&mut q.pending, &mut q.done)).is_err(), true);
+        drop(pending);
+        assert_eq!(cell.try_borrow_mut().is_err(), true);
+        drop(done);
+
+        let q = cell.borrow();
+        assert_eq!(q.pending, vec![3, 4]);
+        assert_eq!(q.done, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_borrow_mut_split_again() {
+        let cell = RcOCell::from_value(((vec![1u32, 2, 3], Vec::new()), 0usize));
+        let (queues, mut moved) = cell.try_borrow_mut_split(|v| (&mut v.0, &mut v.1)).unwrap();
+        let (mut pending, mut done) = RcORefMut::map_split(queues, |q| (&mut q.0, &mut q.1));
+        while let Some(v) = pending.pop() {
+            done.push(v);
+            *moved += 1;
+        }
+
+        drop((pending, done, moved));
+        assert_eq!(cell.get_and_clone(), ((Vec::new(), vec![3, 2, 1]), 3));
+    }
+
+    #[test]
+    fn test_borrow_mut_split_fails() {
+        let cell: RcOCell<(u32, u32)> = RcOCell::new();
+        match cell.try_borrow_mut_split(|_| panic!("unexpected")) {
+            Err::<(RcORefMut<u32, _>, RcORefMut<u32, _>), _>(RcOCellError::NoValue) => {}
+            _ => panic!("unexpected"),
+        };
+
+        cell.set((1, 2));
+        let brw = cell.borrow();
+        match cell.try_borrow_mut_split(|v| (&mut v.0, &mut v.1)) {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(cell.catch(|c| { c.borrow_mut_split(|v| (&mut v.0, &mut v.1)); }).is_err(), true);
+        drop(brw);
+    }
+
+    #[test]
+    fn test_borrow_mut_split_deferred() {
+        let cell = RcOCell::from_value((1u32, 2u32));
+        let (a, b) = cell.borrow_mut_split(|v| (&mut v.0, &mut v.1));
+        cell.set_deferred((5, 6));
+        drop(a);
+        assert_eq!(*b, 2);
+        drop(b);
+        assert_eq!(cell.get_and_clone(), (5, 6));
+    }
+
+    #[test]
+    fn test_weak_with_mut_split() {
+        let cell = queues();
+        let weak = cell.downgrade();
+        let n = weak.with_mut_split(|q| (&mut q.pending, &mut q.done), |p, d| {
+            d.append(p);
+            return d.len();
+        });
+        assert_eq!(n, 3);
+        assert_eq!(cell.borrow().pending.is_empty(), true);
+
+        drop(cell);
+        match weak.try_with_mut_split(|q| (&mut q.pending, &mut q.done), |_, _| ()) {
+            Err(RcOCellError::Dropped) => {}
+            _ => panic!("unexpected"),
+        };
+    }
+}
@@ -0,0 +1,256 @@
+use std::cell::RefCell;
+use std::ops::Deref;
+use std::rc::Rc;
+use crate::{shared, RcOCell};
+
+///
+/// Operation on a cell recorded by SpyRcOCell (feature `spy`).
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CellOp<T> {
+    ///
+    /// The value was borrowed, every read counts, i.e. get_and_clone and map too.
+    ///
+    Borrow,
+    ///
+    /// The value was borrowed mutably, every write counts, i.e. set and compute too.
+    ///
+    BorrowMut,
+    ///
+    /// A value was stored, carries a clone of the value stored.
+    ///
+    Set(T),
+    ///
+    /// The value was removed.
+    ///
+    Clear,
+    ///
+    /// The value was exchanged with the value of another cell, followed by the Set or Clear that resulted from it.
+    ///
+    Swap,
+}
+
+///
+/// The operations recorded for a cell, stored in the shared state of the cell.
+///
+pub(crate) struct Recorder<T> {
+    ops: RefCell<Vec<CellOp<T>>>,
+    clone: fn(&T) -> T,
+}
+
+///
+/// Test double that records the operations performed on a cell (feature `spy`).
+///
+/// The recording lives in the shared state of the cell, so operations are recorded no matter
+/// which handle performs them: the spy itself through Deref, clones of the cell or weak handles.
+/// Hand a clone of the cell to the code under test and inspect operations afterwards.
+/// Recording happens after an operation succeeded and does not change what the operation does or returns.
+///
+/// A cell is recorded by at most one spy, wrapping a cell again makes the new spy record instead.
+/// Recording stops when the spy is dropped.
+///
+pub struct SpyRcOCell<T> {
+    cell: RcOCell<T>,
+    recorder: Rc<Recorder<T>>,
+}
+
+impl <T: Clone + 'static> SpyRcOCell<T> {
+    ///
+    /// Starts recording the operations performed on the cell.
+    ///
+    pub fn wrap(cell: RcOCell<T>) -> SpyRcOCell<T> {
+        let recorder = Rc::new(Recorder {ops: RefCell::new(Vec::new()), clone: T::clone});
+        let previous = shared::with_typed(&cell.rc, |typed| typed.spy.replace(recorder.clone()));
+        drop(previous);
+        return SpyRcOCell {cell, recorder};
+    }
+
+    ///
+    /// Creates a new empty cell and records the operations performed on it.
+    ///
+    pub fn new() -> SpyRcOCell<T> {
+        return SpyRcOCell::wrap(RcOCell::new());
+    }
+
+    ///
+    /// Creates a new cell holding the value and records the operations performed on it.
+    ///
+    pub fn from_value(value: T) -> SpyRcOCell<T> {
+        return SpyRcOCell::wrap(RcOCell::from_value(value));
+    }
+
+    ///
+    /// Returns the operations recorded so far in the order they happened.
+    ///
+    pub fn operations(&self) -> Vec<CellOp<T>> {
+        return self.recorder.ops.borrow().clone();
+    }
+
+    ///
+    /// Forgets the operations recorded so far.
+    ///
+    pub fn reset_ops(&self) {
+        let ops = std::mem::take(&mut *self.recorder.ops.borrow_mut());
+        drop(ops);
+    }
+}
+
+impl <T> SpyRcOCell<T> {
+    ///
+    /// Returns the spied on cell.
+    ///
+    pub fn cell(&self) -> &RcOCell<T> {
+        return &self.cell;
+    }
+}
+
+impl <T: Clone + 'static> Default for SpyRcOCell<T> {
+    fn default() -> Self {
+        return SpyRcOCell::new();
+    }
+}
+
+impl <T> Deref for SpyRcOCell<T> {
+    type Target = RcOCell<T>;
+
+    fn deref(&self) -> &Self::Target {
+        return &self.cell;
+    }
+}
+
+impl <T> Drop for SpyRcOCell<T> {
+    fn drop(&mut self) {
+        let removed = shared::with_existing_typed(&self.cell.rc, |typed| {
+            if typed.spy.as_ref().is_some_and(|spy| Rc::ptr_eq(spy, &self.recorder)) {
+                return typed.spy.take();
+            }
+            return None;
+        }).flatten();
+        drop(removed);
+    }
+}
+
+fn recorder<T>(cell: &RcOCell<T>) -> Option<Rc<Recorder<T>>> {
+    return shared::with_existing_typed(&cell.rc, |typed| typed.spy.clone()).flatten();
+}
+
+///
+/// Records a successful borrow of the value.
+///
+pub(crate) fn borrowed<T>(cell: &RcOCell<T>, mutable: bool) {
+    if let Some(recorder) = recorder(cell) {
+        recorder.ops.borrow_mut().push(if mutable { CellOp::BorrowMut } else { CellOp::Borrow });
+    }
+}
+
+///
+/// Records the current state of the cell after a value was stored or removed.
+///
+pub(crate) fn changed<T>(cell: &RcOCell<T>) {
+    let Some(recorder) = recorder(cell) else {
+        return;
+    };
+
+    let op = match cell.rc.try_borrow() {
+        Ok(value) => match value.as_ref() {
+            Some(value) => CellOp::Set((recorder.clone)(value)),
+            None => CellOp::Clear,
+        },
+        Err(_) => return,
+    };
+
+    recorder.ops.borrow_mut().push(op);
+}
+
+///
+/// Records that the value was exchanged with the value of another cell.
+///
+pub(crate) fn swapped<T>(cell: &RcOCell<T>) {
+    if let Some(recorder) = recorder(cell) {
+        recorder.ops.borrow_mut().push(CellOp::Swap);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn toggle(cell: &RcOCell<bool>) {
+        let on = cell.get_and_clone();
+        cell.set(!on);
+    }
+
+    fn reset(cell: &RcOCell<bool>) -> bool {
+        return cell.get_and_clear();
+    }
+
+    #[test]
+    fn test_spy_records() {
+        let spy = SpyRcOCell::from_value(false);
+        toggle(&spy);
+        toggle(&spy.clone());
+        assert_eq!(reset(&spy), false);
+        assert_eq!(spy.operations(), vec![
+            CellOp::Borrow, CellOp::BorrowMut, CellOp::Set(true),
+            CellOp::Borrow, CellOp::BorrowMut, CellOp::Set(false),
+            CellOp::BorrowMut, CellOp::Clear,
+        ]);
+
+        spy.set(true);
+        spy.reset_ops();
+        assert_eq!(spy.operations(), vec![]);
+        drop(spy.borrow());
+        drop(spy.downgrade().upgrade().borrow_mut());
+        assert_eq!(spy.is_some(), true);
+        assert_eq!(spy.operations(), vec![CellOp::Borrow, CellOp::BorrowMut]);
+    }
+
+    #[test]
+    fn test_spy_swap() {
+        let spy = SpyRcOCell::from_value(1u32);
+        let other = RcOCell::new();
+        spy.swap(&other);
+        assert_eq!(spy.operations(), vec![CellOp::Swap, CellOp::Clear]);
+        assert_eq!(other.get_and_clone(), 1);
+    }
+
+    #[test]
+    fn test_spy_same_behavior() {
+        let spy: SpyRcOCell<u32> = SpyRcOCell::new();
+        match spy.try_borrow() {
+            Err(RcOCellError::NoValue) => {}
+            _ => panic!("unexpected"),
+        };
+
+        spy.set(1);
+        let brw = spy.borrow();
+        match spy.try_set(2) {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(spy.catch(|c| c.set(2)).is_err(), true);
+        drop(brw);
+        assert_eq!(spy.operations(), vec![CellOp::Borrow, CellOp::BorrowMut, CellOp::Set(1), CellOp::Borrow]);
+    }
+
+    #[test]
+    fn test_spy_drop() {
+        let cell = RcOCell::from_value(1u32);
+        let first = SpyRcOCell::wrap(cell.clone());
+        let second = SpyRcOCell::wrap(cell.clone());
+        cell.set(2);
+        assert_eq!(first.operations(), vec![]);
+        assert_eq!(second.operations(), vec![CellOp::BorrowMut, CellOp::Set(2)]);
+
+        drop(first);
+        cell.set(3);
+        assert_eq!(second.operations().len(), 4);
+
+        let ops = second.operations();
+        drop(second);
+        cell.set(4);
+        assert_eq!(ops.len(), 4);
+        let third = SpyRcOCell::wrap(cell);
+        assert_eq!(third.operations(), vec![]);
+    }
+}
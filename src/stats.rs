@@ -0,0 +1,104 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use crate::{shared, RcOCell, WeakRcOCell};
+
+///
+/// Counters of a cell, shared by all its handles (feature `stats`).
+///
+/// Every access to the value counts as a borrow, methods that store or remove a value additionally count as a mutation.
+/// Guard producing methods like borrow count once when the guard is created.
+/// is_some, is_none, Debug and Display are not counted.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CellStats {
+    ///
+    /// Number of successful shared borrows.
+    ///
+    pub borrows: u64,
+    ///
+    /// Number of successful mutable borrows.
+    ///
+    pub borrows_mut: u64,
+    ///
+    /// Number of times a value was stored or removed.
+    ///
+    pub mutations: u64,
+    ///
+    /// Number of borrows that failed or panicked because of another borrow.
+    ///
+    pub borrow_failures: u64,
+}
+
+///
+/// Updates the counters of the cell.
+///
+pub(crate) fn record<T>(rc: &Rc<RefCell<Option<T>>>, f: impl FnOnce(&mut CellStats)) {
+    shared::with_state(rc, |state| f(&mut state.stats));
+}
+
+impl <T> RcOCell<T> {
+    ///
+    /// Returns the counters of the cell.
+    ///
+    pub fn stats(&self) -> CellStats {
+        return shared::with_existing_state(&self.rc, |state| state.stats).unwrap_or_default();
+    }
+
+    ///
+    /// Sets all counters of the cell to zero.
+    ///
+    pub fn reset_stats(&self) {
+        shared::with_existing_state(&self.rc, |state| state.stats = CellStats::default());
+    }
+}
+
+impl <T> WeakRcOCell<T> {
+    ///
+    /// Returns the counters of the cell, all zero if it was dropped.
+    ///
+    pub fn stats(&self) -> CellStats {
+        return self.try_upgrade().map(|cell| cell.stats()).unwrap_or_default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_stats_script() {
+        let x = RcOCell::from_value(1u32);
+        assert_eq!(x.stats(), CellStats::default());
+
+        drop(x.borrow());
+        drop(x.try_borrow().unwrap());
+        x.set(2);
+        x.clear();
+        assert_eq!(x.try_set(3).unwrap(), None);
+        assert_eq!(x.get_and_clone(), 3);
+        assert_eq!(x.stats(), CellStats {borrows: 3, borrows_mut: 3, mutations: 3, borrow_failures: 0});
+
+        let brw = x.borrow();
+        assert_eq!(x.try_borrow_mut().is_err(), true);
+        assert_eq!(x.try_set(4).is_err(), true);
+        drop(brw);
+        let w = x.downgrade();
+        w.set(5);
+        assert_eq!(w.stats(), CellStats {borrows: 4, borrows_mut: 4, mutations: 4, borrow_failures: 2});
+
+        x.reset_stats();
+        assert_eq!(x.stats(), CellStats::default());
+        x.fetch_add(1);
+        assert_eq!(x.stats(), CellStats {borrows: 0, borrows_mut: 1, mutations: 1, borrow_failures: 0});
+    }
+
+    #[test]
+    fn test_stats_panicking_borrow() {
+        let x = RcOCell::from_value(1u32);
+        let brw = x.borrow_mut();
+        let r = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| { x.borrow(); }));
+        assert_eq!(r.is_err(), true);
+        drop(brw);
+        assert_eq!(x.stats(), CellStats {borrows: 0, borrows_mut: 1, mutations: 0, borrow_failures: 1});
+    }
+}
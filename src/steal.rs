@@ -0,0 +1,216 @@
+use std::fmt::{Debug, Formatter};
+use std::ops::{Deref, DerefMut};
+use crate::{RcOCell, RcOCellError};
+
+///
+/// Owns a value that was taken out of a cell, see RcOCell::steal.
+/// Dropping it puts the value back into the cell unless commit or discard was called.
+///
+/// If a new value was stored in the cell while the value was stolen, the new value wins
+/// and the stolen value is dropped instead of being put back. was_repopulated reports this ahead of time.
+/// A cell that is borrowed when the StolenValue is dropped is treated the same, the stolen value is dropped silently
+/// since the borrow either holds a new value or may still store one.
+///
+#[must_use = "the value is put back into the cell right away if the StolenValue is dropped"]
+pub struct StolenValue<T> {
+    cell: RcOCell<T>,
+    value: Option<T>,
+}
+
+impl <T> RcOCell<T> {
+    ///
+    /// Takes the value out of the cell. The cell is empty until the returned StolenValue is dropped,
+    /// which puts the value back, also when the drop happens because a panic unwinds.
+    ///
+    /// Stealing and putting the value back does not notify observers since the value did not change.
+    ///
+    /// Panics if there is no value or the value is borrowed somewhere.
+    ///
+    pub fn steal(&self) -> StolenValue<T> {
+        let value = self.raw_replace(None);
+        if value.is_none() {
+            self.fail("RcOCell::steal on a cell without value");
+        }
+
+        return StolenValue {cell: self.clone(), value};
+    }
+
+    ///
+    /// Takes the value out of the cell, see steal.
+    /// Fails if there is no value or the value is borrowed somewhere.
+    ///
+    pub fn try_steal(&self) -> Result<StolenValue<T>, RcOCellError> {
+        self.check_mut()?;
+        let value = self.raw_replace(None);
+        if value.is_none() {
+            return Err(RcOCellError::NoValue);
+        }
+
+        return Ok(StolenValue {cell: self.clone(), value});
+    }
+}
+
+impl <T> StolenValue<T> {
+    ///
+    /// Returns the cell the value was stolen from.
+    ///
+    pub fn cell(&self) -> &RcOCell<T> {
+        return &self.cell;
+    }
+
+    ///
+    /// Returns true if a value was stored in the cell while this value was stolen.
+    /// The stolen value will not be put back in that case.
+    ///
+    pub fn was_repopulated(&self) -> bool {
+        return self.cell.is_some();
+    }
+
+    ///
+    /// Stores the new value in the cell instead of the stolen value, the stolen value is dropped.
+    /// Panics if the cell is borrowed somewhere.
+    ///
+    pub fn commit(mut self, new_value: T) {
+        self.value = None;
+        self.cell.set(new_value);
+    }
+
+    ///
+    /// Returns the stolen value and leaves the cell empty.
+    ///
+    pub fn discard(mut self) -> T {
+        let value = self.value.take().unwrap();
+        self.cell.changed();
+        return value;
+    }
+}
+
+impl <T> Deref for StolenValue<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        return self.value.as_ref().unwrap();
+    }
+}
+
+impl <T> DerefMut for StolenValue<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        return self.value.as_mut().unwrap();
+    }
+}
+
+impl <T> Drop for StolenValue<T> {
+    fn drop(&mut self) {
+        let Some(value) = self.value.take() else {
+            return;
+        };
+
+        let Ok(mut current) = self.cell.rc.try_borrow_mut() else {
+            return;
+        };
+
+        if current.is_none() {
+            *current = Some(value);
+        }
+    }
+}
+
+impl <T: Debug> Debug for StolenValue<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("StolenValue").field(&self.value).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic;
+    use std::panic::AssertUnwindSafe;
+    use crate::*;
+
+    fn consume(value: Vec<u32>) -> Vec<u32> {
+        let mut value = value;
+        value.push(3);
+        return value;
+    }
+
+    #[test]
+    fn test_steal_put_back() {
+        let x = RcOCell::from_value(vec![1u32]);
+        let mut stolen = x.steal();
+        assert_eq!(x.is_none(), true);
+        stolen.push(2);
+        drop(stolen);
+        assert_eq!(x.get_and_clone(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_steal_commit_discard() {
+        let x = RcOCell::from_value(vec![1u32]);
+        let stolen = x.steal();
+        let value = consume(stolen.iter().cloned().collect());
+        stolen.commit(value);
+        assert_eq!(x.get_and_clone(), vec![1, 3]);
+
+        let stolen = x.steal();
+        assert_eq!(stolen.discard(), vec![1, 3]);
+        assert_eq!(x.is_none(), true);
+    }
+
+    #[test]
+    fn test_steal_repopulated() {
+        let x = RcOCell::from_value(1u32);
+        let stolen = x.steal();
+        assert_eq!(stolen.was_repopulated(), false);
+        x.set(2);
+        assert_eq!(stolen.was_repopulated(), true);
+        drop(stolen);
+        assert_eq!(x.get_and_clone(), 2);
+    }
+
+    #[test]
+    fn test_steal_dropped_while_borrowed() {
+        let x = RcOCell::from_value(1u32);
+        let stolen = x.steal();
+        let guard = x.get_or_insert_default();
+        drop(stolen);
+        drop(guard);
+        assert_eq!(x.get_and_clone(), 0);
+
+        let stolen = x.steal();
+        x.set(2);
+        let brw = x.borrow();
+        drop(stolen);
+        assert_eq!(*brw, 2);
+        drop(brw);
+        assert_eq!(x.get_and_clone(), 2);
+    }
+
+    #[test]
+    fn test_steal_panic() {
+        let x = RcOCell::from_value(1u32);
+        let r = panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut stolen = x.steal();
+            *stolen = 5;
+            panic!("Oh no");
+        }));
+        assert_eq!(r.is_err(), true);
+        assert_eq!(x.get_and_clone(), 5);
+    }
+
+    #[test]
+    fn test_try_steal() {
+        let x: RcOCell<u32> = RcOCell::new();
+        match x.try_steal() {
+            Err(RcOCellError::NoValue) => {}
+            _ => panic!("unexpected"),
+        }
+        x.set(1);
+        let brw = x.borrow();
+        match x.try_steal() {
+            Err(RcOCellError::BorrowError(RcOCellBorrowError::Mut(_))) => {}
+            _ => panic!("unexpected"),
+        }
+        drop(brw);
+        assert_eq!(*x.try_steal().unwrap(), 1);
+    }
+}
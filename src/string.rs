@@ -0,0 +1,225 @@
+use std::fmt;
+use crate::{RcOCell, RcOCellError, RcORef, RcORefMut};
+
+///
+/// Operations for `RcOCell<String>`.
+///
+/// The modifying operations treat an empty cell like an empty String and always leave a String in the cell,
+/// so code relying on the cell always holding a value keeps working.
+/// This is the difference to the cell level operations: clear_string and take_string leave an empty String in the cell
+/// while clear and get_and_clear leave the cell itself empty.
+/// Observers are notified about every modification.
+///
+pub trait StringCellExt {
+    ///
+    /// Borrows the String as str.
+    /// Fails if there is no value or the value is borrowed mutably somewhere.
+    ///
+    fn borrow_str(&self) -> Result<RcORef<'_, str, String>, RcOCellError>;
+
+    ///
+    /// Appends the str, an empty cell gets a new String.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn push_str(&self, s: &str);
+
+    ///
+    /// Appends the str, an empty cell gets a new String.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    fn try_push_str(&self, s: &str) -> Result<(), RcOCellError>;
+
+    ///
+    /// Appends the char, an empty cell gets a new String.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn append_char(&self, c: char);
+
+    ///
+    /// Appends the char, an empty cell gets a new String.
+    /// Fails if the value is borrowed somewhere.
+    ///
+    fn try_append_char(&self, c: char) -> Result<(), RcOCellError>;
+
+    ///
+    /// Empties the String but keeps its allocation, an empty cell gets a new String.
+    /// Unlike clear the cell is populated afterwards.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn clear_string(&self);
+
+    ///
+    /// Returns the length of the String in bytes, 0 if the cell is empty.
+    /// Fails if the value is borrowed mutably somewhere.
+    ///
+    fn str_len(&self) -> Result<usize, RcOCellError>;
+
+    ///
+    /// Takes the String out of the cell and leaves an empty String in its place.
+    /// Unlike get_and_clear the cell is populated afterwards, an empty cell returns an empty String.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    fn take_string(&self) -> String;
+}
+
+///
+/// Runs the Fn with the String, an empty cell gets a new String first.
+///
+fn update<R>(cell: &RcOCell<String>, mut value: RcORefMut<'_, Option<String>, String>, f: impl FnOnce(&mut String) -> R) -> R {
+    let result = f(value.get_or_insert_with(String::new));
+    drop(value);
+    cell.changed();
+    return result;
+}
+
+impl StringCellExt for RcOCell<String> {
+    fn borrow_str(&self) -> Result<RcORef<'_, str, String>, RcOCellError> {
+        return Ok(RcORef::map(self.try_borrow()?, |s| s.as_str()));
+    }
+
+    fn push_str(&self, s: &str) {
+        update(self, self.raw_borrow_mut(), |v| v.push_str(s));
+    }
+
+    fn try_push_str(&self, s: &str) -> Result<(), RcOCellError> {
+        update(self, self.try_raw_borrow_mut()?, |v| v.push_str(s));
+        return Ok(());
+    }
+
+    fn append_char(&self, c: char) {
+        update(self, self.raw_borrow_mut(), |v| v.push(c));
+    }
+
+    fn try_append_char(&self, c: char) -> Result<(), RcOCellError> {
+        update(self, self.try_raw_borrow_mut()?, |v| v.push(c));
+        return Ok(());
+    }
+
+    fn clear_string(&self) {
+        update(self, self.raw_borrow_mut(), |v| v.clear());
+    }
+
+    fn str_len(&self) -> Result<usize, RcOCellError> {
+        return Ok(self.try_raw_borrow()?.as_ref().map(|s| s.len()).unwrap_or(0));
+    }
+
+    fn take_string(&self) -> String {
+        return update(self, self.raw_borrow_mut(), std::mem::take);
+    }
+}
+
+///
+/// Allows `write!` and `writeln!` with the cell, every call appends like push_str, an empty cell gets a new String.
+/// Fails with fmt::Error if the value is borrowed somewhere,
+/// i.e. when a value that is written reads the cell in its Display impl.
+///
+impl fmt::Write for RcOCell<String> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        return self.try_push_str(s).map_err(|_| fmt::Error);
+    }
+
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        return self.try_append_char(c).map_err(|_| fmt::Error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::{Display, Formatter, Write};
+    use crate::*;
+
+    struct Echo(RcOCell<String>);
+
+    impl Display for Echo {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            let mut cell = self.0.clone();
+            write!(cell, "echo")?;
+            return f.write_str("echoed");
+        }
+    }
+
+    #[test]
+    fn test_string_cell() {
+        let text: RcOCell<String> = RcOCell::new();
+        assert_eq!(text.str_len().unwrap(), 0);
+        match text.borrow_str() {
+            Err(RcOCellError::NoValue) => {}
+            _ => panic!("unexpected"),
+        };
+
+        text.push_str("Hello");
+        text.append_char(' ');
+        text.try_push_str("World").unwrap();
+        text.try_append_char('!').unwrap();
+        assert_eq!(&*text.borrow_str().unwrap(), "Hello World!");
+        assert_eq!(text.str_len().unwrap(), 12);
+
+        let capacity = text.borrow().capacity();
+        text.clear_string();
+        assert_eq!(text.is_some(), true);
+        assert_eq!(text.borrow().capacity(), capacity);
+        assert_eq!(text.str_len().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_take_string() {
+        let text = RcOCell::from_value("abc".to_string());
+        assert_eq!(text.take_string(), "abc");
+        assert_eq!(text.get_and_clone(), "");
+        assert_eq!(text.get_and_clear(), "");
+        assert_eq!(text.is_none(), true);
+        assert_eq!(text.take_string(), "");
+        assert_eq!(text.is_some(), true);
+
+        text.clear();
+        text.clear_string();
+        assert_eq!(text.get_and_clone(), "");
+    }
+
+    #[test]
+    fn test_string_cell_borrowed() {
+        let text = RcOCell::from_value("abc".to_string());
+        let s = text.borrow_str().unwrap();
+        assert_eq!(text.str_len().unwrap(), 3);
+        match text.try_push_str("d") {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(text.try_append_char('d').is_err(), true);
+        assert_eq!(text.catch(|text| text.take_string()).is_err(), true);
+        assert_eq!(text.catch(|text| text.clear_string()).is_err(), true);
+        drop(s);
+
+        let brw = text.borrow_mut();
+        assert_eq!(text.borrow_str().is_err(), true);
+        assert_eq!(text.str_len().is_err(), true);
+        drop(brw);
+        assert_eq!(text.get_and_clone(), "abc");
+
+        let changes = RcOCell::from_value(0u32);
+        let changes2 = changes.clone();
+        text.on_set(move |_| { changes2.set(changes2.get_and_clone() + 1); });
+        text.push_str("d");
+        text.take_string();
+        assert_eq!(changes.get_and_clone(), 2);
+    }
+
+    #[test]
+    fn test_fmt_write() {
+        let mut text: RcOCell<String> = RcOCell::new();
+        write!(text, "{}-{}", 1, 2).unwrap();
+        writeln!(text).unwrap();
+        text.write_char('x').unwrap();
+        assert_eq!(text.get_and_clone(), "1-2\nx");
+
+        let brw = text.borrow();
+        let mut handle = text.clone();
+        assert_eq!(write!(handle, "y").is_err(), true);
+        drop(brw);
+
+        assert_eq!(write!(handle, "{}", text).is_err(), true);
+        let echo = Echo(text.clone());
+        assert_eq!(write!(handle, "{}", echo).is_ok(), true);
+        assert_eq!(text.get_and_clone(), "1-2\nxechoechoed");
+    }
+}
@@ -0,0 +1,193 @@
+use crate::{RcOCell, RcOCellError};
+#[cfg(feature = "tracing")]
+use crate::trace;
+#[cfg(feature = "spy")]
+use crate::spy;
+
+///
+/// What swap_or_move_to_self did, depending on which of the two cells held a value.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapOutcome {
+    ///
+    /// Both cells held a value, the values were exchanged.
+    ///
+    Swapped,
+    ///
+    /// Only the other cell held a value, it was moved into this cell and the other cell is now empty.
+    ///
+    Moved,
+    ///
+    /// Only this cell held a value or neither cell did, nothing was changed.
+    ///
+    Unchanged,
+}
+
+impl <T> RcOCell<T> {
+    ///
+    /// Swaps the values of both cells only if both cells hold a value, returns true if they were swapped.
+    /// Unlike swap a value is never exchanged with nothing.
+    /// Both cells stay borrowed mutably from the check until the values were swapped.
+    ///
+    /// Panics if either value is borrowed somewhere or both are the same cell.
+    ///
+    #[track_caller]
+    pub fn swap_if_both_present(&self, other: &RcOCell<T>) -> bool {
+        return match self.try_swap_if_both_present(other) {
+            Ok(swapped) => swapped,
+            Err(e) => self.fail(e),
+        };
+    }
+
+    ///
+    /// Swaps the values of both cells only if both cells hold a value, see swap_if_both_present.
+    /// Fails without changing either cell if either value is borrowed somewhere,
+    /// fails with RcOCellError::DuplicateCell if both are the same cell.
+    ///
+    pub fn try_swap_if_both_present(&self, other: &RcOCell<T>) -> Result<bool, RcOCellError> {
+        return Ok(self.swap_present(other, false)? == SwapOutcome::Swapped);
+    }
+
+    ///
+    /// Swaps the values of both cells if both hold a value, moves the value of the other cell into this cell if only the other cell holds one.
+    /// Nothing is changed if only this cell holds a value, so values only ever move towards this cell, see SwapOutcome.
+    /// Both cells stay borrowed mutably from the check until the values were moved.
+    ///
+    /// Panics if either value is borrowed somewhere or both are the same cell.
+    ///
+    #[track_caller]
+    pub fn swap_or_move_to_self(&self, other: &RcOCell<T>) -> SwapOutcome {
+        return match self.try_swap_or_move_to_self(other) {
+            Ok(outcome) => outcome,
+            Err(e) => self.fail(e),
+        };
+    }
+
+    ///
+    /// Swaps the values of both cells or moves the value of the other cell into this cell, see swap_or_move_to_self.
+    /// Fails without changing either cell if either value is borrowed somewhere,
+    /// fails with RcOCellError::DuplicateCell if both are the same cell.
+    ///
+    pub fn try_swap_or_move_to_self(&self, other: &RcOCell<T>) -> Result<SwapOutcome, RcOCellError> {
+        return self.swap_present(other, true);
+    }
+
+    fn swap_present(&self, other: &RcOCell<T>, move_to_self: bool) -> Result<SwapOutcome, RcOCellError> {
+        if self.id() == other.id() {
+            return Err(RcOCellError::DuplicateCell);
+        }
+
+        let mut this = self.try_raw_borrow_mut()?;
+        let mut that = other.try_raw_borrow_mut()?;
+        let outcome = match (this.is_some(), that.is_some()) {
+            (true, true) => SwapOutcome::Swapped,
+            (false, true) if move_to_self => SwapOutcome::Moved,
+            _ => return Ok(SwapOutcome::Unchanged),
+        };
+
+        std::mem::swap(&mut *this, &mut *that);
+        drop(this);
+        drop(that);
+        #[cfg(feature = "tracing")]
+        {
+            trace::changed(self, "swap");
+            trace::changed(other, "swap");
+        }
+        #[cfg(feature = "spy")]
+        if outcome == SwapOutcome::Swapped {
+            spy::swapped(self);
+            spy::swapped(other);
+        }
+        self.changed();
+        other.changed();
+        return Ok(outcome);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn cells(a: Option<u32>, b: Option<u32>) -> (RcOCell<u32>, RcOCell<u32>) {
+        return (RcOCell::from_option(a), RcOCell::from_option(b));
+    }
+
+    #[test]
+    fn test_swap_if_both_present() {
+        let (a, b) = cells(Some(1), Some(2));
+        assert_eq!(a.swap_if_both_present(&b), true);
+        assert_eq!((a.get_and_clone(), b.get_and_clone()), (2, 1));
+
+        let (a, b) = cells(Some(1), None);
+        assert_eq!(a.swap_if_both_present(&b), false);
+        assert_eq!((a.get_and_clone(), b.is_none()), (1, true));
+
+        let (a, b) = cells(None, Some(2));
+        assert_eq!(a.try_swap_if_both_present(&b).unwrap(), false);
+        assert_eq!((a.is_none(), b.get_and_clone()), (true, 2));
+
+        let (a, b) = cells(None, None);
+        assert_eq!(a.swap_if_both_present(&b), false);
+        assert_eq!((a.is_none(), b.is_none()), (true, true));
+    }
+
+    #[test]
+    fn test_swap_or_move_to_self() {
+        let (a, b) = cells(Some(1), Some(2));
+        assert_eq!(a.swap_or_move_to_self(&b), SwapOutcome::Swapped);
+        assert_eq!((a.get_and_clone(), b.get_and_clone()), (2, 1));
+
+        let (a, b) = cells(None, Some(2));
+        assert_eq!(a.swap_or_move_to_self(&b), SwapOutcome::Moved);
+        assert_eq!((a.get_and_clone(), b.is_none()), (2, true));
+
+        let (a, b) = cells(Some(1), None);
+        assert_eq!(a.try_swap_or_move_to_self(&b).unwrap(), SwapOutcome::Unchanged);
+        assert_eq!((a.get_and_clone(), b.is_none()), (1, true));
+
+        let (a, b) = cells(None, None);
+        assert_eq!(a.swap_or_move_to_self(&b), SwapOutcome::Unchanged);
+        assert_eq!((a.is_none(), b.is_none()), (true, true));
+    }
+
+    #[test]
+    fn test_swap_present_notifies() {
+        let (a, b) = cells(None, Some(2));
+        let seen = RcOCell::from_value(Vec::new());
+        let seen2 = seen.clone();
+        a.on_set(move |v| { seen2.borrow_mut().push(format!("a {}", v)); });
+        let seen3 = seen.clone();
+        b.on_clear(move || { seen3.borrow_mut().push("b cleared".to_string()); });
+        assert_eq!(a.swap_if_both_present(&b), false);
+        assert_eq!(seen.get_and_clone().is_empty(), true);
+        a.swap_or_move_to_self(&b);
+        assert_eq!(seen.get_and_clone(), vec!["a 2".to_string(), "b cleared".to_string()]);
+    }
+
+    #[test]
+    fn test_swap_present_borrowed() {
+        let (a, b) = cells(Some(1), Some(2));
+        let brw = b.borrow();
+        match a.try_swap_if_both_present(&b) {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        match a.try_swap_or_move_to_self(&b) {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(a.catch(|a| a.swap_or_move_to_self(&b)).is_err(), true);
+        drop(brw);
+        assert_eq!((a.get_and_clone(), b.get_and_clone()), (1, 2));
+
+        let brw = a.borrow_mut();
+        assert_eq!(a.try_swap_if_both_present(&b).is_err(), true);
+        drop(brw);
+        assert_eq!((a.get_and_clone(), b.get_and_clone()), (1, 2));
+
+        match a.try_swap_if_both_present(&a.clone()) {
+            Err(RcOCellError::DuplicateCell) => {}
+            _ => panic!("unexpected"),
+        };
+    }
+}
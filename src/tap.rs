@@ -0,0 +1,136 @@
+use crate::RcOCell;
+
+impl <T> RcOCell<T> {
+    ///
+    /// Calls the Fn with the value if present and returns the cell, for peeking at a cell in the middle of an expression.
+    /// Never panics: the Fn is skipped if the cell is empty or the value is borrowed mutably somewhere.
+    ///
+    /// ```
+    /// use rco_cell::RcOCell;
+    ///
+    /// let mut seen = Vec::new();
+    /// let cell = RcOCell::builder()
+    ///     .value(1u32)
+    ///     .build()
+    ///     .tap_value(|v| *v += 1)
+    ///     .tap(|c| { c.inspect(|v| seen.push(*v)); });
+    /// assert_eq!(seen, vec![2]);
+    /// assert_eq!(cell.get_and_clone(), 2);
+    /// ```
+    ///
+    pub fn inspect<F>(&self, f: F) -> &Self
+        where F: FnOnce(&T)
+    {
+        if let Ok(value) = self.try_raw_borrow() {
+            value.as_ref().map(f);
+        }
+
+        return self;
+    }
+
+    ///
+    /// Calls the Fn if the cell is empty and returns the cell.
+    /// Never panics: the Fn is skipped if the cell holds a value or the value is borrowed mutably somewhere,
+    /// as the cell cannot be checked then.
+    ///
+    pub fn inspect_none<F>(&self, f: F) -> &Self
+        where F: FnOnce()
+    {
+        if self.try_raw_borrow().is_ok_and(|value| value.is_none()) {
+            f();
+        }
+
+        return self;
+    }
+
+    ///
+    /// Calls the Fn with the cell and returns the cell, for by-value chains like the one in inspect.
+    /// The Fn may do anything with the cell, including operations that panic.
+    ///
+    pub fn tap<F>(self, f: F) -> Self
+        where F: FnOnce(&Self)
+    {
+        f(&self);
+        return self;
+    }
+
+    ///
+    /// Calls the Fn with a mutable ref to the value if present and returns the cell, the value is modified in place.
+    /// Never panics: the Fn is skipped if the cell is empty or the value is borrowed somewhere.
+    /// Modifies the value like borrow_mut, observers registered with on_set are not called.
+    ///
+    pub fn tap_value<F>(self, f: F) -> Self
+        where F: FnOnce(&mut T)
+    {
+        if let Ok(mut value) = self.try_borrow_mut() {
+            f(&mut value);
+        }
+
+        return self;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use crate::*;
+
+    #[test]
+    fn test_tap_present() {
+        let cell = RcOCell::from_value(1u32);
+        let seen = Cell::new(0);
+        let none = Cell::new(false);
+        let chained = cell.clone()
+            .tap(|c| { c.set(5); })
+            .tap_value(|v| *v *= 2)
+            .tap(|c| {
+                c.inspect(|v| seen.set(*v)).inspect_none(|| none.set(true));
+            });
+
+        assert_eq!(seen.get(), 10);
+        assert_eq!(none.get(), false);
+        assert_eq!(Rc::ptr_eq(&chained.rc, &cell.rc), true);
+        assert_eq!(std::ptr::eq(cell.inspect(|_| {}), &cell), true);
+        assert_eq!(cell.get_and_clone(), 10);
+    }
+
+    #[test]
+    fn test_tap_empty() {
+        let cell: RcOCell<u32> = RcOCell::new();
+        let none = Cell::new(0);
+        let chained = cell.clone()
+            .tap_value(|_| panic!("unexpected"))
+            .tap(|c| {
+                c.inspect(|_| panic!("unexpected"))
+                    .inspect_none(|| none.set(none.get() + 1))
+                    .inspect_none(|| none.set(none.get() + 1));
+            });
+
+        assert_eq!(none.get(), 2);
+        assert_eq!(Rc::ptr_eq(&chained.rc, &cell.rc), true);
+        assert_eq!(cell.is_none(), true);
+    }
+
+    #[test]
+    fn test_tap_borrowed() {
+        let cell = RcOCell::from_value(1u32);
+        let brw = cell.borrow_mut();
+        let chained = cell.clone()
+            .tap_value(|_| panic!("unexpected"))
+            .tap(|c| {
+                c.inspect(|_| panic!("unexpected")).inspect_none(|| panic!("unexpected"));
+            });
+        assert_eq!(Rc::ptr_eq(&chained.rc, &cell.rc), true);
+        drop(brw);
+
+        let seen = Cell::new(0);
+        let brw = cell.borrow();
+        let chained = chained
+            .tap_value(|_| panic!("unexpected"))
+            .tap(|c| { c.inspect(|v| seen.set(*v)); });
+        drop(brw);
+        assert_eq!(seen.get(), 1);
+        assert_eq!(chained.tap_value(|v| *v += 1).get_and_clone(), 2);
+    }
+}
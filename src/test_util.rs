@@ -0,0 +1,219 @@
+//!
+//! Assertion macros for tests of code using cells.
+//!
+//! The macros accept an RcOCell or a WeakRcOCell and only borrow the value, so the value does not need to be Clone.
+//! Unlike `assert_eq!(cell.get_and_clone(), expected)` a failing assertion reports the state of the cell,
+//! which is either the value or one of `<empty>`, `<borrowed mutably>`, `<dropped>` and `<poisoned>`.
+//!
+use std::fmt::Debug;
+use crate::{RcOCell, RcOCellError, WeakRcOCell};
+
+///
+/// Gives the assertion macros access to the value of RcOCell and WeakRcOCell alike.
+///
+#[doc(hidden)]
+pub trait AssertCell<T> {
+    fn assert_cell_state<R>(&self, f: impl FnOnce(Result<&T, RcOCellError>) -> R) -> R;
+}
+
+impl <T> AssertCell<T> for RcOCell<T> {
+    fn assert_cell_state<R>(&self, f: impl FnOnce(Result<&T, RcOCellError>) -> R) -> R {
+        return match self.try_borrow() {
+            Ok(value) => f(Ok(&*value)),
+            Err(e) => f(Err(e)),
+        };
+    }
+}
+
+impl <T> AssertCell<T> for WeakRcOCell<T> {
+    fn assert_cell_state<R>(&self, f: impl FnOnce(Result<&T, RcOCellError>) -> R) -> R {
+        return match self.try_upgrade() {
+            Ok(cell) => cell.assert_cell_state(f),
+            Err(e) => f(Err(e)),
+        };
+    }
+}
+
+///
+/// Describes the state of a cell for the failure message of an assertion.
+///
+#[doc(hidden)]
+pub fn describe<T: Debug>(state: &Result<&T, RcOCellError>) -> String {
+    return match state {
+        Ok(value) => format!("{:?}", value),
+        Err(RcOCellError::NoValue) => "<empty>".to_string(),
+        Err(RcOCellError::BorrowError(_)) => "<borrowed mutably>".to_string(),
+        Err(RcOCellError::Dropped) => "<dropped>".to_string(),
+        Err(RcOCellError::Poisoned) => "<poisoned>".to_string(),
+        Err(e) => format!("<{}>", e),
+    };
+}
+
+///
+/// Asserts that the cell holds a value equal to the expected value.
+/// On failure the message contains the state of the cell and the expected value.
+///
+/// ```
+/// use rco_cell::{assert_cell_eq, RcOCell};
+///
+/// let cell = RcOCell::from_value(vec![1, 2]);
+/// assert_cell_eq!(cell, vec![1, 2]);
+/// assert_cell_eq!(cell.downgrade(), [1, 2]);
+/// ```
+///
+#[macro_export]
+macro_rules! assert_cell_eq {
+    ($cell:expr, $expected:expr $(,)?) => {{
+        use $crate::test_util::AssertCell as _;
+        let expected = &$expected;
+        ($cell).assert_cell_state(|state| {
+            if let Ok(value) = &state {
+                if **value == *expected {
+                    return;
+                }
+            }
+
+            panic!("assertion `cell == expected` failed\n     cell: {}\n expected: {:?}", $crate::test_util::describe(&state), expected);
+        });
+    }};
+}
+
+///
+/// Asserts that the cell is empty, a dropped cell or a value borrowed mutably fails the assertion.
+///
+#[macro_export]
+macro_rules! assert_cell_empty {
+    ($cell:expr $(,)?) => {{
+        use $crate::test_util::AssertCell as _;
+        ($cell).assert_cell_state(|state| {
+            if let Err($crate::RcOCellError::NoValue) = &state {
+                return;
+            }
+
+            panic!("assertion `cell is empty` failed\n     cell: {}", $crate::test_util::describe(&state));
+        });
+    }};
+}
+
+///
+/// Asserts that the cell holds a value that can be borrowed.
+///
+#[macro_export]
+macro_rules! assert_cell_some {
+    ($cell:expr $(,)?) => {{
+        use $crate::test_util::AssertCell as _;
+        ($cell).assert_cell_state(|state| {
+            if state.is_ok() {
+                return;
+            }
+
+            panic!("assertion `cell is some` failed\n     cell: {}", $crate::test_util::describe(&state));
+        });
+    }};
+}
+
+///
+/// Asserts that the cell holds a value matching the pattern, like `std::matches!` an optional if guard is supported.
+///
+/// ```
+/// use rco_cell::{assert_cell_matches, RcOCell};
+///
+/// let cell = RcOCell::from_value(Some(3));
+/// assert_cell_matches!(cell, Some(x) if *x > 2);
+/// ```
+///
+#[macro_export]
+macro_rules! assert_cell_matches {
+    ($cell:expr, $pattern:pat $(if $guard:expr)? $(,)?) => {{
+        use $crate::test_util::AssertCell as _;
+        ($cell).assert_cell_state(|state| {
+            if let Ok(value) = &state {
+                if ::core::matches!(*value, $pattern $(if $guard)?) {
+                    return;
+                }
+            }
+
+            panic!("assertion `cell matches {}` failed\n     cell: {}", stringify!($pattern $(if $guard)?), $crate::test_util::describe(&state));
+        });
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use crate::*;
+
+    #[derive(Debug, PartialEq)]
+    enum Light {
+        On(u8),
+        Off,
+    }
+
+    fn failure(f: impl FnOnce()) -> String {
+        let payload = catch_unwind(AssertUnwindSafe(f)).unwrap_err();
+        return match payload.downcast::<String>() {
+            Ok(message) => *message,
+            Err(_) => panic!("unexpected"),
+        };
+    }
+
+    #[test]
+    fn test_assert_cell_eq() {
+        let cell = RcOCell::from_value(Light::On(3));
+        assert_cell_eq!(cell, Light::On(3));
+        assert_cell_eq!(&cell, Light::On(3));
+        assert_cell_eq!(cell.downgrade(), Light::On(3));
+
+        let message = failure(|| assert_cell_eq!(cell, Light::Off));
+        assert_eq!(message, "assertion `cell == expected` failed\n     cell: On(3)\n expected: Off");
+
+        let brw = cell.borrow_mut();
+        let message = failure(|| assert_cell_eq!(cell, Light::On(3)));
+        assert_eq!(message, "assertion `cell == expected` failed\n     cell: <borrowed mutably>\n expected: On(3)");
+        drop(brw);
+
+        let weak = cell.downgrade();
+        cell.clear();
+        let message = failure(|| assert_cell_eq!(weak, Light::Off));
+        assert_eq!(message, "assertion `cell == expected` failed\n     cell: <empty>\n expected: Off");
+        drop(cell);
+        let message = failure(|| assert_cell_eq!(weak, Light::Off));
+        assert_eq!(message, "assertion `cell == expected` failed\n     cell: <dropped>\n expected: Off");
+    }
+
+    #[test]
+    fn test_assert_cell_empty_some() {
+        let cell: RcOCell<Light> = RcOCell::new();
+        assert_cell_empty!(cell);
+        let message = failure(|| assert_cell_some!(cell));
+        assert_eq!(message, "assertion `cell is some` failed\n     cell: <empty>");
+
+        cell.set(Light::Off);
+        assert_cell_some!(cell);
+        assert_cell_some!(cell.downgrade());
+        let message = failure(|| assert_cell_empty!(cell));
+        assert_eq!(message, "assertion `cell is empty` failed\n     cell: Off");
+
+        let weak = cell.downgrade();
+        drop(cell);
+        let message = failure(|| assert_cell_empty!(weak));
+        assert_eq!(message, "assertion `cell is empty` failed\n     cell: <dropped>");
+    }
+
+    #[test]
+    fn test_assert_cell_matches() {
+        let cell = RcOCell::from_value(Light::On(3));
+        assert_cell_matches!(cell, Light::On(_));
+        assert_cell_matches!(cell, Light::On(x) if *x > 2);
+        assert_cell_matches!(cell.downgrade(), Light::On(3) | Light::Off);
+
+        let message = failure(|| assert_cell_matches!(cell, Light::Off));
+        assert_eq!(message, "assertion `cell matches Light::Off` failed\n     cell: On(3)");
+        let message = failure(|| assert_cell_matches!(cell, Light::On(x) if *x > 3));
+        assert_eq!(message, "assertion `cell matches Light::On(x) if *x > 3` failed\n     cell: On(3)");
+
+        cell.clear();
+        let message = failure(|| assert_cell_matches!(cell, Light::Off));
+        assert_eq!(message, "assertion `cell matches Light::Off` failed\n     cell: <empty>");
+    }
+}
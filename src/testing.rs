@@ -0,0 +1,191 @@
+//!
+//! Support for property based testing of code that takes cells as input.
+//!
+//! The `arbitrary` feature implements `arbitrary::Arbitrary` for RcOCell.
+//! The `proptest` feature adds strategies that also produce shared cells, i.e. cells with further live clones and weak handles,
+//! so effects of the shared state get exercised.
+//!
+use std::fmt::{Debug, Formatter};
+use crate::{RcOCell, WeakRcOCell};
+
+#[cfg(feature = "arbitrary")]
+impl <'a, T: arbitrary::Arbitrary<'a>> arbitrary::Arbitrary<'a> for RcOCell<T> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        return Ok(RcOCell::from_option(Option::<T>::arbitrary(u)?));
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        return Option::<T>::size_hint(depth);
+    }
+}
+
+///
+/// A cell together with further handles to it, produced by `rc_o_cell`.
+///
+pub struct SharedCell<T> {
+    ///
+    /// The cell under test.
+    ///
+    pub cell: RcOCell<T>,
+    ///
+    /// Further live clones of the cell.
+    ///
+    pub clones: Vec<RcOCell<T>>,
+    ///
+    /// Weak handles to the cell.
+    ///
+    pub weaks: Vec<WeakRcOCell<T>>,
+}
+
+///
+/// A weak handle together with the strong handle keeping it alive, produced by `weak_rc_o_cell`.
+/// The handle is already dropped if owner is None.
+///
+pub struct WeakHandle<T> {
+    ///
+    /// The weak handle under test.
+    ///
+    pub weak: WeakRcOCell<T>,
+    ///
+    /// The only strong handle of the cell, None if the cell was dropped.
+    ///
+    pub owner: Option<RcOCell<T>>,
+}
+
+fn fmt_value<T: Debug>(cell: &RcOCell<T>) -> String {
+    return match cell.try_borrow() {
+        Ok(value) => format!("Some({:?})", &*value),
+        Err(e) => format!("{}", e),
+    };
+}
+
+impl <T: Debug> Debug for SharedCell<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SharedCell")
+            .field("value", &fmt_value(&self.cell))
+            .field("clones", &self.clones.len())
+            .field("weaks", &self.weaks.len())
+            .finish()
+    }
+}
+
+impl <T: Debug> Debug for WeakHandle<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match &self.owner {
+            Some(owner) => f.debug_tuple("WeakHandle").field(&fmt_value(owner)).finish(),
+            None => f.write_str("WeakHandle(dropped)"),
+        };
+    }
+}
+
+///
+/// Strategies producing cells, they shrink toward an empty unshared cell and a dropped weak handle respectively.
+///
+#[cfg(feature = "proptest")]
+pub mod strategy {
+    use std::fmt::Debug;
+    use proptest::prelude::*;
+    use crate::RcOCell;
+    use crate::testing::{SharedCell, WeakHandle};
+
+    ///
+    /// Produces empty and populated cells, sometimes with up to 3 further clones and weak handles each.
+    ///
+    pub fn rc_o_cell<S>(inner: S) -> impl Strategy<Value = SharedCell<S::Value>>
+        where S: Strategy, S::Value: Debug
+    {
+        return (proptest::option::of(inner), 0..4usize, 0..4usize).prop_map(|(value, clones, weaks)| {
+            let cell = RcOCell::from_option(value);
+            return SharedCell {
+                clones: (0..clones).map(|_| cell.clone()).collect(),
+                weaks: (0..weaks).map(|_| cell.downgrade()).collect(),
+                cell,
+            };
+        });
+    }
+
+    ///
+    /// Produces weak handles to empty and populated cells, some of them already dropped.
+    ///
+    pub fn weak_rc_o_cell<S>(inner: S) -> impl Strategy<Value = WeakHandle<S::Value>>
+        where S: Strategy, S::Value: Debug
+    {
+        return (any::<bool>(), proptest::option::of(inner)).prop_map(|(alive, value)| {
+            let cell = RcOCell::from_option(value);
+            let weak = cell.downgrade();
+            return WeakHandle {weak, owner: if alive { Some(cell) } else { None }};
+        });
+    }
+}
+
+#[cfg(all(test, feature = "arbitrary"))]
+mod arbitrary_tests {
+    use arbitrary::{Arbitrary, Unstructured};
+    use crate::*;
+
+    #[test]
+    fn test_arbitrary_mix() {
+        let data: Vec<u8> = (0..=255u8).collect();
+        let mut u = Unstructured::new(&data);
+        let mut empty = 0;
+        let mut full = 0;
+        for _ in 0..40 {
+            let cell = RcOCell::<u8>::arbitrary(&mut u).unwrap();
+            if cell.is_some() {
+                full += 1;
+            } else {
+                empty += 1;
+            }
+        }
+        assert_eq!(empty > 0, true);
+        assert_eq!(full > 0, true);
+    }
+}
+
+#[cfg(all(test, feature = "proptest"))]
+mod proptest_tests {
+    use proptest::prelude::*;
+    use proptest::strategy::ValueTree;
+    use proptest::test_runner::{Config, RngAlgorithm, TestRng, TestRunner};
+    use crate::testing::strategy::{rc_o_cell, weak_rc_o_cell};
+
+    proptest! {
+        #[test]
+        fn test_clones_share_value(shared in rc_o_cell(any::<u32>()), value in any::<u32>()) {
+            shared.cell.set(value);
+            for clone in shared.clones.iter() {
+                prop_assert_eq!(clone.get_and_clone(), value);
+            }
+            for weak in shared.weaks.iter() {
+                prop_assert_eq!(weak.get_and_clone(), value);
+            }
+        }
+
+        #[test]
+        fn test_weak_alive_iff_owned(handle in weak_rc_o_cell(any::<u32>())) {
+            prop_assert_eq!(handle.weak.try_upgrade().is_ok(), handle.owner.is_some());
+        }
+    }
+
+    fn sample(seed: u8) -> Vec<String> {
+        let rng = TestRng::from_seed(RngAlgorithm::ChaCha, &[seed; 32]);
+        let mut runner = TestRunner::new_with_rng(Config::default(), rng);
+        return (0..20).map(|_| format!("{:?}", rc_o_cell(any::<u8>()).new_tree(&mut runner).unwrap().current())).collect();
+    }
+
+    #[test]
+    fn test_deterministic_seed() {
+        assert_eq!(sample(1), sample(1));
+    }
+
+    #[test]
+    fn test_shrinks_to_empty_unshared() {
+        let mut runner = TestRunner::deterministic();
+        let mut tree = rc_o_cell(any::<u8>()).new_tree(&mut runner).unwrap();
+        while tree.simplify() {}
+        let shared = tree.current();
+        assert_eq!(shared.cell.is_none(), true);
+        assert_eq!(shared.clones.len(), 0);
+        assert_eq!(shared.weaks.len(), 0);
+    }
+}
@@ -0,0 +1,139 @@
+//!
+//! Records the thread that created a cell and checks it on every access (feature `debug-thread-check`).
+//!
+//! RcOCell is not Send, this only catches handles that reached another thread anyway, i.e. through raw pointers.
+//! It is a debugging aid and no soundness guarantee, the access from the other thread is undefined behavior already.
+//!
+//! The shared state of a cell lives in a thread local table that other threads cannot see,
+//! so the creating thread is kept in a process wide table keyed by the address of the allocation instead.
+//! The entry is removed when the shared state of the cell on the creating thread is dropped. That state holds a weak
+//! reference to the allocation, so the address cannot be reused by another cell while the entry exists.
+//! Every cell gets a shared state while the feature is enabled.
+//!
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::thread::ThreadId;
+use crate::{shared, RcOCell, RcOCellError};
+
+struct Owner {
+    thread: ThreadId,
+    label: Option<Cow<'static, str>>,
+}
+
+static OWNERS: Mutex<BTreeMap<usize, Owner>> = Mutex::new(BTreeMap::new());
+
+fn owners() -> MutexGuard<'static, BTreeMap<usize, Owner>> {
+    return OWNERS.lock().unwrap_or_else(PoisonError::into_inner);
+}
+
+///
+/// Records the current thread as the owner of a new cell.
+/// Wrapping an Rc that already has an owner keeps the owner, so a handle rebuilt on another thread is still checked.
+///
+pub(crate) fn register<T>(rc: &Rc<RefCell<Option<T>>>) {
+    let inserted = match owners().entry(shared::key(rc)) {
+        Entry::Vacant(entry) => {
+            entry.insert(Owner {thread: std::thread::current().id(), label: None});
+            true
+        }
+        Entry::Occupied(_) => false,
+    };
+
+    shared::with_state(rc, |state| state.thread_owner |= inserted);
+}
+
+///
+/// Removes the owner of a cell, called when its shared state on the owning thread is dropped.
+///
+pub(crate) fn forget(key: usize) {
+    let removed = owners().remove(&key);
+    drop(removed);
+}
+
+///
+/// Copies the label into the owner entry, so the error on another thread can name the cell.
+///
+pub(crate) fn labeled<T>(rc: &Rc<RefCell<Option<T>>>, label: Option<Cow<'static, str>>) {
+    if let Some(owner) = owners().get_mut(&shared::key(rc)) {
+        owner.label = label;
+    }
+}
+
+///
+/// Fails with WrongThread if the cell is accessed from a thread other than the one that created it.
+///
+pub(crate) fn check<T>(cell: &RcOCell<T>) -> Result<(), RcOCellError> {
+    let current = std::thread::current().id();
+    let owners = owners();
+    let Some(owner) = owners.get(&shared::key(&cell.rc)) else {
+        return Ok(());
+    };
+
+    if owner.thread == current {
+        return Ok(());
+    }
+
+    return Err(RcOCellError::WrongThread {owner: owner.thread, current, label: owner.label.clone()});
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic;
+    use std::panic::AssertUnwindSafe;
+    use std::thread::ThreadId;
+    use crate::*;
+
+    ///
+    /// Makes another thread the owner of the cell, so accesses on this thread are checked like accesses on a foreign thread.
+    /// Only the id of the other thread crosses the threads, the cell never leaves this thread.
+    ///
+    fn owned_elsewhere(cell: &RcOCell<u32>) -> ThreadId {
+        let other = std::thread::spawn(|| std::thread::current().id()).join().unwrap();
+        set_owner(cell, other);
+        return other;
+    }
+
+    fn set_owner(cell: &RcOCell<u32>, thread: ThreadId) {
+        super::owners().get_mut(&shared::key(&cell.rc)).unwrap().thread = thread;
+    }
+
+    #[test]
+    fn test_wrong_thread() {
+        let cell = RcOCell::from_value(1u32).with_label("config");
+        let current = std::thread::current().id();
+        let owner = owned_elsewhere(&cell);
+
+        let e = cell.try_get_and_clone().unwrap_err().to_string();
+        assert_eq!(e, format!("Cell \"config\" created on thread {:?} was accessed on thread {:?}", owner, current));
+
+        let e = panic::catch_unwind(AssertUnwindSafe(|| { cell.set(2); })).unwrap_err();
+        let msg = e.downcast::<String>().map(|s| *s).unwrap_or_default();
+        assert_eq!(msg.contains("\"config\" created on thread"), true);
+
+        assert_eq!(matches!(cell.try_borrow_mut(), Err(RcOCellError::WrongThread {..})), true);
+        assert_eq!(matches!(cell.downgrade().try_borrow(), Err(RcOCellError::WrongThread {..})), true);
+
+        set_owner(&cell, current);
+        assert_eq!(cell.get_and_clone(), 1);
+    }
+
+    #[test]
+    fn test_owner_forgotten() {
+        let cell = RcOCell::from_value(1u32);
+        let key = shared::key(&cell.rc);
+        assert_eq!(super::owners().contains_key(&key), true);
+        drop(cell);
+        assert_eq!(super::owners().contains_key(&key), false);
+
+        let cell = RcOCell::from_value(3u32);
+        cell.set_label("late");
+        owned_elsewhere(&cell);
+        assert_eq!(cell.try_set(4).unwrap_err().to_string().starts_with("Cell \"late\""), true);
+        set_owner(&cell, std::thread::current().id());
+        assert_eq!(cell.get_and_clone(), 3);
+    }
+}
@@ -0,0 +1,80 @@
+///
+/// Declares functions that return a handle to a cell that exists once per thread.
+///
+/// Each thread gets its own independent cell, it is created the first time the function is called on that thread.
+/// The function returns a clone of the handle so call sites can just write `config().borrow()`.
+///
+/// Without an initializer the cell starts out empty, otherwise the initializer expression,
+/// which must evaluate to a RcOCell, is evaluated on the first call on each thread.
+///
+/// ```
+/// use rco_cell::{rco_thread_local, RcOCell};
+///
+/// rco_thread_local! {
+///     pub fn config() -> RcOCell<String>;
+///     fn counter() -> RcOCell<u32> = RcOCell::from_value(0);
+/// }
+///
+/// config().set("verbose".to_string());
+/// counter().compute_if_present(|n| rco_cell::RcOCellComputeResult::Replace(*n + 1));
+/// assert_eq!(config().borrow().as_str(), "verbose");
+/// assert_eq!(counter().get_and_clone(), 1);
+/// ```
+///
+#[macro_export]
+macro_rules! rco_thread_local {
+    () => {};
+    ($(#[$attr:meta])* $vis:vis fn $name:ident() -> RcOCell<$t:ty>; $($rest:tt)*) => {
+        $crate::rco_thread_local!($(#[$attr])* $vis fn $name() -> RcOCell<$t> = $crate::RcOCell::new(); $($rest)*);
+    };
+    ($(#[$attr:meta])* $vis:vis fn $name:ident() -> RcOCell<$t:ty> = $init:expr; $($rest:tt)*) => {
+        $(#[$attr])*
+        $vis fn $name() -> $crate::RcOCell<$t> {
+            ::std::thread_local! {
+                static CELL: $crate::RcOCell<$t> = $init;
+            }
+            return CELL.with(|cell| cell.clone());
+        }
+
+        $crate::rco_thread_local!($($rest)*);
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use crate::RcOCell;
+
+    static INIT_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    rco_thread_local! {
+        fn empty() -> RcOCell<String>;
+        /// Counts how often it was initialized.
+        fn initialized() -> RcOCell<u32> = {
+            INIT_COUNT.fetch_add(1, Ordering::SeqCst);
+            RcOCell::from_value(1)
+        };
+    }
+
+    #[test]
+    fn test_thread_local() {
+        assert_eq!(empty().is_none(), true);
+        empty().set("main".to_string());
+        assert_eq!(empty().borrow().as_str(), "main");
+        assert_eq!(initialized().get_and_clone(), 1);
+        initialized().set(2);
+        assert_eq!(initialized().get_and_clone(), 2);
+        assert_eq!(INIT_COUNT.load(Ordering::SeqCst), 1);
+
+        std::thread::spawn(|| {
+            assert_eq!(empty().is_none(), true);
+            empty().set("other".to_string());
+            assert_eq!(initialized().get_and_clone(), 1);
+            assert_eq!(initialized().get_and_clone(), 1);
+        }).join().unwrap();
+
+        assert_eq!(INIT_COUNT.load(Ordering::SeqCst), 2);
+        assert_eq!(empty().borrow().as_str(), "main");
+        assert_eq!(initialized().get_and_clone(), 2);
+    }
+}
@@ -0,0 +1,173 @@
+//!
+//! Events emitted through the tracing crate (feature `tracing`).
+//!
+//! All events use the target `rco_cell` and carry the id and label of the cell.
+//! Values are only part of the events if enabled per cell with log_values.
+//!
+use std::fmt::{Debug, Display};
+use std::panic::Location;
+use crate::{shared, RcOCell};
+
+///
+/// Emits a DEBUG event after the operation stored or removed a value.
+///
+pub(crate) fn changed<T>(cell: &RcOCell<T>, op: &'static str) {
+    if !tracing::enabled!(target: "rco_cell", tracing::Level::DEBUG) {
+        return;
+    }
+
+    let label = cell.label();
+    let label = label.as_deref().unwrap_or("");
+    let debug_value = shared::with_existing_typed(&cell.rc, |typed| typed.debug_value).flatten().filter(|_| !cell.is_redacted());
+    let value = debug_value.and_then(|debug_value| {
+        let value = cell.rc.try_borrow().ok()?;
+        return Some(value.as_ref().map(debug_value).unwrap_or_else(|| "None".to_string()));
+    });
+
+    match value {
+        Some(value) => tracing::debug!(target: "rco_cell", op, id = ?cell.id(), label, value),
+        None => tracing::debug!(target: "rco_cell", op, id = ?cell.id(), label),
+    }
+}
+
+///
+/// Emits a WARN event after borrowing the value failed because of another borrow.
+///
+pub(crate) fn borrow_failed<T>(cell: &RcOCell<T>) {
+    let label = cell.label();
+    tracing::warn!(target: "rco_cell", id = ?cell.id(), label = label.as_deref().unwrap_or(""), "borrow conflict");
+}
+
+///
+/// Emits a WARN event right before the cell panics.
+///
+pub(crate) fn failing<T>(cell: &RcOCell<T>, msg: &dyn Display, location: &Location<'_>) {
+    let label = cell.label();
+    tracing::warn!(target: "rco_cell", id = ?cell.id(), label = label.as_deref().unwrap_or(""), %location, "panic: {}", msg);
+}
+
+impl <T: Debug + 'static> RcOCell<T> {
+    ///
+    /// Includes the value in the events of the tracing feature if enabled.
+    /// Disabled by default so sensitive values do not end up in logs.
+    ///
+    pub fn log_values(&self, enabled: bool) {
+        let debug_value: Option<fn(&T) -> String> = if enabled { Some(|v| format!("{:?}", v)) } else { None };
+        shared::with_typed(&self.rc, |typed| typed.debug_value = debug_value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::fmt::Debug;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Level, Metadata, Subscriber};
+    use crate::*;
+
+    #[derive(Debug)]
+    struct Captured {
+        level: Level,
+        fields: HashMap<String, String>,
+    }
+
+    struct Capture(Arc<Mutex<Vec<Captured>>>);
+
+    impl Visit for Captured {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.fields.insert(field.name().to_string(), value.to_string());
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.fields.insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
+
+    impl Subscriber for Capture {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            return true;
+        }
+
+        fn new_span(&self, _: &Attributes<'_>) -> Id {
+            return Id::from_u64(1);
+        }
+
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut captured = Captured {level: *event.metadata().level(), fields: HashMap::new()};
+            event.record(&mut captured);
+            self.0.lock().unwrap().push(captured);
+        }
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    fn capture<F: FnOnce()>(f: F) -> Vec<Captured> {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        tracing::subscriber::with_default(Capture(events.clone()), f);
+        return std::mem::take(&mut *events.lock().unwrap());
+    }
+
+    #[test]
+    fn test_trace_changes() {
+        let x = RcOCell::from_value(1u32).with_label("state");
+        let y = RcOCell::from_value(5u32);
+        let events = capture(|| {
+            x.set(2);
+            x.log_values(true);
+            x.replace(3);
+            x.swap(&y);
+            x.clear();
+        });
+
+        let ops: Vec<&str> = events.iter().map(|e| e.fields["op"].as_str()).collect();
+        assert_eq!(ops, vec!["set", "replace", "swap", "swap", "clear"]);
+        assert_eq!(events.iter().all(|e| e.level == Level::DEBUG), true);
+        assert_eq!(events[0].fields["label"], "state");
+        assert_eq!(events[0].fields["id"], format!("{:?}", x.id()));
+        assert_eq!(events[0].fields.contains_key("value"), false);
+        assert_eq!(events[1].fields["value"], "3");
+        assert_eq!(events[2].fields["value"], "5");
+        assert_eq!(events[3].fields.contains_key("value"), false);
+        assert_eq!(events[4].fields["value"], "None");
+    }
+
+    #[test]
+    fn test_trace_redacted() {
+        let x = RcOCell::from_value(String::from("hunter2"));
+        x.log_values(true);
+        let secret = SecretRcOCell::wrap(x.clone());
+        let events = capture(|| {
+            x.set(String::from("swordfish"));
+            secret.set(String::from("letmein"));
+        });
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events.iter().all(|e| !e.fields.contains_key("value")), true);
+    }
+
+    #[test]
+    fn test_trace_failures() {
+        let x = RcOCell::from_value(1u32).with_label("state");
+        let events = capture(|| {
+            let brw = x.borrow();
+            assert_eq!(x.try_set(2).is_err(), true);
+            let r = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| { x.borrow_mut(); }));
+            assert_eq!(r.is_err(), true);
+            drop(brw);
+        });
+
+        assert_eq!(events.len(), 3);
+        assert_eq!(events.iter().all(|e| e.level == Level::WARN && e.fields["label"] == "state"), true);
+        assert_eq!(events[0].fields["message"], "borrow conflict");
+        assert_eq!(events[2].fields["message"].starts_with("panic: "), true);
+        assert_eq!(events[2].fields["location"].starts_with(file!()), true);
+    }
+}
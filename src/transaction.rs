@@ -0,0 +1,375 @@
+use std::any::Any;
+use std::fmt::{Debug, Display, Formatter};
+use std::marker::PhantomData;
+use crate::{shared, RcOCell};
+
+///
+/// Updates several cells, possibly of different types, all at once or not at all.
+///
+/// The cells taking part have to be registered with `with`.
+/// Writes made in `run` are staged and only applied if the Fn returns Ok and none of the written cells is borrowed or poisoned at commit time.
+/// If the Fn returns an error, panics or a written cell is borrowed or poisoned at commit time nothing is applied.
+/// The transaction holds a handle of every registered cell until it ends, so they cannot be dropped in between.
+///
+/// Applying the staged writes runs no user code, observers are notified and the old values are dropped after all writes were applied.
+///
+/// ```
+/// use rco_cell::{CellTransaction, CellTransactionError, RcOCell};
+///
+/// let a = RcOCell::from_value(10u32);
+/// let b = RcOCell::from_value(0u32);
+/// let r: Result<(), CellTransactionError<&str>> = CellTransaction::new().with(&a).with(&b).run(|txn| {
+///     let amount = 4;
+///     let from = txn.get(&a)?.ok_or("empty")?;
+///     txn.set(&a, from.checked_sub(amount).ok_or("insufficient")?)?;
+///     txn.set(&b, txn.get(&b)?.unwrap_or(0) + amount)?;
+///     Ok(())
+/// });
+/// assert!(r.is_ok());
+/// assert_eq!(a.get_and_clone() + b.get_and_clone(), 10);
+/// ```
+///
+#[derive(Debug, Default)]
+pub struct CellTransaction {
+    participants: Vec<Participant>,
+}
+
+#[derive(Debug)]
+struct Participant {
+    key: usize,
+    ///
+    /// Handle of the cell, keeps the allocation alive so its key cannot be reused by another cell.
+    ///
+    _cell: Box<dyn Any>,
+}
+
+///
+/// Error of CellTransaction::run, nothing was applied if it is returned.
+///
+pub enum CellTransactionError<E> {
+    ///
+    /// The Fn returned the error.
+    ///
+    Aborted(E),
+    ///
+    /// The Fn accessed a cell that was not registered with `with`.
+    ///
+    NotParticipating,
+    ///
+    /// The cell registered at this index (in order of the `with` calls) was borrowed or poisoned at commit time.
+    ///
+    Conflict(usize),
+}
+
+///
+/// Handle to stage writes inside CellTransaction::run.
+///
+pub struct Transaction<E> {
+    participants: Vec<Participant>,
+    staged: Vec<Option<Box<dyn Staged>>>,
+    error: PhantomData<E>,
+}
+
+trait Staged {
+    fn can_commit(&self) -> bool;
+    fn write(&mut self);
+    fn finish(self: Box<Self>);
+    fn as_any(&self) -> &dyn Any;
+}
+
+struct StagedValue<T> {
+    cell: RcOCell<T>,
+    value: Option<Option<T>>,
+    old: Option<Option<T>>,
+}
+
+impl <T: 'static> Staged for StagedValue<T> {
+    fn can_commit(&self) -> bool {
+        return self.cell.check_poison().is_ok() && self.cell.rc.try_borrow_mut().is_ok();
+    }
+
+    fn write(&mut self) {
+        self.old = Some(self.cell.rc.replace(self.value.take().unwrap()));
+    }
+
+    fn finish(self: Box<Self>) {
+        self.cell.changed();
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        return self;
+    }
+}
+
+impl CellTransaction {
+    ///
+    /// Constructs a new transaction without any cells.
+    ///
+    pub fn new() -> CellTransaction {
+        return CellTransaction {participants: Vec::new()};
+    }
+
+    ///
+    /// Registers the cell as part of the transaction.
+    ///
+    pub fn with<T: 'static>(mut self, cell: &RcOCell<T>) -> CellTransaction {
+        self.participants.push(Participant {key: shared::key(&cell.rc), _cell: Box::new(cell.clone())});
+        return self;
+    }
+
+    ///
+    /// Runs the Fn and applies the writes it staged if it returns Ok.
+    ///
+    pub fn run<R, E, F>(self, f: F) -> Result<R, CellTransactionError<E>>
+        where F: FnOnce(&mut Transaction<E>) -> Result<R, CellTransactionError<E>>
+    {
+        let count = self.participants.len();
+        let mut txn = Transaction {
+            participants: self.participants,
+            staged: (0..count).map(|_| None).collect(),
+            error: PhantomData,
+        };
+
+        let result = f(&mut txn)?;
+        txn.commit()?;
+        return Ok(result);
+    }
+}
+
+impl <E> Transaction<E> {
+    fn index<T>(&self, cell: &RcOCell<T>) -> Result<usize, CellTransactionError<E>> {
+        let key = shared::key(&cell.rc);
+        return self.participants.iter().position(|p| p.key == key).ok_or(CellTransactionError::NotParticipating);
+    }
+
+    fn stage<T: 'static>(&mut self, cell: &RcOCell<T>, value: Option<T>) -> Result<(), CellTransactionError<E>> {
+        let index = self.index(cell)?;
+        self.staged[index] = Some(Box::new(StagedValue {cell: cell.clone(), value: Some(value), old: None}));
+        return Ok(());
+    }
+
+    ///
+    /// Stages storing the value in the cell.
+    /// Fails if the cell was not registered.
+    ///
+    pub fn set<T: 'static>(&mut self, cell: &RcOCell<T>, value: T) -> Result<(), CellTransactionError<E>> {
+        return self.stage(cell, Some(value));
+    }
+
+    ///
+    /// Stages clearing the cell.
+    /// Fails if the cell was not registered.
+    ///
+    pub fn clear<T: 'static>(&mut self, cell: &RcOCell<T>) -> Result<(), CellTransactionError<E>> {
+        return self.stage(cell, None);
+    }
+
+    ///
+    /// Returns a clone of the value the cell will hold if the transaction commits,
+    /// i.e. the staged value if the cell was written or its current value otherwise.
+    /// Fails if the cell was not registered.
+    /// Panics if the cell was not written yet and its value is borrowed mutably somewhere.
+    ///
+    pub fn get<T: Clone + 'static>(&self, cell: &RcOCell<T>) -> Result<Option<T>, CellTransactionError<E>> {
+        let index = self.index(cell)?;
+        if let Some(staged) = &self.staged[index] {
+            let staged = staged.as_any().downcast_ref::<StagedValue<T>>().unwrap();
+            return Ok(staged.value.clone().unwrap());
+        }
+
+        return Ok(cell.raw_borrow().clone());
+    }
+
+    fn commit(self) -> Result<(), CellTransactionError<E>> {
+        for (index, staged) in self.staged.iter().enumerate() {
+            if let Some(staged) = staged {
+                if !staged.can_commit() {
+                    return Err(CellTransactionError::Conflict(index));
+                }
+            }
+        }
+
+        let mut written: Vec<Box<dyn Staged>> = self.staged.into_iter().flatten().collect();
+        for staged in written.iter_mut() {
+            staged.write();
+        }
+
+        for staged in written {
+            staged.finish();
+        }
+
+        return Ok(());
+    }
+}
+
+impl <E> From<E> for CellTransactionError<E> {
+    fn from(value: E) -> Self {
+        return CellTransactionError::Aborted(value);
+    }
+}
+
+impl <E: Debug> Debug for CellTransactionError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            CellTransactionError::Aborted(e) => f.debug_tuple("Aborted").field(e).finish(),
+            CellTransactionError::NotParticipating => f.write_str("NotParticipating"),
+            CellTransactionError::Conflict(index) => f.debug_tuple("Conflict").field(index).finish(),
+        };
+    }
+}
+
+impl <E: Display> Display for CellTransactionError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            CellTransactionError::Aborted(e) => Display::fmt(e, f),
+            CellTransactionError::NotParticipating => f.write_str("Cell is not part of the transaction"),
+            CellTransactionError::Conflict(index) => write!(f, "Cell {} of the transaction is borrowed or poisoned", index),
+        };
+    }
+}
+
+impl <E> Debug for Transaction<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Transaction")
+            .field("participants", &self.participants.len())
+            .field("staged", &self.staged.iter().filter(|s| s.is_some()).count())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::panic;
+    use std::panic::AssertUnwindSafe;
+    use std::rc::Rc;
+    use crate::*;
+
+    #[test]
+    fn test_transaction_commit() {
+        let a = RcOCell::from_value(1u32);
+        let b = RcOCell::from_value("x".to_string());
+        let seen = Rc::new(Cell::new(0u32));
+        let seen_clone = seen.clone();
+        let b_clone = b.clone();
+        a.on_set(move |v| {
+            assert_eq!(b_clone.get_and_clone(), "y");
+            seen_clone.set(*v);
+        });
+
+        let r: Result<u32, CellTransactionError<()>> = CellTransaction::new().with(&a).with(&b).run(|txn| {
+            txn.set(&a, 2)?;
+            assert_eq!(a.get_and_clone(), 1);
+            assert_eq!(txn.get(&a)?, Some(2));
+            txn.set(&b, "y".to_string())?;
+            Ok(7)
+        });
+        assert_eq!(r.unwrap(), 7);
+        assert_eq!(a.get_and_clone(), 2);
+        assert_eq!(b.get_and_clone(), "y");
+        assert_eq!(seen.get(), 2);
+    }
+
+    #[test]
+    fn test_transaction_abort() {
+        let a = RcOCell::from_value(1u32);
+        let b = RcOCell::from_value(2u32);
+        let r: Result<(), CellTransactionError<&str>> = CellTransaction::new().with(&a).with(&b).run(|txn| {
+            txn.clear(&a)?;
+            txn.set(&b, 3)?;
+            Err("no")?
+        });
+        match r {
+            Err(CellTransactionError::Aborted("no")) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(a.get_and_clone(), 1);
+        assert_eq!(b.get_and_clone(), 2);
+
+        let c = RcOCell::from_value(0u32);
+        let r: Result<(), CellTransactionError<()>> = CellTransaction::new().with(&a).run(|txn| txn.set(&c, 1));
+        match r {
+            Err(CellTransactionError::NotParticipating) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(c.get_and_clone(), 0);
+    }
+
+    #[test]
+    fn test_transaction_panic() {
+        let a = RcOCell::from_value(1u32);
+        let b = RcOCell::from_value(2u32);
+        let r = panic::catch_unwind(AssertUnwindSafe(|| {
+            let _: Result<(), CellTransactionError<()>> = CellTransaction::new().with(&a).with(&b).run(|txn| {
+                txn.set(&a, 10)?;
+                panic!("Oh no");
+            });
+        }));
+        assert_eq!(r.is_err(), true);
+        assert_eq!(a.get_and_clone(), 1);
+        assert_eq!(b.get_and_clone(), 2);
+    }
+
+    #[test]
+    fn test_transaction_conflict() {
+        let a = RcOCell::from_value(1u32);
+        let b = RcOCell::from_value(2u32);
+        let brw = b.borrow();
+        let r: Result<(), CellTransactionError<()>> = CellTransaction::new().with(&a).with(&b).run(|txn| {
+            txn.set(&a, 10)?;
+            txn.set(&b, 20)?;
+            Ok(())
+        });
+        drop(brw);
+        match r {
+            Err(CellTransactionError::Conflict(1)) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(a.get_and_clone(), 1);
+        assert_eq!(b.get_and_clone(), 2);
+    }
+
+    #[test]
+    fn test_transaction_keeps_cells() {
+        let a = RcOCell::from_value(1u32);
+        let weak = a.downgrade();
+        let txn = CellTransaction::new().with(&a);
+        drop(a);
+        assert_eq!(weak.is_some(), true);
+
+        let b = RcOCell::from_value(2u32);
+        let r: Result<(), CellTransactionError<()>> = txn.run(|txn| txn.set(&b, 3));
+        match r {
+            Err(CellTransactionError::NotParticipating) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(b.get_and_clone(), 2);
+        assert_eq!(weak.try_upgrade().is_err(), true);
+    }
+
+    #[cfg(feature = "poison")]
+    #[test]
+    fn test_transaction_poisoned() {
+        let a = RcOCell::from_value(1u32);
+        let b = RcOCell::from_value(2u32);
+        let r = a.catch(|a| {
+            let _brw = a.borrow_mut();
+            panic!("Oh no");
+        });
+        assert_eq!(r.is_err(), true);
+        assert_eq!(a.is_poisoned(), true);
+
+        let r: Result<(), CellTransactionError<()>> = CellTransaction::new().with(&a).with(&b).run(|txn| {
+            txn.set(&b, 20)?;
+            txn.set(&a, 10)?;
+            Ok(())
+        });
+        match r {
+            Err(CellTransactionError::Conflict(0)) => {}
+            _ => panic!("unexpected"),
+        };
+        a.clear_poison();
+        assert_eq!(a.get_and_clone(), 1);
+        assert_eq!(b.get_and_clone(), 2);
+    }
+}
@@ -0,0 +1,300 @@
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+use crate::{RcOCell, RcOCellError};
+#[cfg(feature = "tracing")]
+use crate::trace;
+
+type Cells3<A, B, C> = (RcOCell<A>, RcOCell<B>, RcOCell<C>);
+
+impl <A, B> RcOCell<(A, B)> {
+    ///
+    /// Moves the elements of the tuple into two new cells.
+    /// The new cells are independent of this cell, later modifications of one of them are not visible in the others.
+    /// Other handles of this cell see an empty cell afterwards.
+    ///
+    /// Fails if there is no value or the value is borrowed somewhere, the cell is not modified then.
+    ///
+    pub fn split(self) -> Result<(RcOCell<A>, RcOCell<B>), RcOCellError> {
+        let (a, b) = self.try_get_and_clear()?;
+        return Ok((RcOCell::from_value(a), RcOCell::from_value(b)));
+    }
+}
+
+impl <A, B> RcOCell<(A, B)> {
+    ///
+    /// Moves the values of both cells into a new cell holding them as a tuple, see join_with.
+    /// Panics if one of the cells is empty or borrowed somewhere, both cells are not modified then.
+    ///
+    #[track_caller]
+    pub fn join(a: &RcOCell<A>, b: &RcOCell<B>) -> RcOCell<(A, B)> {
+        return RcOCell::join_with(a, b, |a, b| (a, b));
+    }
+
+    ///
+    /// Moves the values of both cells into a new cell holding them as a tuple, see join_with.
+    /// Fails if one of the cells is empty or borrowed somewhere, both cells are not modified then.
+    ///
+    pub fn try_join(a: &RcOCell<A>, b: &RcOCell<B>) -> Result<RcOCell<(A, B)>, RcOCellError> {
+        return RcOCell::try_join_with(a, b, |a, b| (a, b));
+    }
+}
+
+impl <A: Clone, B: Clone> RcOCell<(A, B)> {
+    ///
+    /// Clones the values of both cells into a new cell holding them as a tuple, both cells keep their values.
+    /// Fails if one of the cells is empty or borrowed mutably somewhere.
+    ///
+    pub fn join_cloned(a: &RcOCell<A>, b: &RcOCell<B>) -> Result<RcOCell<(A, B)>, RcOCellError> {
+        return Ok(RcOCell::from_value((a.try_get_and_clone()?, b.try_get_and_clone()?)));
+    }
+
+    ///
+    /// Clones the elements of the tuple into two new cells, this cell keeps its value.
+    /// The new cells are independent of this cell, see split.
+    ///
+    /// Fails if there is no value or the value is borrowed mutably somewhere.
+    ///
+    pub fn split_cloned(&self) -> Result<(RcOCell<A>, RcOCell<B>), RcOCellError> {
+        let (a, b) = self.try_get_and_clone()?;
+        return Ok((RcOCell::from_value(a), RcOCell::from_value(b)));
+    }
+}
+
+impl <T> RcOCell<T> {
+    ///
+    /// Takes the values out of both cells and stores what the Fn makes of them in a new cell.
+    /// Either both cells are emptied or none of them, observers of the cells are notified once the new cell was built.
+    /// The new cell is independent of the two cells. If the Fn panics both values are lost and no observer runs.
+    /// If an observer of the first cell panics the observers of the second cell still run, the panic is propagated afterwards.
+    ///
+    /// Panics if one of the cells is empty or borrowed somewhere or both are the same cell, both cells are not modified then.
+    ///
+    #[track_caller]
+    pub fn join_with<A, B, F>(a: &RcOCell<A>, b: &RcOCell<B>, f: F) -> RcOCell<T>
+        where F: FnOnce(A, B) -> T
+    {
+        return match RcOCell::try_join_with(a, b, f) {
+            Ok(joined) => joined,
+            Err(e) => panic!("RcOCell::join failed: {}", e),
+        };
+    }
+
+    ///
+    /// Takes the values out of both cells and stores what the Fn makes of them in a new cell, see join_with.
+    /// Fails if one of the cells is empty or borrowed somewhere, both cells are not modified then.
+    /// Fails with RcOCellError::DuplicateCell if both are the same cell.
+    ///
+    pub fn try_join_with<A, B, F>(a: &RcOCell<A>, b: &RcOCell<B>, f: F) -> Result<RcOCell<T>, RcOCellError>
+        where F: FnOnce(A, B) -> T
+    {
+        if a.id() == b.id() {
+            return Err(RcOCellError::DuplicateCell);
+        }
+
+        a.check_mut()?;
+        b.check_mut()?;
+        if a.is_none() || b.is_none() {
+            return Err(RcOCellError::NoValue);
+        }
+
+        let a_value = a.raw_replace(None).unwrap();
+        let b_value = b.raw_replace(None).unwrap();
+        let joined = RcOCell::from_value(f(a_value, b_value));
+
+        let a_notified = catch_unwind(AssertUnwindSafe(|| a.joined()));
+        b.joined();
+        if let Err(payload) = a_notified {
+            resume_unwind(payload);
+        }
+
+        return Ok(joined);
+    }
+
+    fn joined(&self) {
+        #[cfg(feature = "tracing")]
+        trace::changed(self, "join");
+        self.changed();
+    }
+}
+
+impl <A, B, C> RcOCell<(A, B, C)> {
+    ///
+    /// Moves the elements of the tuple into three new cells.
+    /// The new cells are independent of this cell, later modifications of one of them are not visible in the others.
+    /// Other handles of this cell see an empty cell afterwards.
+    ///
+    /// Fails if there is no value or the value is borrowed somewhere, the cell is not modified then.
+    ///
+    pub fn split(self) -> Result<Cells3<A, B, C>, RcOCellError> {
+        let (a, b, c) = self.try_get_and_clear()?;
+        return Ok((RcOCell::from_value(a), RcOCell::from_value(b), RcOCell::from_value(c)));
+    }
+}
+
+impl <A: Clone, B: Clone, C: Clone> RcOCell<(A, B, C)> {
+    ///
+    /// Clones the elements of the tuple into three new cells, this cell keeps its value.
+    /// The new cells are independent of this cell, see split.
+    ///
+    /// Fails if there is no value or the value is borrowed mutably somewhere.
+    ///
+    pub fn split_cloned(&self) -> Result<Cells3<A, B, C>, RcOCellError> {
+        let (a, b, c) = self.try_get_and_clone()?;
+        return Ok((RcOCell::from_value(a), RcOCell::from_value(b), RcOCell::from_value(c)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_split() {
+        let pair = RcOCell::from_value((1u32, "a".to_string()));
+        let shared = pair.clone();
+        let (a, b) = pair.split().unwrap();
+        assert_eq!(shared.is_none(), true);
+        assert_eq!(a.get_and_clone(), 1);
+        assert_eq!(b.get_and_clone(), "a");
+
+        shared.set((2, "b".to_string()));
+        a.set(3);
+        assert_eq!(shared.get_and_clone(), (2, "b".to_string()));
+        assert_eq!(b.get_and_clone(), "a");
+
+        let triple = RcOCell::from_value((1u8, 2u16, 3u32));
+        let (a, b, c) = triple.split().unwrap();
+        assert_eq!((a.get_and_clone(), b.get_and_clone(), c.get_and_clone()), (1, 2, 3));
+    }
+
+    #[test]
+    fn test_split_fail() {
+        let empty: RcOCell<(u32, u32)> = RcOCell::new();
+        match empty.split() {
+            Err(RcOCellError::NoValue) => {}
+            _ => panic!("unexpected"),
+        };
+
+        let pair = RcOCell::from_value((1u32, 2u32));
+        let shared = pair.clone();
+        let brw = shared.borrow();
+        match pair.split() {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        drop(brw);
+        assert_eq!(shared.get_and_clone(), (1, 2));
+    }
+
+    #[test]
+    fn test_split_cloned() {
+        let pair = RcOCell::from_value((1u32, vec![2u32]));
+        let (a, b) = pair.split_cloned().unwrap();
+        b.borrow_mut().push(3);
+        assert_eq!(pair.get_and_clone(), (1, vec![2]));
+        assert_eq!(a.get_and_clone(), 1);
+        assert_eq!(b.get_and_clone(), vec![2, 3]);
+
+        let triple = RcOCell::from_value((1u8, 2u16, 3u32));
+        let (a, _, c) = triple.split_cloned().unwrap();
+        assert_eq!((a.get_and_clone(), c.get_and_clone()), (1, 3));
+        assert_eq!(triple.get_and_clone(), (1, 2, 3));
+
+        let brw = triple.borrow_mut();
+        assert_eq!(triple.split_cloned().is_err(), true);
+        drop(brw);
+        let empty: RcOCell<(u32, u32)> = RcOCell::new();
+        assert_eq!(empty.split_cloned().is_err(), true);
+    }
+
+    #[test]
+    fn test_join() {
+        let a = RcOCell::from_value(1u32);
+        let b = RcOCell::from_value("b".to_string());
+        let cleared = RcOCell::from_value(0u32);
+        let cleared2 = cleared.clone();
+        a.on_clear(move || { cleared2.set(cleared2.get_and_clone() + 1); });
+
+        let joined = RcOCell::join(&a, &b);
+        assert_eq!(joined.get_and_clone(), (1, "b".to_string()));
+        assert_eq!(a.is_none(), true);
+        assert_eq!(b.is_none(), true);
+        assert_eq!(cleared.get_and_clone(), 1);
+
+        a.set(2);
+        assert_eq!(joined.get_and_clone(), (1, "b".to_string()));
+
+        let sum = RcOCell::join_with(&a, &RcOCell::from_value(3u32), |a, b| a + b);
+        assert_eq!(sum.get_and_clone(), 5);
+    }
+
+    #[test]
+    fn test_join_fail() {
+        let a = RcOCell::from_value(1u32);
+        let b: RcOCell<u32> = RcOCell::new();
+        match RcOCell::try_join(&a, &b) {
+            Err(RcOCellError::NoValue) => {}
+            _ => panic!("unexpected"),
+        };
+        match RcOCell::try_join(&b, &a) {
+            Err(RcOCellError::NoValue) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(a.get_and_clone(), 1);
+        assert_eq!(a.catch(|a| RcOCell::join(a, &b)).is_err(), true);
+        assert_eq!(a.get_and_clone(), 1);
+
+        b.set(2);
+        let brw = b.borrow();
+        match RcOCell::try_join(&a, &b) {
+            Err(RcOCellError::BorrowError(_)) => {}
+            _ => panic!("unexpected"),
+        };
+        drop(brw);
+        assert_eq!(a.get_and_clone(), 1);
+        assert_eq!(b.get_and_clone(), 2);
+
+        match RcOCell::try_join(&a, &a) {
+            Err(RcOCellError::DuplicateCell) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(a.get_and_clone(), 1);
+    }
+
+    #[test]
+    fn test_join_observer_panic() {
+        let a = RcOCell::from_value(1u32);
+        let b = RcOCell::from_value(2u32);
+        let seen = RcOCell::from_value(Vec::new());
+        let s = seen.clone();
+        a.on_clear(move || panic!("Oh no"));
+        b.on_clear(move || { s.borrow_mut().push("b cleared"); });
+
+        let built = RcOCell::from_value(false);
+        let r = a.catch(|a| RcOCell::join_with(a, &b, |x, y| {
+            built.set(true);
+            x + y
+        }));
+        assert_eq!(r.is_err(), true);
+        assert_eq!(built.get_and_clone(), true);
+        assert_eq!(seen.get_and_clone(), vec!["b cleared"]);
+        assert_eq!(a.is_none(), true);
+        assert_eq!(b.is_none(), true);
+    }
+
+    #[test]
+    fn test_join_cloned() {
+        let a = RcOCell::from_value(1u32);
+        let b = RcOCell::from_value(vec![2u32]);
+        let joined = RcOCell::join_cloned(&a, &b).unwrap();
+        joined.borrow_mut().1.push(3);
+        assert_eq!(joined.get_and_clone(), (1, vec![2, 3]));
+        assert_eq!(b.get_and_clone(), vec![2]);
+        assert_eq!(a.get_and_clone(), 1);
+
+        let brw = b.borrow_mut();
+        assert_eq!(RcOCell::join_cloned(&a, &b).is_err(), true);
+        drop(brw);
+        b.clear();
+        assert_eq!(RcOCell::join_cloned(&a, &b).is_err(), true);
+    }
+}
@@ -0,0 +1,91 @@
+//!
+//! Unwind safety of cells.
+//!
+//! Without the `poison` feature RcOCell and WeakRcOCell are neither UnwindSafe nor RefUnwindSafe, just like `Rc<RefCell<T>>`,
+//! since a panic can leave a half modified value behind that is observed afterwards.
+//! With the feature such a value is flagged by poisoning, so both traits are implemented like they are for Mutex.
+//!
+//! catch runs a Fn under catch_unwind without requiring AssertUnwindSafe at every call site.
+//! It does not prevent poisoning, a panic during a mutable access inside the Fn still poisons the cell.
+//!
+use std::any::Any;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+#[cfg(feature = "poison")]
+use std::panic::{RefUnwindSafe, UnwindSafe};
+use crate::{RcOCell, WeakRcOCell};
+
+#[cfg(feature = "poison")]
+impl <T> UnwindSafe for RcOCell<T> {}
+
+#[cfg(feature = "poison")]
+impl <T> RefUnwindSafe for RcOCell<T> {}
+
+#[cfg(feature = "poison")]
+impl <T> UnwindSafe for WeakRcOCell<T> {}
+
+#[cfg(feature = "poison")]
+impl <T> RefUnwindSafe for WeakRcOCell<T> {}
+
+impl <T> RcOCell<T> {
+    ///
+    /// Calls the Fn with the cell and catches a panic of the Fn, see std::panic::catch_unwind.
+    ///
+    pub fn catch<R, F>(&self, f: F) -> Result<R, Box<dyn Any + Send>>
+        where F: FnOnce(&RcOCell<T>) -> R
+    {
+        return catch_unwind(AssertUnwindSafe(|| f(self)));
+    }
+}
+
+impl <T> WeakRcOCell<T> {
+    ///
+    /// Calls the Fn with the weak cell and catches a panic of the Fn, see std::panic::catch_unwind.
+    ///
+    pub fn catch<R, F>(&self, f: F) -> Result<R, Box<dyn Any + Send>>
+        where F: FnOnce(&WeakRcOCell<T>) -> R
+    {
+        return catch_unwind(AssertUnwindSafe(|| f(self)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_catch() {
+        let x = RcOCell::from_value(1u32);
+        assert_eq!(x.catch(|x| x.get_and_clone() + 1).unwrap(), 2);
+        let brw = x.borrow();
+        let r = x.catch(|x| x.set(3));
+        drop(brw);
+        assert_eq!(r.is_err(), true);
+        assert_eq!(x.get_and_clone(), 1);
+
+        let w = x.downgrade();
+        drop(x);
+        assert_eq!(w.catch(|w| w.get_and_clone()).is_err(), true);
+    }
+
+    #[cfg(feature = "poison")]
+    #[test]
+    fn test_unwind_safe() {
+        use std::panic::{RefUnwindSafe, UnwindSafe};
+        fn assert_unwind_safe<T: UnwindSafe + RefUnwindSafe>() {}
+        assert_unwind_safe::<RcOCell<std::cell::Cell<u32>>>();
+        assert_unwind_safe::<WeakRcOCell<String>>();
+
+        let x = RcOCell::from_value(1u32);
+        let r = std::panic::catch_unwind(|| {
+            let mut brw = x.borrow_mut();
+            *brw = 2;
+            panic!("Oh no");
+        });
+        assert_eq!(r.is_err(), true);
+        assert_eq!(x.is_poisoned(), true);
+
+        x.clear_poison();
+        assert_eq!(x.catch(|x| { let _brw = x.borrow_mut(); panic!("Oh no"); }).is_err(), true);
+        assert_eq!(x.is_poisoned(), true);
+    }
+}
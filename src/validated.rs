@@ -0,0 +1,496 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
+use crate::{RcOCell, RcOCellComputeError, RcOCellComputeResult, RcOCellError, RcORef, RcORefMut};
+
+///
+/// Error of the writing methods of ValidatedRcOCell.
+///
+pub enum ValidationError<T, E> {
+    ///
+    /// The cell could not be accessed, the validator was not called.
+    ///
+    Cell(RcOCellError),
+    ///
+    /// The validator rejected the value, the cell was not changed and the rejected value is handed back.
+    ///
+    Invalid {
+        error: E,
+        value: T,
+    },
+}
+
+impl <T, E: Debug> Debug for ValidationError<T, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            ValidationError::Cell(e) => Debug::fmt(e, f),
+            ValidationError::Invalid {error, ..} => Debug::fmt(error, f),
+        };
+    }
+}
+
+impl <T, E: Display> Display for ValidationError<T, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            ValidationError::Cell(e) => Display::fmt(e, f),
+            ValidationError::Invalid {error, ..} => Display::fmt(error, f),
+        };
+    }
+}
+
+impl <T, E> From<RcOCellError> for ValidationError<T, E> {
+    fn from(value: RcOCellError) -> Self {
+        return ValidationError::Cell(value);
+    }
+}
+
+type Validator<T, E> = dyn Fn(&T) -> Result<(), E>;
+
+///
+/// A cell whose values are checked by a validator before they are stored.
+///
+/// set, replace, the compute methods and borrow_mut consult the validator, a rejected value is never stored.
+/// The compute Fns only get a shared reference, so values cannot be changed in place without validation.
+/// borrow_mut snapshots the value (requires Clone), if the validator rejects the value when the guard is dropped
+/// the snapshot is restored, see ValidatedRefMut.
+/// The validator runs while the value is borrowed, so it must not access the cell.
+///
+/// Only writes through this type are validated, writes through the cell returned by cell() or other clones of it are not.
+/// Clones of the ValidatedRcOCell share the value and the validator.
+///
+pub struct ValidatedRcOCell<T, E> {
+    cell: RcOCell<T>,
+    validator: Rc<Validator<T, E>>,
+}
+
+///
+/// Mutable borrow of the value of a ValidatedRcOCell, returned by borrow_mut.
+///
+/// The value is validated when the guard is dropped, a rejected value is replaced by the snapshot taken when the guard was created.
+/// Dropping cannot report the rejection, use commit to end the borrow and get the rejected value back.
+///
+pub struct ValidatedRefMut<'a, T, E> {
+    guard: Option<RcORefMut<'a, T>>,
+    snapshot: Option<T>,
+    validator: &'a Validator<T, E>,
+}
+
+impl <T> RcOCell<T> {
+    ///
+    /// Returns a clone of the cell whose writes are checked by the validator, see ValidatedRcOCell.
+    /// Fails with RcOCellComputeError::Compute if the validator rejects the current value
+    /// and with RcOCellComputeError::Cell if the value is borrowed mutably.
+    ///
+    pub fn with_validator<E, F>(&self, validator: F) -> Result<ValidatedRcOCell<T, E>, RcOCellComputeError<E>>
+        where F: Fn(&T) -> Result<(), E> + 'static
+    {
+        if let Some(value) = self.try_raw_borrow()?.as_ref() {
+            validator(value).map_err(RcOCellComputeError::Compute)?;
+        }
+
+        return Ok(ValidatedRcOCell {cell: self.clone(), validator: Rc::new(validator)});
+    }
+}
+
+impl <T, E> ValidatedRcOCell<T, E> {
+    ///
+    /// Constructs a new empty cell whose writes are checked by the validator.
+    ///
+    pub fn new<F>(validator: F) -> ValidatedRcOCell<T, E>
+        where F: Fn(&T) -> Result<(), E> + 'static
+    {
+        return ValidatedRcOCell {cell: RcOCell::new(), validator: Rc::new(validator)};
+    }
+
+    ///
+    /// Constructs a new cell holding the value whose writes are checked by the validator.
+    /// Fails with ValidationError::Invalid if the validator rejects the value.
+    ///
+    pub fn from_value<F>(value: T, validator: F) -> Result<ValidatedRcOCell<T, E>, ValidationError<T, E>>
+        where F: Fn(&T) -> Result<(), E> + 'static
+    {
+        if let Err(error) = validator(&value) {
+            return Err(ValidationError::Invalid {error, value});
+        }
+
+        return Ok(ValidatedRcOCell {cell: RcOCell::from_value(value), validator: Rc::new(validator)});
+    }
+
+    ///
+    /// Returns the cell, writes through it are not validated.
+    ///
+    pub fn cell(&self) -> &RcOCell<T> {
+        return &self.cell;
+    }
+
+    ///
+    /// Runs the validator with the value.
+    ///
+    pub fn validate(&self, value: &T) -> Result<(), E> {
+        return (self.validator)(value);
+    }
+
+    fn checked(&self, value: T) -> Result<T, ValidationError<T, E>> {
+        return match self.validate(&value) {
+            Ok(()) => Ok(value),
+            Err(error) => Err(ValidationError::Invalid {error, value}),
+        };
+    }
+
+    ///
+    /// Sets the value returning the old value (if an old value existed).
+    /// Fails with ValidationError::Invalid if the validator rejects the value.
+    /// Panics if the value is borrowed somewhere.
+    ///
+    #[track_caller]
+    pub fn set(&self, value: T) -> Result<Option<T>, ValidationError<T, E>> {
+        return Ok(self.cell.set(self.checked(value)?));
+    }
+
+    ///
+    /// Sets the value returning the old value (if an old value existed).
+    /// Fails with ValidationError::Cell if the value is borrowed somewhere, the validator is not called then,
+    /// and with ValidationError::Invalid if the validator rejects the value.
+    ///
+    pub fn try_set(&self, value: T) -> Result<Option<T>, ValidationError<T, E>> {
+        self.cell.check_mut()?;
+        return Ok(self.cell.set(self.checked(value)?));
+    }
+
+    ///
+    /// Replaces the value returning the old value.
+    /// Fails with ValidationError::Invalid if the validator rejects the value.
+    /// Panics if there is no value or the value is borrowed somewhere.
+    ///
+    #[track_caller]
+    pub fn replace(&self, value: T) -> Result<T, ValidationError<T, E>> {
+        return Ok(self.cell.replace(self.checked(value)?));
+    }
+
+    ///
+    /// Replaces the value returning the old value.
+    /// Fails with ValidationError::Cell if there is no value or the value is borrowed somewhere, the validator is not called then,
+    /// and with ValidationError::Invalid if the validator rejects the value.
+    ///
+    pub fn try_replace(&self, value: T) -> Result<T, ValidationError<T, E>> {
+        if self.cell.try_raw_borrow_mut()?.is_none() {
+            return Err(ValidationError::Cell(RcOCellError::NoValue));
+        }
+
+        return Ok(self.cell.replace(self.checked(value)?));
+    }
+
+    ///
+    /// Clears the value returning the old value (if an old value existed).
+    /// Panics if the value is borrowed somewhere.
+    ///
+    #[track_caller]
+    pub fn clear(&self) -> Option<T> {
+        return self.cell.clear();
+    }
+
+    ///
+    /// Clears the value returning the old value (if an old value existed).
+    /// Fails if the value is borrowed somewhere.
+    ///
+    pub fn try_clear(&self) -> Result<Option<T>, RcOCellError> {
+        return self.cell.try_clear();
+    }
+
+    ///
+    /// Runs the Fn with the value (if present), conditionally creating/updating/removing it.
+    /// A Replace result is validated, if it is rejected nothing is applied and ValidationError::Invalid is returned.
+    /// Panics if the value is borrowed elsewhere.
+    ///
+    #[track_caller]
+    pub fn compute<F>(&self, f: F) -> Result<(), ValidationError<T, E>>
+        where F: FnOnce(Option<&T>) -> RcOCellComputeResult<T>
+    {
+        return self.cell.compute_result(|value| self.checked_result(f(value.map(|v| &*v))));
+    }
+
+    ///
+    /// Runs the Fn with the value (if present), conditionally creating/updating/removing it, see compute.
+    /// Fails with ValidationError::Cell if the value is borrowed elsewhere, the Fn is not called then.
+    ///
+    pub fn try_compute<F>(&self, f: F) -> Result<(), ValidationError<T, E>>
+        where F: FnOnce(Option<&T>) -> RcOCellComputeResult<T>
+    {
+        return self.cell.try_compute_result(|value| self.checked_result(f(value.map(|v| &*v)))).map_err(flatten);
+    }
+
+    ///
+    /// Runs the Fn if the value is present, conditionally updating/removing it.
+    /// Returns true if the Fn was executed, false if the value was not present.
+    /// A Replace result is validated, if it is rejected nothing is applied and ValidationError::Invalid is returned.
+    /// Panics if the value is borrowed elsewhere.
+    ///
+    #[track_caller]
+    pub fn compute_if_present<F>(&self, f: F) -> Result<bool, ValidationError<T, E>>
+        where F: FnOnce(&T) -> RcOCellComputeResult<T>
+    {
+        return self.cell.compute_if_present_result(|value| self.checked_result(f(value)));
+    }
+
+    ///
+    /// Runs the Fn if the value is present, conditionally updating/removing it, see compute_if_present.
+    /// Fails with ValidationError::Cell if the value is borrowed elsewhere, the Fn is not called then.
+    ///
+    pub fn try_compute_if_present<F>(&self, f: F) -> Result<bool, ValidationError<T, E>>
+        where F: FnOnce(&T) -> RcOCellComputeResult<T>
+    {
+        return self.cell.try_compute_if_present_result(|value| self.checked_result(f(value))).map_err(flatten);
+    }
+
+    fn checked_result(&self, result: RcOCellComputeResult<T>) -> Result<RcOCellComputeResult<T>, ValidationError<T, E>> {
+        return match result {
+            RcOCellComputeResult::Replace(value) => Ok(RcOCellComputeResult::Replace(self.checked(value)?)),
+            other => Ok(other),
+        };
+    }
+
+    ///
+    /// Borrows the value.
+    /// Panics if the value is already borrowed mutably somewhere or there is no value.
+    ///
+    #[track_caller]
+    pub fn borrow(&self) -> RcORef<'_, T> {
+        return self.cell.borrow();
+    }
+
+    ///
+    /// Borrows the value.
+    /// Fails if the value is already borrowed mutably somewhere or there is no value.
+    ///
+    pub fn try_borrow(&self) -> Result<RcORef<'_, T>, RcOCellError> {
+        return self.cell.try_borrow();
+    }
+
+    ///
+    /// Returns true if the value is set.
+    /// Never panics.
+    ///
+    pub fn is_some(&self) -> bool {
+        return self.cell.is_some();
+    }
+
+    ///
+    /// Returns true if the value is not set.
+    /// Never panics.
+    ///
+    pub fn is_none(&self) -> bool {
+        return self.cell.is_none();
+    }
+}
+
+impl <T: Clone, E> ValidatedRcOCell<T, E> {
+    ///
+    /// Borrows the value mutably, the value is validated when the guard is dropped, see ValidatedRefMut.
+    /// Panics if the value is already borrowed somewhere or there is no value.
+    ///
+    #[track_caller]
+    pub fn borrow_mut(&self) -> ValidatedRefMut<'_, T, E> {
+        let guard = self.cell.borrow_mut();
+        let snapshot = T::clone(&guard);
+        return ValidatedRefMut {guard: Some(guard), snapshot: Some(snapshot), validator: &*self.validator};
+    }
+
+    ///
+    /// Borrows the value mutably, the value is validated when the guard is dropped, see ValidatedRefMut.
+    /// Fails if the value is already borrowed somewhere or there is no value.
+    ///
+    pub fn try_borrow_mut(&self) -> Result<ValidatedRefMut<'_, T, E>, RcOCellError> {
+        let guard = self.cell.try_borrow_mut()?;
+        let snapshot = T::clone(&guard);
+        return Ok(ValidatedRefMut {guard: Some(guard), snapshot: Some(snapshot), validator: &*self.validator});
+    }
+
+    ///
+    /// Clones the value.
+    /// Panics if the cell is empty or the value is currently mutably borrowed.
+    ///
+    #[track_caller]
+    pub fn get_and_clone(&self) -> T {
+        return self.cell.get_and_clone();
+    }
+}
+
+fn flatten<T, E>(error: RcOCellComputeError<ValidationError<T, E>>) -> ValidationError<T, E> {
+    return match error {
+        RcOCellComputeError::Cell(e) => ValidationError::Cell(e),
+        RcOCellComputeError::Compute(e) => e,
+    };
+}
+
+impl <T, E> ValidatedRefMut<'_, T, E> {
+    ///
+    /// Ends the borrow and validates the value.
+    /// If the validator rejects the value the snapshot is restored and the rejected value is returned as ValidationError::Invalid.
+    ///
+    pub fn commit(mut self) -> Result<(), ValidationError<T, E>> {
+        return self.finish();
+    }
+
+    fn finish(&mut self) -> Result<(), ValidationError<T, E>> {
+        let (Some(mut guard), Some(snapshot)) = (self.guard.take(), self.snapshot.take()) else {
+            return Ok(());
+        };
+
+        return match (self.validator)(&guard) {
+            Ok(()) => Ok(()),
+            Err(error) => Err(ValidationError::Invalid {error, value: std::mem::replace(&mut *guard, snapshot)}),
+        };
+    }
+}
+
+impl <T, E> Deref for ValidatedRefMut<'_, T, E> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        return self.guard.as_ref().unwrap();
+    }
+}
+
+impl <T, E> DerefMut for ValidatedRefMut<'_, T, E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        return self.guard.as_mut().unwrap();
+    }
+}
+
+impl <T, E> Drop for ValidatedRefMut<'_, T, E> {
+    fn drop(&mut self) {
+        drop(self.finish());
+    }
+}
+
+impl <T: Debug, E> Debug for ValidatedRefMut<'_, T, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return Debug::fmt(&**self, f);
+    }
+}
+
+impl <T, E> Clone for ValidatedRcOCell<T, E> {
+    fn clone(&self) -> Self {
+        return ValidatedRcOCell {cell: self.cell.clone(), validator: self.validator.clone()};
+    }
+}
+
+impl <T: Debug, E> Debug for ValidatedRcOCell<T, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return f.debug_tuple("ValidatedRcOCell").field(&self.cell).finish();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn percent() -> ValidatedRcOCell<u32, String> {
+        return ValidatedRcOCell::new(|v: &u32| if *v <= 100 { Ok(()) } else { Err(format!("{} > 100", v)) });
+    }
+
+    #[test]
+    fn test_validated_set() {
+        let cell = percent();
+        assert_eq!(cell.set(50).unwrap(), None);
+        match cell.set(150) {
+            Err(ValidationError::Invalid {error, value: 150}) => assert_eq!(error, "150 > 100"),
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(cell.get_and_clone(), 50);
+        assert_eq!(cell.replace(60).unwrap(), 50);
+        match cell.try_replace(101) {
+            Err(ValidationError::Invalid {value: 101, ..}) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(cell.get_and_clone(), 60);
+
+        let brw = cell.borrow();
+        match cell.try_set(200) {
+            Err(ValidationError::Cell(RcOCellError::BorrowError(_))) => {}
+            _ => panic!("unexpected"),
+        };
+        drop(brw);
+
+        cell.clear();
+        match cell.try_replace(1) {
+            Err(ValidationError::Cell(RcOCellError::NoValue)) => {}
+            _ => panic!("unexpected"),
+        };
+    }
+
+    #[test]
+    fn test_validated_construction() {
+        match ValidatedRcOCell::from_value(101u32, |v: &u32| if *v <= 100 { Ok(()) } else { Err(()) }) {
+            Err(ValidationError::Invalid {value: 101, ..}) => {}
+            _ => panic!("unexpected"),
+        };
+        let cell = ValidatedRcOCell::from_value(1u32, |v: &u32| if *v <= 100 { Ok(()) } else { Err(()) }).unwrap();
+        assert_eq!(cell.is_some(), true);
+
+        let raw = RcOCell::from_value(200u32);
+        match raw.with_validator(|v: &u32| if *v <= 100 { Ok(()) } else { Err("too big") }) {
+            Err(RcOCellComputeError::Compute("too big")) => {}
+            _ => panic!("unexpected"),
+        };
+        raw.set(20);
+        let validated = raw.with_validator(|v: &u32| if *v <= 100 { Ok(()) } else { Err("too big") }).unwrap();
+        assert_eq!(validated.set(101).is_err(), true);
+        assert_eq!(raw.get_and_clone(), 20);
+    }
+
+    #[test]
+    fn test_validated_compute() {
+        let cell = percent();
+        cell.compute(|v| RcOCellComputeResult::Replace(v.copied().unwrap_or(0) + 90)).unwrap();
+        assert_eq!(cell.get_and_clone(), 90);
+        match cell.compute(|v| RcOCellComputeResult::Replace(v.unwrap() + 20)) {
+            Err(ValidationError::Invalid {value: 110, ..}) => {}
+            _ => panic!("unexpected"),
+        };
+        match cell.try_compute_if_present(|v| RcOCellComputeResult::Replace(v * 2)) {
+            Err(ValidationError::Invalid {value: 180, ..}) => {}
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(cell.get_and_clone(), 90);
+        assert_eq!(cell.compute_if_present(|_| RcOCellComputeResult::Remove).unwrap(), true);
+        assert_eq!(cell.try_compute_if_present(|v| RcOCellComputeResult::Replace(*v)).unwrap(), false);
+
+        cell.set(1).unwrap();
+        let brw = cell.borrow();
+        match cell.try_compute(|_| RcOCellComputeResult::Remove) {
+            Err(ValidationError::Cell(RcOCellError::BorrowError(_))) => {}
+            _ => panic!("unexpected"),
+        };
+        drop(brw);
+    }
+
+    #[test]
+    fn test_validated_guard() {
+        let cell = percent();
+        cell.set(10).unwrap();
+        *cell.borrow_mut() += 5;
+        assert_eq!(cell.get_and_clone(), 15);
+
+        {
+            let mut guard = cell.borrow_mut();
+            *guard = 500;
+            assert_eq!(*guard, 500);
+        }
+        assert_eq!(cell.get_and_clone(), 15);
+
+        let mut guard = cell.try_borrow_mut().unwrap();
+        *guard = 300;
+        match guard.commit() {
+            Err(ValidationError::Invalid {error, value: 300}) => assert_eq!(error, "300 > 100"),
+            _ => panic!("unexpected"),
+        };
+        assert_eq!(cell.get_and_clone(), 15);
+
+        let mut guard = cell.borrow_mut();
+        *guard = 100;
+        assert_eq!(cell.try_borrow_mut().is_err(), true);
+        guard.commit().unwrap();
+        assert_eq!(cell.get_and_clone(), 100);
+    }
+}
@@ -0,0 +1,247 @@
+use crate::{RcOCell, WeakRcOCell};
+
+///
+/// List of weak cells that removes the entries of dropped cells, i.e. for observer and registry patterns.
+///
+/// Dropped entries are removed while iterating with iter_alive and by retain_alive, push never scans the list.
+/// Every cell is upgraded right before it is visited, cells dropped while iterating are skipped.
+/// Cells cannot be added while iterating, push takes the list mutably.
+///
+pub struct WeakCellList<T> {
+    cells: Vec<WeakRcOCell<T>>,
+}
+
+///
+/// Iterator over the live cells of a WeakCellList, returned by iter_alive.
+///
+/// Entries of dropped cells are removed from the list as the iterator passes them.
+/// Entries the iterator did not reach are kept when it is dropped early.
+///
+pub struct AliveCells<'a, T> {
+    cells: &'a mut Vec<WeakRcOCell<T>>,
+    read: usize,
+    write: usize,
+}
+
+impl <T> WeakCellList<T> {
+    ///
+    /// Creates an empty list.
+    ///
+    pub fn new() -> WeakCellList<T> {
+        return WeakCellList {cells: Vec::new()};
+    }
+
+    ///
+    /// Adds the weak cell to the list.
+    ///
+    pub fn push(&mut self, weak: WeakRcOCell<T>) {
+        self.cells.push(weak);
+    }
+
+    ///
+    /// Adds a weak handle of the cell to the list.
+    ///
+    pub fn push_cell(&mut self, cell: &RcOCell<T>) {
+        self.cells.push(cell.downgrade());
+    }
+
+    ///
+    /// Returns an iterator over strong handles of the live cells in the order they were pushed, see AliveCells.
+    ///
+    pub fn iter_alive(&mut self) -> AliveCells<'_, T> {
+        return AliveCells {cells: &mut self.cells, read: 0, write: 0};
+    }
+
+    ///
+    /// Removes the entries of dropped cells.
+    ///
+    pub fn retain_alive(&mut self) {
+        self.cells.retain(|cell| cell.try_upgrade().is_ok());
+    }
+
+    ///
+    /// Returns the number of live cells, the entries of dropped cells are not removed.
+    ///
+    pub fn len_alive(&self) -> usize {
+        return self.cells.iter().filter(|cell| cell.try_upgrade().is_ok()).count();
+    }
+
+    ///
+    /// Calls the Fn with the value of every live cell that holds a value.
+    /// Cells that are empty or borrowed mutably somewhere are skipped.
+    /// The value is borrowed while the Fn runs, so the Fn cannot modify that cell.
+    ///
+    pub fn for_each_present<F>(&self, mut f: F)
+        where F: FnMut(&T)
+    {
+        for weak in self.cells.iter() {
+            let Ok(cell) = weak.try_upgrade() else {
+                continue;
+            };
+
+            let Ok(value) = cell.try_borrow() else {
+                continue;
+            };
+
+            f(&value);
+        }
+    }
+}
+
+impl <T: Clone> WeakCellList<T> {
+    ///
+    /// Sets every live cell to a clone of the value and returns how many cells were set.
+    /// Cells that are borrowed somewhere are skipped.
+    ///
+    pub fn broadcast_set_cloned(&self, value: &T) -> usize {
+        let mut set = 0;
+        for weak in self.cells.iter() {
+            let Ok(cell) = weak.try_upgrade() else {
+                continue;
+            };
+
+            if cell.try_set(value.clone()).is_ok() {
+                set += 1;
+            }
+        }
+
+        return set;
+    }
+}
+
+impl <T> Default for WeakCellList<T> {
+    fn default() -> Self {
+        return WeakCellList::new();
+    }
+}
+
+impl <T> Clone for WeakCellList<T> {
+    fn clone(&self) -> Self {
+        return WeakCellList {cells: self.cells.clone()};
+    }
+}
+
+impl <T> FromIterator<WeakRcOCell<T>> for WeakCellList<T> {
+    fn from_iter<I: IntoIterator<Item=WeakRcOCell<T>>>(iter: I) -> Self {
+        return WeakCellList {cells: iter.into_iter().collect()};
+    }
+}
+
+impl <T> Extend<WeakRcOCell<T>> for WeakCellList<T> {
+    fn extend<I: IntoIterator<Item=WeakRcOCell<T>>>(&mut self, iter: I) {
+        self.cells.extend(iter);
+    }
+}
+
+impl <T> Iterator for AliveCells<'_, T> {
+    type Item = RcOCell<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.read < self.cells.len() {
+            let index = self.read;
+            self.read += 1;
+            if let Ok(cell) = self.cells[index].try_upgrade() {
+                self.cells.swap(self.write, index);
+                self.write += 1;
+                return Some(cell);
+            }
+        }
+
+        return None;
+    }
+}
+
+impl <T> Drop for AliveCells<'_, T> {
+    fn drop(&mut self) {
+        self.cells.drain(self.write..self.read);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use crate::*;
+
+    #[test]
+    fn test_weak_list_prunes() {
+        let mut list = WeakCellList::new();
+        let mut cells: Vec<Option<RcOCell<u32>>> = (0..5).map(|i| Some(RcOCell::from_value(i))).collect();
+        for cell in cells.iter().flatten() {
+            list.push_cell(cell);
+        }
+        let extra = RcOCell::from_value(5u32);
+        list.push(extra.downgrade());
+
+        cells[1] = None;
+        cells[3] = None;
+        assert_eq!(list.len_alive(), 4);
+        assert_eq!(list.cells.len(), 6);
+
+        let values: Vec<u32> = list.iter_alive().map(|c| c.get_and_clone()).collect();
+        assert_eq!(values, vec![0, 2, 4, 5]);
+        assert_eq!(list.cells.len(), 4);
+
+        drop(extra);
+        cells[0] = None;
+        let mut iter = list.iter_alive();
+        assert_eq!(iter.next().unwrap().get_and_clone(), 2);
+        drop(iter);
+        assert_eq!(list.cells.len(), 3);
+        list.retain_alive();
+        assert_eq!(list.cells.len(), 2);
+        assert_eq!(list.iter_alive().map(|c| c.get_and_clone()).collect::<Vec<_>>(), vec![2, 4]);
+    }
+
+    #[test]
+    fn test_weak_list_present_and_broadcast() {
+        let cells: Vec<RcOCell<u32>> = vec![RcOCell::from_value(1), RcOCell::new(), RcOCell::from_value(3)];
+        let list: WeakCellList<u32> = cells.iter().map(RcOCell::downgrade).collect();
+
+        let brw = cells[2].borrow_mut();
+        let mut seen = Vec::new();
+        list.for_each_present(|v| seen.push(*v));
+        assert_eq!(seen, vec![1]);
+        assert_eq!(list.broadcast_set_cloned(&7), 2);
+        drop(brw);
+        assert_eq!(cells.iter().map(|c| c.get_and_clone()).collect::<Vec<_>>(), vec![7, 7, 3]);
+
+        let mut seen = Vec::new();
+        list.for_each_present(|v| seen.push(*v));
+        assert_eq!(seen, vec![7, 7, 3]);
+    }
+
+    #[test]
+    fn test_weak_list_drop_while_iterating() {
+        let owners: RefCell<Vec<Option<RcOCell<u32>>>> = RefCell::new((0..4).map(|i| Some(RcOCell::from_value(i))).collect());
+        let mut list: WeakCellList<u32> = owners.borrow().iter().flatten().map(RcOCell::downgrade).collect();
+
+        let mut seen = Vec::new();
+        list.for_each_present(|v| {
+            seen.push(*v);
+            owners.borrow_mut()[*v as usize + 1] = None;
+        });
+        assert_eq!(seen, vec![0, 2]);
+
+        owners.borrow_mut()[0] = Some(RcOCell::from_value(9));
+        let mut visited = Vec::new();
+        for cell in list.iter_alive() {
+            visited.push(cell.get_and_clone());
+            owners.borrow_mut()[2] = None;
+        }
+        assert_eq!(visited, vec![2]);
+        assert_eq!(list.len_alive(), 0);
+        assert_eq!(list.cells.len(), 1);
+
+        let observed = RcOCell::from_value(0u32);
+        let dropped_by_observer = RcOCell::from_value(0u32);
+        let mut list = WeakCellList::new();
+        list.push_cell(&observed);
+        list.push_cell(&dropped_by_observer);
+        let slot = std::rc::Rc::new(RefCell::new(Some(dropped_by_observer)));
+        let slot2 = slot.clone();
+        observed.on_set(move |_| { slot2.borrow_mut().take(); });
+        assert_eq!(list.broadcast_set_cloned(&1), 1);
+        assert_eq!(slot.borrow().is_none(), true);
+        assert_eq!(list.len_alive(), 1);
+    }
+}